@@ -1,9 +1,32 @@
 //! Модуль для обработки аудио (компрессия, эквализация)
 
 use crate::error::{Error, Result, ErrorType};
+use crate::audio::biquad::Biquad;
 use crate::audio::models::AudioData;
 use crate::logging::{log_info, log_debug};
 
+/// Добротность полок эквалайзера (плавный переход без выброса)
+const DEFAULT_EQ_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// Время упреждения лимитера в миллисекундах: за это время до пика
+/// усиление плавно снижается до нужного уровня
+const LIMITER_LOOKAHEAD_MS: f32 = 5.0;
+
+/// Время восстановления лимитера по умолчанию в миллисекундах
+pub const DEFAULT_LIMITER_RELEASE_MS: f32 = 50.0;
+
+/// Проверяет диапазоны оригинала `(начало, конец)`: границы конечны,
+/// неотрицательны и начало меньше конца
+pub(crate) fn validate_keep_original_ranges(ranges: &[(f64, f64)]) -> Result<()> {
+    match ranges.iter().find(|&&(start, end)| !(start.is_finite() && end.is_finite() && start >= 0.0 && start < end)) {
+        Some(&(start, end)) => Err(Error::new(
+            ErrorType::InvalidParameters,
+            &format!("Некорректный диапазон оригинала: {} - {} с", start, end),
+        )),
+        None => Ok(()),
+    }
+}
+
 /// Процессор аудио для применения различных эффектов обработки
 pub struct AudioProcessor;
 
@@ -96,26 +119,120 @@ impl AudioProcessor {
             }
             
             // Применяем усиление и компенсацию
-            let processed_sample = sample * gain * makeup_gain_linear;
-            
-            // Ограничиваем значение в диапазоне [-1.0, 1.0]
-            let limited_sample = processed_sample.max(-1.0).min(1.0);
-            
-            processed_samples.push(limited_sample);
+            processed_samples.push(sample * gain * makeup_gain_linear);
         }
         
         log_debug("Компрессия применена успешно");
         
-        // Создаем новый объект AudioData с обработанными данными
-        Ok(AudioData::new(
-            processed_samples,
-            audio.sample_rate,
-            audio.channels,
-        ))
+        // Пики после компенсационного усиления плавно ограничиваем 0 дБ полной шкалы
+        let processed = AudioData::new(processed_samples, audio.sample_rate, audio.channels);
+        Self::apply_limiter(&processed, 0.0, DEFAULT_LIMITER_RELEASE_MS)
+    }
+
+    /// Применяет лимитер с упреждением (brickwall), ограничивающий пики потолком
+    ///
+    /// В отличие от жесткого ограничения сэмплов усиление снижается плавно
+    /// за 5 мс до пика и восстанавливается экспоненциально,
+    /// поэтому форма сигнала не искажается. Усиление общее для всех каналов
+    /// кадра, задержки сигнала нет: весь буфер обрабатывается целиком.
+    ///
+    /// # Аргументы
+    ///
+    /// * `audio` - Аудио данные для обработки
+    /// * `ceiling_db` - Потолок в дБ полной шкалы (например, -1.0)
+    /// * `release_ms` - Время восстановления усиления в миллисекундах
+    ///   (0 - восстановление за время упреждения)
+    ///
+    /// # Возвращает
+    ///
+    /// * `Result<AudioData>` - Аудио данные, ни один сэмпл которых не превышает потолок
+    pub fn apply_limiter(audio: &AudioData, ceiling_db: f32, release_ms: f32) -> Result<AudioData> {
+        if !ceiling_db.is_finite() {
+            return Err(Error::new(
+                ErrorType::InvalidParameters,
+                &format!("Некорректный потолок лимитера: {} дБ", ceiling_db),
+            ));
+        }
+        if release_ms.is_nan() || release_ms < 0.0 {
+            return Err(Error::new(
+                ErrorType::InvalidParameters,
+                &format!("Время восстановления лимитера должно быть неотрицательным: {} мс", release_ms),
+            ));
+        }
+        if audio.samples.is_empty() {
+            return Ok(audio.clone());
+        }
+
+        log_debug(&format!("Применение лимитера: потолок={} дБ, восстановление={} мс", ceiling_db, release_ms));
+
+        // Расчет ведется в f64, чтобы округление не выводило сэмплы за потолок
+        let ceiling = 10.0_f64.powf(ceiling_db as f64 / 20.0);
+        let channels = audio.channels.max(1) as usize;
+        let sample_rate = audio.sample_rate.max(1) as f64;
+
+        // Усиление, необходимое каждому кадру, чтобы не превысить потолок
+        let required: Vec<f64> = audio.samples.chunks(channels)
+            .map(|frame| {
+                let peak = frame.iter().fold(0.0f64, |max, &sample| max.max((sample as f64).abs()));
+                if peak > ceiling { ceiling / peak } else { 1.0 }
+            })
+            .collect();
+
+        // Минимум на окне упреждения, сглаженный скользящим средним той же длины:
+        // к моменту пика усиление успевает опуститься до требуемого
+        let lookahead = ((LIMITER_LOOKAHEAD_MS as f64 * 0.001 * sample_rate) as usize).max(1);
+        let held = Self::sliding_min(&required, lookahead);
+        let release_coeff = if release_ms > 0.0 {
+            (-1.0 / (release_ms as f64 * 0.001 * sample_rate)).exp()
+        } else {
+            0.0
+        };
+
+        let mut result = audio.clone();
+        let mut window_sum = lookahead as f64;
+        let mut gain = 1.0f64;
+        for (n, frame) in result.samples.chunks_mut(channels).enumerate() {
+            window_sum += held[n] - if n >= lookahead { held[n - lookahead] } else { 1.0 };
+            let smoothed = window_sum / lookahead as f64;
+
+            // Восстановление не быстрее времени release; минимум с требуемым
+            // усилением страхует от накопления ошибки скользящей суммы
+            gain = (1.0 - release_coeff * (1.0 - gain)).min(smoothed).min(required[n]);
+            for sample in frame.iter_mut() {
+                *sample = (*sample as f64 * gain) as f32;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Возвращает минимумы `values` на окнах `[i, i + window)`
+    fn sliding_min(values: &[f64], window: usize) -> Vec<f64> {
+        let mut minima = vec![0.0; values.len()];
+        // Индексы кандидатов в минимум; значения возрастают от начала к концу очереди
+        let mut candidates: std::collections::VecDeque<usize> = std::collections::VecDeque::new();
+        for i in (0..values.len()).rev() {
+            while candidates.back().is_some_and(|&back| values[back] >= values[i]) {
+                candidates.pop_back();
+            }
+            candidates.push_back(i);
+            while candidates.front().is_some_and(|&front| front >= i + window) {
+                candidates.pop_front();
+            }
+            minima[i] = values[candidates[0]];
+        }
+        minima
     }
 
     /// Применяет эквализацию к аудио данным
     ///
+    /// Трехполосный эквалайзер из биквадратных фильтров: низкочастотная полка
+    /// на `low_freq`, колоколообразный фильтр средних частот с центром между
+    /// частотами разделения и высокочастотная полка на `high_freq`. Добротность
+    /// средней полосы подбирается так, чтобы полоса занимала промежуток между
+    /// частотами разделения; задать ее явно можно через
+    /// [`AudioProcessor::apply_equalization_with_q`].
+    ///
     /// # Аргументы
     ///
     /// * `audio` - Аудио данные для обработки
@@ -135,10 +252,30 @@ impl AudioProcessor {
         high_gain: f32,
         low_freq: f32,
         high_freq: f32,
+    ) -> Result<AudioData> {
+        // Добротность полосы шириной от low_freq до high_freq (в октавах)
+        let ratio = high_freq / low_freq;
+        let q = if ratio > 1.0 { ratio.sqrt() / (ratio - 1.0) } else { DEFAULT_EQ_Q };
+
+        Self::apply_equalization_with_q(audio, low_gain, mid_gain, high_gain, low_freq, high_freq, q)
+    }
+
+    /// Применяет эквализацию с заданной добротностью средней полосы
+    ///
+    /// Аналог [`AudioProcessor::apply_equalization`]; `q` - добротность
+    /// колоколообразного фильтра средних частот (больше - уже полоса).
+    pub fn apply_equalization_with_q(
+        audio: &AudioData,
+        low_gain: f32,
+        mid_gain: f32,
+        high_gain: f32,
+        low_freq: f32,
+        high_freq: f32,
+        q: f32,
     ) -> Result<AudioData> {
         log_info(&format!(
-            "Применение эквализации: низкие={} дБ (до {} Гц), средние={} дБ, высокие={} дБ (от {} Гц)",
-            low_gain, low_freq, mid_gain, high_freq, high_gain
+            "Применение эквализации: низкие={} дБ (до {} Гц), средние={} дБ (Q={}), высокие={} дБ (от {} Гц)",
+            low_gain, low_freq, mid_gain, q, high_gain, high_freq
         ));
 
         if audio.samples.is_empty() {
@@ -155,55 +292,44 @@ impl AudioProcessor {
             ));
         }
 
-        // Конвертируем усиление из дБ в линейные значения
-        let low_gain_linear = 10.0_f32.powf(low_gain / 20.0);
-        let mid_gain_linear = 10.0_f32.powf(mid_gain / 20.0);
-        let high_gain_linear = 10.0_f32.powf(high_gain / 20.0);
-
-        // Рассчитываем коэффициенты фильтров
-        // Используем простые фильтры первого порядка для демонстрации
-        let dt = 1.0 / audio.sample_rate as f32;
-        let rc_low = 1.0 / (2.0 * std::f32::consts::PI * low_freq);
-        let rc_high = 1.0 / (2.0 * std::f32::consts::PI * high_freq);
-        
-        let alpha_low = dt / (rc_low + dt);
-        let alpha_high = dt / (rc_high + dt);
-
-        // Создаем буферы для фильтрованных сигналов
-        let mut low_pass = vec![0.0; audio.samples.len()];
-        let mut high_pass = vec![0.0; audio.samples.len()];
-        let mut band_pass = vec![0.0; audio.samples.len()];
-
-        // Применяем фильтры
-        // Низкочастотный фильтр
-        low_pass[0] = audio.samples[0];
-        for i in 1..audio.samples.len() {
-            low_pass[i] = low_pass[i-1] + alpha_low * (audio.samples[i] - low_pass[i-1]);
+        if low_freq <= 0.0 || high_freq >= audio.sample_rate as f32 / 2.0 {
+            return Err(Error::new(
+                ErrorType::InvalidParameters,
+                &format!("Частоты разделения {}-{} Гц вне допустимого диапазона (0, {}) Гц",
+                    low_freq, high_freq, audio.sample_rate / 2),
+            ));
         }
 
-        // Высокочастотный фильтр (разница между входным сигналом и низкочастотным)
-        high_pass[0] = audio.samples[0];
-        for i in 1..audio.samples.len() {
-            high_pass[i] = alpha_high * (high_pass[i-1] + audio.samples[i] - audio.samples[i-1]);
+        if q <= 0.0 {
+            return Err(Error::new(
+                ErrorType::InvalidParameters,
+                &format!("Добротность эквалайзера должна быть положительной: {}", q),
+            ));
         }
 
-        // Полосовой фильтр (средние частоты)
-        // Это разница между входным сигналом и суммой низких и высоких частот
-        for i in 0..audio.samples.len() {
-            band_pass[i] = audio.samples[i] - (low_pass[i] + high_pass[i]);
-        }
+        let sample_rate = audio.sample_rate as f64;
+        let mid_freq = (low_freq as f64 * high_freq as f64).sqrt();
+        let filters = [
+            Biquad::low_shelf(sample_rate, low_freq as f64, low_gain as f64, DEFAULT_EQ_Q as f64),
+            Biquad::peaking(sample_rate, mid_freq, mid_gain as f64, q as f64),
+            Biquad::high_shelf(sample_rate, high_freq as f64, high_gain as f64, DEFAULT_EQ_Q as f64),
+        ];
 
-        // Применяем усиление и объединяем сигналы
-        let mut processed_samples = Vec::with_capacity(audio.samples.len());
-        for i in 0..audio.samples.len() {
-            let eq_sample = low_pass[i] * low_gain_linear + 
-                           band_pass[i] * mid_gain_linear + 
-                           high_pass[i] * high_gain_linear;
-            
-            // Ограничиваем значение в диапазоне [-1.0, 1.0]
-            let limited_sample = eq_sample.max(-1.0).min(1.0);
-            
-            processed_samples.push(limited_sample);
+        // Каждый канал фильтруется отдельно
+        let channels = audio.channels.max(1) as usize;
+        let mut processed_samples = vec![0.0f32; audio.samples.len()];
+        for channel in 0..channels {
+            let signal: Vec<f64> = audio.samples.iter()
+                .skip(channel)
+                .step_by(channels)
+                .map(|&sample| sample as f64)
+                .collect();
+            let filtered = filters.iter().fold(signal, |signal, filter| filter.process(&signal));
+
+            for (sample, value) in processed_samples.iter_mut().skip(channel).step_by(channels).zip(filtered) {
+                // Ограничиваем значение в диапазоне [-1.0, 1.0]
+                *sample = (value as f32).clamp(-1.0, 1.0);
+            }
         }
 
         log_debug("Эквализация применена успешно");
@@ -216,6 +342,194 @@ impl AudioProcessor {
         ))
     }
 
+    /// Применяет де-эссер, подавляющий сибилянты (свистящие и шипящие звуки)
+    ///
+    /// Сигнал разделяется на полосу сибилянтов выше `freq` и остальную часть
+    /// фильтром нулевой фазы (прямой и обратный проход), поэтому сумма полос
+    /// совпадает с исходным сигналом. Компрессия применяется только к полосе
+    /// сибилянтов, более низкие частоты не изменяются.
+    ///
+    /// # Аргументы
+    ///
+    /// * `audio` - Аудио данные для обработки
+    /// * `freq` - Нижняя граница полосы сибилянтов в Гц (обычно 5000-8000)
+    /// * `threshold_db` - Порог уровня полосы сибилянтов в дБ, выше которого начинается подавление
+    /// * `ratio` - Коэффициент компрессии полосы сибилянтов
+    ///
+    /// # Возвращает
+    ///
+    /// * `Result<AudioData>` - Обработанные аудио данные
+    pub fn apply_deesser(
+        audio: &AudioData,
+        freq: f32,
+        threshold_db: f32,
+        ratio: f32,
+    ) -> Result<AudioData> {
+        const ATTACK_MS: f32 = 1.0;
+        const RELEASE_MS: f32 = 20.0;
+
+        log_info(&format!(
+            "Применение де-эссера: частота={} Гц, порог={} дБ, соотношение={}:1",
+            freq, threshold_db, ratio
+        ));
+
+        if ratio <= 1.0 {
+            return Err(Error::new(
+                ErrorType::InvalidParameters,
+                "Коэффициент де-эссера должен быть больше 1.0",
+            ));
+        }
+
+        if freq <= 0.0 || freq >= audio.sample_rate as f32 / 2.0 {
+            return Err(Error::new(
+                ErrorType::InvalidParameters,
+                &format!("Частота де-эссера {} Гц вне допустимого диапазона (0, {}) Гц",
+                    freq, audio.sample_rate / 2),
+            ));
+        }
+
+        if audio.samples.is_empty() {
+            return Ok(audio.clone());
+        }
+
+        let channels = audio.channels.max(1) as usize;
+        let dt = 1.0 / audio.sample_rate as f32;
+        let rc = 1.0 / (2.0 * std::f32::consts::PI * freq);
+        let alpha = dt / (rc + dt);
+
+        // Выделяем полосу сибилянтов в каждом канале: нижние частоты получаем
+        // фильтром нижних частот в прямом и обратном направлении (без сдвига фазы)
+        let mut high_band = vec![0.0f32; audio.samples.len()];
+        for channel in 0..channels {
+            let indices: Vec<usize> = (channel..audio.samples.len()).step_by(channels).collect();
+
+            let mut low = audio.samples[indices[0]];
+            let mut forward = Vec::with_capacity(indices.len());
+            for &i in &indices {
+                low += alpha * (audio.samples[i] - low);
+                forward.push(low);
+            }
+
+            let mut low = forward[forward.len() - 1];
+            for (k, &i) in indices.iter().enumerate().rev() {
+                low += alpha * (forward[k] - low);
+                high_band[i] = audio.samples[i] - low;
+            }
+        }
+
+        let attack_coef = (-1.0 / (ATTACK_MS * 0.001 * audio.sample_rate as f32)).exp();
+        let release_coef = (-1.0 / (RELEASE_MS * 0.001 * audio.sample_rate as f32)).exp();
+        let threshold_linear = 10.0_f32.powf(threshold_db / 20.0);
+        let slope = 1.0 - 1.0 / ratio;
+
+        let mut processed_samples = audio.samples.clone();
+        let mut envelope = 0.0f32;
+
+        // Огибающая общая для всех каналов кадра, чтобы не смещать стереопанораму
+        for (frame, high_frame) in processed_samples.chunks_mut(channels).zip(high_band.chunks(channels)) {
+            let level = high_frame.iter().fold(0.0f32, |max, &sample| max.max(sample.abs()));
+            let coef = if level > envelope { attack_coef } else { release_coef };
+            envelope = coef * envelope + (1.0 - coef) * level;
+
+            if envelope <= threshold_linear {
+                continue;
+            }
+
+            let db_gain_reduction = 20.0 * (envelope / threshold_linear).log10() * slope;
+            let gain = 10.0_f32.powf(-db_gain_reduction / 20.0);
+
+            for (sample, &high) in frame.iter_mut().zip(high_frame) {
+                // Исходный сэмпл - сумма полос, ослабляем только полосу сибилянтов
+                *sample = (*sample - high * (1.0 - gain)).clamp(-1.0, 1.0);
+            }
+        }
+
+        log_debug("Де-эссер применен успешно");
+
+        Ok(AudioData::new(
+            processed_samples,
+            audio.sample_rate,
+            audio.channels,
+        ))
+    }
+
+    /// Смешивает дубляж с оригинальной звуковой дорожкой
+    ///
+    /// Вне диапазонов `keep_original_ranges` оригинал приглушается до
+    /// `original_gain_db` и складывается с дубляжем. Внутри диапазонов (например,
+    /// песни, которые не нужно дублировать) оригинал звучит с полной громкостью,
+    /// а дубляж не подмешивается. Переключение на краях диапазонов плавное
+    /// (10 мс внутрь диапазона), чтобы не было щелчков.
+    ///
+    /// # Аргументы
+    ///
+    /// * `dub` - Аудио дубляжа
+    /// * `original` - Оригинальная звуковая дорожка с той же частотой и числом каналов
+    /// * `original_gain_db` - Уровень оригинала под дубляжем в дБ (например, -12.0)
+    /// * `keep_original_ranges` - Диапазоны времени `(начало, конец)` в секундах,
+    ///   где звучит только оригинал
+    ///
+    /// # Возвращает
+    ///
+    /// * `Result<AudioData>` - Смешанное аудио длиной с более длинную из дорожек
+    pub fn mix_with_original(
+        dub: &AudioData,
+        original: &AudioData,
+        original_gain_db: f32,
+        keep_original_ranges: &[(f64, f64)],
+    ) -> Result<AudioData> {
+        const CROSSFADE_SECONDS: f64 = 0.01;
+
+        log_info(&format!(
+            "Смешивание с оригиналом: уровень оригинала={} дБ, диапазонов без дубляжа={}",
+            original_gain_db, keep_original_ranges.len()
+        ));
+
+        if dub.sample_rate != original.sample_rate || dub.channels != original.channels {
+            return Err(Error::new(
+                ErrorType::InvalidParameters,
+                &format!(
+                    "Формат оригинала ({} Гц, {} кан.) не совпадает с форматом дубляжа ({} Гц, {} кан.)",
+                    original.sample_rate, original.channels, dub.sample_rate, dub.channels
+                ),
+            ));
+        }
+
+        validate_keep_original_ranges(keep_original_ranges)?;
+
+        let channels = dub.channels.max(1) as usize;
+        let sample_rate = dub.sample_rate as f64;
+        let ducked_gain = 10.0_f32.powf(original_gain_db / 20.0);
+        let len = dub.samples.len().max(original.samples.len());
+
+        let mut mixed = vec![0.0f32; len];
+        for (frame_index, frame) in mixed.chunks_mut(channels).enumerate() {
+            let time = frame_index as f64 / sample_rate;
+
+            // Доля оригинала: 1 внутри диапазона, линейный переход у его краев
+            let keep = keep_original_ranges.iter()
+                .map(|&(start, end)| {
+                    let depth = (time - start).min(end - time);
+                    (depth / CROSSFADE_SECONDS).clamp(0.0, 1.0) as f32
+                })
+                .fold(0.0f32, f32::max);
+
+            let original_gain = ducked_gain + (1.0 - ducked_gain) * keep;
+            let dub_gain = 1.0 - keep;
+
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                let index = frame_index * channels + channel;
+                let dub_sample = dub.samples.get(index).copied().unwrap_or(0.0);
+                let original_sample = original.samples.get(index).copied().unwrap_or(0.0);
+                *sample = (dub_sample * dub_gain + original_sample * original_gain).clamp(-1.0, 1.0);
+            }
+        }
+
+        log_debug("Смешивание с оригиналом завершено");
+
+        Ok(AudioData::new(mixed, dub.sample_rate, dub.channels))
+    }
+
     /// Нормализует громкость аудио
     ///
     /// # Аргументы
@@ -270,6 +584,66 @@ impl AudioProcessor {
             audio.channels,
         ))
     }
+
+    /// Измеряет интегральную громкость аудио в LUFS по ITU-R BS.1770
+    ///
+    /// Возвращает `None` для пустого аудио и тишины.
+    pub fn integrated_loudness(audio: &AudioData) -> Option<f32> {
+        crate::audio::loudness::integrated_loudness(audio).map(|loudness| loudness as f32)
+    }
+
+    /// Нормализует интегральную громкость аудио (EBU R128)
+    ///
+    /// # Аргументы
+    ///
+    /// * `audio` - Аудио данные для обработки
+    /// * `target_lufs` - Целевая интегральная громкость в LUFS (обычно -16.0 или -23.0)
+    ///
+    /// # Возвращает
+    ///
+    /// * `Result<AudioData>` - Обработанные аудио данные; пики выше 0 дБ
+    ///   полной шкалы ограничиваются лимитером, а не обрезаются
+    pub fn normalize_loudness(audio: &AudioData, target_lufs: f32) -> Result<AudioData> {
+        log_info(&format!("Нормализация громкости к {} LUFS", target_lufs));
+
+        if audio.samples.is_empty() {
+            return Err(Error::new(
+                ErrorType::InvalidParameters,
+                "Аудио данные пусты",
+            ));
+        }
+
+        let current_lufs = match Self::integrated_loudness(audio) {
+            Some(loudness) => loudness,
+            None => {
+                log_debug("Аудио содержит только тишину, нормализация не требуется");
+                return Ok(audio.clone());
+            }
+        };
+
+        let gain_db = target_lufs - current_lufs;
+        let gain_linear = 10.0_f32.powf(gain_db / 20.0);
+
+        log_debug(&format!(
+            "Текущая громкость: {:.2} LUFS, целевая: {:.2} LUFS, усиление: {:.2} дБ",
+            current_lufs, target_lufs, gain_db
+        ));
+
+        let processed_samples: Vec<f32> = audio.samples.iter()
+            .map(|&sample| sample * gain_linear)
+            .collect();
+        let processed = AudioData::new(processed_samples, audio.sample_rate, audio.channels);
+
+        // Пики, превысившие полную шкалу после усиления, плавно ограничиваем лимитером
+        let overs = processed.samples.iter().filter(|&&sample| sample.abs() > 1.0).count();
+        if overs == 0 {
+            return Ok(processed);
+        }
+        log_debug(&format!(
+            "При нормализации к {} LUFS {} сэмплов превысили 0 дБ, применяется лимитер", target_lufs, overs
+        ));
+        Self::apply_limiter(&processed, 0.0, DEFAULT_LIMITER_RELEASE_MS)
+    }
 }
 
 #[cfg(test)]
@@ -327,6 +701,66 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_audio_data_limiter_uses_lookahead_limiter() {
+        let samples: Vec<f32> = (0..4410)
+            .map(|i| if i == 2000 { 1.5 } else { 0.5 * (i as f32 * 0.06).sin() })
+            .collect();
+        let audio = AudioData::new(samples, 44100, 1);
+
+        let expected = AudioProcessor::apply_limiter(&audio, -6.0, DEFAULT_LIMITER_RELEASE_MS).unwrap();
+        let limited = audio.apply_limiter(10.0_f32.powf(-6.0 / 20.0));
+        let max_diff = limited.samples.iter().zip(&expected.samples)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0f32, f32::max);
+        assert!(max_diff < 1e-5, "Расхождение с лимитером процессора: {}", max_diff);
+        assert_eq!(audio.apply_limiter(0.0).samples, audio.samples);
+    }
+
+    #[test]
+    fn test_limiter_keeps_peaks_below_ceiling_without_clipping() {
+        // Тон 440 Гц с всплеском до +6 дБ полной шкалы посередине
+        let sample_rate = 44100;
+        let samples: Vec<f32> = (0..sample_rate)
+            .map(|i| {
+                let t = i as f32 / sample_rate as f32;
+                let amplitude = if (0.4..0.6).contains(&t) { 2.0 } else { 0.5 };
+                amplitude * (2.0 * std::f32::consts::PI * 440.0 * t).sin()
+            })
+            .collect();
+        let audio = AudioData::new(samples, sample_rate, 1);
+
+        let ceiling_db = -1.0;
+        let ceiling = 10.0_f32.powf(ceiling_db / 20.0);
+        let limited = AudioProcessor::apply_limiter(&audio, ceiling_db, DEFAULT_LIMITER_RELEASE_MS).unwrap();
+        assert_eq!(limited.samples.len(), audio.samples.len());
+        assert!(limited.sample_peak() <= ceiling, "{}", limited.sample_peak());
+
+        // Сигнал масштабируется плавно меняющимся усилением, а не ограничивается:
+        // вершины синусоиды не срезаны до потолка
+        let gains: Vec<Option<f32>> = audio.samples.iter().zip(&limited.samples)
+            .map(|(original, limited)| if original.abs() > 0.1 { Some(limited / original) } else { None })
+            .collect();
+        assert!(gains.iter().flatten().all(|&gain| gain > 0.0 && gain <= 1.0));
+        let max_step = gains.windows(2)
+            .filter_map(|pair| Some((pair[1]? - pair[0]?).abs()))
+            .fold(0.0f32, f32::max);
+        assert!(max_step < 0.01, "Скачок усиления {}", max_step);
+        let (mut longest_plateau, mut plateau) = (0, 0);
+        for sample in &limited.samples {
+            plateau = if sample.abs() >= ceiling - 1e-4 { plateau + 1 } else { 0 };
+            longest_plateau = longest_plateau.max(plateau);
+        }
+        assert!(longest_plateau <= 2, "Срезано подряд сэмплов: {}", longest_plateau);
+
+        // Тихая часть до всплеска (с учетом упреждения) не изменяется
+        let before_burst = (0.39 * sample_rate as f32) as usize;
+        assert_eq!(&limited.samples[..before_burst], &audio.samples[..before_burst]);
+
+        assert!(AudioProcessor::apply_limiter(&audio, f32::NAN, 50.0).is_err());
+        assert!(AudioProcessor::apply_limiter(&audio, -1.0, -5.0).is_err());
+    }
+
     #[test]
     fn test_equalization() {
         // Создаем тестовые данные
@@ -350,6 +784,77 @@ mod tests {
         assert_eq!(equalized.samples.len(), audio.samples.len());
     }
 
+    /// Амплитуда составляющей частоты `freq` в сигнале
+    fn tone_amplitude(samples: &[f32], sample_rate: u32, freq: f32) -> f32 {
+        let (sin_sum, cos_sum) = samples.iter().enumerate().fold((0.0f32, 0.0f32), |(s, c), (i, &sample)| {
+            let phase = 2.0 * std::f32::consts::PI * freq * i as f32 / sample_rate as f32;
+            (s + sample * phase.sin(), c + sample * phase.cos())
+        });
+        2.0 * (sin_sum * sin_sum + cos_sum * cos_sum).sqrt() / samples.len() as f32
+    }
+
+    #[test]
+    fn test_deesser_reduces_sibilant_band_only() {
+        // Речь в низких частотах и усиленная составляющая в полосе сибилянтов
+        let sample_rate = 44100;
+        let samples: Vec<f32> = (0..sample_rate)
+            .map(|i| {
+                let t = i as f32 / sample_rate as f32;
+                0.3 * (2.0 * std::f32::consts::PI * 200.0 * t).sin()
+                    + 0.4 * (2.0 * std::f32::consts::PI * 8000.0 * t).sin()
+            })
+            .collect();
+        let audio = AudioData::new(samples, sample_rate, 1);
+
+        let processed = AudioProcessor::apply_deesser(&audio, 6000.0, -30.0, 8.0).unwrap();
+        assert_eq!(processed.samples.len(), audio.samples.len());
+
+        // Пропускаем начало, пока огибающая устанавливается
+        let skip = sample_rate as usize / 10;
+        let original = &audio.samples[skip..];
+        let deessed = &processed.samples[skip..];
+
+        let sibilant_before = tone_amplitude(original, sample_rate, 8000.0);
+        let sibilant_after = tone_amplitude(deessed, sample_rate, 8000.0);
+        assert!(sibilant_after < sibilant_before * 0.6,
+            "Полоса сибилянтов не ослаблена: {} -> {}", sibilant_before, sibilant_after);
+
+        let low_before = tone_amplitude(original, sample_rate, 200.0);
+        let low_after = tone_amplitude(deessed, sample_rate, 200.0);
+        assert!((low_after - low_before).abs() < low_before * 0.01,
+            "Низкие частоты изменены: {} -> {}", low_before, low_after);
+
+        assert!(AudioProcessor::apply_deesser(&audio, 6000.0, -30.0, 1.0).is_err());
+        assert!(AudioProcessor::apply_deesser(&audio, 30000.0, -30.0, 4.0).is_err());
+    }
+
+    #[test]
+    fn test_mix_with_original_keeps_original_in_ranges() {
+        let sample_rate = 8000;
+        let dub = AudioData::new(vec![0.3; 2 * sample_rate as usize], sample_rate, 1);
+        let original = AudioData::new(vec![0.5; 2 * sample_rate as usize], sample_rate, 1);
+
+        let mixed = AudioProcessor::mix_with_original(&dub, &original, -6.0, &[(0.5, 1.5)]).unwrap();
+        assert_eq!(mixed.samples.len(), dub.samples.len());
+
+        let at = |time: f64| mixed.samples[(time * sample_rate as f64) as usize];
+        let ducked = 0.3 + 0.5 * 10.0f32.powf(-6.0 / 20.0);
+
+        // Вне диапазона - дубляж поверх приглушенного оригинала
+        assert!((at(0.25) - ducked).abs() < 1e-5, "{}", at(0.25));
+        assert!((at(1.75) - ducked).abs() < 1e-5, "{}", at(1.75));
+
+        // Внутри диапазона - только оригинал с полной громкостью
+        for time in [0.52, 1.0, 1.48] {
+            assert!((at(time) - 0.5).abs() < 1e-6, "{}: {}", time, at(time));
+        }
+
+        // Несовпадающий формат и пустой диапазон - ошибка
+        let stereo = AudioData::new(vec![0.5; 100], sample_rate, 2);
+        assert!(AudioProcessor::mix_with_original(&dub, &stereo, -6.0, &[]).is_err());
+        assert!(AudioProcessor::mix_with_original(&dub, &original, -6.0, &[(1.0, 1.0)]).is_err());
+    }
+
     #[test]
     fn test_normalize_volume() {
         // Создаем тестовые данные с максимальной амплитудой 0.5
@@ -376,4 +881,33 @@ mod tests {
         assert!((max_amplitude - target_amplitude).abs() < 0.01, 
                 "Ожидаемая амплитуда: {}, фактическая: {}", target_amplitude, max_amplitude);
     }
+
+    #[test]
+    fn test_normalize_loudness_to_target_lufs() {
+        // Речеподобный сигнал: тон 220 Гц с гармоникой и паузами
+        let samples: Vec<f32> = (0..48000 * 3)
+            .map(|i| {
+                let t = i as f32 / 48000.0;
+                let envelope = if (t * 2.0).fract() < 0.7 { 1.0 } else { 0.0 };
+                let tone = (2.0 * std::f32::consts::PI * 220.0 * t).sin() + 0.5 * (2.0 * std::f32::consts::PI * 660.0 * t).sin();
+                0.1 * envelope * tone
+            })
+            .collect();
+        let audio = AudioData::new(samples, 48000, 1);
+
+        // Приводим сигнал к -23 LUFS
+        let measured = AudioProcessor::integrated_loudness(&audio).unwrap();
+        let gain = 10f32.powf((-23.0 - measured) / 20.0);
+        let audio = AudioData::new(audio.samples.iter().map(|s| s * gain).collect(), 48000, 1);
+        assert!((AudioProcessor::integrated_loudness(&audio).unwrap() + 23.0).abs() < 0.05);
+
+        let normalized = AudioProcessor::normalize_loudness(&audio, -16.0).unwrap();
+        let loudness = AudioProcessor::integrated_loudness(&normalized).unwrap();
+        assert!((loudness + 16.0).abs() < 0.5, "Громкость после нормализации: {:.2} LUFS", loudness);
+
+        // Тишина не изменяется, пустое аудио - ошибка
+        let silence = AudioData::new(vec![0.0; 4800], 48000, 1);
+        assert_eq!(AudioProcessor::normalize_loudness(&silence, -16.0).unwrap(), silence);
+        assert!(AudioProcessor::normalize_loudness(&AudioData::new(Vec::new(), 48000, 1), -16.0).is_err());
+    }
 }
\ No newline at end of file
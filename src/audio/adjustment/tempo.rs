@@ -2,7 +2,7 @@
 
 use crate::error::{Error, Result, ErrorType};
 use crate::audio::models::AudioData;
-use crate::logging::{log_info, log_debug};
+use crate::logging::{log_info, log_debug, log_warning};
 use crate::audio::AudioAnalyzer;
 
 /// Алгоритмы изменения темпа
@@ -14,8 +14,29 @@ pub enum TempoAlgorithm {
     Fir,
     /// Алгоритм на основе линейной интерполяции (низкое качество, очень быстрый)
     Linear,
+    /// Растяжение во времени методом WSOLA с сохранением высоты тона
+    Wsola,
 }
 
+impl TempoAlgorithm {
+    /// Сохраняет ли алгоритм высоту тона при изменении темпа
+    ///
+    /// Sinc, FIR и линейный алгоритмы изменяют темп передискретизацией,
+    /// поэтому высота тона меняется вместе с темпом.
+    pub fn preserves_pitch(&self) -> bool {
+        match self {
+            TempoAlgorithm::Sinc | TempoAlgorithm::Fir | TempoAlgorithm::Linear => false,
+            TempoAlgorithm::Wsola => true,
+        }
+    }
+}
+
+/// Допустимое относительное отклонение основной частоты после изменения темпа
+const PITCH_TOLERANCE: f32 = 0.05;
+
+/// Длина кадра WSOLA в миллисекундах
+const WSOLA_FRAME_MS: u32 = 20;
+
 /// Улучшенный корректировщик темпа аудио
 pub struct TempoAdjuster;
 
@@ -60,9 +81,106 @@ impl TempoAdjuster {
             TempoAlgorithm::Sinc => Self::adjust_tempo_sinc(audio, tempo_factor as f32),
             TempoAlgorithm::Fir => Self::adjust_tempo_fir(audio, tempo_factor as f32),
             TempoAlgorithm::Linear => Self::adjust_tempo_linear(audio, tempo_factor as f32),
+            TempoAlgorithm::Wsola => Self::adjust_tempo_wsola(audio, tempo_factor),
         }
     }
 
+    /// Изменяет темп аудио и в отладочных сборках проверяет сохранение высоты тона
+    ///
+    /// Основная частота оценивается до и после обработки; если она сместилась
+    /// больше допустимого, выводится предупреждение. В релизных сборках
+    /// проверка не выполняется.
+    pub fn adjust_tempo_checked(
+        audio: &AudioData,
+        tempo_factor: f64,
+        algorithm: TempoAlgorithm,
+    ) -> Result<AudioData> {
+        let adjusted = Self::adjust_tempo(audio, tempo_factor, algorithm)?;
+
+        if cfg!(debug_assertions) {
+            Self::verify_pitch(audio, &adjusted, algorithm);
+        }
+
+        Ok(adjusted)
+    }
+
+    /// Проверяет, что основная частота обработанного аудио совпадает с исходной
+    ///
+    /// Возвращает `false` и выводит предупреждение, если высота тона сместилась
+    /// больше допустимого. Если основную частоту оценить не удалось, проверка
+    /// считается пройденной.
+    pub fn verify_pitch(original: &AudioData, processed: &AudioData, algorithm: TempoAlgorithm) -> bool {
+        let (before, after) = match (Self::estimate_pitch(original), Self::estimate_pitch(processed)) {
+            (Some(before), Some(after)) => (before, after),
+            _ => return true,
+        };
+
+        let shift = after / before;
+        if (shift - 1.0).abs() > PITCH_TOLERANCE {
+            log_warning(&format!(
+                "Сдвиг высоты тона после изменения темпа ({:?}): {:.1} Гц -> {:.1} Гц (x{:.2})",
+                algorithm, before, after, shift
+            ));
+            return false;
+        }
+
+        true
+    }
+
+    /// Оценивает основную частоту аудио методом автокорреляции
+    ///
+    /// Анализирует первый канал в диапазоне 50 - 1000 Гц; возвращает `None`
+    /// для тишины, слишком коротких или непериодических сигналов.
+    pub fn estimate_pitch(audio: &AudioData) -> Option<f32> {
+        let channels = audio.channels.max(1) as usize;
+        let max_frames = (audio.sample_rate as usize / 5).max(1); // не более 200 мс
+        let frames: Vec<f32> = audio.samples.iter()
+            .step_by(channels)
+            .take(max_frames)
+            .copied()
+            .collect();
+
+        let min_lag = (audio.sample_rate / 1000).max(1) as usize;
+        let max_lag = (audio.sample_rate / 50) as usize;
+        if frames.len() < max_lag * 2 {
+            return None;
+        }
+
+        let energy: f32 = frames.iter().map(|s| s * s).sum();
+        if energy <= f32::EPSILON {
+            return None;
+        }
+
+        let correlation = |lag: usize| -> f32 {
+            frames.iter().zip(&frames[lag..]).map(|(a, b)| a * b).sum::<f32>() / (frames.len() - lag) as f32
+        };
+        let correlations: Vec<f32> = (min_lag..=max_lag).map(correlation).collect();
+
+        // Первый локальный максимум, близкий к глобальному, дает период основного тона
+        let global_max = correlations.iter().cloned().fold(f32::MIN, f32::max);
+        if global_max <= 0.0 {
+            return None;
+        }
+
+        let peak = (1..correlations.len() - 1).find(|&i| {
+            correlations[i] >= global_max * 0.9
+                && correlations[i] >= correlations[i - 1]
+                && correlations[i] >= correlations[i + 1]
+        })?;
+
+        // Уточняем положение пика параболической интерполяцией
+        let (left, center, right) = (correlations[peak - 1], correlations[peak], correlations[peak + 1]);
+        let denominator = left - 2.0 * center + right;
+        let offset = if denominator.abs() > f32::EPSILON {
+            0.5 * (left - right) / denominator
+        } else {
+            0.0
+        };
+
+        let lag = (peak + min_lag) as f32 + offset;
+        Some(audio.sample_rate as f32 / lag)
+    }
+
     /// Подгоняет длительность аудио сегмента под целевую длительность
     ///
     /// # Аргументы
@@ -226,6 +344,93 @@ impl TempoAdjuster {
         ))
     }
 
+    /// Изменяет темп аудио методом WSOLA без изменения высоты тона
+    ///
+    /// Аудио разбивается на перекрывающиеся наполовину кадры с окном Ханна,
+    /// которые берутся из исходного сигнала с шагом, умноженным на
+    /// `tempo_factor`. Положение каждого кадра уточняется в пределах четверти
+    /// кадра по максимуму корреляции с естественным продолжением предыдущего,
+    /// чтобы фазы перекрывающихся кадров совпадали. Положение выбирается по
+    /// сумме каналов и применяется ко всем каналам одинаково.
+    fn adjust_tempo_wsola(audio: &AudioData, tempo_factor: f64) -> Result<AudioData> {
+        log_debug("Применение алгоритма WSOLA");
+
+        let channels = audio.channels.max(1) as usize;
+        let input_frames = audio.samples.len() / channels;
+        let frame_len = ((audio.sample_rate * WSOLA_FRAME_MS / 1000) as usize).max(4) & !1;
+
+        // Слишком короткий сигнал нельзя разбить на кадры
+        if input_frames < frame_len * 2 {
+            log_debug("Аудио короче двух кадров WSOLA, применяется линейная интерполяция");
+            return Self::adjust_tempo_linear(audio, tempo_factor as f32);
+        }
+
+        let synthesis_hop = frame_len / 2;
+        let analysis_hop = synthesis_hop as f64 * tempo_factor;
+        let tolerance = frame_len / 4;
+        let output_frames = (input_frames as f64 / tempo_factor).round() as usize;
+        let last_start = input_frames - frame_len;
+
+        let mono: Vec<f32> = audio.samples.chunks_exact(channels)
+            .map(|frame| frame.iter().sum())
+            .collect();
+        let window: Vec<f32> = (0..frame_len)
+            .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / frame_len as f32).cos())
+            .collect();
+
+        let mut output = vec![0.0f32; (output_frames + frame_len) * channels];
+        let mut weights = vec![0.0f32; output_frames + frame_len];
+        let mut previous: Option<usize> = None;
+        let mut k = 0;
+        while k * synthesis_hop < output_frames {
+            let ideal = ((k as f64 * analysis_hop).round() as usize).min(last_start);
+
+            let start = match previous {
+                None => ideal,
+                Some(previous) => {
+                    // Естественное продолжение предыдущего кадра перекрывается с началом нового
+                    let natural = (previous + synthesis_hop).min(last_start);
+                    let from = ideal.saturating_sub(tolerance);
+                    let to = (ideal + tolerance).min(last_start);
+                    let mut best = (ideal, f32::MIN);
+                    for candidate in from..=to {
+                        let correlation: f32 = mono[natural..natural + synthesis_hop].iter()
+                            .zip(&mono[candidate..candidate + synthesis_hop])
+                            .map(|(a, b)| a * b)
+                            .sum();
+                        if correlation > best.1 {
+                            best = (candidate, correlation);
+                        }
+                    }
+                    best.0
+                }
+            };
+
+            let offset = k * synthesis_hop;
+            for (i, &w) in window.iter().enumerate() {
+                let source = (start + i) * channels;
+                let target = (offset + i) * channels;
+                for channel in 0..channels {
+                    output[target + channel] += audio.samples[source + channel] * w;
+                }
+                weights[offset + i] += w;
+            }
+
+            previous = Some(start);
+            k += 1;
+        }
+
+        // Компенсируем неравномерную сумму окон на краях
+        for (frame, &weight) in output.chunks_exact_mut(channels).zip(&weights) {
+            if weight > 1e-6 {
+                frame.iter_mut().for_each(|sample| *sample /= weight);
+            }
+        }
+        output.truncate(output_frames * channels);
+
+        Ok(AudioData::new(output, audio.sample_rate, audio.channels))
+    }
+
     /// Адаптивное изменение темпа с сохранением пауз
     pub fn adaptive_tempo_adjustment(
         audio: &AudioData,
@@ -358,4 +563,36 @@ mod tests {
             adjusted_duration
         );
     }
+
+    #[test]
+    fn test_wsola_preserves_pitch() {
+        let sample_rate = 16000;
+        let audio = AudioData::new(
+            (0..sample_rate)
+                .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / sample_rate as f32).sin())
+                .collect(),
+            sample_rate,
+            1,
+        );
+        assert!(TempoAlgorithm::Wsola.preserves_pitch());
+
+        // Растяжение и сжатие в 1.5 раза
+        for target_duration in [1.5f32, 1.0 / 1.5] {
+            let adjusted = TempoAdjuster::fit_to_duration(&audio, target_duration, TempoAlgorithm::Wsola).unwrap();
+            assert!((adjusted.duration() as f32 - target_duration).abs() < 1e-3);
+
+            // Кадры складываются в фазе: громкость не проседает
+            let rms = (adjusted.samples.iter().map(|s| s * s).sum::<f32>() / adjusted.samples.len() as f32).sqrt();
+            assert!((rms - 0.5 / 2f32.sqrt()).abs() < 0.02, "RMS после WSOLA: {}", rms);
+
+            // Частота сохраняется в начале и в середине результата
+            let middle = adjusted.samples.len() / 2;
+            for part in [&adjusted.samples[..], &adjusted.samples[middle..]] {
+                let part = AudioData::new(part.to_vec(), sample_rate, 1);
+                let pitch = TempoAdjuster::estimate_pitch(&part).unwrap();
+                assert!((pitch - 440.0).abs() < 440.0 * 0.02, "Частота после WSOLA: {}", pitch);
+            }
+            assert!(TempoAdjuster::verify_pitch(&audio, &adjusted, TempoAlgorithm::Wsola));
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use crate::error::{Error, Result};
-use crate::audio::models::{AudioData, AudioSegment};
+use crate::audio::models::{AudioData, AudioSegment, AudioTrack};
 
 /// Анализатор аудио
 pub struct AudioAnalyzer;
@@ -103,6 +103,122 @@ impl AudioAnalyzer {
         silences
     }
     
+    /// Анализирует весь трек по сегментам, не объединяя их в один буфер
+    ///
+    /// Сегменты с текстом считаются речью, остальное время трека - тишиной.
+    pub fn analyze_track(track: &AudioTrack) -> TrackAnalysis {
+        let mut segments = Vec::with_capacity(track.segments.len());
+        let mut sum_squares = 0.0f64;
+        let mut total_samples = 0usize;
+        let mut peak = 0.0f32;
+
+        for segment in &track.segments {
+            let samples = &segment.audio.samples;
+            let rms = Self::calculate_rms(samples);
+            let segment_peak = Self::find_peak_amplitude(samples);
+
+            sum_squares += (rms as f64).powi(2) * samples.len() as f64;
+            total_samples += samples.len();
+            peak = peak.max(segment_peak);
+
+            segments.push(SegmentLevels {
+                start_time: segment.start_time,
+                end_time: segment.end_time,
+                is_speech: !segment.text.is_empty(),
+                rms,
+                peak: segment_peak,
+            });
+        }
+
+        let start = track.segments.iter().map(|s| s.start_time).fold(f64::INFINITY, f64::min);
+        let end = track.segments.iter().map(|s| s.end_time).fold(f64::NEG_INFINITY, f64::max);
+        let total_duration = if end > start { end - start } else { 0.0 };
+
+        // Пересекающиеся интервалы речи учитываются один раз
+        let mut speech_duration = 0.0;
+        let mut covered_until = f64::NEG_INFINITY;
+        for (region_start, region_end) in track.speech_regions() {
+            let region_start = region_start.max(covered_until);
+            if region_end > region_start {
+                speech_duration += region_end - region_start;
+            }
+            covered_until = covered_until.max(region_end);
+        }
+
+        // Диапазон громкости - разница между самым громким и самым тихим сегментом речи
+        let speech_levels_db: Vec<f32> = segments.iter()
+            .filter(|levels| levels.is_speech && levels.rms > 0.0)
+            .map(|levels| 20.0 * levels.rms.log10())
+            .collect();
+        let loudness_range_db = match (
+            speech_levels_db.iter().cloned().reduce(f32::max),
+            speech_levels_db.iter().cloned().reduce(f32::min),
+        ) {
+            (Some(loudest), Some(quietest)) => loudest - quietest,
+            _ => 0.0,
+        };
+
+        TrackAnalysis {
+            total_duration,
+            speech_duration,
+            silence_duration: (total_duration - speech_duration).max(0.0),
+            rms: if total_samples > 0 { (sum_squares / total_samples as f64).sqrt() as f32 } else { 0.0 },
+            peak,
+            loudness_range_db,
+            segments,
+        }
+    }
+
+    /// Находит самую тихую точку внутри паузы
+    ///
+    /// Энергия считается в скользящем окне 10 мс; точкой разделения выбирается
+    /// середина самого длинного участка с минимальной энергией. Так разрез не
+    /// попадает на затухающий хвост речи при неравномерной паузе.
+    fn quietest_point(audio: &AudioData, silence: &SilenceSegment) -> usize {
+        let channels = audio.channels.max(1) as usize;
+        let samples = &audio.samples[silence.start_sample..silence.end_sample];
+        let window = ((0.01 * audio.sample_rate as f64) as usize * channels)
+            .clamp(1, samples.len().max(1));
+
+        if samples.len() <= window {
+            let middle = (silence.start_sample + silence.end_sample) / 2;
+            return middle - middle % channels;
+        }
+
+        // Энергия каждого окна, вычисляемая скользящей суммой
+        let mut energies = Vec::with_capacity(samples.len() - window + 1);
+        let mut energy: f32 = samples[..window].iter().map(|s| s * s).sum();
+        energies.push(energy);
+        for i in window..samples.len() {
+            energy += samples[i] * samples[i] - samples[i - window] * samples[i - window];
+            energies.push(energy.max(0.0));
+        }
+
+        let min_energy = energies.iter().cloned().fold(f32::INFINITY, f32::min);
+        let tolerance = min_energy * 0.01 + 1e-9;
+
+        // Самый длинный участок окон с минимальной энергией
+        let (mut best_start, mut best_len) = (0, 0);
+        let mut run_start = None;
+        for (i, &energy) in energies.iter().chain(std::iter::once(&f32::INFINITY)).enumerate() {
+            match (energy <= min_energy + tolerance, run_start) {
+                (true, None) => run_start = Some(i),
+                (false, Some(start)) => {
+                    if i - start > best_len {
+                        best_start = start;
+                        best_len = i - start;
+                    }
+                    run_start = None;
+                }
+                _ => {}
+            }
+        }
+
+        // Середина участка, смещенная на половину окна к центру окна
+        let split = silence.start_sample + best_start + (best_len - 1) / 2 + window / 2;
+        split - split % channels
+    }
+    
     /// Анализирует сегмент аудио и определяет оптимальный коэффициент растяжения/сжатия
     pub fn analyze_segment(segment: &AudioSegment, target_duration: f64) -> Result<SegmentAnalysis> {
         if segment.audio.is_empty() {
@@ -146,9 +262,8 @@ impl AudioAnalyzer {
         let mut start_time = segment.start_time;
         
         for silence in &audio_analysis.silences {
-            // Берем середину паузы как точку разделения
-            let split_sample = (silence.start_sample + silence.end_sample) / 2;
-            let _split_time = silence.start_time + (silence.end_time - silence.start_time) / 2.0;
+            // Разделяем в самой тихой части паузы
+            let split_sample = Self::quietest_point(&segment.audio, silence);
             
             // Создаем сегмент до паузы
             let samples = segment.audio.samples[start_sample..split_sample].to_vec();
@@ -250,6 +365,40 @@ pub struct SegmentAnalysis {
     pub should_split: bool,
 }
 
+/// Уровни отдельного сегмента трека
+#[derive(Debug, Clone)]
+pub struct SegmentLevels {
+    /// Время начала сегмента в секундах
+    pub start_time: f64,
+    /// Время окончания сегмента в секундах
+    pub end_time: f64,
+    /// Является ли сегмент речью
+    pub is_speech: bool,
+    /// RMS сегмента
+    pub rms: f32,
+    /// Пиковая амплитуда сегмента
+    pub peak: f32,
+}
+
+/// Результат анализа всего трека
+#[derive(Debug, Clone)]
+pub struct TrackAnalysis {
+    /// Длительность трека в секундах
+    pub total_duration: f64,
+    /// Суммарная длительность речи в секундах
+    pub speech_duration: f64,
+    /// Суммарная длительность тишины в секундах
+    pub silence_duration: f64,
+    /// RMS всего трека
+    pub rms: f32,
+    /// Пиковая амплитуда всего трека
+    pub peak: f32,
+    /// Диапазон громкости речи в дБ (между самым громким и самым тихим сегментом)
+    pub loudness_range_db: f32,
+    /// Уровни по сегментам
+    pub segments: Vec<SegmentLevels>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -290,4 +439,57 @@ mod tests {
         assert_eq!(silences[1].start_sample, 22150);
         assert_eq!(silences[1].end_sample, 44100);
     }
+    
+    #[test]
+    fn test_split_segment_at_quietest_point() {
+        let sample_rate = 44100;
+        let tone = |count: usize| (0..count)
+            .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 220.0 * i as f32 / sample_rate as f32).sin());
+        
+        // Речь, пауза с тихим хвостом 200 мс и полной тишиной 50 мс, снова речь
+        let mut samples: Vec<f32> = tone(13230).collect();
+        samples.extend((0..8820).map(|i| if i % 2 == 0 { 0.01 } else { -0.01 }));
+        samples.extend(std::iter::repeat(0.0).take(2205));
+        samples.extend(tone(13230));
+        
+        let segment = AudioSegment::new(
+            AudioData::new(samples, sample_rate, 1),
+            0.0,
+            0.85,
+            "test".to_string()
+        );
+        
+        let parts = AudioAnalyzer::split_segment(&segment).unwrap();
+        assert_eq!(parts.len(), 2);
+        
+        // Разрез в полной тишине, а не в середине паузы (13230 + 5512)
+        let split = parts[0].audio.samples.len();
+        assert!((22050..22050 + 2205).contains(&split), "Точка разделения: {}", split);
+        assert!((split as i64 - (22050 + 1102)).abs() < 50);
+    }
+    
+    #[test]
+    fn test_analyze_track_totals() {
+        let mut track = AudioTrack::new(1000, 1);
+        track.add_segment(AudioSegment::new(AudioData::new(vec![0.0; 500], 1000, 1), 0.0, 0.5, String::new()));
+        track.add_segment(AudioSegment::new(AudioData::new(vec![0.5; 1000], 1000, 1), 0.5, 1.5, "one".to_string()));
+        track.add_segment(AudioSegment::new(AudioData::new(vec![0.0; 500], 1000, 1), 1.5, 2.0, String::new()));
+        track.add_segment(AudioSegment::new(AudioData::new(vec![-0.05; 1000], 1000, 1), 2.0, 3.0, "two".to_string()));
+        
+        let analysis = AudioAnalyzer::analyze_track(&track);
+        
+        assert!((analysis.total_duration - 3.0).abs() < 1e-9);
+        assert!((analysis.speech_duration - 2.0).abs() < 1e-9);
+        assert!((analysis.silence_duration - 1.0).abs() < 1e-9);
+        assert_eq!(analysis.peak, 0.5);
+        assert_eq!(analysis.segments.len(), 4);
+        assert_eq!(analysis.segments.iter().filter(|s| s.is_speech).count(), 2);
+        
+        // Сегменты речи отличаются по уровню в 10 раз - 20 дБ
+        assert!((analysis.loudness_range_db - 20.0).abs() < 1e-3);
+        
+        // RMS по всем сэмплам трека
+        let expected_rms = ((0.25 * 1000.0 + 0.0025 * 1000.0) / 3000.0f64).sqrt() as f32;
+        assert!((analysis.rms - expected_rms).abs() < 1e-6);
+    }
 }
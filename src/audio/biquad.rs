@@ -0,0 +1,88 @@
+//! Биквадратные фильтры (коэффициенты по Robert Bristow-Johnson, Audio EQ Cookbook).
+
+use std::f64::consts::PI;
+
+/// Биквадратный фильтр (прямая форма I)
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+}
+
+impl Biquad {
+    /// Создает фильтр по коэффициентам передаточной функции, нормируя их на `a[0]`
+    pub(crate) fn new(b: [f64; 3], a: [f64; 3]) -> Self {
+        Self {
+            b: [b[0] / a[0], b[1] / a[0], b[2] / a[0]],
+            a: [a[1] / a[0], a[2] / a[0]],
+        }
+    }
+
+    /// Низкочастотная полка: усиление `gain_db` ниже частоты `freq`
+    ///
+    /// `freq` - середина перехода, где усиление равно половине `gain_db`.
+    pub(crate) fn low_shelf(sample_rate: f64, freq: f64, gain_db: f64, q: f64) -> Self {
+        let (a, cos, alpha) = Self::params(sample_rate, freq, gain_db, q);
+        let sqrt_a = 2.0 * a.sqrt() * alpha;
+        Self::new(
+            [
+                a * ((a + 1.0) - (a - 1.0) * cos + sqrt_a),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                a * ((a + 1.0) - (a - 1.0) * cos - sqrt_a),
+            ],
+            [
+                (a + 1.0) + (a - 1.0) * cos + sqrt_a,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                (a + 1.0) + (a - 1.0) * cos - sqrt_a,
+            ],
+        )
+    }
+
+    /// Высокочастотная полка: усиление `gain_db` выше частоты `freq`
+    pub(crate) fn high_shelf(sample_rate: f64, freq: f64, gain_db: f64, q: f64) -> Self {
+        let (a, cos, alpha) = Self::params(sample_rate, freq, gain_db, q);
+        let sqrt_a = 2.0 * a.sqrt() * alpha;
+        Self::new(
+            [
+                a * ((a + 1.0) + (a - 1.0) * cos + sqrt_a),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                a * ((a + 1.0) + (a - 1.0) * cos - sqrt_a),
+            ],
+            [
+                (a + 1.0) - (a - 1.0) * cos + sqrt_a,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                (a + 1.0) - (a - 1.0) * cos - sqrt_a,
+            ],
+        )
+    }
+
+    /// Колоколообразный (пиковый) фильтр с усилением `gain_db` на частоте `freq`
+    pub(crate) fn peaking(sample_rate: f64, freq: f64, gain_db: f64, q: f64) -> Self {
+        let (a, cos, alpha) = Self::params(sample_rate, freq, gain_db, q);
+        Self::new(
+            [1.0 + alpha * a, -2.0 * cos, 1.0 - alpha * a],
+            [1.0 + alpha / a, -2.0 * cos, 1.0 - alpha / a],
+        )
+    }
+
+    /// Общие параметры формул: амплитуда `A`, косинус и `alpha` центральной частоты
+    fn params(sample_rate: f64, freq: f64, gain_db: f64, q: f64) -> (f64, f64, f64) {
+        let w0 = 2.0 * PI * freq / sample_rate;
+        (10f64.powf(gain_db / 40.0), w0.cos(), w0.sin() / (2.0 * q))
+    }
+
+    /// Фильтрует сигнал
+    pub(crate) fn process(&self, input: &[f64]) -> Vec<f64> {
+        let (mut x1, mut x2, mut y1, mut y2) = (0.0, 0.0, 0.0, 0.0);
+        input.iter()
+            .map(|&x| {
+                let y = self.b[0] * x + self.b[1] * x1 + self.b[2] * x2 - self.a[0] * y1 - self.a[1] * y2;
+                x2 = x1;
+                x1 = x;
+                y2 = y1;
+                y1 = y;
+                y
+            })
+            .collect()
+    }
+}
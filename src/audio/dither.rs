@@ -0,0 +1,82 @@
+//! Дизеринг при преобразовании float сэмплов в 16-битный PCM.
+
+/// Тип дизеринга при экспорте в 16-битный PCM
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DitherType {
+    /// Без дизеринга (сэмплы округляются до ближайшего значения)
+    #[default]
+    None,
+    /// Прямоугольный шум амплитудой ±0.5 младшего разряда
+    Rectangular,
+    /// Треугольный шум (TPDF) амплитудой ±1 младший разряд
+    Triangular,
+    /// Треугольный шум с формированием спектра ошибки квантования:
+    /// шум смещается в область высоких частот, где он менее заметен
+    NoiseShaped,
+}
+
+/// Масштаб 16-битного сэмпла: 1.0 соответствует 32767, а -1.0 - -32767,
+/// поэтому шкала симметрична и не переполняется
+const PCM16_SCALE: f32 = 32767.0;
+
+/// Квантователь float сэмплов в 16-битный PCM с дизерингом
+pub(crate) struct Quantizer {
+    dither: DitherType,
+    /// Состояние генератора псевдослучайных чисел (xorshift32)
+    rng_state: u32,
+    /// Ошибка квантования предыдущего сэмпла по каждому каналу
+    errors: Vec<f32>,
+}
+
+impl Quantizer {
+    /// Создает квантователь для указанного количества каналов
+    pub(crate) fn new(dither: DitherType, channels: u16) -> Self {
+        Self {
+            dither,
+            rng_state: 0x9E37_79B9,
+            errors: vec![0.0; channels.max(1) as usize],
+        }
+    }
+
+    /// Квантует сэмпл канала `channel`
+    pub(crate) fn quantize(&mut self, sample: f32, channel: usize) -> i16 {
+        let scaled = sample.clamp(-1.0, 1.0) * PCM16_SCALE;
+
+        match self.dither {
+            DitherType::None => Self::round_to_i16(scaled),
+            DitherType::Rectangular => {
+                let noise = self.uniform();
+                Self::round_to_i16(scaled + noise)
+            }
+            DitherType::Triangular => {
+                let noise = self.uniform() + self.uniform();
+                Self::round_to_i16(scaled + noise)
+            }
+            DitherType::NoiseShaped => {
+                // Обратная связь по ошибке первого порядка: спектр ошибки
+                // умножается на (1 - z^-1) и смещается к высоким частотам
+                let target = scaled - self.errors[channel];
+                let noise = self.uniform() + self.uniform();
+                let quantized = Self::round_to_i16(target + noise);
+                self.errors[channel] = quantized as f32 - target;
+                quantized
+            }
+        }
+    }
+
+    /// Равномерно распределенное число в диапазоне [-0.5, 0.5)
+    fn uniform(&mut self) -> f32 {
+        let mut x = self.rng_state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.rng_state = x;
+        (x >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+    }
+
+    /// Округляет до ближайшего значения в симметричной шкале: шум дизеринга
+    /// у полной шкалы не выводит сэмпл -1.0 за -32767
+    fn round_to_i16(value: f32) -> i16 {
+        value.round().clamp(-PCM16_SCALE, PCM16_SCALE) as i16
+    }
+}
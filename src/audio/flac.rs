@@ -0,0 +1,309 @@
+//! Кодирование FLAC (16 бит) без внешних программ.
+//!
+//! Каждый канал кадра кодируется отдельно: постоянным значением, одним из
+//! фиксированных предсказателей порядка 0-4 с остатками в коде Райса или
+//! без сжатия, если так короче. MD5 сигнала не вычисляется (нулевое
+//! значение в STREAMINFO допускается форматом).
+
+use crate::audio::metadata::{OutputMetadata, VENDOR};
+
+/// Количество сэмплов канала в одном кадре
+const BLOCK_SIZE: usize = 4096;
+
+/// Разрядность сэмплов
+const BITS_PER_SAMPLE: u32 = 16;
+
+/// Максимальный параметр Райса при 4-битном кодировании параметра
+const MAX_RICE_PARAMETER: u32 = 14;
+
+/// Максимальный порядок фиксированного предсказателя
+const MAX_FIXED_ORDER: usize = 4;
+
+/// Кодирует перемеженные по кадрам 16-битные сэмплы в файл FLAC
+///
+/// Количество сэмплов должно быть кратно количеству каналов (1 - 8).
+/// Заданные поля `metadata` записываются в блок VORBIS_COMMENT.
+pub(crate) fn encode_flac(samples: &[i16], sample_rate: u32, channels: u16, metadata: &OutputMetadata) -> Vec<u8> {
+    let channels = channels.clamp(1, 8) as usize;
+    let frames = samples.len() / channels;
+
+    let mut output = b"fLaC".to_vec();
+    let comment = vorbis_comment(metadata);
+    write_metadata_header(&mut output, 0, 34, comment.is_none());
+    output.extend(stream_info(sample_rate, channels, frames));
+    if let Some(comment) = comment {
+        write_metadata_header(&mut output, 4, comment.len(), true);
+        output.extend(comment);
+    }
+
+    let mut channel = Vec::with_capacity(BLOCK_SIZE);
+    for (number, block) in samples[..frames * channels].chunks(BLOCK_SIZE * channels).enumerate() {
+        let block_size = block.len() / channels;
+        let mut writer = BitWriter::new();
+        write_frame_header(&mut writer, number as u32, block_size, channels);
+        for index in 0..channels {
+            channel.clear();
+            channel.extend(block.iter().skip(index).step_by(channels).map(|&sample| sample as i32));
+            write_subframe(&mut writer, &channel);
+        }
+        let mut frame = writer.into_bytes();
+        let crc = crc16(&frame);
+        frame.extend_from_slice(&crc.to_be_bytes());
+        output.extend(frame);
+    }
+    output
+}
+
+/// Записывает заголовок блока метаданных
+fn write_metadata_header(output: &mut Vec<u8>, block_type: u8, length: usize, last: bool) {
+    output.push((if last { 0x80 } else { 0 }) | block_type);
+    output.extend_from_slice(&(length as u32).to_be_bytes()[1..]);
+}
+
+/// Возвращает тело блока STREAMINFO
+fn stream_info(sample_rate: u32, channels: usize, frames: usize) -> Vec<u8> {
+    let block_size = frames.clamp(16, BLOCK_SIZE) as u16;
+    let mut writer = BitWriter::new();
+    writer.write(block_size as u64, 16);
+    writer.write(block_size as u64, 16);
+    // Минимальный и максимальный размер кадра неизвестны
+    writer.write(0, 24);
+    writer.write(0, 24);
+    writer.write(sample_rate as u64, 20);
+    writer.write(channels as u64 - 1, 3);
+    writer.write(BITS_PER_SAMPLE as u64 - 1, 5);
+    writer.write(frames as u64, 36);
+    let mut body = writer.into_bytes();
+    // MD5 не вычисляется
+    body.extend_from_slice(&[0; 16]);
+    body
+}
+
+/// Возвращает тело блока VORBIS_COMMENT или `None`, если метаданных нет
+fn vorbis_comment(metadata: &OutputMetadata) -> Option<Vec<u8>> {
+    if metadata.is_empty() {
+        return None;
+    }
+    let comments: Vec<String> = metadata.vorbis_comments()
+        .into_iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect();
+
+    let mut body = Vec::new();
+    body.extend_from_slice(&(VENDOR.len() as u32).to_le_bytes());
+    body.extend_from_slice(VENDOR.as_bytes());
+    body.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+    for comment in comments {
+        body.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+        body.extend_from_slice(comment.as_bytes());
+    }
+    Some(body)
+}
+
+/// Записывает заголовок кадра с фиксированным размером блока
+fn write_frame_header(writer: &mut BitWriter, number: u32, block_size: usize, channels: usize) {
+    // Синхрокод, зарезервированный бит и фиксированный размер блока
+    writer.write(0b1111_1111_1111_1000, 16);
+    // Размер блока - 16 бит в конце заголовка, частота - из STREAMINFO
+    writer.write(0b0111, 4);
+    writer.write(0b0000, 4);
+    // Независимые каналы, 16 бит на сэмпл
+    writer.write(channels as u64 - 1, 4);
+    writer.write(0b100, 3);
+    writer.write(0, 1);
+    write_utf8_number(writer, number);
+    writer.write(block_size as u64 - 1, 16);
+    let crc = crc8(writer.bytes());
+    writer.write(crc as u64, 8);
+}
+
+/// Записывает номер кадра в кодировке, аналогичной UTF-8
+fn write_utf8_number(writer: &mut BitWriter, number: u32) {
+    if number < 0x80 {
+        writer.write(number as u64, 8);
+        return;
+    }
+    let continuation_bytes = match number {
+        0..=0x7FF => 1,
+        0x800..=0xFFFF => 2,
+        0x1_0000..=0x1F_FFFF => 3,
+        0x20_0000..=0x3FF_FFFF => 4,
+        _ => 5,
+    };
+    let prefix = (0xFF00u32 >> (continuation_bytes + 1)) & 0xFF;
+    writer.write((prefix | (number >> (6 * continuation_bytes))) as u64, 8);
+    for shift in (0..continuation_bytes).rev() {
+        writer.write((0x80 | ((number >> (6 * shift)) & 0x3F)) as u64, 8);
+    }
+}
+
+/// Записывает подкадр одного канала, выбирая самый короткий способ кодирования
+fn write_subframe(writer: &mut BitWriter, samples: &[i32]) {
+    if samples.iter().all(|&sample| sample == samples[0]) {
+        writer.write(0b0000_0000, 8);
+        writer.write_signed(samples[0], BITS_PER_SAMPLE);
+        return;
+    }
+
+    let verbatim_bits = samples.len() as u64 * BITS_PER_SAMPLE as u64;
+    let best = (0..=MAX_FIXED_ORDER.min(samples.len() - 1))
+        .map(|order| {
+            let residuals = fixed_residuals(samples, order);
+            let (parameter, bits) = best_rice_parameter(&residuals);
+            (order, residuals, parameter, bits + (order as u64 * BITS_PER_SAMPLE as u64))
+        })
+        .min_by_key(|&(_, _, _, bits)| bits);
+
+    match best {
+        Some((order, residuals, parameter, bits)) if bits < verbatim_bits => {
+            writer.write(0b0001_0000 | (order as u64) << 1, 8);
+            for &sample in &samples[..order] {
+                writer.write_signed(sample, BITS_PER_SAMPLE);
+            }
+            // Метод кодирования остатков 0 (4-битный параметр), один раздел
+            writer.write(0, 2);
+            writer.write(0, 4);
+            writer.write(parameter as u64, 4);
+            for &residual in &residuals {
+                writer.write_rice(zigzag(residual), parameter);
+            }
+        }
+        _ => {
+            writer.write(0b0000_0010, 8);
+            for &sample in samples {
+                writer.write_signed(sample, BITS_PER_SAMPLE);
+            }
+        }
+    }
+}
+
+/// Возвращает остатки фиксированного предсказателя порядка `order`
+fn fixed_residuals(samples: &[i32], order: usize) -> Vec<i32> {
+    (order..samples.len())
+        .map(|i| {
+            let s = |offset: usize| samples[i - offset];
+            match order {
+                0 => s(0),
+                1 => s(0) - s(1),
+                2 => s(0) - 2 * s(1) + s(2),
+                3 => s(0) - 3 * s(1) + 3 * s(2) - s(3),
+                _ => s(0) - 4 * s(1) + 6 * s(2) - 4 * s(3) + s(4),
+            }
+        })
+        .collect()
+}
+
+/// Подбирает параметр Райса с наименьшим размером остатков в битах
+fn best_rice_parameter(residuals: &[i32]) -> (u32, u64) {
+    (0..=MAX_RICE_PARAMETER)
+        .map(|parameter| {
+            let bits = residuals.iter()
+                .map(|&residual| (zigzag(residual) >> parameter) as u64 + 1 + parameter as u64)
+                .sum::<u64>();
+            (parameter, bits)
+        })
+        .min_by_key(|&(_, bits)| bits)
+        .unwrap_or((0, 0))
+}
+
+/// Переводит знаковый остаток в беззнаковый (0, -1, 1, -2, ... -> 0, 1, 2, 3, ...)
+fn zigzag(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+/// CRC-8 заголовка кадра (полином x^8 + x^2 + x + 1)
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |crc, &byte| {
+        (0..8).fold(crc ^ byte, |crc, _| if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 })
+    })
+}
+
+/// CRC-16 кадра (полином x^16 + x^15 + x^2 + 1)
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0u16, |crc, &byte| {
+        (0..8).fold(crc ^ ((byte as u16) << 8), |crc, _| if crc & 0x8000 != 0 { (crc << 1) ^ 0x8005 } else { crc << 1 })
+    })
+}
+
+/// Запись битового потока (старшие биты первыми)
+struct BitWriter {
+    bytes: Vec<u8>,
+    accumulator: u64,
+    pending_bits: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            accumulator: 0,
+            pending_bits: 0,
+        }
+    }
+
+    /// Записывает младшие `bits` бит значения
+    fn write(&mut self, value: u64, bits: u32) {
+        if bits > 32 {
+            self.write(value >> 32, bits - 32);
+            self.write(value & 0xFFFF_FFFF, 32);
+            return;
+        }
+        self.accumulator = (self.accumulator << bits) | (value & ((1u64 << bits) - 1));
+        self.pending_bits += bits;
+        while self.pending_bits >= 8 {
+            self.pending_bits -= 8;
+            self.bytes.push((self.accumulator >> self.pending_bits) as u8);
+        }
+    }
+
+    /// Записывает знаковое значение в дополнительном коде
+    fn write_signed(&mut self, value: i32, bits: u32) {
+        self.write(value as u32 as u64, bits);
+    }
+
+    /// Записывает значение кодом Райса: частное в унарном коде и `parameter` младших бит
+    fn write_rice(&mut self, value: u32, parameter: u32) {
+        let mut quotient = value >> parameter;
+        while quotient >= 32 {
+            self.write(0, 32);
+            quotient -= 32;
+        }
+        self.write(1, quotient + 1);
+        self.write(value as u64, parameter);
+    }
+
+    /// Возвращает уже записанные целые байты
+    fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Дополняет поток нулями до целого байта и возвращает его
+    fn into_bytes(mut self) -> Vec<u8> {
+        if self.pending_bits > 0 {
+            self.write(0, 8 - self.pending_bits);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utf8_number_and_crc() {
+        let encode = |number: u32| {
+            let mut writer = BitWriter::new();
+            write_utf8_number(&mut writer, number);
+            writer.into_bytes()
+        };
+        assert_eq!(encode(0x41), [0x41]);
+        assert_eq!(encode(0x7FF), [0xDF, 0xBF]);
+        assert_eq!(encode(0x800), [0xE0, 0xA0, 0x80]);
+        assert_eq!(encode(0x1_0000), [0xF0, 0x90, 0x80, 0x80]);
+
+        // Контрольные значения CRC для строки "123456789"
+        assert_eq!(crc8(b"123456789"), 0xF4);
+        assert_eq!(crc16(b"123456789"), 0xFEE8);
+    }
+}
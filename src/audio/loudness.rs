@@ -0,0 +1,143 @@
+//! Измерение интегральной громкости по ITU-R BS.1770 (EBU R128).
+
+use crate::audio::biquad::Biquad;
+use crate::audio::models::AudioData;
+
+/// Длительность блока измерения в секундах
+const BLOCK_SECONDS: f64 = 0.4;
+
+/// Шаг блоков измерения в секундах (перекрытие 75%)
+const STEP_SECONDS: f64 = 0.1;
+
+/// Абсолютный порог стробирования в LUFS
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+
+/// Относительный порог стробирования в LU ниже громкости, прошедшей абсолютный порог
+const RELATIVE_GATE_LU: f64 = 10.0;
+
+/// Первая ступень K-взвешивания: полка, поднимающая высокие частоты на ~4 дБ
+fn k_weighting_shelf(sample_rate: f64) -> Biquad {
+    let gain_db = 3.999_843_853_973_347;
+    let q = 0.707_175_236_955_419_3;
+    let fc = 1_681.974_450_955_532;
+
+    let k = (std::f64::consts::PI * fc / sample_rate).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.499_666_774_154_541_6);
+    Biquad::new(
+        [vh + vb * k / q + k * k, 2.0 * (k * k - vh), vh - vb * k / q + k * k],
+        [1.0 + k / q + k * k, 2.0 * (k * k - 1.0), 1.0 - k / q + k * k],
+    )
+}
+
+/// Вторая ступень K-взвешивания: фильтр верхних частот (RLB)
+fn k_weighting_high_pass(sample_rate: f64) -> Biquad {
+    let q = 0.500_327_037_325_395_3;
+    let fc = 38.135_470_876_139_82;
+
+    let k = (std::f64::consts::PI * fc / sample_rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    // Числитель не нормируется на a0, как в эталонной реализации
+    Biquad::new(
+        [a0, -2.0 * a0, a0],
+        [a0, 2.0 * (k * k - 1.0), 1.0 - k / q + k * k],
+    )
+}
+
+/// Переводит суммарную среднеквадратичную мощность каналов в LUFS
+fn to_lufs(power: f64) -> f64 {
+    -0.691 + 10.0 * power.log10()
+}
+
+/// Измеряет интегральную громкость аудио в LUFS
+///
+/// Каждый канал взвешивается K-фильтром, мощность считается по блокам
+/// 400 мс с перекрытием 75% и суммируется по каналам с весом 1 (объемные
+/// каналы не выделяются). Блоки тише -70 LUFS и блоки более чем на 10 LU
+/// тише громкости, прошедшей абсолютный порог, отбрасываются. Аудио короче
+/// одного блока измеряется целиком. Возвращает `None` для пустого аудио и
+/// тишины.
+pub(crate) fn integrated_loudness(audio: &AudioData) -> Option<f64> {
+    let channels = audio.channels.max(1) as usize;
+    let frames = audio.samples.len() / channels;
+    if frames == 0 || audio.sample_rate == 0 {
+        return None;
+    }
+
+    let sample_rate = audio.sample_rate as f64;
+    let shelf = k_weighting_shelf(sample_rate);
+    let high_pass = k_weighting_high_pass(sample_rate);
+
+    // Квадраты K-взвешенного сигнала, сложенные по каналам
+    let mut power = vec![0.0f64; frames];
+    for channel in 0..channels {
+        let signal: Vec<f64> = audio.samples.iter()
+            .skip(channel)
+            .step_by(channels)
+            .take(frames)
+            .map(|&sample| sample as f64)
+            .collect();
+        let weighted = high_pass.process(&shelf.process(&signal));
+        for (total, sample) in power.iter_mut().zip(weighted) {
+            *total += sample * sample;
+        }
+    }
+
+    let block = ((BLOCK_SECONDS * sample_rate).round() as usize).clamp(1, frames);
+    let step = ((STEP_SECONDS * sample_rate).round() as usize).max(1);
+
+    // Накопленные суммы позволяют получить мощность любого блока за O(1)
+    let mut cumulative = Vec::with_capacity(frames + 1);
+    cumulative.push(0.0f64);
+    for value in &power {
+        cumulative.push(cumulative.last().unwrap() + value);
+    }
+    let blocks: Vec<f64> = (0..=(frames - block) / step)
+        .map(|i| (cumulative[i * step + block] - cumulative[i * step]) / block as f64)
+        .collect();
+
+    let gated_mean = |threshold: f64| -> Option<f64> {
+        let gated: Vec<f64> = blocks.iter().copied()
+            .filter(|&block_power| block_power > 0.0 && to_lufs(block_power) > threshold)
+            .collect();
+        if gated.is_empty() {
+            None
+        } else {
+            Some(gated.iter().sum::<f64>() / gated.len() as f64)
+        }
+    };
+
+    let relative_gate = to_lufs(gated_mean(ABSOLUTE_GATE_LUFS)?) - RELATIVE_GATE_LU;
+    gated_mean(relative_gate.max(ABSOLUTE_GATE_LUFS)).map(to_lufs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(frequency: f32, amplitude: f32, seconds: f32, sample_rate: u32) -> AudioData {
+        let samples = (0..(seconds * sample_rate as f32) as usize)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * frequency * i as f32 / sample_rate as f32).sin())
+            .collect();
+        AudioData::new(samples, sample_rate, 1)
+    }
+
+    #[test]
+    fn test_integrated_loudness_reference_levels() {
+        // Синус 997 Гц с полной амплитудой в одном канале: -3.01 LUFS
+        for sample_rate in [44100, 48000] {
+            let loudness = integrated_loudness(&sine(997.0, 1.0, 2.0, sample_rate)).unwrap();
+            assert!((loudness + 3.01).abs() < 0.05, "{} Гц: {:.3} LUFS", sample_rate, loudness);
+        }
+
+        // Тихая часть ниже относительного порога почти не занижает громкость
+        // (без стробирования получилось бы около -5.4 LUFS)
+        let mut audio = sine(997.0, 1.0, 2.0, 48000);
+        audio.samples.extend(sine(997.0, 0.01, 2.0, 48000).samples);
+        let loudness = integrated_loudness(&audio).unwrap();
+        assert!((loudness + 3.01).abs() < 0.5, "{:.3} LUFS", loudness);
+
+        assert_eq!(integrated_loudness(&AudioData::new(vec![0.0; 48000], 48000, 1)), None);
+        assert_eq!(integrated_loudness(&AudioData::new(Vec::new(), 48000, 1)), None);
+    }
+}
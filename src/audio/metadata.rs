@@ -0,0 +1,118 @@
+//! Метаданные (теги) выходного аудио файла.
+
+/// Метаданные, записываемые в выходной файл
+///
+/// Записываются в MP3 (ID3), в комментарии Vorbis файлов FLAC, OGG и Opus,
+/// а также в MP3, собранный напрямую из данных провайдера. WAV сохраняется
+/// без тегов.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutputMetadata {
+    /// Название
+    pub title: Option<String>,
+    /// Комментарий
+    pub comment: Option<String>,
+}
+
+impl OutputMetadata {
+    /// Создает пустые метаданные
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Устанавливает название
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Устанавливает комментарий
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    /// Проверяет, что ни одно поле не задано
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.comment.is_none()
+    }
+
+    /// Возвращает аргументы ffmpeg (`-metadata key=value`) для заданных полей
+    pub(crate) fn ffmpeg_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        for (key, value) in [("title", &self.title), ("comment", &self.comment)] {
+            if let Some(value) = value {
+                args.push("-metadata".to_string());
+                args.push(format!("{}={}", key, value));
+            }
+        }
+        args
+    }
+
+    /// Возвращает заданные поля как пары ключ - значение комментариев Vorbis
+    pub(crate) fn vorbis_comments(&self) -> Vec<(&'static str, &str)> {
+        [("TITLE", &self.title), ("COMMENT", &self.comment)]
+            .into_iter()
+            .filter_map(|(key, value)| value.as_deref().map(|value| (key, value)))
+            .collect()
+    }
+
+    /// Кодирует метаданные в тег ID3v2.3 для начала MP3 файла
+    ///
+    /// Текст записывается в UTF-16 с BOM, который поддерживают все
+    /// распространенные проигрыватели.
+    pub(crate) fn id3v2_tag(&self) -> Vec<u8> {
+        let mut frames = Vec::new();
+        if let Some(title) = &self.title {
+            let mut body = vec![ID3_ENCODING_UTF16];
+            body.extend(utf16_with_bom(title));
+            push_id3_frame(&mut frames, b"TIT2", &body);
+        }
+        if let Some(comment) = &self.comment {
+            // Кодировка, язык, пустое краткое описание с терминатором и текст
+            let mut body = vec![ID3_ENCODING_UTF16];
+            body.extend_from_slice(b"eng");
+            body.extend(utf16_with_bom(""));
+            body.extend_from_slice(&[0, 0]);
+            body.extend(utf16_with_bom(comment));
+            push_id3_frame(&mut frames, b"COMM", &body);
+        }
+
+        let mut tag = Vec::with_capacity(10 + frames.len());
+        tag.extend_from_slice(b"ID3");
+        tag.extend_from_slice(&[3, 0, 0]);
+        tag.extend_from_slice(&syncsafe(frames.len() as u32));
+        tag.extend(frames);
+        tag
+    }
+}
+
+/// Строка производителя в комментариях Vorbis (FLAC, Opus)
+pub(crate) const VENDOR: &str = concat!("tts-sync ", env!("CARGO_PKG_VERSION"));
+
+/// Код кодировки текста UTF-16 с BOM в кадрах ID3v2
+const ID3_ENCODING_UTF16: u8 = 1;
+
+/// Кодирует строку в UTF-16LE с BOM
+fn utf16_with_bom(text: &str) -> Vec<u8> {
+    let mut bytes = vec![0xFF, 0xFE];
+    bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+    bytes
+}
+
+/// Добавляет кадр ID3v2.3 (размер кадра - обычное 32-битное число)
+fn push_id3_frame(tag: &mut Vec<u8>, id: &[u8; 4], body: &[u8]) {
+    tag.extend_from_slice(id);
+    tag.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    tag.extend_from_slice(&[0, 0]);
+    tag.extend_from_slice(body);
+}
+
+/// Кодирует размер тега в 4 байта по 7 бит (syncsafe)
+fn syncsafe(size: u32) -> [u8; 4] {
+    [
+        ((size >> 21) & 0x7F) as u8,
+        ((size >> 14) & 0x7F) as u8,
+        ((size >> 7) & 0x7F) as u8,
+        (size & 0x7F) as u8,
+    ]
+}
@@ -1,13 +1,36 @@
 mod models;
+mod dither;
+mod metadata;
 mod analysis;
+mod biquad;
+mod flac;
+mod loudness;
+mod mp3;
+#[cfg(feature = "opus")]
+mod opus;
+mod project;
+mod raw_pcm;
+mod sink;
+#[cfg(feature = "vorbis")]
+mod vorbis;
 pub mod adjustment;
 pub mod utils;
 
-pub use models::{AudioData, AudioSegment, AudioTrack};
+pub use models::{AudioData, AudioSegment, AudioTrack, MergePolicy};
+pub(crate) use models::pcm16_wav_header;
+pub(crate) use mp3::{validate_mp3_bitrate, DEFAULT_MP3_BITRATE};
+pub use dither::DitherType;
+pub use metadata::OutputMetadata;
+pub use raw_pcm::{Endianness, RawPcmSpec};
+pub use sink::AudioSink;
+#[cfg(feature = "playback")]
+pub use sink::PlaybackSink;
+pub(crate) use sink::SinkWriter;
 pub use analysis::{
-    AudioAnalyzer, AudioAnalysis, SegmentAnalysis, SilenceSegment
+    AudioAnalyzer, AudioAnalysis, SegmentAnalysis, SilenceSegment, SegmentLevels, TrackAnalysis
 };
 pub use adjustment::tempo::{TempoAdjuster, TempoAlgorithm};
 pub use adjustment::synchronizer::AudioSynchronizer;
 pub use adjustment::processor::AudioProcessor;
-pub use utils::{decode_mp3_to_samples};
+pub(crate) use adjustment::processor::validate_keep_original_ranges;
+pub use utils::{decode_mp3_to_samples, decode_raw_tts_audio, decode_tts_audio, decode_tts_audio_with_limit, SampleRateMismatch};
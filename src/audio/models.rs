@@ -1,11 +1,19 @@
-use crate::error::{Error, Result};
+use crate::audio::adjustment::processor::{AudioProcessor, DEFAULT_LIMITER_RELEASE_MS};
+use crate::audio::adjustment::tempo::{TempoAdjuster, TempoAlgorithm};
+use crate::audio::dither::{DitherType, Quantizer};
+use crate::audio::flac::encode_flac;
+use crate::audio::metadata::OutputMetadata;
+use crate::audio::mp3::{encode_mp3, DEFAULT_MP3_BITRATE};
+use crate::error::{Error, ErrorType, Result};
 use std::path::Path;
 use crate::logging::{log_debug, log_info, log_warning};
+use serde::{Deserialize, Serialize};
 
 /// Аудио данные
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AudioData {
     /// Аудио сэмплы (моно)
+    #[serde(with = "crate::audio::project::samples_base64")]
     pub samples: Vec<f32>,
     /// Частота дискретизации
     pub sample_rate: u32,
@@ -55,29 +63,475 @@ impl AudioData {
         }
     }
 
+    /// Возвращает копию аудио с нормализованной громкостью
+    ///
+    /// Не изменяющий вариант [`AudioData::normalize`].
+    pub fn normalized(&self, target_peak: f32) -> Self {
+        let mut result = self.clone();
+        result.normalize(target_peak);
+        result
+    }
+
+    /// Преобразует сэмплы в 16-битный PCM (little-endian)
+    ///
+    /// Значения вне диапазона -1.0..=1.0 ограничиваются; возвращает байты
+    /// PCM и количество ограниченных (клиппированных) сэмплов.
+    pub fn to_pcm16_le(&self) -> (Vec<u8>, usize) {
+        self.to_pcm16_le_dithered(DitherType::None)
+    }
+
+    /// Преобразует сэмплы в 16-битный PCM (little-endian) с указанным дизерингом
+    pub fn to_pcm16_le_dithered(&self, dither: DitherType) -> (Vec<u8>, usize) {
+        let channels = self.channels.max(1) as usize;
+        let mut quantizer = Quantizer::new(dither, self.channels);
+        let mut bytes = Vec::with_capacity(self.samples.len() * 2);
+        let mut clipped = 0;
+
+        for (i, &sample) in self.samples.iter().enumerate() {
+            if !(-1.0..=1.0).contains(&sample) {
+                clipped += 1;
+            }
+            let pcm_sample = quantizer.quantize(sample, i % channels);
+            bytes.extend_from_slice(&pcm_sample.to_le_bytes());
+        }
+
+        (bytes, clipped)
+    }
+
+    /// Кодирует аудио в WAV (16-битный PCM) в памяти
+    pub fn to_wav_bytes(&self) -> Vec<u8> {
+        let (pcm_data, _) = self.to_pcm16_le();
+        let mut bytes = pcm16_wav_header(self.sample_rate, self.channels, pcm_data.len() as u32);
+        bytes.extend_from_slice(&pcm_data);
+        bytes
+    }
+
+    /// Кодирует аудио в FLAC (16 бит) в памяти
+    pub fn to_flac_bytes(&self) -> Vec<u8> {
+        self.to_flac_bytes_with(DitherType::None, &OutputMetadata::new()).0
+    }
+
+    /// Кодирует аудио в FLAC с дизерингом и тегами, возвращая также
+    /// количество ограниченных сэмплов
+    pub(crate) fn to_flac_bytes_with(&self, dither: DitherType, metadata: &OutputMetadata) -> (Vec<u8>, usize) {
+        let (pcm_data, clipped) = self.to_pcm16_le_dithered(dither);
+        let samples: Vec<i16> = pcm_data.chunks_exact(2)
+            .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]))
+            .collect();
+        (encode_flac(&samples, self.sample_rate, self.channels, metadata), clipped)
+    }
+
+    /// Кодирует аудио в MP3 с постоянным битрейтом `bitrate` (кбит/с) в памяти
+    ///
+    /// Использует LAME и требует функции `mp3-encoder`; без нее возвращает
+    /// ошибку. Поддерживаются моно и стерео.
+    pub fn to_mp3_bytes(&self, bitrate: u32) -> Result<Vec<u8>> {
+        self.to_mp3_bytes_with(bitrate, &OutputMetadata::new())
+    }
+
+    /// Кодирует аудио в MP3, добавляя тег ID3v2 с заданными метаданными
+    pub(crate) fn to_mp3_bytes_with(&self, bitrate: u32, metadata: &OutputMetadata) -> Result<Vec<u8>> {
+        let mut bytes = if metadata.is_empty() { Vec::new() } else { metadata.id3v2_tag() };
+        bytes.extend(encode_mp3(&self.samples, self.sample_rate, self.channels, bitrate)?);
+        Ok(bytes)
+    }
+
+    /// Кодирует аудио в OGG (Vorbis, VBR с качеством 0.6) в памяти
+    #[cfg(feature = "vorbis")]
+    pub fn to_ogg_bytes(&self) -> Result<Vec<u8>> {
+        self.to_ogg_bytes_with(&OutputMetadata::new())
+    }
+
+    /// Кодирует аудио в OGG, записывая метаданные в комментарии Vorbis
+    #[cfg(feature = "vorbis")]
+    pub(crate) fn to_ogg_bytes_with(&self, metadata: &OutputMetadata) -> Result<Vec<u8>> {
+        crate::audio::vorbis::encode_vorbis(&self.samples, self.sample_rate, self.channels, metadata)
+    }
+
+    /// Кодирует аудио в Opus (96 кбит/с) в памяти
+    ///
+    /// Аудио с частотой, не поддерживаемой Opus, предварительно
+    /// передискретизируется в 48 кГц. Поддерживаются моно и стерео.
+    #[cfg(feature = "opus")]
+    pub fn to_opus_bytes(&self) -> Result<Vec<u8>> {
+        self.to_opus_bytes_with(&OutputMetadata::new())
+    }
+
+    /// Кодирует аудио в Opus, записывая метаданные в комментарии
+    #[cfg(feature = "opus")]
+    pub(crate) fn to_opus_bytes_with(&self, metadata: &OutputMetadata) -> Result<Vec<u8>> {
+        use crate::audio::opus::{encode_opus, opus_sample_rate};
+
+        let sample_rate = opus_sample_rate(self.sample_rate);
+        let resampled;
+        let audio = if sample_rate == self.sample_rate {
+            self
+        } else {
+            resampled = self.resample(sample_rate)?;
+            &resampled
+        };
+        encode_opus(&audio.samples, audio.sample_rate, audio.channels, self.sample_rate, metadata)
+    }
+
+    /// Возвращает максимальную амплитуду сэмплов
+    pub fn sample_peak(&self) -> f32 {
+        self.samples.iter().fold(0.0f32, |max, &sample| max.max(sample.abs()))
+    }
+
+    /// Возвращает громкость (RMS по всем каналам) для последовательных окон
+    /// длиной `window_ms`
+    ///
+    /// Предназначено для отображения шкалы громкости. K-взвешивание не
+    /// применяется, значения - линейная амплитуда. Последнее неполное окно
+    /// тоже учитывается; при `window_ms == 0` возвращается пустой вектор.
+    pub fn loudness_over_time(&self, window_ms: u32) -> Vec<f32> {
+        let channels = self.channels.max(1) as usize;
+        let window_frames = (self.sample_rate as u64 * window_ms as u64 / 1000) as usize;
+        if window_frames == 0 {
+            return Vec::new();
+        }
+
+        self.samples
+            .chunks(window_frames * channels)
+            .map(|window| {
+                let sum_squares: f32 = window.iter().map(|&s| s * s).sum();
+                (sum_squares / window.len() as f32).sqrt()
+            })
+            .collect()
+    }
+
+    /// Возвращает запас громкости: на сколько дБ пик ниже полной шкалы
+    ///
+    /// Для тишины возвращает бесконечность, для сигнала с перегрузкой -
+    /// отрицательное значение.
+    pub fn headroom_db(&self) -> f32 {
+        -20.0 * self.sample_peak().log10()
+    }
+
+    /// Возвращает количество сэмплов, достигающих полной шкалы (|x| >= 1.0)
+    pub fn clip_count(&self) -> usize {
+        self.samples.iter().filter(|sample| sample.abs() >= 1.0).count()
+    }
+
+    /// Оценивает истинный пик с учетом межсэмпловых пиков
+    ///
+    /// Сигнал каждого канала восстанавливается с 4-кратной передискретизацией
+    /// (оконная sinc интерполяция), поэтому значение учитывает пики между
+    /// сэмплами, которые появляются после кодирования и ЦАП.
+    pub fn true_peak(&self) -> f32 {
+        const OVERSAMPLING: usize = 4;
+        const HALF_TAPS: isize = 16;
+
+        let channels = self.channels.max(1) as usize;
+        let frames = self.samples.len() / channels;
+        let mut peak = self.sample_peak();
+
+        for channel in 0..channels {
+            let sample_at = |frame: isize| -> f32 {
+                if frame < 0 || frame as usize >= frames {
+                    0.0
+                } else {
+                    self.samples[frame as usize * channels + channel]
+                }
+            };
+
+            for frame in 0..frames as isize {
+                for phase in 1..OVERSAMPLING {
+                    let fraction = phase as f32 / OVERSAMPLING as f32;
+                    let mut value = 0.0f32;
+
+                    for tap in -HALF_TAPS + 1..=HALF_TAPS {
+                        let distance = tap as f32 - fraction;
+                        let x = std::f32::consts::PI * distance;
+                        let sinc = x.sin() / x;
+                        // Окно Ханна по ширине фильтра
+                        let window = 0.5 + 0.5 * (std::f32::consts::PI * distance / HALF_TAPS as f32).cos();
+                        value += sample_at(frame + tap) * sinc * window;
+                    }
+
+                    peak = peak.max(value.abs());
+                }
+            }
+        }
+
+        peak
+    }
+
+    /// Нормализует громкость по истинному пику
+    ///
+    /// В отличие от [`AudioData::normalize`] учитывает межсэмпловые пики,
+    /// оставляя запас, который предотвращает клиппинг после кодирования.
+    pub fn normalize_true_peak(&mut self, target_peak: f32) {
+        if self.is_empty() {
+            return;
+        }
+
+        let peak = self.true_peak();
+        if peak > 0.0 {
+            let gain = target_peak / peak;
+            for sample in &mut self.samples {
+                *sample *= gain;
+            }
+        }
+    }
+
+    /// Плавно уменьшает громкость в конце аудио до нуля
+    ///
+    /// Последний сэмпл после затухания равен нулю, что исключает щелчок
+    /// при переходе к тишине.
+    pub fn fade_out(&mut self, duration: f64) {
+        let channels = self.channels.max(1) as usize;
+        let total_frames = self.samples.len() / channels;
+        let fade_frames = ((duration * self.sample_rate as f64) as usize).min(total_frames);
+        
+        if fade_frames == 0 {
+            return;
+        }
+        
+        let first_frame = total_frames - fade_frames;
+        for k in 0..fade_frames {
+            let gain = 1.0 - (k + 1) as f32 / fade_frames as f32;
+            let frame_start = (first_frame + k) * channels;
+            for sample in &mut self.samples[frame_start..frame_start + channels] {
+                *sample *= gain;
+            }
+        }
+    }
+
     /// Изменяет частоту дискретизации аудио
+    ///
+    /// Использует линейную интерполяцию по кадрам, длительность сохраняется.
     pub fn resample(&self, new_sample_rate: u32) -> Result<Self> {
-        if self.sample_rate == new_sample_rate {
-            return Ok(self.clone());
+        if new_sample_rate == 0 || self.sample_rate == 0 {
+            return Err(Error::new(
+                ErrorType::InvalidParameters,
+                &format!("Некорректная частота дискретизации: {} -> {} Гц", self.sample_rate, new_sample_rate),
+            ));
+        }
+
+        if self.sample_rate == new_sample_rate || self.samples.is_empty() {
+            return Ok(Self::new(self.samples.clone(), new_sample_rate, self.channels));
+        }
+
+        let channels = self.channels.max(1) as usize;
+        let input_frames = self.samples.len() / channels;
+        let ratio = self.sample_rate as f64 / new_sample_rate as f64;
+        let output_frames = (input_frames as f64 / ratio).round() as usize;
+        let mut samples = Vec::with_capacity(output_frames * channels);
+
+        for frame in 0..output_frames {
+            let pos = frame as f64 * ratio;
+            let index = (pos.floor() as usize).min(input_frames - 1);
+            let next = (index + 1).min(input_frames - 1);
+            let fraction = (pos - index as f64) as f32;
+
+            for channel in 0..channels {
+                let current = self.samples[index * channels + channel];
+                let following = self.samples[next * channels + channel];
+                samples.push(current + (following - current) * fraction);
+            }
+        }
+
+        Ok(Self::new(samples, new_sample_rate, self.channels))
+    }
+
+    /// Сводит многоканальное аудио в моно
+    ///
+    /// Каждый кадр заменяется средним значением его каналов.
+    pub fn to_mono(&self) -> Self {
+        let channels = self.channels.max(1) as usize;
+        if channels == 1 {
+            return Self::new(self.samples.clone(), self.sample_rate, 1);
+        }
+
+        let samples = self.samples
+            .chunks_exact(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect();
+
+        Self::new(samples, self.sample_rate, 1)
+    }
+
+    /// Размещает моно аудио в стерео панорамой `pan`
+    ///
+    /// `pan` от -1.0 (левый канал) до 1.0 (правый канал), 0.0 - по центру
+    /// (одинаковый сигнал в обоих каналах). Используется закон постоянной
+    /// мощности: сумма квадратов усилений каналов равна 1, поэтому в центре
+    /// каждый канал ослаблен на 3 дБ. Многоканальное аудио предварительно
+    /// сводится в моно.
+    pub fn to_stereo(&self, pan: f32) -> Self {
+        self.to_stereo_with_regions(pan, &[])
+    }
+
+    /// Размещает моно аудио в стерео, задавая панораму отдельным интервалам
+    ///
+    /// `regions` - интервалы `(начало, конец, панорама)` в секундах от начала
+    /// аудио; вне интервалов используется `pan`. При пересечении интервалов
+    /// действует последний. Закон панорамы тот же, что в [`AudioData::to_stereo`].
+    pub fn to_stereo_with_regions(&self, pan: f32, regions: &[(f64, f64, f32)]) -> Self {
+        let mono = self.to_mono();
+        let gains = |pan: f32| {
+            let angle = (pan.clamp(-1.0, 1.0) + 1.0) * std::f32::consts::FRAC_PI_4;
+            (angle.cos(), angle.sin())
+        };
+
+        let mut frame_gains = vec![gains(pan); mono.samples.len()];
+        for &(start, end, region_pan) in regions {
+            let start = ((start.max(0.0) * self.sample_rate as f64).round() as usize).min(frame_gains.len());
+            let end = ((end.max(0.0) * self.sample_rate as f64).round() as usize).min(frame_gains.len());
+            if start < end {
+                frame_gains[start..end].fill(gains(region_pan));
+            }
+        }
+
+        let samples = mono.samples
+            .iter()
+            .zip(frame_gains)
+            .flat_map(|(&sample, (left_gain, right_gain))| [sample * left_gain, sample * right_gain])
+            .collect();
+
+        Self::new(samples, self.sample_rate, 2)
+    }
+
+    /// Разбивает аудио на последовательные части не более `chunk_samples` сэмплов
+    ///
+    /// Размер части округляется вниз до целого числа кадров (но не меньше одного
+    /// кадра), поэтому кадры многоканального аудио не разрываются. Обратная
+    /// операция - [`AudioData::concat`].
+    pub fn split_into_chunks(&self, chunk_samples: usize) -> Vec<Self> {
+        let channels = self.channels.max(1) as usize;
+        let chunk_len = (chunk_samples / channels).max(1) * channels;
+
+        self.samples
+            .chunks(chunk_len)
+            .map(|chunk| Self::new(chunk.to_vec(), self.sample_rate, self.channels))
+            .collect()
+    }
+
+    /// Объединяет последовательные части аудио в одно аудио
+    ///
+    /// Все части должны иметь одинаковые частоту дискретизации и количество каналов.
+    pub fn concat(chunks: &[Self]) -> Result<Self> {
+        let first = chunks.first().ok_or_else(|| {
+            Error::InvalidParameters("Cannot concatenate an empty list of chunks".to_string())
+        })?;
+
+        if let Some(chunk) = chunks.iter().find(|chunk| {
+            chunk.sample_rate != first.sample_rate || chunk.channels != first.channels
+        }) {
+            return Err(Error::new(
+                ErrorType::InvalidParameters,
+                &format!("Формат фрагмента не совпадает: {} Гц/{} кан. вместо {} Гц/{} кан.",
+                    chunk.sample_rate, chunk.channels, first.sample_rate, first.channels),
+            ));
         }
 
-        // Здесь будет использоваться библиотека rubato для ресемплинга
-        // Реализация будет добавлена позже
-        Err(Error::AudioProcessing("Resampling not implemented yet".to_string()))
+        let samples = chunks.iter().flat_map(|chunk| chunk.samples.iter().copied()).collect();
+        Ok(Self::new(samples, first.sample_rate, first.channels))
+    }
+
+    /// Сравнивает аудио с допуском `tolerance` по амплитуде каждого сэмпла
+    ///
+    /// Частота дискретизации, количество каналов и длина должны совпадать.
+    pub fn approx_eq(&self, other: &Self, tolerance: f32) -> bool {
+        self.sample_rate == other.sample_rate
+            && self.channels == other.channels
+            && self.samples.len() == other.samples.len()
+            && self.samples
+                .iter()
+                .zip(&other.samples)
+                .all(|(a, b)| (a - b).abs() <= tolerance)
+    }
+
+    /// Вычисляет хэш содержимого для ключей кэша
+    ///
+    /// Сэмплы квантуются до 16 бит, поэтому различия ниже разрешения
+    /// 16-битного PCM обычно не меняют хэш. Используется FNV-1a, значение
+    /// стабильно между запусками и версиями компилятора.
+    pub fn content_hash(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+        const FNV_PRIME: u64 = 0x100000001b3;
+
+        let header = self.sample_rate.to_le_bytes()
+            .into_iter()
+            .chain(self.channels.to_le_bytes());
+        let samples = self.samples.iter().flat_map(|&sample| {
+            ((sample.clamp(-1.0, 1.0) * 32767.0).round() as i16).to_le_bytes()
+        });
+
+        header.chain(samples).fold(FNV_OFFSET, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+        })
     }
 
-    /// Загружает аудио данные из файла
-    pub fn from_file<P: AsRef<Path>>(_path: P) -> Result<Self> {
-        // Здесь будет использоваться библиотека symphonia для загрузки аудио
-        // Реализация будет добавлена позже
-        Err(Error::AudioProcessing("Loading from file not implemented yet".to_string()))
+    /// Загружает аудио данные из файла WAV, MP3, FLAC, OGG (функция `vorbis`)
+    /// или Opus (функция `opus`)
+    ///
+    /// Частота дискретизации и количество каналов сохраняются как в файле;
+    /// Opus всегда декодируется с частотой 48 кГц.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let extension = audio_file_extension(path);
+        if !is_supported_file_format(&extension) {
+            return Err(Error::new(
+                ErrorType::InvalidParameters,
+                &format!("Неподдерживаемый формат аудио файла '{}': ожидается {}", path.display(), SUPPORTED_FILE_FORMATS),
+            ));
+        }
+
+        log_debug(&format!("Загрузка аудио из файла: {}", path.display()));
+        let data = std::fs::read(path)?;
+        crate::audio::utils::decode_audio_file_data(&data, &extension)
     }
 
-    /// Сохраняет аудио данные в файл
-    pub fn to_file<P: AsRef<Path>>(&self, _path: P) -> Result<()> {
-        // Здесь будет использоваться библиотека hound для сохранения аудио
-        // Реализация будет добавлена позже
-        Err(Error::AudioProcessing("Saving to file not implemented yet".to_string()))
+    /// Сохраняет аудио данные в файл WAV, FLAC (16 бит), MP3 (192 кбит/с),
+    /// OGG (Vorbis) или Opus (96 кбит/с)
+    ///
+    /// Формат определяется по расширению файла. Для MP3 нужна функция
+    /// `mp3-encoder`, для OGG - `vorbis`, для Opus - `opus`.
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let extension = audio_file_extension(path);
+        if !is_supported_file_format(&extension) {
+            return Err(Error::new(
+                ErrorType::InvalidParameters,
+                &format!("Неподдерживаемый формат для сохранения '{}': ожидается {}", path.display(), SUPPORTED_FILE_FORMATS),
+            ));
+        }
+        if extension == "flac" && self.channels > 8 {
+            return Err(Error::new(
+                ErrorType::InvalidParameters,
+                &format!("FLAC поддерживает не более 8 каналов, получено {}", self.channels),
+            ));
+        }
+        if matches!(extension.as_str(), "mp3" | "opus") && self.channels > 2 {
+            return Err(Error::new(
+                ErrorType::InvalidParameters,
+                &format!("{} поддерживает один или два канала, получено {}", extension.to_uppercase(), self.channels),
+            ));
+        }
+
+        let channels = self.channels.max(1) as usize;
+        if self.samples.len() % channels != 0 {
+            return Err(Error::new(
+                ErrorType::AudioProcessingError,
+                &format!("Количество сэмплов {} не кратно количеству каналов {}: последний кадр неполный",
+                    self.samples.len(), self.channels),
+            ));
+        }
+
+        log_debug(&format!("Сохранение аудио в файл: {}", path.display()));
+        let bytes = match extension.as_str() {
+            "flac" => self.to_flac_bytes(),
+            "mp3" => self.to_mp3_bytes(DEFAULT_MP3_BITRATE)?,
+            #[cfg(feature = "vorbis")]
+            "ogg" => self.to_ogg_bytes()?,
+            #[cfg(feature = "opus")]
+            "opus" => self.to_opus_bytes()?,
+            _ => self.to_wav_bytes(),
+        };
+        std::fs::write(path, bytes)?;
+        Ok(())
     }
 
     /// Применяет компрессию динамического диапазона
@@ -122,6 +576,23 @@ impl AudioData {
         Ok(result)
     }
 
+    /// Применяет пиковый лимитер с потолком `ceiling` (линейная амплитуда)
+    ///
+    /// Обертка над [`AudioProcessor::apply_limiter`] с упреждением и временем
+    /// восстановления по умолчанию; нулевой или отрицательный потолок
+    /// оставляет аудио без изменений.
+    ///
+    /// [`AudioProcessor::apply_limiter`]: crate::audio::AudioProcessor::apply_limiter
+    pub fn apply_limiter(&self, ceiling: f32) -> Self {
+        if self.is_empty() || ceiling.is_nan() || ceiling <= 0.0 {
+            return self.clone();
+        }
+
+        let ceiling_db = 20.0 * ceiling.log10();
+        AudioProcessor::apply_limiter(self, ceiling_db, DEFAULT_LIMITER_RELEASE_MS)
+            .unwrap_or_else(|_| self.clone())
+    }
+
     /// Применяет эквализацию
     pub fn apply_equalization(
         &self,
@@ -146,15 +617,64 @@ impl AudioData {
             return self.clone();
         }
 
-        let mut result = self.clone();
         let target_amplitude = 10.0f32.powf(target_db / 20.0);
-        result.normalize(target_amplitude);
+        self.normalized(target_amplitude)
+    }
+
+    /// Нормализует громкость в дБ по истинному пику
+    pub fn normalize_db_true_peak(&self, target_db: f32) -> Self {
+        let mut result = self.clone();
+        result.normalize_true_peak(10.0f32.powf(target_db / 20.0));
         result
     }
 }
 
+/// Форматы файлов, поддерживаемые [`AudioData::from_file`] и [`AudioData::to_file`]
+const SUPPORTED_FILE_FORMATS: &str = "wav, mp3, flac, ogg (функция vorbis) или opus (функция opus)";
+
+/// Проверяет, что файлы с расширением `extension` можно читать и записывать
+fn is_supported_file_format(extension: &str) -> bool {
+    matches!(extension, "wav" | "mp3" | "flac")
+        || (extension == "ogg" && cfg!(feature = "vorbis"))
+        || (extension == "opus" && cfg!(feature = "opus"))
+}
+
+/// Возвращает расширение файла в нижнем регистре (пустое, если его нет)
+fn audio_file_extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("")
+        .to_lowercase()
+}
+
+/// Формирует 44-байтный заголовок WAV для 16-битного PCM с `data_size` байтами данных
+pub(crate) fn pcm16_wav_header(sample_rate: u32, channels: u16, data_size: u32) -> Vec<u8> {
+    let mut header = Vec::with_capacity(44);
+    
+    // RIFF chunk
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&(data_size + 36).to_le_bytes()); // 44 байта заголовка - 8 байтов
+    header.extend_from_slice(b"WAVE");
+    
+    // fmt subchunk
+    header.extend_from_slice(b"fmt ");
+    header.extend_from_slice(&16u32.to_le_bytes()); // размер подчанка fmt (16 байтов)
+    header.extend_from_slice(&1u16.to_le_bytes()); // аудио формат (1 = PCM)
+    header.extend_from_slice(&channels.to_le_bytes());
+    header.extend_from_slice(&sample_rate.to_le_bytes());
+    header.extend_from_slice(&(sample_rate * channels as u32 * 2).to_le_bytes()); // байт рейт
+    header.extend_from_slice(&(channels * 2).to_le_bytes()); // блок выравнивания
+    header.extend_from_slice(&16u16.to_le_bytes()); // биты на сэмпл
+    
+    // data subchunk
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&data_size.to_le_bytes());
+    
+    header
+}
+
 /// Сегмент аудио
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AudioSegment {
     /// Аудио данные
     pub audio: AudioData,
@@ -165,7 +685,17 @@ pub struct AudioSegment {
     /// Текст сегмента
     pub text: String,
     /// Исходные бинарные данные аудио (если доступны, например, MP3 от OpenAI)
+    #[serde(default, with = "crate::audio::project::raw_data_base64")]
     pub raw_data: Option<Vec<u8>>,
+    /// Идентификатор исходного субтитра (если указан)
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Панорама сегмента в стерео от -1.0 (слева) до 1.0 (справа), если задана
+    #[serde(default)]
+    pub pan: Option<f32>,
+    /// Порядковый номер исходного субтитра (для пауз и тишины - `None`)
+    #[serde(default)]
+    pub cue: Option<usize>,
 }
 
 impl AudioSegment {
@@ -177,6 +707,9 @@ impl AudioSegment {
             end_time,
             text,
             raw_data: None,
+            id: None,
+            pan: None,
+            cue: None,
         }
     }
 
@@ -188,16 +721,49 @@ impl AudioSegment {
             end_time,
             text,
             raw_data: Some(raw_data),
+            id: None,
+            pan: None,
+            cue: None,
         }
     }
 
+    /// Устанавливает идентификатор исходного субтитра
+    pub fn with_id(mut self, id: Option<String>) -> Self {
+        self.id = id;
+        self
+    }
+
+    /// Устанавливает панораму сегмента в стерео
+    pub fn with_pan(mut self, pan: Option<f32>) -> Self {
+        self.pan = pan;
+        self
+    }
+
+    /// Устанавливает порядковый номер исходного субтитра
+    pub fn with_cue(mut self, cue: Option<usize>) -> Self {
+        self.cue = cue;
+        self
+    }
+
     /// Возвращает длительность сегмента в секундах
     pub fn duration(&self) -> f64 {
         self.end_time - self.start_time
     }
 
     /// Изменяет темп сегмента без изменения высоты тона
+    ///
+    /// Используется алгоритм [`TempoAlgorithm::Wsola`]; другой алгоритм можно
+    /// выбрать через [`AudioSegment::adjust_tempo_with`].
     pub fn adjust_tempo(&self, tempo_factor: f64) -> Result<Self> {
+        self.adjust_tempo_with(tempo_factor, TempoAlgorithm::Wsola)
+    }
+
+    /// Изменяет темп сегмента указанным алгоритмом
+    ///
+    /// Время начала и окончания, текст и идентификатор сохраняются. Исходные
+    /// данные провайдера (`raw_data`) сбрасываются, так как больше не
+    /// соответствуют аудио.
+    pub fn adjust_tempo_with(&self, tempo_factor: f64, algorithm: TempoAlgorithm) -> Result<Self> {
         if tempo_factor <= 0.0 {
             return Err(Error::InvalidParameters(format!("Invalid tempo factor: {}", tempo_factor)));
         }
@@ -206,14 +772,32 @@ impl AudioSegment {
             return Ok(self.clone());
         }
 
-        // Здесь будет использоваться библиотека rubato для изменения темпа
-        // Реализация будет добавлена позже
-        Err(Error::AudioProcessing("Tempo adjustment not implemented yet".to_string()))
+        let audio = TempoAdjuster::adjust_tempo(&self.audio, tempo_factor, algorithm)?;
+        Ok(Self {
+            audio,
+            start_time: self.start_time,
+            end_time: self.end_time,
+            text: self.text.clone(),
+            raw_data: None,
+            id: self.id.clone(),
+            pan: self.pan,
+            cue: self.cue,
+        })
     }
 }
 
+/// Способ объединения перекрывающихся сегментов трека
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergePolicy {
+    /// Сэмплы сегмента, идущего позже в списке, заменяют предыдущие
+    Overwrite,
+    /// Сэмплы перекрывающихся сегментов складываются (без ограничения уровня)
+    #[default]
+    Sum,
+}
+
 /// Аудио трек
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AudioTrack {
     /// Сегменты аудио
     pub segments: Vec<AudioSegment>,
@@ -250,41 +834,119 @@ impl AudioTrack {
 
     /// Сортирует сегменты по времени начала
     pub fn sort_by_start_time(&mut self) {
-        self.segments.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
+        self.segments.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    }
+
+    /// Возвращает количество сэмплов объединенного трека
+    ///
+    /// Трек начинается с нуля и заканчивается временем окончания самого
+    /// позднего сегмента; длина выравнивается по кадру.
+    fn merged_len(&self) -> usize {
+        let max_end = self.segments.iter()
+            .map(|s| s.end_time)
+            .fold(0.0, f64::max);
+        let frames = (max_end * self.sample_rate as f64).round() as usize;
+        frames * self.channels.max(1) as usize
+    }
+
+    /// Возвращает положение сегмента в результате объединения: индекс первого
+    /// сэмпла в результате и количество сэмплов в начале сегмента, которые
+    /// приходятся на время до нуля и отбрасываются
+    ///
+    /// Начало выравнивается по кадру, чтобы не перепутать каналы.
+    fn merged_placement(&self, segment: &AudioSegment) -> (usize, usize) {
+        let channels = self.channels.max(1) as usize;
+        let start_frame = (segment.start_time * self.sample_rate as f64).round();
+        if start_frame >= 0.0 {
+            (start_frame as usize * channels, 0)
+        } else {
+            (0, ((-start_frame) as usize * channels).min(segment.audio.samples.len()))
+        }
+    }
+
+    /// Возвращает положение реплик в результате [`AudioTrack::merge`]
+    ///
+    /// Элементы `(номер субтитра, первый сэмпл, конец)` упорядочены по номеру
+    /// субтитра; индексы относятся к чередующимся сэмплам объединенного
+    /// буфера, конец не включается. Учитываются только сегменты с
+    /// [`AudioSegment::cue`].
+    pub fn cue_sample_map(&self) -> Vec<(usize, usize, usize)> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+
+        let total_samples = self.merged_len();
+        let mut map: Vec<(usize, usize, usize)> = self.segments.iter()
+            .filter_map(|segment| {
+                let cue = segment.cue?;
+                let (start, skipped) = self.merged_placement(segment);
+                let start = start.min(total_samples);
+                let end = (start + segment.audio.samples.len() - skipped).min(total_samples);
+                Some((cue, start, end))
+            })
+            .collect();
+        map.sort_by_key(|&(cue, start, _)| (cue, start));
+        map
+    }
+
+    /// Возвращает длительность в секундах, которую будет иметь результат [`AudioTrack::merge`]
+    ///
+    /// Учитывает округление количества сэмплов и не объединяет сегменты.
+    pub fn merged_duration(&self) -> f64 {
+        if self.is_empty() || self.sample_rate == 0 {
+            return 0.0;
+        }
+        self.merged_len() as f64 / (self.sample_rate as f64 * self.channels.max(1) as f64)
     }
 
     /// Объединяет все сегменты в один аудио файл
+    ///
+    /// Перекрывающиеся сегменты складываются ([`MergePolicy::Sum`]).
     pub fn merge(&self) -> Result<AudioData> {
+        self.merge_with(MergePolicy::default())
+    }
+
+    /// Объединяет все сегменты в один аудио файл с указанным способом объединения
+    ///
+    /// Размещение сегментов:
+    /// - результат начинается с нуля и заканчивается временем окончания самого
+    ///   позднего сегмента, поэтому сэмплы совпадают со временем субтитров;
+    /// - сегмент размещается с ближайшего кадра `start_time * sample_rate`,
+    ///   поэтому погрешность времени не сдвигает его на кадр раньше;
+    /// - промежутки между сегментами заполняются тишиной;
+    /// - сэмплы сегмента до нуля и за концом результата отбрасываются.
+    pub fn merge_with(&self, policy: MergePolicy) -> Result<AudioData> {
+        self.merge_with_overruns(policy).map(|(audio, _)| audio)
+    }
+
+    /// Объединяет все сегменты как [`AudioTrack::merge_with`]
+    ///
+    /// Дополнительно возвращает индексы сегментов, которые не поместились
+    /// в итоговый буфер и были обрезаны.
+    pub fn merge_with_overruns(&self, policy: MergePolicy) -> Result<(AudioData, Vec<usize>)> {
         if self.is_empty() {
             log_warning("Попытка объединить пустой аудио трек");
-            return Ok(AudioData::new(Vec::new(), self.sample_rate, self.channels));
+            return Ok((AudioData::new(Vec::new(), self.sample_rate, self.channels), Vec::new()));
         }
 
         // Определяем общую длительность
-        let min_start = self.segments.iter()
-            .map(|s| s.start_time)
-            .fold(f64::INFINITY, f64::min);
-            
-        let max_end = self.segments.iter()
-            .map(|s| s.end_time)
-            .fold(0.0, f64::max);
-            
-        let total_duration = max_end - min_start;
-        let total_samples = (total_duration * self.sample_rate as f64 * self.channels as f64) as usize;
+        let total_samples = self.merged_len();
+        let total_duration = total_samples as f64 / (self.sample_rate as f64 * self.channels.max(1) as f64);
         
         log_debug(&format!("Объединение {} аудио сегментов, общая длительность: {:.2}с, всего сэмплов: {}", 
             self.segments.len(), total_duration, total_samples));
         
         if total_samples == 0 {
             log_warning("Объединение привело к нулевому количеству сэмплов");
-            return Ok(AudioData::new(Vec::new(), self.sample_rate, self.channels));
+            return Ok((AudioData::new(Vec::new(), self.sample_rate, self.channels), Vec::new()));
         }
         
         let mut merged_samples = vec![0.0; total_samples];
+        let mut overruns = Vec::new();
         
         // Объединяем сегменты
         for (i, segment) in self.segments.iter().enumerate() {
-            let start_sample = ((segment.start_time - min_start) * self.sample_rate as f64 * self.channels as f64) as usize;
+            let (start_sample, skipped) = self.merged_placement(segment);
             let num_samples = segment.audio.samples.len();
             
             log_debug(&format!("Сегмент {}/{}: старт: {:.2}с, длительность: {:.2}с, сэмплов: {}", 
@@ -295,15 +957,20 @@ impl AudioTrack {
                 continue;
             }
             
-            for (j, &sample) in segment.audio.samples.iter().enumerate() {
-                let pos = start_sample + j;
-                if pos < merged_samples.len() {
-                    merged_samples[pos] = sample;
-                } else {
-                    log_warning(&format!("Выход за пределы буфера при объединении сегмента {}/{}: позиция {} >= {}", 
-                        i + 1, self.segments.len(), pos, merged_samples.len()));
-                    break;
-                }
+            // Часть сегмента за пределами буфера отбрасывается, остальное записывается
+            let source = &segment.audio.samples[skipped..];
+            let available = total_samples.saturating_sub(start_sample);
+            if skipped > 0 || source.len() > available {
+                log_warning(&format!("Сегмент {}/{} выходит за пределы буфера и будет обрезан: начало {:.3}с, {} сэмплов с позиции {} при размере {}", 
+                    i + 1, self.segments.len(), segment.start_time, source.len(), start_sample, total_samples));
+                overruns.push(i);
+            }
+            
+            let source = &source[..source.len().min(available)];
+            let target = &mut merged_samples[start_sample.min(total_samples)..][..source.len()];
+            match policy {
+                MergePolicy::Overwrite => target.copy_from_slice(source),
+                MergePolicy::Sum => target.iter_mut().zip(source).for_each(|(merged, &sample)| *merged += sample),
             }
         }
         
@@ -311,7 +978,74 @@ impl AudioTrack {
         log_info(&format!("Успешно объединено {} сегментов в один аудио файл длительностью {:.2}с", 
             self.segments.len(), result.duration()));
         
-        Ok(result)
+        Ok((result, overruns))
+    }
+}
+
+impl AudioTrack {
+    /// Дополняет трек тишиной до указанного времени
+    ///
+    /// Перед тишиной конец последней реплики плавно затухает в течение
+    /// `fade_duration` секунд, чтобы избежать щелчка на границе. Репликой
+    /// считается последний сегмент с субтитром или текстом: следующие за
+    /// ним паузы не содержат звука и затухания не требуют.
+    pub fn pad_with_silence(&mut self, until: f64, fade_duration: f64) {
+        let last_end = match self.segments.last() {
+            Some(last_segment) if last_segment.end_time < until => last_segment.end_time,
+            _ => return,
+        };
+        
+        let last_speech = self.segments.iter_mut()
+            .rev()
+            .find(|segment| segment.cue.is_some() || !segment.text.is_empty());
+        if let Some(last_speech) = last_speech {
+            let ends_with_signal = last_speech.audio.samples.last()
+                .map(|sample| *sample != 0.0)
+                .unwrap_or(false);
+            
+            if ends_with_signal && fade_duration > 0.0 {
+                last_speech.audio.fade_out(fade_duration);
+                // Исходные данные больше не соответствуют измененному аудио
+                last_speech.raw_data = None;
+            }
+        }
+        
+        let silence_duration = until - last_end;
+        let silence_samples = vec![0.0f32; (self.sample_rate as f64 * silence_duration) as usize * self.channels as usize];
+        let silence_data = AudioData::new(silence_samples, self.sample_rate, self.channels);
+        
+        self.add_segment(AudioSegment::new(
+            silence_data,
+            last_end,
+            until,
+            String::new()
+        ));
+    }
+
+    /// Возвращает интервалы `(начало, конец, панорама)` сегментов с заданной панорамой
+    ///
+    /// Время отсчитывается от начала результата [`AudioTrack::merge`], поэтому
+    /// интервалы подходят для [`AudioData::to_stereo_with_regions`].
+    pub fn pan_regions(&self) -> Vec<(f64, f64, f32)> {
+        self.segments.iter()
+            .filter_map(|segment| segment.pan.map(|pan| {
+                (segment.start_time, segment.start_time + segment.audio.duration(), pan)
+            }))
+            .collect()
+    }
+
+    /// Возвращает интервалы речи `(начало, конец)` в секундах
+    ///
+    /// Интервалы соответствуют размещенным сегментам с текстом (сегменты
+    /// тишины и паузы не учитываются) и отсортированы по времени начала.
+    /// Подходят, например, для приглушения фоновой дорожки под речью.
+    pub fn speech_regions(&self) -> Vec<(f64, f64)> {
+        let mut regions: Vec<(f64, f64)> = self.segments.iter()
+            .filter(|segment| !segment.text.is_empty() && segment.end_time > segment.start_time)
+            .map(|segment| (segment.start_time, segment.end_time))
+            .collect();
+        regions.sort_by(|a, b| a.0.total_cmp(&b.0));
+        regions
     }
 }
 
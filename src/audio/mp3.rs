@@ -0,0 +1,156 @@
+//! Кодирование MP3 с постоянным битрейтом с помощью LAME (`mp3lame-encoder`).
+//!
+//! Кодировщик доступен при включенной функции `mp3-encoder`. LAME сам
+//! выбирает версию MPEG и частоту кадров для частоты дискретизации
+//! исходного аудио, поэтому передискретизация перед кодированием не нужна.
+//! Поток начинается с кадра с тегами Info и LAME, по которым декодеры с
+//! поддержкой воспроизведения без пауз отбрасывают задержку кодека и
+//! дополнение последнего кадра.
+
+use crate::error::{Error, ErrorType, Result};
+
+/// Допустимые битрейты MP3 в кбит/с (значения, которые принимает LAME)
+const MP3_BITRATES: [u32; 16] = [8, 16, 24, 32, 40, 48, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320];
+
+/// Битрейт MP3 по умолчанию в кбит/с
+pub(crate) const DEFAULT_MP3_BITRATE: u32 = 192;
+
+/// Проверяет, что битрейт `bitrate` (кбит/с) допустим для MP3
+pub(crate) fn validate_mp3_bitrate(bitrate: u32) -> Result<()> {
+    if MP3_BITRATES.contains(&bitrate) {
+        Ok(())
+    } else {
+        Err(Error::new(
+            ErrorType::InvalidParameters,
+            &format!("Битрейт MP3 {} кбит/с не поддерживается, допустимые значения: {:?}", bitrate, MP3_BITRATES),
+        ))
+    }
+}
+
+/// Кодирует перемеженные по кадрам сэмплы (-1.0..=1.0) в поток кадров MP3
+///
+/// Битрейт должен быть одним из `MP3_BITRATES`, каналов - один или два.
+/// Если для частоты дискретизации битрейт слишком велик, LAME использует
+/// наибольший допустимый.
+#[cfg(feature = "mp3-encoder")]
+pub(crate) fn encode_mp3(samples: &[f32], sample_rate: u32, channels: u16, bitrate: u32) -> Result<Vec<u8>> {
+    use mp3lame_encoder::{Bitrate, Builder, FlushGap, InterleavedPcm, MonoPcm, Quality};
+
+    validate_mp3_bitrate(bitrate)?;
+    if channels == 0 || channels > 2 {
+        return Err(Error::new(
+            ErrorType::InvalidParameters,
+            &format!("MP3 поддерживает один или два канала, получено {}", channels),
+        ));
+    }
+    let brate = match bitrate {
+        8 => Bitrate::Kbps8,
+        16 => Bitrate::Kbps16,
+        24 => Bitrate::Kbps24,
+        32 => Bitrate::Kbps32,
+        40 => Bitrate::Kbps40,
+        48 => Bitrate::Kbps48,
+        64 => Bitrate::Kbps64,
+        80 => Bitrate::Kbps80,
+        96 => Bitrate::Kbps96,
+        112 => Bitrate::Kbps112,
+        128 => Bitrate::Kbps128,
+        160 => Bitrate::Kbps160,
+        192 => Bitrate::Kbps192,
+        224 => Bitrate::Kbps224,
+        256 => Bitrate::Kbps256,
+        _ => Bitrate::Kbps320,
+    };
+
+    let lame_error = |stage: &str, error: &dyn std::fmt::Display| {
+        Error::new(ErrorType::AudioProcessingError, &format!("Ошибка LAME ({}): {}", stage, error))
+    };
+    let mut builder = Builder::new()
+        .ok_or_else(|| Error::new(ErrorType::AudioProcessingError, "Не удалось создать кодировщик LAME"))?;
+    builder.set_num_channels(channels as u8).map_err(|e| lame_error("каналы", &e))?;
+    builder.set_sample_rate(sample_rate).map_err(|e| lame_error("частота дискретизации", &e))?;
+    builder.set_brate(brate).map_err(|e| lame_error("битрейт", &e))?;
+    builder.set_quality(Quality::Good).map_err(|e| lame_error("качество", &e))?;
+    let mut encoder = builder.build().map_err(|e| lame_error("инициализация", &e))?;
+
+    // Неполный последний кадр отбрасываем, LAME ожидает целые кадры
+    let frames = samples.len() / channels as usize;
+    let samples: Vec<f32> = samples[..frames * channels as usize].iter()
+        .map(|sample| sample.clamp(-1.0, 1.0))
+        .collect();
+
+    let mut output = Vec::with_capacity(mp3lame_encoder::max_required_buffer_size(frames));
+    let encoded = if channels == 1 {
+        encoder.encode_to_vec(MonoPcm(samples.as_slice()), &mut output)
+    } else {
+        encoder.encode_to_vec(InterleavedPcm(samples.as_slice()), &mut output)
+    };
+    encoded.map_err(|e| lame_error("кодирование", &e))?;
+    // Для завершения потока LAME нужно до 7200 байт
+    output.reserve(7200);
+    encoder.flush_to_vec::<FlushGap>(&mut output).map_err(|e| lame_error("завершение", &e))?;
+
+    // Первый кадр потока зарезервирован под тег Info, который LAME может
+    // сформировать только после кодирования всего аудио
+    let mut tag = Vec::with_capacity(encoder.lame_tag_size());
+    if encoder.lame_tag_encode_to_vec(&mut tag).is_some() && tag.len() <= output.len() {
+        output[..tag.len()].copy_from_slice(&tag);
+    }
+    Ok(output)
+}
+
+/// Заглушка для сборки без функции `mp3-encoder`: встроенного кодировщика нет
+#[cfg(not(feature = "mp3-encoder"))]
+pub(crate) fn encode_mp3(_samples: &[f32], _sample_rate: u32, _channels: u16, bitrate: u32) -> Result<Vec<u8>> {
+    validate_mp3_bitrate(bitrate)?;
+    Err(Error::new(
+        ErrorType::AudioProcessingError,
+        "Встроенный кодировщик MP3 недоступен: библиотека собрана без функции mp3-encoder",
+    ))
+}
+
+#[cfg(all(test, feature = "mp3-encoder"))]
+mod tests {
+    use super::*;
+    use crate::audio::utils::decode_audio_file_data;
+    use std::f64::consts::PI;
+
+    fn sine(frequency: f64, amplitude: f64, frames: usize, sample_rate: u32, channels: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|i| {
+                let sample = amplitude * (2.0 * PI * frequency * i as f64 / sample_rate as f64).sin();
+                std::iter::repeat(sample as f32).take(channels)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_mp3_decodes_back() {
+        for (sample_rate, channels, bitrate) in [(44100, 1, 128), (48000, 2, 192), (24000, 1, 64)] {
+            let frames = sample_rate as usize;
+            let samples = sine(440.0, 0.5, frames, sample_rate, channels);
+            let mp3 = encode_mp3(&samples, sample_rate, channels as u16, bitrate).unwrap();
+            assert_eq!(mp3[0], 0xFF);
+            assert_eq!(mp3[1] & 0xE0, 0xE0);
+
+            // Поток постоянного битрейта: размер соответствует битрейту
+            let expected_bytes = bitrate as usize * 1000 / 8;
+            assert!(mp3.len() > expected_bytes * 9 / 10 && mp3.len() < expected_bytes * 12 / 10,
+                "{} Гц, {} кбит/с: {} байт", sample_rate, bitrate, mp3.len());
+
+            // Частота сохраняется, задержка кодека и дополнение отбрасываются
+            let decoded = decode_audio_file_data(&mp3, "mp3").unwrap();
+            assert_eq!(decoded.sample_rate, sample_rate);
+            assert_eq!(decoded.channels, channels as u16);
+            assert_eq!(decoded.samples.len(), frames * channels);
+        }
+    }
+
+    #[test]
+    fn test_encode_mp3_rejects_unsupported_parameters() {
+        let samples = vec![0.0; 1152];
+        assert!(encode_mp3(&samples, 44100, 1, 100).is_err());
+        assert!(encode_mp3(&samples, 44100, 3, 128).is_err());
+        assert!(encode_mp3(&samples, 44100, 1, 56).is_err());
+    }
+}
@@ -0,0 +1,322 @@
+//! Кодирование и декодирование Ogg Opus (функция `opus`).
+//!
+//! Кодек - libopus, транслированный в Rust (`unsafe-libopus`), контейнер -
+//! Ogg по RFC 7845 (`ogg`). Аудио кодируется кадрами по 20 мс с битрейтом
+//! 96 кбит/с, как у ffmpeg. Opus работает с частотами 8, 12, 16, 24 и
+//! 48 кГц; аудио с другой частотой предварительно передискретизируется в
+//! 48 кГц. Задержка кодировщика записывается в заголовок (pre-skip), а
+//! длина аудио - в позицию гранулы последней страницы, поэтому декодер
+//! восстанавливает исходное количество сэмплов. Декодированное аудио всегда
+//! имеет частоту 48 кГц.
+
+use crate::audio::metadata::{OutputMetadata, VENDOR};
+use crate::audio::models::AudioData;
+use crate::error::{Error, ErrorType, Result};
+use ogg::reading::PacketReader;
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+use std::io::Cursor;
+use unsafe_libopus::{
+    opus_decode_float, opus_decoder_create, opus_decoder_destroy, opus_encode_float, opus_encoder_create,
+    opus_encoder_ctl, opus_encoder_destroy, opus_strerror, OpusDecoder, OpusEncoder, OPUS_APPLICATION_AUDIO,
+    OPUS_GET_LOOKAHEAD_REQUEST, OPUS_OK, OPUS_SET_BITRATE_REQUEST,
+};
+
+/// Частоты дискретизации, с которыми работает кодек
+const OPUS_SAMPLE_RATES: [u32; 5] = [8000, 12000, 16000, 24000, 48000];
+
+/// Частота, в которой отсчитываются pre-skip и позиции гранул
+const GRANULE_RATE: u32 = 48000;
+
+/// Битрейт в бит/с
+const BITRATE: i32 = 96_000;
+
+/// Количество кадров Opus в секунде (кадры по 20 мс)
+const FRAMES_PER_SECOND: u32 = 50;
+
+/// Наибольший размер пакета, рекомендуемый libopus
+const MAX_PACKET_SIZE: usize = 4000;
+
+/// Наибольшая длительность пакета в сэмплах канала при 48 кГц (120 мс)
+const MAX_PACKET_SAMPLES: usize = 5760;
+
+/// Номер логического потока Ogg
+///
+/// Постоянный номер делает результат кодирования воспроизводимым.
+const STREAM_SERIAL: u32 = 1;
+
+/// Возвращает частоту, с которой аудио с частотой `sample_rate` передается
+/// кодировщику
+pub(crate) fn opus_sample_rate(sample_rate: u32) -> u32 {
+    if OPUS_SAMPLE_RATES.contains(&sample_rate) {
+        sample_rate
+    } else {
+        GRANULE_RATE
+    }
+}
+
+/// Кодирует перемеженные по кадрам сэмплы в файл Ogg Opus
+///
+/// Частота должна быть одной из частот Opus (см. [`opus_sample_rate`]),
+/// каналов - один или два. `input_sample_rate` - частота исходного аудио до
+/// передискретизации, она записывается в заголовок. Заданные поля
+/// `metadata` записываются в комментарии.
+pub(crate) fn encode_opus(
+    samples: &[f32],
+    sample_rate: u32,
+    channels: u16,
+    input_sample_rate: u32,
+    metadata: &OutputMetadata,
+) -> Result<Vec<u8>> {
+    if !OPUS_SAMPLE_RATES.contains(&sample_rate) {
+        return Err(Error::new(
+            ErrorType::InvalidParameters,
+            &format!("Частота {} Гц не поддерживается Opus: ожидается одна из {:?}", sample_rate, OPUS_SAMPLE_RATES),
+        ));
+    }
+    if channels == 0 || channels > 2 {
+        return Err(Error::new(
+            ErrorType::InvalidParameters,
+            &format!("Opus поддерживает один или два канала, получено {}", channels),
+        ));
+    }
+
+    let encoder = Encoder::new(sample_rate, channels)?;
+    let channels = channels as usize;
+    let granule_scale = (GRANULE_RATE / sample_rate) as u64;
+    let frame_size = (sample_rate / FRAMES_PER_SECOND) as usize;
+    let pre_skip = encoder.lookahead()? as u64 * granule_scale;
+
+    // Дополняем тишиной, чтобы вытолкнуть задержку кодировщика и заполнить
+    // последний кадр
+    let frames = samples.len() / channels;
+    let lookahead = (pre_skip / granule_scale) as usize;
+    let total_frames = (frames + lookahead).div_ceil(frame_size).max(1);
+    let mut padded = samples[..frames * channels].to_vec();
+    padded.resize(total_frames * frame_size * channels, 0.0);
+
+    let mut writer = PacketWriter::new(Vec::new());
+    writer.write_packet(opus_head(channels as u8, pre_skip as u16, input_sample_rate), STREAM_SERIAL, PacketWriteEndInfo::EndPage, 0)?;
+    writer.write_packet(opus_tags(metadata), STREAM_SERIAL, PacketWriteEndInfo::EndPage, 0)?;
+
+    // Позиция гранулы последней страницы отсекает дополнение при декодировании
+    let end_granule = pre_skip + frames as u64 * granule_scale;
+    for (index, frame) in padded.chunks_exact(frame_size * channels).enumerate() {
+        let packet = encoder.encode(frame, frame_size)?;
+        let last = index + 1 == total_frames;
+        let granule = if last { end_granule } else { (index as u64 + 1) * frame_size as u64 * granule_scale };
+        let end_info = if last { PacketWriteEndInfo::EndStream } else { PacketWriteEndInfo::NormalPacket };
+        writer.write_packet(packet, STREAM_SERIAL, end_info, granule)?;
+    }
+    Ok(writer.into_inner())
+}
+
+/// Декодирует файл Ogg Opus в перемеженные по кадрам сэмплы с частотой 48 кГц
+pub(crate) fn decode_opus(data: &[u8]) -> Result<AudioData> {
+    let invalid = |message: &str| Error::new(ErrorType::AudioProcessingError, &format!("Некорректный файл Ogg Opus: {}", message));
+    let mut reader = PacketReader::new(Cursor::new(data));
+
+    let head = reader.read_packet().map_err(ogg_error)?.ok_or_else(|| invalid("нет заголовка OpusHead"))?;
+    if head.data.len() < 19 || &head.data[..8] != b"OpusHead" {
+        return Err(invalid("нет заголовка OpusHead"));
+    }
+    let channels = head.data[9] as usize;
+    let pre_skip = u16::from_le_bytes([head.data[10], head.data[11]]) as usize;
+    if head.data[18] != 0 || channels == 0 || channels > 2 {
+        return Err(invalid("поддерживаются только потоки с одним или двумя каналами"));
+    }
+    reader.read_packet().map_err(ogg_error)?.ok_or_else(|| invalid("нет заголовка OpusTags"))?;
+
+    let decoder = Decoder::new(channels as u16)?;
+    let mut pcm = vec![0.0f32; MAX_PACKET_SAMPLES * channels];
+    let mut samples = Vec::new();
+    let mut end_granule = 0;
+    while let Some(packet) = reader.read_packet().map_err(ogg_error)? {
+        let decoded = decoder.decode(&packet.data, &mut pcm)?;
+        samples.extend_from_slice(&pcm[..decoded * channels]);
+        end_granule = packet.absgp_page();
+    }
+
+    // Отбрасываем задержку кодировщика и дополнение последнего кадра
+    let frames = (end_granule as usize).saturating_sub(pre_skip);
+    let start = (pre_skip * channels).min(samples.len());
+    let end = (start + frames * channels).min(samples.len());
+    Ok(AudioData::new(samples[start..end].to_vec(), GRANULE_RATE, channels as u16))
+}
+
+/// Формирует заголовок OpusHead (RFC 7845, раздел 5.1)
+fn opus_head(channels: u8, pre_skip: u16, input_sample_rate: u32) -> Vec<u8> {
+    let mut head = b"OpusHead".to_vec();
+    head.push(1);
+    head.push(channels);
+    head.extend_from_slice(&pre_skip.to_le_bytes());
+    head.extend_from_slice(&input_sample_rate.to_le_bytes());
+    // Усиление 0 дБ, семейство раскладки каналов 0 (моно или стерео)
+    head.extend_from_slice(&0i16.to_le_bytes());
+    head.push(0);
+    head
+}
+
+/// Формирует заголовок OpusTags с комментариями (RFC 7845, раздел 5.2)
+fn opus_tags(metadata: &OutputMetadata) -> Vec<u8> {
+    let comments = metadata.vorbis_comments();
+    let mut tags = b"OpusTags".to_vec();
+    tags.extend_from_slice(&(VENDOR.len() as u32).to_le_bytes());
+    tags.extend_from_slice(VENDOR.as_bytes());
+    tags.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+    for (key, value) in comments {
+        let comment = format!("{}={}", key, value);
+        tags.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+        tags.extend_from_slice(comment.as_bytes());
+    }
+    tags
+}
+
+/// Возвращает ошибку обработки аудио с описанием кода ошибки libopus
+fn opus_error(stage: &str, code: i32) -> Error {
+    Error::new(ErrorType::AudioProcessingError, &format!("Ошибка Opus ({}): {}", stage, opus_strerror(code)))
+}
+
+/// Преобразует ошибку чтения Ogg в ошибку обработки аудио
+fn ogg_error(error: ogg::OggReadError) -> Error {
+    Error::new(ErrorType::AudioProcessingError, &format!("Ошибка чтения Ogg: {}", error))
+}
+
+/// Кодировщик libopus, освобождаемый при удалении
+struct Encoder(*mut OpusEncoder);
+
+impl Encoder {
+    fn new(sample_rate: u32, channels: u16) -> Result<Self> {
+        let mut code = OPUS_OK;
+        // SAFETY: параметры проверены вызывающим кодом, код ошибки пишется в локальную переменную
+        let encoder = Self(unsafe { opus_encoder_create(sample_rate as i32, channels as i32, OPUS_APPLICATION_AUDIO, &mut code) });
+        if encoder.0.is_null() || code != OPUS_OK {
+            return Err(opus_error("создание кодировщика", code));
+        }
+        // SAFETY: кодировщик создан успешно, запрос принимает одно целое число
+        let code = unsafe { opus_encoder_ctl!(encoder.0, OPUS_SET_BITRATE_REQUEST, BITRATE) };
+        if code != OPUS_OK {
+            return Err(opus_error("установка битрейта", code));
+        }
+        Ok(encoder)
+    }
+
+    /// Возвращает задержку кодировщика в сэмплах канала
+    fn lookahead(&self) -> Result<i32> {
+        let mut lookahead = 0;
+        // SAFETY: кодировщик жив, запрос записывает одно целое число
+        let code = unsafe { opus_encoder_ctl!(self.0, OPUS_GET_LOOKAHEAD_REQUEST, &mut lookahead) };
+        if code != OPUS_OK {
+            return Err(opus_error("задержка кодировщика", code));
+        }
+        Ok(lookahead)
+    }
+
+    /// Кодирует кадр из `frame_size` сэмплов канала в пакет
+    fn encode(&self, frame: &[f32], frame_size: usize) -> Result<Vec<u8>> {
+        let mut packet = vec![0u8; MAX_PACKET_SIZE];
+        // SAFETY: `frame` содержит `frame_size` сэмплов каждого канала, размер
+        // буфера пакета передается кодировщику
+        let size = unsafe {
+            opus_encode_float(self.0, frame.as_ptr(), frame_size as i32, packet.as_mut_ptr(), packet.len() as i32)
+        };
+        if size < 0 {
+            return Err(opus_error("кодирование", size));
+        }
+        packet.truncate(size as usize);
+        Ok(packet)
+    }
+}
+
+impl Drop for Encoder {
+    fn drop(&mut self) {
+        // SAFETY: указатель получен из opus_encoder_create и освобождается один раз
+        unsafe { opus_encoder_destroy(self.0) }
+    }
+}
+
+/// Декодер libopus с частотой 48 кГц, освобождаемый при удалении
+struct Decoder(*mut OpusDecoder);
+
+impl Decoder {
+    fn new(channels: u16) -> Result<Self> {
+        let mut code = OPUS_OK;
+        // SAFETY: код ошибки пишется в локальную переменную
+        let decoder = Self(unsafe { opus_decoder_create(GRANULE_RATE as i32, channels as i32, &mut code) });
+        if decoder.0.is_null() || code != OPUS_OK {
+            return Err(opus_error("создание декодера", code));
+        }
+        Ok(decoder)
+    }
+
+    /// Декодирует пакет в `pcm` и возвращает количество сэмплов канала
+    ///
+    /// Буфер `pcm` должен вмещать `MAX_PACKET_SAMPLES` сэмплов каждого канала.
+    fn decode(&self, packet: &[u8], pcm: &mut [f32]) -> Result<usize> {
+        // SAFETY: буфер вмещает наибольшую длительность пакета Opus
+        let decoded = unsafe {
+            opus_decode_float(self.0, packet.as_ptr(), packet.len() as i32, pcm.as_mut_ptr(), MAX_PACKET_SAMPLES as i32, 0)
+        };
+        if decoded < 0 {
+            return Err(opus_error("декодирование", decoded));
+        }
+        Ok(decoded as usize)
+    }
+}
+
+impl Drop for Decoder {
+    fn drop(&mut self) {
+        // SAFETY: указатель получен из opus_decoder_create и освобождается один раз
+        unsafe { opus_decoder_destroy(self.0) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(frames: usize, sample_rate: u32, channels: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|i| {
+                let t = i as f32 / sample_rate as f32;
+                std::iter::repeat(0.5 * (2.0 * std::f32::consts::PI * 440.0 * t).sin()).take(channels)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_opus_decodes_back() {
+        for (sample_rate, channels) in [(48000u32, 1usize), (24000, 1), (48000, 2)] {
+            // Длина не кратна кадру 20 мс
+            let frames = sample_rate as usize + 123;
+            let samples = sine(frames, sample_rate, channels);
+            let metadata = OutputMetadata::new().with_title("Дубляж");
+            let opus = encode_opus(&samples, sample_rate, channels as u16, sample_rate, &metadata).unwrap();
+            assert_eq!(&opus[..4], b"OggS");
+            let tag = "TITLE=Дубляж".as_bytes();
+            assert!(opus.windows(tag.len()).any(|w| w == tag));
+
+            // Декодер выдает 48 кГц: длительность совпадает с исходной
+            let decoded = decode_opus(&opus).unwrap();
+            assert_eq!((decoded.sample_rate, decoded.channels), (48000, channels as u16));
+            let expected_frames = frames * (48000 / sample_rate) as usize;
+            assert_eq!(decoded.samples.len(), expected_frames * channels);
+            assert!((decoded.duration() - frames as f64 / sample_rate as f64).abs() < 1e-9);
+
+            // Задержка кодировщика отброшена: тон начинается с первого сэмпла
+            let original = sine(expected_frames, 48000, channels);
+            let error = decoded.samples.iter().zip(&original).map(|(a, b)| (a - b).abs()).sum::<f32>()
+                / original.len() as f32;
+            assert!(error < 0.05, "{} Гц, {} каналов: {}", sample_rate, channels, error);
+        }
+    }
+
+    #[test]
+    fn test_encode_opus_rejects_unsupported_parameters() {
+        let samples = vec![0.0; 960];
+        assert!(encode_opus(&samples, 44100, 1, 44100, &OutputMetadata::new()).is_err());
+        assert!(encode_opus(&samples, 48000, 3, 48000, &OutputMetadata::new()).is_err());
+        assert!(decode_opus(b"not an ogg file").is_err());
+        assert_eq!(opus_sample_rate(44100), 48000);
+        assert_eq!(opus_sample_rate(24000), 24000);
+    }
+}
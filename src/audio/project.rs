@@ -0,0 +1,122 @@
+//! Сохранение и загрузка аудио трека в формате проекта (`.ttsproj`).
+//!
+//! Проект - это JSON с сегментами, таймингами и текстами; сэмплы хранятся
+//! в base64 (f32 little-endian), поэтому трек восстанавливается без потерь
+//! и без повторного синтеза речи.
+
+use crate::audio::models::AudioTrack;
+use crate::error::{Error, ErrorType, Result};
+use crate::logging::log_debug;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Идентификатор формата файла проекта
+const PROJECT_FORMAT: &str = "tts-sync-project";
+/// Текущая версия формата файла проекта
+const PROJECT_VERSION: u32 = 1;
+
+/// Содержимое файла проекта
+#[derive(Serialize, Deserialize)]
+struct ProjectFile {
+    format: String,
+    version: u32,
+    track: AudioTrack,
+}
+
+impl AudioTrack {
+    /// Сериализует трек в JSON формата проекта
+    pub fn to_project_json(&self) -> Result<String> {
+        let project = ProjectFile {
+            format: PROJECT_FORMAT.to_string(),
+            version: PROJECT_VERSION,
+            track: self.clone(),
+        };
+
+        Ok(serde_json::to_string_pretty(&project)?)
+    }
+
+    /// Восстанавливает трек из JSON формата проекта
+    pub fn from_project_json(json: &str) -> Result<Self> {
+        let project: ProjectFile = serde_json::from_str(json).map_err(|e| Error::new(
+            ErrorType::AudioProcessingError,
+            &format!("Ошибка чтения проекта: {}", e)
+        ))?;
+
+        if project.format != PROJECT_FORMAT {
+            return Err(Error::new(
+                ErrorType::AudioProcessingError,
+                &format!("Неизвестный формат проекта: {}", project.format)
+            ));
+        }
+
+        if project.version > PROJECT_VERSION {
+            return Err(Error::new(
+                ErrorType::AudioProcessingError,
+                &format!("Неподдерживаемая версия проекта: {}", project.version)
+            ));
+        }
+
+        Ok(project.track)
+    }
+
+    /// Сохраняет трек в файл проекта
+    pub fn save_project<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let json = self.to_project_json()?;
+        std::fs::write(path.as_ref(), json)?;
+        log_debug(&format!("Проект сохранен: {}", path.as_ref().display()));
+        Ok(())
+    }
+
+    /// Загружает трек из файла проекта
+    pub fn load_project<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let json = std::fs::read_to_string(path.as_ref())?;
+        log_debug(&format!("Загрузка проекта: {}", path.as_ref().display()));
+        Self::from_project_json(&json)
+    }
+}
+
+/// Сериализация сэмплов в base64 (f32 little-endian)
+pub(crate) mod samples_base64 {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(samples: &[f32], serializer: S) -> Result<S::Ok, S::Error> {
+        let bytes: Vec<u8> = samples.iter().flat_map(|sample| sample.to_le_bytes()).collect();
+        serializer.serialize_str(&STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f32>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        let bytes = STANDARD.decode(encoded).map_err(de::Error::custom)?;
+
+        if bytes.len() % 4 != 0 {
+            return Err(de::Error::custom("длина данных сэмплов не кратна 4 байтам"));
+        }
+
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect())
+    }
+}
+
+/// Сериализация исходных бинарных данных в base64
+pub(crate) mod raw_data_base64 {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &Option<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error> {
+        match data {
+            Some(bytes) => serializer.serialize_some(&STANDARD.encode(bytes)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<u8>>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|encoded| STANDARD.decode(encoded).map_err(de::Error::custom))
+            .transpose()
+    }
+}
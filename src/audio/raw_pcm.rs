@@ -0,0 +1,98 @@
+//! Декодирование "сырого" PCM без заголовка (например, от локальных TTS движков).
+
+use crate::audio::models::AudioData;
+use crate::error::{Error, ErrorType, Result};
+
+/// Порядок байтов в сэмплах PCM
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Endianness {
+    /// Младший байт первым
+    #[default]
+    Little,
+    /// Старший байт первым
+    Big,
+}
+
+/// Описание формата сырого PCM
+///
+/// Сэмплы - целые числа со знаком (8-битные - без знака, как в WAV),
+/// каналы чередуются по кадрам.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawPcmSpec {
+    /// Разрядность сэмпла: 8, 16, 24 или 32 бита
+    pub bits: u16,
+    /// Порядок байтов
+    pub endian: Endianness,
+    /// Количество каналов
+    pub channels: u16,
+    /// Частота дискретизации
+    pub sample_rate: u32,
+}
+
+impl Default for RawPcmSpec {
+    /// 16 бит, little-endian, моно, 24000 Гц (формат `pcm` OpenAI)
+    fn default() -> Self {
+        Self {
+            bits: 16,
+            endian: Endianness::Little,
+            channels: 1,
+            sample_rate: 24000,
+        }
+    }
+}
+
+impl RawPcmSpec {
+    /// Возвращает размер одного сэмпла в байтах
+    pub fn bytes_per_sample(&self) -> usize {
+        self.bits as usize / 8
+    }
+
+    /// Декодирует байты в float сэмплы в диапазоне [-1.0, 1.0)
+    ///
+    /// Неполный последний кадр отбрасывается.
+    pub fn decode(&self, data: &[u8]) -> Result<AudioData> {
+        if !matches!(self.bits, 8 | 16 | 24 | 32) {
+            return Err(Error::new(
+                ErrorType::InvalidParameters,
+                &format!("Неподдерживаемая разрядность PCM: {} бит", self.bits),
+            ));
+        }
+        if self.channels == 0 || self.sample_rate == 0 {
+            return Err(Error::new(
+                ErrorType::InvalidParameters,
+                "Количество каналов и частота дискретизации PCM должны быть больше нуля",
+            ));
+        }
+
+        let sample_size = self.bytes_per_sample();
+        let frame_size = sample_size * self.channels as usize;
+        let usable = data.len() / frame_size * frame_size;
+        let scale = (1u64 << (self.bits - 1)) as f32;
+
+        let samples = data[..usable]
+            .chunks_exact(sample_size)
+            .map(|bytes| self.read_sample(bytes) as f32 / scale)
+            .collect();
+
+        Ok(AudioData::new(samples, self.sample_rate, self.channels))
+    }
+
+    /// Читает один сэмпл как целое со знаком
+    fn read_sample(&self, bytes: &[u8]) -> i32 {
+        if self.bits == 8 {
+            return bytes[0] as i32 - 128;
+        }
+
+        // Собираем байты в старшие разряды i32, чтобы сохранить знак при сдвиге
+        let mut value: u32 = 0;
+        for i in 0..bytes.len() {
+            let byte = match self.endian {
+                Endianness::Little => bytes[bytes.len() - 1 - i],
+                Endianness::Big => bytes[i],
+            };
+            value = (value << 8) | byte as u32;
+        }
+        let shift = 32 - self.bits as u32;
+        ((value << shift) as i32) >> shift
+    }
+}
@@ -0,0 +1,213 @@
+//! Приемники аудио для прослушивания результата во время синхронизации.
+
+use crate::audio::models::{AudioData, AudioSegment};
+use crate::error::Result;
+
+#[cfg(feature = "playback")]
+pub use playback::PlaybackSink;
+
+/// Приемник готовых сегментов аудио (например, проигрыватель для предпрослушивания)
+///
+/// Сегменты передаются в порядке воспроизведения, включая сегменты тишины
+/// между репликами, поэтому последовательное воспроизведение их сэмплов
+/// соответствует итоговому треку.
+pub trait AudioSink {
+    /// Принимает очередной сегмент
+    fn write_segment(&mut self, segment: &AudioSegment) -> Result<()>;
+
+    /// Вызывается после передачи последнего сегмента
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Передает сегменты в приемник подряд по шкале времени результата
+///
+/// Промежутки между сегментами заполняются тишиной, начало сегмента,
+/// перекрывающееся с уже переданным звуком или лежащее до нуля, отбрасывается.
+pub(crate) struct SinkWriter<'a, S: AudioSink + ?Sized> {
+    sink: &'a mut S,
+    sample_rate: u32,
+    channels: u16,
+    /// Время конца переданного звука в секундах
+    position: f64,
+}
+
+impl<'a, S: AudioSink + ?Sized> SinkWriter<'a, S> {
+    pub(crate) fn new(sink: &'a mut S, sample_rate: u32, channels: u16) -> Self {
+        Self { sink, sample_rate, channels, position: 0.0 }
+    }
+
+    /// Передает сегмент так, чтобы его звук начинался в `start_time` секунд
+    pub(crate) fn write_at(&mut self, segment: &AudioSegment, start_time: f64) -> Result<()> {
+        self.write_silence_until(start_time)?;
+
+        let mut segment = segment.clone();
+        let duration = segment.audio.duration();
+        let channels = segment.audio.channels.max(1) as usize;
+        let skipped_frames = ((self.position - start_time).max(0.0) * segment.audio.sample_rate as f64).round() as usize;
+        let skipped = (skipped_frames * channels).min(segment.audio.samples.len());
+        if skipped == segment.audio.samples.len() {
+            return Ok(());
+        }
+        segment.audio.samples.drain(..skipped);
+        segment.start_time = start_time.max(self.position);
+        segment.end_time = start_time + duration;
+        segment.raw_data = None;
+
+        self.sink.write_segment(&segment)?;
+        self.position = segment.end_time;
+        Ok(())
+    }
+
+    /// Передает тишину до `end_time` секунд и завершает передачу
+    pub(crate) fn finish_at(&mut self, end_time: f64) -> Result<()> {
+        self.write_silence_until(end_time)?;
+        self.sink.finish()
+    }
+
+    fn write_silence_until(&mut self, time: f64) -> Result<()> {
+        let gap = time - self.position;
+        if gap <= 1e-6 {
+            return Ok(());
+        }
+        let silence = AudioData::new(
+            vec![0.0f32; (gap * self.sample_rate as f64).round() as usize * self.channels as usize],
+            self.sample_rate,
+            self.channels
+        );
+        self.sink.write_segment(&AudioSegment::new(silence, self.position, time, String::new()))?;
+        self.position = time;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "playback")]
+mod playback {
+    use super::AudioSink;
+    use crate::audio::models::AudioSegment;
+    use crate::error::{Error, ErrorType, Result};
+    use crate::logging::log_warning;
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+    use std::collections::VecDeque;
+    use std::sync::mpsc;
+    use std::sync::{Arc, Mutex};
+    use std::thread::JoinHandle;
+    use std::time::Duration;
+
+    /// Приемник, воспроизводящий сегменты на устройстве вывода по умолчанию (cpal)
+    ///
+    /// Поток вывода создается в отдельном потоке, так как `cpal::Stream`
+    /// не везде можно передавать между потоками. Сегменты добавляются в
+    /// очередь воспроизведения; [`AudioSink::finish`] ждет, пока очередь
+    /// не будет проиграна.
+    pub struct PlaybackSink {
+        queue: Arc<Mutex<VecDeque<f32>>>,
+        sample_rate: u32,
+        channels: u16,
+        stop: Option<mpsc::Sender<()>>,
+        thread: Option<JoinHandle<()>>,
+    }
+
+    impl PlaybackSink {
+        /// Открывает устройство вывода по умолчанию с указанными частотой и
+        /// количеством каналов (совпадающими с параметрами синхронизации)
+        pub fn new(sample_rate: u32, channels: u16) -> Result<Self> {
+            let queue = Arc::new(Mutex::new(VecDeque::new()));
+            let (stop, stop_receiver) = mpsc::channel::<()>();
+            let (ready, ready_receiver) = mpsc::channel::<Result<()>>();
+
+            let stream_queue = queue.clone();
+            let thread = std::thread::spawn(move || {
+                let stream = match Self::open_stream(sample_rate, channels, stream_queue) {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        let _ = ready.send(Err(e));
+                        return;
+                    }
+                };
+                let _ = ready.send(Ok(()));
+                // Поток вывода живет, пока приемник не завершен или не удален
+                let _ = stop_receiver.recv();
+                drop(stream);
+            });
+
+            let opened = ready_receiver.recv().unwrap_or_else(|_| Err(Error::new(
+                ErrorType::AudioProcessingError,
+                "Поток воспроизведения завершился до открытия устройства"
+            )));
+            if let Err(e) = opened {
+                let _ = thread.join();
+                return Err(e);
+            }
+
+            Ok(Self { queue, sample_rate, channels, stop: Some(stop), thread: Some(thread) })
+        }
+
+        fn open_stream(sample_rate: u32, channels: u16, queue: Arc<Mutex<VecDeque<f32>>>) -> Result<cpal::Stream> {
+            let device_error = |message: String| Error::new(ErrorType::AudioProcessingError, &message);
+            let device = cpal::default_host()
+                .default_output_device()
+                .ok_or_else(|| device_error("Устройство вывода звука не найдено".to_string()))?;
+            let config = cpal::StreamConfig {
+                channels,
+                sample_rate: cpal::SampleRate(sample_rate),
+                buffer_size: cpal::BufferSize::Default,
+            };
+
+            let stream = device.build_output_stream(
+                &config,
+                move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                    let mut queue = queue.lock().unwrap();
+                    for sample in data.iter_mut() {
+                        *sample = queue.pop_front().unwrap_or(0.0);
+                    }
+                },
+                |e| log_warning(&format!("Ошибка воспроизведения: {}", e)),
+                None,
+            ).map_err(|e| device_error(format!("Не удалось открыть поток воспроизведения: {}", e)))?;
+            stream.play()
+                .map_err(|e| device_error(format!("Не удалось начать воспроизведение: {}", e)))?;
+            Ok(stream)
+        }
+
+        fn stop(&mut self) {
+            self.stop.take();
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+
+    impl AudioSink for PlaybackSink {
+        fn write_segment(&mut self, segment: &AudioSegment) -> Result<()> {
+            if segment.audio.channels != self.channels {
+                return Err(Error::new(
+                    ErrorType::InvalidParameters,
+                    &format!("Сегмент с {} каналами, устройство открыто с {}", segment.audio.channels, self.channels),
+                ));
+            }
+            let audio = if segment.audio.sample_rate == self.sample_rate {
+                segment.audio.clone()
+            } else {
+                segment.audio.resample(self.sample_rate)?
+            };
+            self.queue.lock().unwrap().extend(audio.samples);
+            Ok(())
+        }
+
+        fn finish(&mut self) -> Result<()> {
+            while !self.queue.lock().unwrap().is_empty() {
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            self.stop();
+            Ok(())
+        }
+    }
+
+    impl Drop for PlaybackSink {
+        fn drop(&mut self) {
+            self.stop();
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::error::{Error, Result, ErrorType};
 use crate::audio::models::AudioData;
+use crate::audio::raw_pcm::RawPcmSpec;
 use crate::logging::{log_debug, log_info, log_warning, log_error};
 use std::io::Cursor;
 use symphonia::core::io::MediaSourceStream;
@@ -10,8 +11,139 @@ use symphonia::core::audio::{SampleBuffer};
 use symphonia::core::probe::Hint;
 use symphonia::default::{get_probe, get_codecs};
 
+/// Поведение при несовпадении частоты дискретизации, возвращенной провайдером, с ожидаемой
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SampleRateMismatch {
+    /// Передискретизировать аудио с фактической частоты в частоту конвейера
+    #[default]
+    Resample,
+    /// Считать, что аудио записано с ожидаемой частотой
+    Ignore,
+    /// Вернуть ошибку
+    Error,
+}
+
 /// Декодирует MP3 данные в аудио сэмплы
+///
+/// Аудио сводится в моно и передискретизируется в `sample_rate`, если
+/// фактическая частота декодированных данных отличается.
 pub fn decode_mp3_to_samples(mp3_data: &[u8], sample_rate: u32, _channels: u16) -> Result<AudioData> {
+    decode_tts_audio(mp3_data, sample_rate, sample_rate, SampleRateMismatch::Resample)
+}
+
+/// Декодирует аудио от TTS провайдера и приводит его к частоте конвейера
+///
+/// `expected_sample_rate` - частота, которую должен вернуть провайдер,
+/// `target_sample_rate` - частота дискретизации конвейера. При несовпадении
+/// фактической частоты с ожидаемой выводится предупреждение, а дальнейшее
+/// поведение определяется `on_mismatch`.
+pub fn decode_tts_audio(
+    data: &[u8],
+    expected_sample_rate: u32,
+    target_sample_rate: u32,
+    on_mismatch: SampleRateMismatch,
+) -> Result<AudioData> {
+    decode_tts_audio_with_limit(data, expected_sample_rate, target_sample_rate, on_mismatch, None)
+}
+
+/// Декодирует аудио от TTS провайдера с ограничением размера результата
+///
+/// То же, что [`decode_tts_audio`], но декодирование прерывается с ошибкой,
+/// как только количество декодированных сэмплов (всех каналов) превысит
+/// `max_samples`. Защищает от неограниченного расхода памяти на поврежденных
+/// или слишком длинных данных. `None` - без ограничения.
+pub fn decode_tts_audio_with_limit(
+    data: &[u8],
+    expected_sample_rate: u32,
+    target_sample_rate: u32,
+    on_mismatch: SampleRateMismatch,
+    max_samples: Option<usize>,
+) -> Result<AudioData> {
+    conform_tts_audio(decode_samples(data, None, max_samples)?, expected_sample_rate, target_sample_rate, on_mismatch)
+}
+
+/// Декодирует аудио файла в контейнере (WAV, MP3, FLAC, OGG, Opus) без
+/// изменения частоты и каналов
+///
+/// `extension` - расширение файла, подсказка для определения формата. OGG
+/// и Opus декодируются встроенными декодерами с учетом задержки кодека.
+pub(crate) fn decode_audio_file_data(data: &[u8], extension: &str) -> Result<AudioData> {
+    match extension {
+        #[cfg(feature = "vorbis")]
+        "ogg" => crate::audio::vorbis::decode_vorbis(data),
+        #[cfg(feature = "opus")]
+        "opus" => crate::audio::opus::decode_opus(data),
+        _ => decode_samples(data, Some(extension), None),
+    }
+}
+
+/// Декодирует сырой PCM от TTS провайдера по описанию `spec` и приводит
+/// его к частоте конвейера
+///
+/// Частота из `spec` считается фактической частотой аудио; параметры
+/// `expected_sample_rate`, `target_sample_rate` и `on_mismatch` имеют тот же
+/// смысл, что и в [`decode_tts_audio`].
+pub fn decode_raw_tts_audio(
+    data: &[u8],
+    spec: &RawPcmSpec,
+    expected_sample_rate: u32,
+    target_sample_rate: u32,
+    on_mismatch: SampleRateMismatch,
+) -> Result<AudioData> {
+    if data.is_empty() {
+        return Err(Error::new(ErrorType::AudioProcessingError, "Пустые PCM данные"));
+    }
+    log_debug(&format!("Декодирование PCM ({} бит, {:?}) размером {} байт", spec.bits, spec.endian, data.len()));
+    
+    conform_tts_audio(spec.decode(data)?, expected_sample_rate, target_sample_rate, on_mismatch)
+}
+
+/// Сводит декодированное аудио в моно и приводит его к частоте конвейера
+fn conform_tts_audio(
+    decoded: AudioData,
+    expected_sample_rate: u32,
+    target_sample_rate: u32,
+    on_mismatch: SampleRateMismatch,
+) -> Result<AudioData> {
+    let mono = decoded.to_mono();
+
+    let source_rate = if decoded.sample_rate != expected_sample_rate {
+        log_warning(&format!(
+            "Провайдер вернул аудио с частотой {} Гц вместо ожидаемой {} Гц",
+            decoded.sample_rate, expected_sample_rate
+        ));
+
+        match on_mismatch {
+            SampleRateMismatch::Resample => decoded.sample_rate,
+            SampleRateMismatch::Ignore => expected_sample_rate,
+            SampleRateMismatch::Error => {
+                return Err(Error::new(
+                    ErrorType::AudioProcessingError,
+                    &format!(
+                        "Неожиданная частота дискретизации: {} Гц вместо {} Гц",
+                        decoded.sample_rate, expected_sample_rate
+                    ),
+                ));
+            }
+        }
+    } else {
+        decoded.sample_rate
+    };
+
+    let audio = AudioData::new(mono.samples, source_rate, 1);
+    if source_rate == target_sample_rate {
+        return Ok(audio);
+    }
+
+    log_debug(&format!("Передискретизация аудио: {} Гц -> {} Гц", source_rate, target_sample_rate));
+    audio.resample(target_sample_rate)
+}
+
+/// Декодирует аудио данные, сохраняя фактические частоту и количество каналов
+///
+/// `extension` - необязательная подсказка формата по расширению файла.
+/// При превышении `max_samples` декодирование прерывается с ошибкой.
+fn decode_samples(mp3_data: &[u8], extension: Option<&str>, max_samples: Option<usize>) -> Result<AudioData> {
     log_debug(&format!("Декодирование MP3 данных размером {} байт", mp3_data.len()));
     
     if mp3_data.is_empty() {
@@ -38,7 +170,10 @@ pub fn decode_mp3_to_samples(mp3_data: &[u8], sample_rate: u32, _channels: u16)
     };
     let metadata_opts = MetadataOptions::default();
     let probe = get_probe();
-    let hint = Hint::new();
+    let mut hint = Hint::new();
+    if let Some(extension) = extension {
+        hint.with_extension(extension);
+    }
 
     // Определяем формат
     let probe_result = match probe.format(&hint, mss, &format_opts, &metadata_opts) {
@@ -83,6 +218,8 @@ pub fn decode_mp3_to_samples(mp3_data: &[u8], sample_rate: u32, _channels: u16)
     let mut decoder = decoder;
     let mut _sample_count = 0;
     let mut all_samples = Vec::new();
+    let mut decoded_rate = codec_params.sample_rate.unwrap_or(0);
+    let mut decoded_channels = codec_params.channels.map(|c| c.count() as u16).unwrap_or(1);
 
     // Декодируем пакеты
     loop {
@@ -118,10 +255,20 @@ pub fn decode_mp3_to_samples(mp3_data: &[u8], sample_rate: u32, _channels: u16)
 
                 // Создаем буфер для сэмплов
                 let mut sample_buffer = SampleBuffer::<f32>::new(duration, spec);
+                decoded_rate = spec.rate;
+                decoded_channels = spec.channels.count() as u16;
                 
-                // Заполняем буфер и конвертируем в f32
-                sample_buffer.copy_planar_ref(decoded);
+                // Заполняем буфер и конвертируем в f32 (сэмплы каналов чередуются)
+                sample_buffer.copy_interleaved_ref(decoded);
                 let samples = sample_buffer.samples();
+                if let Some(max_samples) = max_samples {
+                    if all_samples.len() + samples.len() > max_samples {
+                        return Err(Error::new(
+                            ErrorType::AudioProcessingError,
+                            &format!("Декодированное аудио превышает предел в {} сэмплов", max_samples)
+                        ));
+                    }
+                }
                 all_samples.extend_from_slice(samples);
                 
                 _sample_count += samples.len();
@@ -138,30 +285,12 @@ pub fn decode_mp3_to_samples(mp3_data: &[u8], sample_rate: u32, _channels: u16)
         return Err(Error::new(ErrorType::AudioProcessingError, "Не получены аудио сэмплы"));
     }
     
-    // Преобразуем сэмплы в моно если требуется
-    // Расчёт происходит на уровне уже имеющихся сэмплов
-    log_debug(&format!("Декодировано всего {} сэмплов. Создаём AudioData...", all_samples.len()));
+    log_debug(&format!("Декодировано всего {} сэмплов ({} Гц, каналов: {}). Создаём AudioData...",
+        all_samples.len(), decoded_rate, decoded_channels));
     
-    // Возвращаем AudioData с заданной частотой дискретизации
     Ok(AudioData {
         samples: all_samples,
-        sample_rate,
-        channels: 1 // По умолчанию используем монофонический формат
+        sample_rate: decoded_rate,
+        channels: decoded_channels,
     })
 }
-
-/// Преобразует многоканальное аудио в моно
-fn convert_to_mono(samples: &[f32], channels: usize) -> Vec<f32> {
-    if channels == 1 {
-        return samples.to_vec();
-    }
-
-    let mut mono = Vec::with_capacity(samples.len() / channels);
-    
-    for chunk in samples.chunks(channels) {
-        let sum: f32 = chunk.iter().sum();
-        mono.push(sum / channels as f32);
-    }
-    
-    mono
-} 
\ No newline at end of file
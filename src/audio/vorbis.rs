@@ -0,0 +1,112 @@
+//! Кодирование и декодирование Ogg Vorbis (функция `vorbis`).
+//!
+//! Кодирует aoTuV (`vorbis_rs`) в режиме VBR с качеством 0.6, как
+//! `-q:a 6` у ffmpeg. Декодирует libvorbisfile: задержка кодека и
+//! дополнение последнего блока отбрасываются по позициям гранул, поэтому
+//! количество сэмплов совпадает с исходным.
+
+use crate::audio::metadata::OutputMetadata;
+use crate::audio::models::AudioData;
+use crate::error::{Error, ErrorType, Result};
+use std::io::Cursor;
+use std::num::{NonZeroU32, NonZeroU8};
+use vorbis_rs::{VorbisBitrateManagementStrategy, VorbisDecoder, VorbisEncoderBuilder, VorbisError};
+
+/// Целевое качество VBR (от -0.2 до 1.0)
+const QUALITY: f32 = 0.6;
+
+/// Количество сэмплов канала, передаваемых кодировщику за один вызов
+const BLOCK_SIZE: usize = 4096;
+
+/// Номер логического потока Ogg
+///
+/// Постоянный номер делает результат кодирования воспроизводимым.
+const STREAM_SERIAL: i32 = 1;
+
+/// Кодирует перемеженные по кадрам сэмплы в файл Ogg Vorbis
+///
+/// Заданные поля `metadata` записываются в комментарии Vorbis.
+pub(crate) fn encode_vorbis(samples: &[f32], sample_rate: u32, channels: u16, metadata: &OutputMetadata) -> Result<Vec<u8>> {
+    let sampling_frequency = NonZeroU32::new(sample_rate).ok_or_else(|| Error::new(
+        ErrorType::InvalidParameters,
+        "Некорректная частота дискретизации для Vorbis: 0 Гц",
+    ))?;
+    let channel_count = u8::try_from(channels).ok().and_then(NonZeroU8::new).ok_or_else(|| Error::new(
+        ErrorType::InvalidParameters,
+        &format!("Vorbis поддерживает от 1 до 255 каналов, получено {}", channels),
+    ))?;
+
+    let mut builder = VorbisEncoderBuilder::new_with_serial(sampling_frequency, channel_count, Vec::new(), STREAM_SERIAL);
+    builder.bitrate_management_strategy(VorbisBitrateManagementStrategy::QualityVbr { target_quality: QUALITY });
+    builder.comment_tags(metadata.vorbis_comments()).map_err(vorbis_error)?;
+    let mut encoder = builder.build().map_err(vorbis_error)?;
+
+    // Кодировщик принимает сэмплы раздельно по каналам
+    let channels = channels as usize;
+    let frames = samples.len() / channels;
+    let planar: Vec<Vec<f32>> = (0..channels)
+        .map(|channel| samples.iter().skip(channel).step_by(channels).take(frames).copied().collect())
+        .collect();
+    for start in (0..frames).step_by(BLOCK_SIZE) {
+        let end = (start + BLOCK_SIZE).min(frames);
+        let block: Vec<&[f32]> = planar.iter().map(|channel| &channel[start..end]).collect();
+        encoder.encode_audio_block(&block).map_err(vorbis_error)?;
+    }
+    encoder.finish().map_err(vorbis_error)
+}
+
+/// Декодирует файл Ogg Vorbis в перемеженные по кадрам сэмплы
+pub(crate) fn decode_vorbis(data: &[u8]) -> Result<AudioData> {
+    let mut decoder = VorbisDecoder::new(Cursor::new(data.to_vec())).map_err(vorbis_error)?;
+    let sample_rate = decoder.sampling_frequency().get();
+    let channels = decoder.channels().get() as usize;
+
+    let mut samples = Vec::new();
+    while let Some(block) = decoder.decode_audio_block().map_err(vorbis_error)? {
+        let block = block.samples();
+        let frames = block.first().map_or(0, |channel| channel.len());
+        samples.reserve(frames * channels);
+        for frame in 0..frames {
+            samples.extend(block.iter().map(|channel| channel[frame]));
+        }
+    }
+    Ok(AudioData::new(samples, sample_rate, channels as u16))
+}
+
+/// Преобразует ошибку `vorbis_rs` в ошибку обработки аудио
+fn vorbis_error(error: VorbisError) -> Error {
+    Error::new(ErrorType::AudioProcessingError, &format!("Ошибка Vorbis: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_vorbis_decodes_back() {
+        for (sample_rate, channels) in [(44100u32, 1usize), (24000, 1), (48000, 2)] {
+            // Длина не кратна размеру блока кодировщика
+            let frames = sample_rate as usize + 123;
+            let samples: Vec<f32> = (0..frames)
+                .flat_map(|i| {
+                    let t = i as f32 / sample_rate as f32;
+                    std::iter::repeat(0.5 * (2.0 * std::f32::consts::PI * 440.0 * t).sin()).take(channels)
+                })
+                .collect();
+            let ogg = encode_vorbis(&samples, sample_rate, channels as u16, &OutputMetadata::new().with_title("Дубляж")).unwrap();
+            assert_eq!(&ogg[..4], b"OggS");
+            let tag = "TITLE=Дубляж".as_bytes();
+            assert!(ogg.windows(tag.len()).any(|w| w == tag));
+
+            let decoded = decode_vorbis(&ogg).unwrap();
+            assert_eq!((decoded.sample_rate, decoded.channels), (sample_rate, channels as u16));
+            assert_eq!(decoded.samples.len(), samples.len());
+            assert!((decoded.duration() - frames as f64 / sample_rate as f64).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_decode_vorbis_rejects_garbage() {
+        assert!(decode_vorbis(b"not an ogg file").is_err());
+    }
+}
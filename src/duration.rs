@@ -0,0 +1,143 @@
+//! Разбор длительностей, заданных в человекочитаемом виде.
+
+use crate::error::{Error, ErrorType, Result};
+
+/// Разбирает строку длительности и возвращает количество секунд
+///
+/// Поддерживаемые форматы:
+/// * `"01:23:45.678"` и `"23:45.678"` - часы, минуты и секунды через двоеточие;
+/// * `"83s"`, `"1h2m3s"`, `"1m30.5s"` - значения с единицами `h`, `m`, `s`;
+/// * `"83"`, `"83.5"` - секунды.
+pub fn parse_duration(s: &str) -> Result<f64> {
+    let input = s.trim();
+    if input.is_empty() {
+        return Err(invalid_duration(s));
+    }
+
+    let seconds = if input.contains(':') {
+        parse_clock(input)
+    } else if input.ends_with(|c: char| c.is_ascii_alphabetic()) {
+        parse_units(input)
+    } else {
+        parse_number(input)
+    }
+    .ok_or_else(|| invalid_duration(s))?;
+
+    Ok(seconds)
+}
+
+/// Разбирает формат `[ЧЧ:]ММ:СС[.ммм]`
+fn parse_clock(input: &str) -> Option<f64> {
+    let parts: Vec<&str> = input.split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+
+    let (last, rest) = parts.split_last()?;
+    let mut seconds = parse_number(last)?;
+    if seconds >= 60.0 {
+        return None;
+    }
+
+    for (part, multiplier) in rest.iter().rev().zip([60.0, 3600.0]) {
+        if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let value: f64 = part.parse().ok()?;
+        if multiplier == 60.0 && rest.len() == 2 && value >= 60.0 {
+            return None;
+        }
+        seconds += value * multiplier;
+    }
+
+    Some(seconds)
+}
+
+/// Разбирает формат с единицами измерения, например `1h2m3s`
+fn parse_units(input: &str) -> Option<f64> {
+    let mut total = 0.0;
+    let mut number = String::new();
+    let mut last_rank = usize::MAX;
+
+    for c in input.chars() {
+        if c.is_ascii_digit() || c == '.' {
+            number.push(c);
+            continue;
+        }
+
+        let (multiplier, rank) = match c.to_ascii_lowercase() {
+            'h' => (3600.0, 2),
+            'm' => (60.0, 1),
+            's' => (1.0, 0),
+            _ => return None,
+        };
+
+        // Единицы должны идти от больших к меньшим и не повторяться
+        if rank >= last_rank {
+            return None;
+        }
+        last_rank = rank;
+
+        total += parse_number(&number)? * multiplier;
+        number.clear();
+    }
+
+    if number.is_empty() {
+        Some(total)
+    } else {
+        None
+    }
+}
+
+/// Разбирает неотрицательное конечное число
+fn parse_number(input: &str) -> Option<f64> {
+    if input.is_empty() || !input.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+
+    input.parse::<f64>().ok().filter(|value| value.is_finite())
+}
+
+fn invalid_duration(s: &str) -> Error {
+    Error::new(
+        ErrorType::InvalidParameters,
+        &format!("Некорректная длительность: '{}'", s)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_clock_format() {
+        assert!((parse_duration("01:23:45.678").unwrap() - 5025.678).abs() < 1e-9);
+        assert_eq!(parse_duration("00:00:10").unwrap(), 10.0);
+        assert_eq!(parse_duration("02:30").unwrap(), 150.0);
+    }
+
+    #[test]
+    fn test_parse_unit_format() {
+        assert_eq!(parse_duration("83s").unwrap(), 83.0);
+        assert_eq!(parse_duration("1h2m3s").unwrap(), 3723.0);
+        assert_eq!(parse_duration("1m30.5s").unwrap(), 90.5);
+        assert_eq!(parse_duration("2h").unwrap(), 7200.0);
+    }
+
+    #[test]
+    fn test_parse_plain_seconds() {
+        assert_eq!(parse_duration("83").unwrap(), 83.0);
+        assert_eq!(parse_duration(" 12.25 ").unwrap(), 12.25);
+    }
+
+    #[test]
+    fn test_parse_malformed_input() {
+        for input in ["", "abc", "1x", "1s2m", "1:2:3:4", "00:61", "-5", "1h2", "..", "1:xx"] {
+            let result = parse_duration(input);
+            assert!(
+                matches!(result, Err(Error::InvalidParameters(_))),
+                "Ожидалась ошибка для '{}'", input
+            );
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use serde::Deserialize;
 use thiserror::Error;
 
 /// Типы ошибок, которые могут возникнуть при синхронизации
@@ -15,6 +16,18 @@ pub enum ErrorType {
     Synchronization,
     /// Неверные параметры
     InvalidParameters,
+    /// Ошибка аудио (синоним `AudioProcessingError`)
+    AudioError,
+    /// Ошибка библиотеки rubato
+    RubatoError,
+    /// Ошибка HTTP запроса
+    Http,
+    /// Ошибка сериализации или разбора JSON
+    Json,
+    /// Операция отменена
+    Cancelled,
+    /// Неизвестная ошибка
+    Unknown,
 }
 
 /// Ошибки, которые могут возникнуть при синхронизации
@@ -29,6 +42,9 @@ pub enum Error {
     #[error("Ошибка OpenAI API: {0}")]
     OpenAi(String),
     
+    #[error("Ошибка OpenAI API: {0}")]
+    OpenAiApi(OpenAiApiError),
+    
     #[error("Ошибка обработки аудио: {0}")]
     AudioProcessing(String),
     
@@ -40,10 +56,29 @@ pub enum Error {
 
     #[error("Ошибка логирования: {0}")]
     LoggedError(String),
+
+    #[error("Ошибка в библиотеке rubato: {0}")]
+    Rubato(String),
+
+    /// Ошибка HTTP запроса; для ошибок reqwest источник - `reqwest::Error`
+    #[error("Ошибка HTTP запроса: {0}")]
+    Http(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    #[error("Ошибка JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Операция отменена: {0}")]
+    Cancelled(String),
+
+    #[error("Неизвестная ошибка: {0}")]
+    Unknown(String),
 }
 
 impl Error {
     /// Создает новую ошибку указанного типа с сообщением
+    ///
+    /// Единственный конструктор ошибок по типу: каждому `ErrorType`
+    /// соответствует вариант `Error`.
     pub fn new(error_type: ErrorType, message: &str) -> Self {
         match error_type {
             ErrorType::Io => Self::Io(std::io::Error::new(std::io::ErrorKind::Other, message)),
@@ -52,9 +87,209 @@ impl Error {
             ErrorType::AudioProcessingError => Self::AudioProcessing(message.to_string()),
             ErrorType::Synchronization => Self::Synchronization(message.to_string()),
             ErrorType::InvalidParameters => Self::InvalidParameters(message.to_string()),
+            ErrorType::AudioError => Self::AudioProcessing(message.to_string()),
+            ErrorType::RubatoError => Self::Rubato(message.to_string()),
+            ErrorType::Http => Self::Http(message.into()),
+            ErrorType::Json => Self::Json(serde::de::Error::custom(message)),
+            ErrorType::Cancelled => Self::Cancelled(message.to_string()),
+            ErrorType::Unknown => Self::Unknown(message.to_string()),
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(error: reqwest::Error) -> Self {
+        Self::Http(Box::new(error))
+    }
+}
+
+/// Категория ошибки OpenAI API
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenAiErrorKind {
+    /// Исчерпана квота или баланс аккаунта
+    Quota,
+    /// Превышено ограничение частоты запросов
+    RateLimit,
+    /// Неверный или отсутствующий API ключ, нет доступа
+    Authentication,
+    /// Некорректный запрос (параметры, модель, текст)
+    InvalidRequest,
+    /// Ошибка на стороне сервера, запрос можно повторить
+    Server,
+    /// Прочие ошибки
+    Other,
+}
+
+/// Ошибка, возвращенная OpenAI API
+///
+/// Поля заполняются из тела ответа вида
+/// `{"error": {"message": ..., "type": ..., "code": ...}}`; если тело
+/// не удалось разобрать, `message` содержит его целиком.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenAiApiError {
+    /// HTTP статус ответа
+    pub status: u16,
+    /// Текст ошибки
+    pub message: String,
+    /// Тип ошибки (поле `type`), например `invalid_request_error`
+    pub error_type: Option<String>,
+    /// Код ошибки (поле `code`), например `insufficient_quota`
+    pub code: Option<String>,
+}
+
+/// Тело ответа OpenAI API с ошибкой
+#[derive(Deserialize)]
+struct OpenAiErrorBody {
+    error: OpenAiErrorDetail,
+}
+
+#[derive(Deserialize)]
+struct OpenAiErrorDetail {
+    message: Option<String>,
+    #[serde(rename = "type")]
+    error_type: Option<String>,
+    code: Option<serde_json::Value>,
+}
+
+impl OpenAiApiError {
+    /// Разбирает ответ OpenAI API с HTTP статусом `status` и телом `body`
+    pub fn from_response(status: u16, body: &str) -> Self {
+        match serde_json::from_str::<OpenAiErrorBody>(body) {
+            Ok(parsed) => Self {
+                status,
+                message: parsed.error.message.unwrap_or_default(),
+                error_type: parsed.error.error_type,
+                code: parsed.error.code.and_then(|code| match code {
+                    serde_json::Value::String(code) => Some(code),
+                    serde_json::Value::Null => None,
+                    other => Some(other.to_string()),
+                }),
+            },
+            Err(_) => Self {
+                status,
+                message: body.trim().to_string(),
+                error_type: None,
+                code: None,
+            },
+        }
+    }
+
+    /// Определяет категорию ошибки по коду, типу и HTTP статусу
+    pub fn kind(&self) -> OpenAiErrorKind {
+        let is = |value: &Option<String>, expected: &str| value.as_deref() == Some(expected);
+        
+        if is(&self.code, "insufficient_quota") || is(&self.error_type, "insufficient_quota") {
+            OpenAiErrorKind::Quota
+        } else if self.status == 429 {
+            OpenAiErrorKind::RateLimit
+        } else if self.status == 401 || self.status == 403 || is(&self.code, "invalid_api_key") {
+            OpenAiErrorKind::Authentication
+        } else if self.status >= 500 || is(&self.error_type, "server_error") {
+            OpenAiErrorKind::Server
+        } else if (400..500).contains(&self.status) || is(&self.error_type, "invalid_request_error") {
+            OpenAiErrorKind::InvalidRequest
+        } else {
+            OpenAiErrorKind::Other
+        }
+    }
+}
+
+impl std::fmt::Display for OpenAiApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.status, self.message)?;
+        match (&self.error_type, &self.code) {
+            (Some(error_type), Some(code)) => write!(f, " ({}, {})", error_type, code),
+            (Some(value), None) | (None, Some(value)) => write!(f, " ({})", value),
+            (None, None) => Ok(()),
         }
     }
 }
 
 /// Результат с обработкой ошибок
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_maps_every_error_type() {
+        let cases = [
+            (ErrorType::Io, "Ошибка ввода/вывода: сообщение"),
+            (ErrorType::VttParsing, "Ошибка парсинга VTT: сообщение"),
+            (ErrorType::OpenAi, "Ошибка OpenAI API: сообщение"),
+            (ErrorType::AudioProcessingError, "Ошибка обработки аудио: сообщение"),
+            (ErrorType::Synchronization, "Ошибка синхронизации: сообщение"),
+            (ErrorType::InvalidParameters, "Неверные параметры: сообщение"),
+            (ErrorType::AudioError, "Ошибка обработки аудио: сообщение"),
+            (ErrorType::RubatoError, "Ошибка в библиотеке rubato: сообщение"),
+            (ErrorType::Http, "Ошибка HTTP запроса: сообщение"),
+            (ErrorType::Json, "Ошибка JSON: сообщение"),
+            (ErrorType::Cancelled, "Операция отменена: сообщение"),
+            (ErrorType::Unknown, "Неизвестная ошибка: сообщение"),
+        ];
+
+        for (error_type, expected) in cases {
+            let error = Error::new(error_type, "сообщение");
+            let variant_matches = match error_type {
+                ErrorType::Io => matches!(error, Error::Io(_)),
+                ErrorType::VttParsing => matches!(error, Error::VttParsing(_)),
+                ErrorType::OpenAi => matches!(error, Error::OpenAi(_)),
+                ErrorType::AudioProcessingError | ErrorType::AudioError => {
+                    matches!(error, Error::AudioProcessing(_))
+                }
+                ErrorType::Synchronization => matches!(error, Error::Synchronization(_)),
+                ErrorType::InvalidParameters => matches!(error, Error::InvalidParameters(_)),
+                ErrorType::RubatoError => matches!(error, Error::Rubato(_)),
+                ErrorType::Http => matches!(error, Error::Http(_)),
+                ErrorType::Json => matches!(error, Error::Json(_)),
+                ErrorType::Cancelled => matches!(error, Error::Cancelled(_)),
+                ErrorType::Unknown => matches!(error, Error::Unknown(_)),
+            };
+
+            assert!(variant_matches, "{:?} -> {:?}", error_type, error);
+            assert_eq!(error.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_from_conversions() {
+        let json_error = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        let error: Error = json_error.into();
+        assert!(matches!(error, Error::Json(_)));
+        assert!(error.to_string().starts_with("Ошибка JSON: EOF"), "{}", error);
+
+        // Ошибка reqwest сохраняется как источник
+        let reqwest_error = reqwest::Client::new().get("http://[invalid").build().unwrap_err();
+        let error: Error = reqwest_error.into();
+        assert!(matches!(error, Error::Http(_)));
+        let source = std::error::Error::source(&error).unwrap();
+        assert!(source.downcast_ref::<reqwest::Error>().is_some());
+    }
+
+    #[test]
+    fn test_openai_api_error_parses_json_body() {
+        let body = r#"{"error": {"message": "You exceeded your current quota", "type": "insufficient_quota", "param": null, "code": "insufficient_quota"}}"#;
+        let error = OpenAiApiError::from_response(429, body);
+        assert_eq!(error.message, "You exceeded your current quota");
+        assert_eq!(error.error_type.as_deref(), Some("insufficient_quota"));
+        assert_eq!(error.code.as_deref(), Some("insufficient_quota"));
+        assert_eq!(error.kind(), OpenAiErrorKind::Quota);
+
+        let body = r#"{"error": {"message": "Invalid voice", "type": "invalid_request_error", "param": "voice", "code": null}}"#;
+        let error = OpenAiApiError::from_response(400, body);
+        assert_eq!(error.code, None);
+        assert_eq!(error.kind(), OpenAiErrorKind::InvalidRequest);
+        assert_eq!(error.to_string(), "400 Invalid voice (invalid_request_error)");
+
+        assert_eq!(OpenAiApiError::from_response(429, r#"{"error": {"message": "Slow down", "type": "requests"}}"#).kind(),
+            OpenAiErrorKind::RateLimit);
+        assert_eq!(OpenAiApiError::from_response(401, r#"{"error": {"message": "Bad key", "code": "invalid_api_key"}}"#).kind(),
+            OpenAiErrorKind::Authentication);
+
+        // Тело не в формате JSON сохраняется как текст ошибки
+        let error = OpenAiApiError::from_response(502, "Bad Gateway\n");
+        assert_eq!(error.message, "Bad Gateway");
+        assert_eq!(error.kind(), OpenAiErrorKind::Server);
+    }
+}
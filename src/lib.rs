@@ -5,33 +5,54 @@ pub mod audio;
 pub mod sync;
 pub mod progress;
 pub mod logging;
+pub mod duration;
 
-pub use error::{Error, Result, ErrorType};
+pub use error::{Error, OpenAiApiError, OpenAiErrorKind, Result, ErrorType};
 pub use logging::{
     setup_logging, setup_test_logging, log_error, log_warning, log_info, log_debug, log_trace
 };
-pub use vtt::{Subtitle, SubtitleTrack, VttParser};
+pub use vtt::{EmptyVttBehavior, Subtitle, SubtitleTrack, VttParser};
 pub use tts::{
-    TtsProvider, OpenAiTts, TtsOptions, TtsSegment,
-    OpenAiVoice, OpenAiTtsModel, OpenAiAudioFormat
+    TtsProvider, DynTtsProvider, OpenAiTts, TtsOptions, TtsSegment, WordTiming,
+    OpenAiVoice, OpenAiTtsModel, OpenAiAudioFormat,
+    RateLimiter, RateLimitConfig, RetryPolicy, DedupTtsProvider
 };
 pub use audio::{
-    AudioData, AudioSegment, AudioTrack,
-    AudioAnalyzer, AudioAnalysis, SegmentAnalysis, SilenceSegment,
-    TempoAdjuster, AudioSynchronizer, AudioProcessor
+    AudioData, AudioSegment, AudioTrack, AudioSink, MergePolicy, OutputMetadata,
+    AudioAnalyzer, AudioAnalysis, SegmentAnalysis, SilenceSegment, SegmentLevels, TrackAnalysis,
+    TempoAdjuster, AudioSynchronizer, AudioProcessor, SampleRateMismatch, DitherType,
+    Endianness, RawPcmSpec
 };
-pub use progress::{ProgressTracker, ProgressCallback, ChildProgressTracker};
-pub use sync::core::SyncCore;
+pub use duration::parse_duration;
+pub use progress::{
+    ProgressTracker, ProgressCallback, ChildProgressTracker, DetailedProgressCallback, ProgressUpdate
+};
+pub use sync::batch::{BatchOptions, SyncJob};
+pub use sync::pacing::PunctuationPauses;
+pub use sync::core::{CueRangeBehavior, DurationLimitBehavior, MuxMode, SpeechAnchor, SyncCore};
+pub use sync::report::{Diagnostic, DiagnosticKind, GapCompression, SyncReport, SegmentReport};
+pub use tokio_util::sync::CancellationToken;
 
 /// Форматы выходного аудио файла
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AudioFormat {
-    /// MP3 формат
+    /// MP3 формат (ffmpeg или встроенный кодировщик LAME с функцией `mp3-encoder`)
     Mp3,
     /// WAV формат
     Wav,
-    /// OGG формат
+    /// OGG формат (Vorbis)
+    ///
+    /// Кодируется ffmpeg или встроенным кодировщиком с функцией `vorbis`. Без
+    /// обоих трек сохраняется в WAV рядом с запрошенным путем или
+    /// синхронизация завершается ошибкой (см. [`SyncOptions::require_ffmpeg`])
     Ogg,
+    /// FLAC формат (сжатие без потерь, встроенный кодировщик)
+    Flac,
+    /// Opus формат
+    ///
+    /// Кодируется ffmpeg или встроенным кодировщиком с функцией `opus`; без
+    /// обоих - как для [`AudioFormat::Ogg`]
+    Opus,
 }
 
 /// Алгоритмы изменения темпа
@@ -43,6 +64,29 @@ pub enum TempoAlgorithm {
     Fir,
     /// Алгоритм на основе линейной интерполяции (низкое качество, очень быстрый)
     Linear,
+    /// Растяжение во времени методом WSOLA с сохранением высоты тона
+    Wsola,
+}
+
+/// Способ нормализации громкости результата
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NormalizationMode {
+    /// По пиковому уровню (`normalization_target_db`)
+    #[default]
+    Peak,
+    /// По интегральной громкости EBU R128 (`normalization_target_lufs`)
+    Lufs,
+}
+
+impl From<TempoAlgorithm> for audio::TempoAlgorithm {
+    fn from(algorithm: TempoAlgorithm) -> Self {
+        match algorithm {
+            TempoAlgorithm::Sinc => audio::TempoAlgorithm::Sinc,
+            TempoAlgorithm::Fir => audio::TempoAlgorithm::Fir,
+            TempoAlgorithm::Linear => audio::TempoAlgorithm::Linear,
+            TempoAlgorithm::Wsola => audio::TempoAlgorithm::Wsola,
+        }
+    }
 }
 
 /// Настройки для синхронизации TTS с видео и субтитрами
@@ -51,12 +95,35 @@ pub struct SyncOptions {
     /// Голос для TTS
     pub voice: String,
     
+    /// Модель TTS OpenAI
+    pub model: OpenAiTtsModel,
+    
+    /// Базовая скорость речи OpenAI TTS (0.25 - 4.0)
+    pub speed: f32,
+    
+    /// Повтор запросов к OpenAI TTS при ограничении частоты и ошибках сервера
+    pub retry_policy: RetryPolicy,
+    
+    /// Базовый адрес OpenAI-совместимого API (None - публичный OpenAI API)
+    pub base_url: Option<String>,
+    
+    /// Директория кэша ответов OpenAI TTS на диске (None - без кэша)
+    pub tts_cache_dir: Option<String>,
+    
     /// Формат выходного аудио файла
     pub output_format: AudioFormat,
     
+    /// Битрейт MP3 в кбит/с (8, 16, 24, 32, 40, 48, 64, 80, 96, 112, 128, 160, 192, 224, 256 или 320)
+    pub mp3_bitrate: u32,
+    
     /// Частота дискретизации выходного аудио
     pub sample_rate: u32,
     
+    /// Сохранять ли частоту дискретизации провайдера до экспорта без
+    /// пересэмплирования; если включено, `sample_rate` не используется
+    /// (см. [`SyncOptions::output_sample_rate`])
+    pub auto_sample_rate: bool,
+    
     /// Максимальная длительность сегмента в секундах
     pub max_segment_duration: f64,
     
@@ -69,6 +136,9 @@ pub struct SyncOptions {
     /// Применять ли эквализацию
     pub apply_equalization: bool,
     
+    /// Применять ли де-эссер для подавления сибилянтов
+    pub apply_deesser: bool,
+    
     /// Алгоритм изменения темпа
     pub tempo_algorithm: TempoAlgorithm,
     
@@ -89,23 +159,198 @@ pub struct SyncOptions {
     pub eq_low_freq: f32,
     pub eq_high_freq: f32,
     
+    /// Параметры де-эссера: нижняя граница полосы сибилянтов в Гц, порог в дБ и коэффициент
+    pub deesser_freq: f32,
+    pub deesser_threshold: f32,
+    pub deesser_ratio: f32,
+    
     /// Целевой уровень нормализации громкости в дБ
     pub normalization_target_db: f32,
     
+    /// Учитывать ли межсэмпловые пики (true peak) при нормализации
+    pub true_peak_normalization: bool,
+    
+    /// Способ нормализации громкости результата
+    pub normalization_mode: NormalizationMode,
+    
+    /// Целевая интегральная громкость в LUFS при нормализации по громкости
+    pub normalization_target_lufs: f32,
+    
+    /// Применять ли лимитер с упреждением после компрессии и эквализации
+    /// (плавно ограничивает пики после компенсационного усиления)
+    ///
+    /// По умолчанию выключен, чтобы не менять звук без явного запроса:
+    /// компрессия и нормализация к LUFS и без него не допускают клиппинга,
+    /// ограничивая пики 0 дБ полной шкалы. Включается также
+    /// [`SyncOptionsBuilder::with_limiter_ceiling_db`].
+    pub apply_limiter: bool,
+    
+    /// Запас между целевым уровнем нормализации и потолком лимитера в дБ
+    pub limiter_headroom_db: f32,
+    
+    /// Потолок лимитера в дБ полной шкалы (None - целевой уровень
+    /// нормализации плюс `limiter_headroom_db`)
+    pub limiter_ceiling_db: Option<f32>,
+    
+    /// Глобальное смещение всего дубляжа в миллисекундах (может быть отрицательным)
+    pub global_offset_ms: i64,
+    
+    /// Длительность затухания перед завершающей тишиной в миллисекундах
+    pub tail_fade_ms: u64,
+    
+    /// Тишина перед началом результата в миллисекундах (все содержимое сдвигается)
+    pub pre_roll_ms: u64,
+    
+    /// Тишина после окончания результата в миллисекундах
+    pub post_roll_ms: u64,
+    
+    /// Компромисс между ускорением синтеза (0.0) и изменением темпа (1.0);
+    /// при значении меньше 1.0 не уложившиеся в субтитр реплики синтезируются
+    /// повторно, и каждая из них стоит двух запросов к API
+    pub intelligibility_bias: f32,
+    
+    /// Заменять ли переносы строк и повторяющиеся пробелы в тексте субтитров
+    /// одиночными пробелами перед синтезом
+    pub collapse_whitespace: bool,
+    
+    /// Удалять ли из текста для синтеза теги и описания звуков (`[музыка]`, `♪`);
+    /// исходный текст субтитров в отчетах сохраняется
+    pub normalize_spoken_text: bool,
+    
+    /// Ожидаемая частота дискретизации аудио от TTS провайдера
+    pub tts_sample_rate: u32,
+    
+    /// Поведение при несовпадении частоты аудио от провайдера с ожидаемой
+    pub sample_rate_mismatch: SampleRateMismatch,
+    
+    /// Формат сырого PCM от провайдера без заголовка (None - аудио в контейнере)
+    pub raw_pcm: Option<RawPcmSpec>,
+    
+    /// Максимальная длительность результата в секундах (None - без ограничения)
+    pub max_output_duration: Option<f64>,
+    
+    /// Поведение при превышении максимальной длительности результата
+    pub max_output_duration_behavior: DurationLimitBehavior,
+    
+    /// Порог в миллисекундах, ниже которого пауза между соседними репликами
+    /// удаляется и речь идет непрерывно (0 - отключено)
+    pub micro_gap_threshold_ms: u64,
+    
+    /// Пауза в секундах после последней реплики; промежутки между
+    /// репликами воспроизводятся по исходным субтитрам (0 - без паузы)
+    pub trailing_pause: f64,
+    
+    /// Пауза в миллисекундах после последней реплики
+    ///
+    /// Учитывается, только если `trailing_pause` оставлена по умолчанию.
+    #[deprecated(since = "0.1.7", note = "используйте `trailing_pause` (в секундах)")]
+    pub default_pause_ms: u64,
+    
+    /// Минимальная длительность речи сегмента в секундах; речь более коротких
+    /// реплик не сжимается сильнее (по умолчанию 0 - без ограничения;
+    /// например, 0.1 избавляет от искажений в репликах короче 100 мс)
+    pub min_segment_duration: f64,
+    
+    /// Допустимое отклонение длительности результата от видео в секундах;
+    /// при превышении в отчет добавляется предупреждение
+    pub duration_tolerance: f64,
+    
+    /// Способ объединения перекрывающихся сегментов при сохранении результата
+    pub merge_policy: MergePolicy,
+    
+    /// Отклонение пика сегмента от целевого, при котором нормализация
+    /// пропускается (0 - нормализуются все сегменты)
+    pub normalization_tolerance: f32,
+    
+    /// Поведение для субтитров за пределами длительности видео
+    pub cue_range_behavior: CueRangeBehavior,
+    
+    /// Положение речи внутри субтитра, если она короче него
+    /// (`Subtitle::target_speech_duration`); остаток заполняется тишиной
+    pub speech_anchor: SpeechAnchor,
+    
+    /// Максимальная длительность промежутка между репликами в секундах;
+    /// более длинные промежутки сокращаются (None - сохранять тайминги видео)
+    pub max_gap_duration: Option<f64>,
+    
+    /// Паузы на знаках препинания при синтезе (None - паузы определяет провайдер)
+    pub punctuation_pauses: Option<PunctuationPauses>,
+    
+    /// Тип дизеринга при экспорте в 16-битный PCM
+    pub dither: DitherType,
+    
+    /// Панорама диалога для стерео результата: от -1.0 (слева) до 1.0 (справа),
+    /// 0.0 - по центру (None - моно результат)
+    pub stereo_pan: Option<f32>,
+    
+    /// Размещать реплики в стерео по позиции субтитров (`position:20%`);
+    /// действует только вместе с `stereo_pan`, реплики без позиции
+    /// получают панораму `stereo_pan`
+    pub position_panning: bool,
+    
+    /// Запрашивать ли у провайдера тайминги слов для отчета о синхронизации
+    /// (провайдеры без данных выравнивания, например OpenAI, их не возвращают)
+    pub request_timestamps: bool,
+    
+    /// Количество потоков для декодирования аудио сегментов
+    /// (0 - декодирование без пула потоков)
+    pub decode_concurrency: usize,
+    
+    /// Максимальное количество одновременных запросов к TTS провайдеру
+    pub max_concurrent_requests: usize,
+    
+    /// Максимальное количество декодированных сэмплов одного сегмента
+    /// (None - без ограничения)
+    pub max_decoded_samples: Option<usize>,
+    
+    /// Корректировать ли длительность сегментов одновременно с синтезом следующих
+    pub pipelined_synthesis: bool,
+    
+    /// Продолжать ли синхронизацию, если синтез отдельной реплики завершился
+    /// ошибкой: вместо речи вставляется тишина, а ошибки возвращаются в
+    /// [`SyncResult::failed_segments`]
+    pub continue_on_error: bool,
+    
+    /// Диапазоны времени `(начало, конец)` в секундах, где при смешивании с
+    /// оригинальной дорожкой звучит только оригинал, а дубляж заглушается
+    /// (например, песни без перевода)
+    pub keep_original_ranges: Vec<(f64, f64)>,
+    
+    /// Завершать ли синхронизацию ошибкой, если для выходного формата нужен
+    /// ffmpeg (MP3, OGG или Opus без встроенного кодировщика), а он не
+    /// установлен (иначе трек сохраняется в WAV рядом с запрошенным путем)
+    pub require_ffmpeg: bool,
+    
+    /// Записывать ли в MP3, OGG, Opus и FLAC теги с названием и комментарием
+    /// (версия библиотеки, голос и модель синтеза)
+    pub write_metadata: bool,
+    
+    /// Название, записываемое в теги при `write_metadata`
+    pub metadata_title: Option<String>,
+    
     /// Уровень логирования
     pub log_level: log::LevelFilter,
 }
 
 impl Default for SyncOptions {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
             voice: "alloy".to_string(),
+            model: OpenAiTtsModel::Tts1,
+            speed: 1.0,
+            retry_policy: RetryPolicy::default(),
+            base_url: None,
+            tts_cache_dir: None,
             output_format: AudioFormat::Mp3,
+            mp3_bitrate: audio::DEFAULT_MP3_BITRATE,
             sample_rate: 44100,
+            auto_sample_rate: false,
             max_segment_duration: 10.0,
             normalize_volume: true,
             apply_compression: false,
             apply_equalization: false,
+            apply_deesser: false,
             tempo_algorithm: TempoAlgorithm::Sinc,
             preserve_pauses: true,
             
@@ -123,18 +368,428 @@ impl Default for SyncOptions {
             eq_low_freq: 300.0,
             eq_high_freq: 3000.0,
             
+            // Параметры де-эссера по умолчанию
+            deesser_freq: 6000.0,
+            deesser_threshold: -30.0,
+            deesser_ratio: 4.0,
+            
             // Целевой уровень нормализации громкости
             normalization_target_db: -3.0,
+            true_peak_normalization: false,
+            normalization_mode: NormalizationMode::Peak,
+            normalization_target_lufs: -16.0,
+            apply_limiter: false,
+            limiter_headroom_db: 1.0,
+            limiter_ceiling_db: None,
+            
+            global_offset_ms: 0,
+            tail_fade_ms: 10,
+            pre_roll_ms: 0,
+            post_roll_ms: 0,
+            intelligibility_bias: 1.0,
+            collapse_whitespace: true,
+            normalize_spoken_text: false,
+            tts_sample_rate: 24000,
+            sample_rate_mismatch: SampleRateMismatch::Resample,
+            raw_pcm: None,
+            max_output_duration: None,
+            max_output_duration_behavior: DurationLimitBehavior::Truncate,
+            micro_gap_threshold_ms: 80,
+            trailing_pause: sync::core::DEFAULT_SEGMENT_PAUSE,
+            default_pause_ms: DEFAULT_PAUSE_MS,
+            min_segment_duration: sync::core::DEFAULT_MIN_SEGMENT_DURATION,
+            duration_tolerance: sync::core::DEFAULT_DURATION_TOLERANCE,
+            merge_policy: MergePolicy::default(),
+            normalization_tolerance: sync::core::DEFAULT_NORMALIZATION_TOLERANCE,
+            cue_range_behavior: CueRangeBehavior::Clamp,
+            speech_anchor: SpeechAnchor::Start,
+            max_gap_duration: None,
+            punctuation_pauses: None,
+            dither: DitherType::None,
+            stereo_pan: None,
+            position_panning: false,
+            request_timestamps: false,
+            decode_concurrency: sync::core::DEFAULT_DECODE_CONCURRENCY,
+            max_concurrent_requests: sync::core::DEFAULT_MAX_CONCURRENT_REQUESTS,
+            max_decoded_samples: Some(sync::core::DEFAULT_MAX_DECODED_SAMPLES),
+            pipelined_synthesis: false,
+            continue_on_error: false,
+            keep_original_ranges: Vec::new(),
+            require_ffmpeg: false,
+            write_metadata: false,
+            metadata_title: None,
             
             log_level: log::LevelFilter::Info,
         }
     }
 }
 
+impl SyncOptions {
+    /// Возвращает потолок лимитера (линейная амплитуда)
+    ///
+    /// Потолок равен `limiter_ceiling_db`, если он задан, иначе целевому
+    /// уровню нормализации плюс запас `limiter_headroom_db`, поэтому
+    /// компрессия, эквализация и нормализация не конфликтуют друг с другом.
+    /// Потолок не выше 0 дБ полной шкалы.
+    pub fn limiter_ceiling(&self) -> f32 {
+        let ceiling_db = self.limiter_ceiling_db
+            .unwrap_or(self.normalization_target_db + self.limiter_headroom_db.max(0.0))
+            .min(0.0);
+        10.0f32.powf(ceiling_db / 20.0)
+    }
+
+    /// Возвращает частоту дискретизации, с которой аудио обрабатывается и экспортируется
+    ///
+    /// При `auto_sample_rate` это частота провайдера (`raw_pcm.sample_rate`
+    /// для сырого PCM, иначе `tts_sample_rate`), поэтому аудио провайдера
+    /// не пересэмплируется; иначе - `sample_rate`.
+    pub fn output_sample_rate(&self) -> u32 {
+        if self.auto_sample_rate {
+            self.raw_pcm.map_or(self.tts_sample_rate, |spec| spec.sample_rate)
+        } else {
+            self.sample_rate
+        }
+    }
+
+    /// Возвращает метаданные выходного файла
+    ///
+    /// Пустые, если `write_metadata` отключен. Голос и модель в комментарии
+    /// соответствуют провайдеру OpenAI, создаваемому по этим настройкам.
+    pub fn output_metadata(&self) -> OutputMetadata {
+        if !self.write_metadata {
+            return OutputMetadata::new();
+        }
+        let metadata = OutputMetadata::new().with_comment(format!(
+            "tts-sync {}, voice: {}, model: {}",
+            env!("CARGO_PKG_VERSION"),
+            self.voice,
+            self.model
+        ));
+        match &self.metadata_title {
+            Some(title) => metadata.with_title(title.clone()),
+            None => metadata,
+        }
+    }
+    
+    /// Создает построитель настроек со значениями по умолчанию
+    pub fn builder() -> SyncOptionsBuilder {
+        SyncOptionsBuilder::new()
+    }
+    
+    /// Проверяет согласованность настроек
+    ///
+    /// Возвращает ошибку `InvalidParameters`, если частота дискретизации вне
+    /// диапазона 8 - 192 кГц, скорость речи вне диапазона OpenAI, битрейт MP3
+    /// недопустим, параметры компрессии, эквализации или де-эссера
+    /// некорректны, потолок лимитера выше 0 дБ либо панорама вне диапазона
+    /// от -1.0 до 1.0.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |message: String| Err(Error::new(ErrorType::InvalidParameters, &message));
+        
+        for (name, rate) in [("sample_rate", self.sample_rate), ("tts_sample_rate", self.tts_sample_rate)] {
+            if !(MIN_SAMPLE_RATE..=MAX_SAMPLE_RATE).contains(&rate) {
+                return invalid(format!("Частота дискретизации {} = {} Гц вне диапазона {} - {} Гц",
+                    name, rate, MIN_SAMPLE_RATE, MAX_SAMPLE_RATE));
+            }
+        }
+        tts::validate_speed(self.speed)?;
+        if self.output_format == AudioFormat::Mp3 {
+            audio::validate_mp3_bitrate(self.mp3_bitrate)?;
+        }
+        if self.max_segment_duration.is_nan() || self.max_segment_duration <= 0.0 {
+            return invalid(format!("Максимальная длительность сегмента должна быть положительной: {}",
+                self.max_segment_duration));
+        }
+        
+        if self.compression_ratio.is_nan() || self.compression_ratio <= 1.0 {
+            return invalid(format!("Коэффициент компрессии должен быть больше 1.0: {}", self.compression_ratio));
+        }
+        if !(self.compression_attack > 0.0 && self.compression_release > 0.0) {
+            return invalid(format!("Время атаки и восстановления компрессора должно быть положительным: {} мс, {} мс",
+                self.compression_attack, self.compression_release));
+        }
+        
+        let nyquist = self.output_sample_rate() as f32 / 2.0;
+        if !(self.eq_low_freq > 0.0 && self.eq_low_freq < self.eq_high_freq && self.eq_high_freq < nyquist) {
+            return invalid(format!(
+                "Частоты эквалайзера должны удовлетворять 0 < eq_low_freq < eq_high_freq < {} Гц: {} Гц, {} Гц",
+                nyquist, self.eq_low_freq, self.eq_high_freq));
+        }
+        if !(self.deesser_freq > 0.0 && self.deesser_freq < nyquist) {
+            return invalid(format!("Частота де-эссера {} Гц вне диапазона 0 - {} Гц", self.deesser_freq, nyquist));
+        }
+        if self.deesser_ratio.is_nan() || self.deesser_ratio < 1.0 {
+            return invalid(format!("Коэффициент де-эссера должен быть не меньше 1.0: {}", self.deesser_ratio));
+        }
+        
+        if let Some(ceiling_db) = self.limiter_ceiling_db {
+            if !ceiling_db.is_finite() || ceiling_db > 0.0 {
+                return invalid(format!("Потолок лимитера {} дБ должен быть не выше 0 дБ", ceiling_db));
+            }
+        }
+        
+        if let Some(pan) = self.stereo_pan {
+            if !(-1.0..=1.0).contains(&pan) {
+                return invalid(format!("Панорама {} вне диапазона от -1.0 до 1.0", pan));
+            }
+        }
+        audio::validate_keep_original_ranges(&self.keep_original_ranges)?;
+        self.effective_trailing_pause()?;
+        Ok(())
+    }
+    
+    /// Пауза после последней реплики в секундах с учетом устаревшего
+    /// `default_pause_ms`; отрицательная или нечисловая пауза - ошибка
+    #[allow(deprecated)]
+    fn effective_trailing_pause(&self) -> Result<f64> {
+        let pause = if self.trailing_pause == sync::core::DEFAULT_SEGMENT_PAUSE
+            && self.default_pause_ms != DEFAULT_PAUSE_MS
+        {
+            self.default_pause_ms as f64 / 1000.0
+        } else {
+            self.trailing_pause
+        };
+        if !pause.is_finite() || pause < 0.0 {
+            return Err(Error::new(
+                ErrorType::InvalidParameters,
+                &format!("Пауза после последней реплики должна быть неотрицательной: {} с", pause),
+            ));
+        }
+        Ok(pause)
+    }
+}
+
+/// Значение устаревшего [`SyncOptions::default_pause_ms`] по умолчанию
+const DEFAULT_PAUSE_MS: u64 = 200;
+
+/// Минимальная частота дискретизации, принимаемая [`SyncOptions::validate`]
+const MIN_SAMPLE_RATE: u32 = 8000;
+
+/// Максимальная частота дискретизации, принимаемая [`SyncOptions::validate`]
+const MAX_SAMPLE_RATE: u32 = 192_000;
+
+/// Построитель [`SyncOptions`]
+///
+/// Незаданные параметры берутся из [`SyncOptions::default`], настройки
+/// проверяются в [`SyncOptionsBuilder::build`].
+///
+/// ```
+/// use tts_sync::{AudioFormat, SyncOptions};
+///
+/// let options = SyncOptions::builder()
+///     .with_voice("nova")
+///     .with_sample_rate(48000)
+///     .with_output_format(AudioFormat::Wav)
+///     .build()
+///     .unwrap();
+/// assert_eq!(options.voice, "nova");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SyncOptionsBuilder {
+    options: SyncOptions,
+    channels: Option<u16>,
+}
+
+impl SyncOptionsBuilder {
+    /// Создает построитель со значениями по умолчанию
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Устанавливает голос для TTS
+    pub fn with_voice(mut self, voice: impl Into<String>) -> Self {
+        self.options.voice = voice.into();
+        self
+    }
+    
+    /// Устанавливает модель TTS OpenAI
+    pub fn with_model(mut self, model: OpenAiTtsModel) -> Self {
+        self.options.model = model;
+        self
+    }
+    
+    /// Устанавливает базовую скорость речи OpenAI TTS (0.25 - 4.0)
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.options.speed = speed;
+        self
+    }
+    
+    /// Устанавливает формат выходного аудио файла
+    pub fn with_output_format(mut self, format: AudioFormat) -> Self {
+        self.options.output_format = format;
+        self
+    }
+    
+    /// Устанавливает битрейт MP3 в кбит/с
+    pub fn with_mp3_bitrate(mut self, bitrate: u32) -> Self {
+        self.options.mp3_bitrate = bitrate;
+        self
+    }
+    
+    /// Устанавливает частоту дискретизации выходного аудио
+    pub fn with_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.options.sample_rate = sample_rate;
+        self
+    }
+    
+    /// Устанавливает количество каналов результата: 1 (моно) или 2 (стерео)
+    ///
+    /// Стерео результат получается панорамированием диалога (`stereo_pan`);
+    /// если панорама не задана, диалог размещается по центру.
+    pub fn with_channels(mut self, channels: u16) -> Self {
+        self.channels = Some(channels);
+        self
+    }
+    
+    /// Устанавливает панораму диалога для стерео результата (от -1.0 до 1.0)
+    pub fn with_stereo_pan(mut self, pan: f32) -> Self {
+        self.options.stereo_pan = Some(pan);
+        self
+    }
+    
+    /// Устанавливает алгоритм изменения темпа
+    pub fn with_tempo_algorithm(mut self, algorithm: TempoAlgorithm) -> Self {
+        self.options.tempo_algorithm = algorithm;
+        self
+    }
+    
+    /// Включает или отключает нормализацию громкости
+    pub fn with_normalize_volume(mut self, normalize: bool) -> Self {
+        self.options.normalize_volume = normalize;
+        self
+    }
+    
+    /// Устанавливает целевой уровень нормализации громкости в дБ
+    pub fn with_normalization_target_db(mut self, target_db: f32) -> Self {
+        self.options.normalization_target_db = target_db;
+        self
+    }
+    
+    /// Включает компрессию динамического диапазона с указанными параметрами
+    ///
+    /// Порог и усиление задаются в дБ, атака и восстановление - в миллисекундах.
+    pub fn with_compression(mut self, threshold: f32, ratio: f32, attack: f32, release: f32, makeup_gain: f32) -> Self {
+        self.options.apply_compression = true;
+        self.options.compression_threshold = threshold;
+        self.options.compression_ratio = ratio;
+        self.options.compression_attack = attack;
+        self.options.compression_release = release;
+        self.options.compression_makeup_gain = makeup_gain;
+        self
+    }
+    
+    /// Включает эквализацию с указанными усилениями полос в дБ и частотами
+    /// раздела полос в Гц
+    pub fn with_equalization(mut self, low_gain: f32, mid_gain: f32, high_gain: f32, low_freq: f32, high_freq: f32) -> Self {
+        self.options.apply_equalization = true;
+        self.options.eq_low_gain = low_gain;
+        self.options.eq_mid_gain = mid_gain;
+        self.options.eq_high_gain = high_gain;
+        self.options.eq_low_freq = low_freq;
+        self.options.eq_high_freq = high_freq;
+        self
+    }
+    
+    /// Включает де-эссер с нижней границей полосы сибилянтов в Гц, порогом в дБ
+    /// и коэффициентом подавления
+    pub fn with_deesser(mut self, freq: f32, threshold: f32, ratio: f32) -> Self {
+        self.options.apply_deesser = true;
+        self.options.deesser_freq = freq;
+        self.options.deesser_threshold = threshold;
+        self.options.deesser_ratio = ratio;
+        self
+    }
+    
+    /// Включает лимитер с потолком в дБ полной шкалы (не выше 0 дБ)
+    pub fn with_limiter_ceiling_db(mut self, ceiling_db: f32) -> Self {
+        self.options.apply_limiter = true;
+        self.options.limiter_ceiling_db = Some(ceiling_db);
+        self
+    }
+    
+    /// Устанавливает максимальное количество одновременных запросов к TTS провайдеру
+    pub fn with_max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.options.max_concurrent_requests = max_concurrent_requests;
+        self
+    }
+    
+    /// Устанавливает, продолжать ли синхронизацию при ошибке синтеза реплики
+    pub fn with_continue_on_error(mut self, continue_on_error: bool) -> Self {
+        self.options.continue_on_error = continue_on_error;
+        self
+    }
+    
+    /// Устанавливает диапазоны времени, где при смешивании с оригиналом звучит только оригинал
+    pub fn with_keep_original_ranges(mut self, ranges: Vec<(f64, f64)>) -> Self {
+        self.options.keep_original_ranges = ranges;
+        self
+    }
+    
+    /// Устанавливает уровень логирования
+    pub fn with_log_level(mut self, log_level: log::LevelFilter) -> Self {
+        self.options.log_level = log_level;
+        self
+    }
+    
+    /// Проверяет и возвращает настройки
+    ///
+    /// Возвращает ошибку `InvalidParameters`, если количество каналов
+    /// не 1 и не 2 или настройки не проходят [`SyncOptions::validate`].
+    pub fn build(self) -> Result<SyncOptions> {
+        let mut options = self.options;
+        match self.channels {
+            None => {}
+            Some(1) => options.stereo_pan = None,
+            Some(2) => options.stereo_pan = Some(options.stereo_pan.unwrap_or(0.0)),
+            Some(channels) => {
+                return Err(Error::new(
+                    ErrorType::InvalidParameters,
+                    &format!("Поддерживается 1 или 2 канала, указано: {}", channels),
+                ));
+            }
+        }
+        options.validate()?;
+        Ok(options)
+    }
+}
+
+/// Прогресс, до которого доходит ядро синхронизации; остаток шкалы занимают
+/// эффекты и сохранение результата
+const SYNC_CORE_PROGRESS_END: f32 = 85.0;
+
+/// Источник субтитров для синхронизации
+#[derive(Clone, Copy)]
+enum SubtitleSource<'a> {
+    /// VTT файл; результат сохраняется рядом с ним
+    File(&'a str),
+    /// Субтитры в памяти и путь для сохранения результата
+    Track {
+        subtitles: &'a SubtitleTrack,
+        output_path: &'a str,
+    },
+}
+
+/// Результат синхронизации с отчетом по сегментам
+#[derive(Debug)]
+pub struct SyncResult {
+    /// Путь к сохраненному аудио файлу
+    pub output_path: String,
+    /// Длительность итогового аудио в секундах
+    pub total_duration: f64,
+    /// Отчеты по сегментам в порядке субтитров: исходная и итоговая
+    /// длительность речи, коэффициент изменения длительности и тайминги реплик
+    pub segments: Vec<SegmentReport>,
+    /// Ошибки синтеза `(номер субтитра, ошибка)` для реплик, замененных
+    /// тишиной при `continue_on_error`, в порядке субтитров
+    pub failed_segments: Vec<(usize, Error)>,
+}
+
 /// Основной интерфейс для синхронизации TTS с видео и субтитрами
 pub struct TtsSync {
     options: SyncOptions,
     progress_tracker: ProgressTracker,
+    rate_limiter: Option<RateLimiter>,
+    cancellation_token: Option<CancellationToken>,
 }
 
 impl TtsSync {
@@ -154,6 +809,8 @@ impl TtsSync {
         Self { 
             options,
             progress_tracker: ProgressTracker::new(),
+            rate_limiter: None,
+            cancellation_token: None,
         }
     }
     
@@ -165,7 +822,41 @@ impl TtsSync {
     /// Устанавливает функцию обратного вызова для отслеживания прогресса
     pub fn with_progress_callback(mut self, callback: ProgressCallback) -> Self {
         log_debug("Установлена функция обратного вызова для отслеживания прогресса");
-        self.progress_tracker = ProgressTracker::with_callback(callback);
+        self.progress_tracker.set_callback(callback);
+        self
+    }
+    
+    /// Устанавливает функцию обратного вызова, получающую вместе с прогрессом
+    /// оценку оставшегося времени и скорость генерации сегментов
+    pub fn with_detailed_progress_callback(mut self, callback: DetailedProgressCallback) -> Self {
+        log_debug("Установлена функция обратного вызова для отслеживания прогресса с оценкой времени");
+        self.progress_tracker.set_detailed_callback(callback);
+        self
+    }
+    
+    /// Подписывается на обновления прогресса синхронизации
+    ///
+    /// Альтернатива функции обратного вызова для асинхронного кода: обновления
+    /// (прогресс, статус) приходят в порядке их отправки
+    /// (см. [`ProgressTracker::subscribe`]).
+    pub fn subscribe_progress(&self) -> tokio::sync::broadcast::Receiver<(f32, String)> {
+        self.progress_tracker.subscribe()
+    }
+    
+    /// Устанавливает модель TTS OpenAI
+    pub fn with_model(mut self, model: OpenAiTtsModel) -> Self {
+        log_debug(&format!("Установлена модель TTS: {}", model));
+        self.options.model = model;
+        self
+    }
+    
+    /// Устанавливает базовую скорость речи OpenAI TTS
+    ///
+    /// Допустимый диапазон 0.25 - 4.0 проверяется при создании провайдера.
+    /// Более высокая скорость уменьшает необходимое ускорение темпа.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        log_debug(&format!("Установлена скорость речи: {}", speed));
+        self.options.speed = speed;
         self
     }
     
@@ -190,6 +881,13 @@ impl TtsSync {
         self
     }
     
+    /// Устанавливает применение де-эссера
+    pub fn with_deesser(mut self, apply_deesser: bool) -> Self {
+        log_debug(&format!("Установлено применение де-эссера: {}", apply_deesser));
+        self.options.apply_deesser = apply_deesser;
+        self
+    }
+    
     /// Устанавливает нормализацию громкости
     pub fn with_volume_normalization(mut self, normalize_volume: bool) -> Self {
         log_debug(&format!("Установлена нормализация громкости: {}", normalize_volume));
@@ -197,6 +895,27 @@ impl TtsSync {
         self
     }
     
+    /// Включает нормализацию громкости по истинному пику (true peak)
+    ///
+    /// Оставляет запас на межсэмпловые пики, уменьшая клиппинг после кодирования.
+    pub fn with_true_peak_normalization(mut self, enabled: bool) -> Self {
+        log_debug(&format!("Установлена нормализация по истинному пику: {}", enabled));
+        self.options.true_peak_normalization = enabled;
+        self
+    }
+    
+    /// Включает нормализацию интегральной громкости результата (EBU R128)
+    ///
+    /// Заменяет нормализацию по пику; обычно -16 LUFS для подкастов
+    /// и -23 LUFS для вещания.
+    pub fn with_loudness_normalization(mut self, target_lufs: f32) -> Self {
+        log_debug(&format!("Установлена нормализация громкости к {} LUFS", target_lufs));
+        self.options.normalize_volume = true;
+        self.options.normalization_mode = NormalizationMode::Lufs;
+        self.options.normalization_target_lufs = target_lufs;
+        self
+    }
+    
     /// Устанавливает сохранение пауз при адаптивном изменении темпа
     pub fn with_preserve_pauses(mut self, preserve_pauses: bool) -> Self {
         log_debug(&format!("Установлено сохранение пауз: {}", preserve_pauses));
@@ -204,35 +923,398 @@ impl TtsSync {
         self
     }
     
+    /// Устанавливает паузу после последней реплики в секундах
+    ///
+    /// Промежутки между репликами воспроизводятся по исходным субтитрам,
+    /// 0 отключает добавление искусственной паузы. Отрицательная или
+    /// нечисловая пауза приводит к ошибке при синхронизации.
+    pub fn with_segment_pause(mut self, pause: f64) -> Self {
+        log_debug(&format!("Установлена пауза после реплик: {} с", pause));
+        self.options.trailing_pause = pause;
+        self
+    }
+    
+    /// Устанавливает глобальное смещение всего дубляжа в миллисекундах
+    pub fn with_global_offset_ms(mut self, offset_ms: i64) -> Self {
+        log_debug(&format!("Установлено глобальное смещение: {} мс", offset_ms));
+        self.options.global_offset_ms = offset_ms;
+        self
+    }
+    
+    /// Устанавливает компромисс между ускорением синтеза и изменением темпа
+    ///
+    /// 0.0 - всегда точный тайминг за счет ускорения речи, 1.0 - всегда
+    /// естественная скорость речи с корректировкой темпа. При значении меньше
+    /// 1.0 реплики, не уложившиеся в субтитр, синтезируются повторно (второй
+    /// запрос к API на каждую такую реплику).
+    pub fn with_intelligibility_bias(mut self, bias: f32) -> Self {
+        log_debug(&format!("Установлен компромисс скорости и темпа: {}", bias));
+        self.options.intelligibility_bias = bias.clamp(0.0, 1.0);
+        self
+    }
+    
+    /// Устанавливает ограничение частоты запросов к TTS провайдеру
+    ///
+    /// Ограничитель общий для всех вызовов синхронизации этого экземпляра.
+    pub fn with_rate_limit(mut self, config: RateLimitConfig) -> Self {
+        log_debug(&format!("Установлено ограничение частоты запросов: {:?}", config));
+        self.rate_limiter = Some(RateLimiter::new(config));
+        self
+    }
+    
+    /// Устанавливает токен отмены синхронизации
+    ///
+    /// После вызова [`CancellationToken::cancel`] выполняющаяся синхронизация
+    /// завершается ошибкой [`Error::Cancelled`], выходной файл не создается.
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        log_debug("Установлен токен отмены синхронизации");
+        self.cancellation_token = Some(token);
+        self
+    }
+    
+    /// Устанавливает повтор запросов к TTS провайдеру при временных ошибках
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        log_debug(&format!("Установлен повтор запросов: {:?}", policy));
+        self.options.retry_policy = policy;
+        self
+    }
+    
+    /// Устанавливает базовый адрес OpenAI-совместимого API
+    ///
+    /// Например, `http://localhost:4000/v1` для прокси LiteLLM.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into();
+        log_debug(&format!("Установлен адрес API: {}", base_url));
+        self.options.base_url = Some(base_url);
+        self
+    }
+    
+    /// Устанавливает директорию кэша ответов OpenAI TTS на диске
+    ///
+    /// Повторная синхронизация с теми же текстом, голосом, моделью и
+    /// скоростью берет речь из кэша без обращения к API. `None` отключает кэш.
+    pub fn with_cache_dir(mut self, cache_dir: Option<String>) -> Self {
+        log_debug(&format!("Установлена директория кэша TTS: {:?}", cache_dir));
+        self.options.tts_cache_dir = cache_dir;
+        self
+    }
+    
+    /// Создает TTS провайдер OpenAI с текущими настройками
+    fn create_tts_provider(&self, api_key: &str) -> Result<OpenAiTts> {
+        tts::validate_speed(self.options.speed)?;
+        
+        let tts_options = TtsOptions {
+            model: self.options.model.clone(),
+            voice: OpenAiVoice::from_str(&self.options.voice)?,
+            speed: self.options.speed,
+            response_format: OpenAiAudioFormat::Mp3,
+            retry: self.options.retry_policy,
+        };
+        
+        let mut tts_provider = OpenAiTts::new(api_key.to_string(), tts_options);
+        if let Some(rate_limiter) = &self.rate_limiter {
+            tts_provider = tts_provider.with_rate_limiter(rate_limiter.clone());
+        }
+        if let Some(base_url) = &self.options.base_url {
+            tts_provider = tts_provider.with_base_url(base_url.clone());
+        }
+        if let Some(cache_dir) = &self.options.tts_cache_dir {
+            tts_provider = tts_provider.with_cache_dir(cache_dir);
+        }
+        
+        Ok(tts_provider)
+    }
+    
+    /// Создает ядро синхронизации с текущими настройками
+    fn create_sync_core(&self) -> Result<SyncCore> {
+        let trailing_pause = self.options.effective_trailing_pause()?;
+        let sync_core = SyncCore::new_with_options(
+            self.progress_tracker.clone(),
+            self.options.output_sample_rate(),
+            1, // Моно аудио
+            self.options.normalize_volume,
+            10.0f32.powf(self.options.normalization_target_db / 20.0),
+            self.options.preserve_pauses,
+            self.options.tempo_algorithm.into(),
+        )
+        .with_progress_range(0.0, SYNC_CORE_PROGRESS_END)
+        .with_true_peak_normalization(self.options.true_peak_normalization)
+        .with_global_offset_ms(self.options.global_offset_ms)
+        .with_tail_fade_ms(self.options.tail_fade_ms)
+        .with_pre_roll_ms(self.options.pre_roll_ms)
+        .with_post_roll_ms(self.options.post_roll_ms)
+        .with_intelligibility_bias(self.options.intelligibility_bias)
+        .with_whitespace_normalization(self.options.collapse_whitespace)
+        .with_spoken_text_normalization(self.options.normalize_spoken_text)
+        .with_tts_sample_rate(self.options.tts_sample_rate)
+        .with_sample_rate_mismatch(self.options.sample_rate_mismatch)
+        .with_micro_gap_threshold_ms(self.options.micro_gap_threshold_ms)
+        .with_segment_pause(trailing_pause)
+        .with_min_segment_duration(self.options.min_segment_duration)
+        .with_duration_tolerance(self.options.duration_tolerance)
+        .with_merge_policy(self.options.merge_policy)
+        .with_normalization_tolerance(self.options.normalization_tolerance)
+        .with_position_panning(self.options.position_panning && self.options.stereo_pan.is_some())
+        .with_request_timestamps(self.options.request_timestamps)
+        .with_cue_range_behavior(self.options.cue_range_behavior)
+        .with_speech_anchor(self.options.speech_anchor)
+        .with_max_gap_duration(self.options.max_gap_duration)
+        .with_punctuation_pauses(self.options.punctuation_pauses)
+        .with_dither(self.options.dither)
+        .with_decode_concurrency(self.options.decode_concurrency)
+        .with_max_concurrent_requests(self.options.max_concurrent_requests)
+        .with_max_decoded_samples(self.options.max_decoded_samples)
+        .with_pipelining(self.options.pipelined_synthesis)
+        .with_continue_on_error(self.options.continue_on_error)
+        .with_keep_original_ranges(self.options.keep_original_ranges.clone())
+        .with_require_ffmpeg(self.options.require_ffmpeg)
+        .with_mp3_bitrate(self.options.mp3_bitrate)
+        .with_output_metadata(self.options.output_metadata());
+        
+        let sync_core = match self.options.raw_pcm {
+            Some(spec) => sync_core.with_raw_pcm(spec),
+            None => sync_core,
+        };
+        
+        let sync_core = match &self.cancellation_token {
+            Some(token) => sync_core.with_cancellation_token(token.clone()),
+            None => sync_core,
+        };
+        
+        Ok(match self.options.max_output_duration {
+            Some(max_duration) => sync_core.with_max_output_duration(
+                max_duration,
+                self.options.max_output_duration_behavior
+            ),
+            None => sync_core,
+        })
+    }
+    
     /// Синхронизирует TTS с видео и субтитрами
+    ///
+    /// Возвращает путь к сохраненному файлу; отчет по сегментам возвращает
+    /// [`TtsSync::synchronize_detailed`].
     pub async fn synchronize(
         &self,
         vtt_path: &str,
         video_duration: f64,
         api_key: &str,
     ) -> Result<String> {
-        log_info(&format!("Начало синхронизации TTS для файла: {}", vtt_path));
-        
+        self.synchronize_detailed(vtt_path, video_duration, api_key).await
+            .map(|result| result.output_path)
+    }
+    
+    /// Синхронизирует TTS с видео и субтитрами, возвращая длительность
+    /// результата и отчет по каждому сегменту
+    pub async fn synchronize_detailed(
+        &self,
+        vtt_path: &str,
+        video_duration: f64,
+        api_key: &str,
+    ) -> Result<SyncResult> {
         // Создаем TTS провайдер
-        let tts_options = TtsOptions {
-            model: OpenAiTtsModel::Tts1Hd,
-            voice: OpenAiVoice::from_str(&self.options.voice)?,
-            speed: 1.0,
-            response_format: OpenAiAudioFormat::Mp3,
-        };
+        let tts_provider = self.create_tts_provider(api_key)?;
         
-        let tts_provider = OpenAiTts::new(api_key.to_string(), tts_options);
+        self.synchronize_internal(SubtitleSource::File(vtt_path), video_duration, &tts_provider, None).await
+    }
+    
+    /// Синхронизирует TTS с видео и субтитрами, используя указанный TTS провайдер
+    pub async fn synchronize_with_provider<P: TtsProvider + Send + Sync + ?Sized>(
+        &self,
+        vtt_path: &str,
+        video_duration: f64,
+        tts_provider: &P,
+    ) -> Result<String> {
+        self.synchronize_detailed_with_provider(vtt_path, video_duration, tts_provider).await
+            .map(|result| result.output_path)
+    }
+    
+    /// Аналог [`TtsSync::synchronize_detailed`] с указанным TTS провайдером
+    pub async fn synchronize_detailed_with_provider<P: TtsProvider + Send + Sync + ?Sized>(
+        &self,
+        vtt_path: &str,
+        video_duration: f64,
+        tts_provider: &P,
+    ) -> Result<SyncResult> {
+        self.synchronize_internal(SubtitleSource::File(vtt_path), video_duration, tts_provider, None).await
+    }
+    
+    /// Синхронизирует TTS с субтитрами, уже загруженными в память, и сохраняет
+    /// результат в `output_path`
+    ///
+    /// Аналог [`TtsSync::synchronize`] без чтения VTT файла; субтитры
+    /// не обязаны быть отсортированы по времени начала.
+    pub async fn synchronize_track(
+        &self,
+        subtitles: &SubtitleTrack,
+        video_duration: f64,
+        api_key: &str,
+        output_path: &str,
+    ) -> Result<String> {
+        self.synchronize_track_detailed(subtitles, video_duration, api_key, output_path).await
+            .map(|result| result.output_path)
+    }
+    
+    /// Аналог [`TtsSync::synchronize_track`], возвращающий длительность
+    /// результата и отчет по каждому сегменту
+    pub async fn synchronize_track_detailed(
+        &self,
+        subtitles: &SubtitleTrack,
+        video_duration: f64,
+        api_key: &str,
+        output_path: &str,
+    ) -> Result<SyncResult> {
+        let tts_provider = self.create_tts_provider(api_key)?;
+        
+        self.synchronize_track_detailed_with_provider(subtitles, video_duration, &tts_provider, output_path).await
+    }
+    
+    /// Аналог [`TtsSync::synchronize_track`] с указанным TTS провайдером
+    pub async fn synchronize_track_with_provider<P: TtsProvider + Send + Sync + ?Sized>(
+        &self,
+        subtitles: &SubtitleTrack,
+        video_duration: f64,
+        tts_provider: &P,
+        output_path: &str,
+    ) -> Result<String> {
+        self.synchronize_track_detailed_with_provider(subtitles, video_duration, tts_provider, output_path).await
+            .map(|result| result.output_path)
+    }
+    
+    /// Аналог [`TtsSync::synchronize_track_detailed`] с указанным TTS провайдером
+    pub async fn synchronize_track_detailed_with_provider<P: TtsProvider + Send + Sync + ?Sized>(
+        &self,
+        subtitles: &SubtitleTrack,
+        video_duration: f64,
+        tts_provider: &P,
+        output_path: &str,
+    ) -> Result<SyncResult> {
+        let source = SubtitleSource::Track { subtitles, output_path };
+        self.synchronize_internal(source, video_duration, tts_provider, None).await
+    }
+    
+    /// Синхронизирует TTS с видео и субтитрами, вызывая `hook` для объединенного
+    /// аудио перед кодированием
+    ///
+    /// Позволяет применить собственную обработку к итоговым сэмплам после
+    /// встроенных эффектов.
+    pub async fn synchronize_with_post_hook<H>(
+        &self,
+        vtt_path: &str,
+        video_duration: f64,
+        api_key: &str,
+        mut hook: H,
+    ) -> Result<String>
+    where
+        H: FnMut(&mut AudioData) + Send,
+    {
+        let tts_provider = self.create_tts_provider(api_key)?;
+        
+        self.synchronize_internal(SubtitleSource::File(vtt_path), video_duration, &tts_provider, Some(&mut hook)).await
+            .map(|result| result.output_path)
+    }
+    
+    /// Аналог [`TtsSync::synchronize_with_post_hook`] с указанным TTS провайдером
+    pub async fn synchronize_with_provider_and_post_hook<P, H>(
+        &self,
+        vtt_path: &str,
+        video_duration: f64,
+        tts_provider: &P,
+        mut hook: H,
+    ) -> Result<String>
+    where
+        P: TtsProvider + Send + Sync + ?Sized,
+        H: FnMut(&mut AudioData) + Send,
+    {
+        self.synchronize_internal(SubtitleSource::File(vtt_path), video_duration, tts_provider, Some(&mut hook)).await
+            .map(|result| result.output_path)
+    }
+    
+    /// Синхронизирует несколько VTT файлов последовательно
+    ///
+    /// Возвращает результаты в порядке заданий. Если задан
+    /// `BatchOptions::max_failure_rate` и доля неудач его превышает, пакет
+    /// прерывается, а оставшиеся задания завершаются ошибкой без обращения к API.
+    pub async fn synchronize_batch(
+        &self,
+        jobs: &[SyncJob],
+        api_key: &str,
+        options: &BatchOptions,
+    ) -> Result<Vec<Result<String>>> {
+        let tts_provider = self.create_tts_provider(api_key)?;
+        
+        Ok(self.synchronize_batch_with_provider(jobs, &tts_provider, options).await)
+    }
+    
+    /// Аналог [`TtsSync::synchronize_batch`] с указанным TTS провайдером
+    pub async fn synchronize_batch_with_provider<P: TtsProvider + Send + Sync + ?Sized>(
+        &self,
+        jobs: &[SyncJob],
+        tts_provider: &P,
+        options: &BatchOptions,
+    ) -> Vec<Result<String>> {
+        log_info(&format!("Пакетная синхронизация {} файлов", jobs.len()));
+        
+        let mut results = Vec::with_capacity(jobs.len());
+        let mut failed = 0;
+        
+        for job in jobs {
+            let result = self.synchronize_internal(SubtitleSource::File(&job.vtt_path), job.video_duration, tts_provider, None).await
+                .map(|result| result.output_path);
+            if let Err(e) = &result {
+                failed += 1;
+                log_warning(&format!("Ошибка синхронизации файла {}: {}", job.vtt_path, e));
+            }
+            results.push(result);
+            
+            if options.should_abort(results.len(), failed) {
+                log_warning(&format!(
+                    "Пакет прерван: {} из {} заданий завершились ошибкой, пропущено заданий: {}",
+                    failed, results.len(), jobs.len() - results.len()
+                ));
+                break;
+            }
+        }
+        
+        let attempted = results.len();
+        results.extend(jobs[attempted..].iter().map(|job| {
+            Err(Error::new(
+                ErrorType::Synchronization,
+                &format!("Задание {} пропущено: пакет прерван из-за большого числа ошибок", job.vtt_path)
+            ))
+        }));
+        
+        results
+    }
+    
+    /// Выполняет синхронизацию и сохраняет результат в файл
+    async fn synchronize_internal<P: TtsProvider + Send + Sync + ?Sized>(
+        &self,
+        source: SubtitleSource<'_>,
+        video_duration: f64,
+        tts_provider: &P,
+        post_hook: Option<&mut (dyn FnMut(&mut AudioData) + Send)>,
+    ) -> Result<SyncResult> {
+        if self.options.output_format == AudioFormat::Mp3 {
+            audio::validate_mp3_bitrate(self.options.mp3_bitrate)?;
+        }
         
         // Создаем ядро синхронизации с расширенными параметрами
-        let sync_core = SyncCore::new(
-            self.progress_tracker.clone(),
-            self.options.sample_rate,
-            1, // Моно аудио
-            self.options.normalize_volume,
-        );
+        let sync_core = self.create_sync_core()?;
         
         // Синхронизируем TTS с субтитрами
-        let mut audio_track = match sync_core.synchronize(vtt_path, video_duration, &tts_provider).await {
+        let synchronized = match source {
+            SubtitleSource::File(vtt_path) => {
+                log_info(&format!("Начало синхронизации TTS для файла: {}", vtt_path));
+                sync_core.synchronize(vtt_path, video_duration, tts_provider).await
+            }
+            SubtitleSource::Track { subtitles, output_path } => {
+                log_info(&format!("Начало синхронизации TTS для {} субтитров, результат: {}",
+                    subtitles.len(), output_path));
+                sync_core.synchronize_track(subtitles, video_duration, tts_provider).await
+            }
+        };
+        let mut audio_track = match synchronized {
             Ok(track) => track,
             Err(e) => {
                 log_error::<(), _>(&e, "Ошибка при синхронизации TTS")?;
@@ -241,11 +1323,14 @@ impl TtsSync {
         };
         
         // Применяем дополнительную обработку аудио, если требуется
-        if self.options.apply_compression || self.options.apply_equalization || self.options.normalize_volume {
+        if self.options.apply_compression || self.options.apply_equalization || self.options.apply_deesser
+            || self.options.normalize_volume
+            || self.options.stereo_pan.is_some() || post_hook.is_some() {
             self.progress_tracker.update(90.0, "Применение аудио эффектов")?;
             
             // Объединяем все сегменты в один аудио файл для обработки
-            let merged_audio = audio_track.merge()?;
+            let merged_audio = audio_track.merge_with(self.options.merge_policy)?;
+            let pan_regions = audio_track.pan_regions();
             
             // Применяем компрессию, если включена
             let processed_audio = if self.options.apply_compression {
@@ -275,16 +1360,71 @@ impl TtsSync {
                 processed_audio
             };
             
-            // Нормализуем громкость, если включена
-            let processed_audio = if self.options.normalize_volume {
+            // Подавляем сибилянты, если включено
+            let processed_audio = if self.options.apply_deesser {
+                AudioProcessor::apply_deesser(
+                    &processed_audio,
+                    self.options.deesser_freq,
+                    self.options.deesser_threshold,
+                    self.options.deesser_ratio
+                )?
+            } else {
+                processed_audio
+            };
+            
+            // В режиме LUFS усиление применяем до лимитера, чтобы лимитер
+            // ограничивал пики уже нормализованного сигнала
+            let loudness_mode = self.options.normalize_volume
+                && self.options.normalization_mode == NormalizationMode::Lufs;
+            let processed_audio = if loudness_mode && !processed_audio.is_empty() {
+                log_info("Нормализация громкости");
+                AudioProcessor::normalize_loudness(&processed_audio, self.options.normalization_target_lufs)?
+            } else {
+                processed_audio
+            };
+            
+            // Ограничиваем пики потолком, согласованным с целевым уровнем нормализации
+            let processed_audio = if self.options.apply_limiter {
+                let ceiling_db = 20.0 * self.options.limiter_ceiling().log10();
+                log_info(&format!("Применение лимитера с потолком {:.2} дБ", ceiling_db));
+                AudioProcessor::apply_limiter(
+                    &processed_audio, ceiling_db, audio::adjustment::processor::DEFAULT_LIMITER_RELEASE_MS
+                )?
+            } else {
+                processed_audio
+            };
+            
+            // Нормализуем пиковый уровень, если включен
+            let processed_audio = if self.options.normalize_volume && !loudness_mode {
                 log_info("Нормализация громкости");
-                processed_audio.normalize_db(self.options.normalization_target_db)
+                if self.options.true_peak_normalization {
+                    processed_audio.normalize_db_true_peak(self.options.normalization_target_db)
+                } else {
+                    processed_audio.normalize_db(self.options.normalization_target_db)
+                }
             } else {
                 processed_audio
             };
             
+            // Размещаем диалог в стерео, если требуется
+            let processed_audio = match self.options.stereo_pan {
+                Some(pan) => {
+                    log_info(&format!("Преобразование в стерео, панорама: {:.2}, реплик с позицией: {}",
+                        pan, pan_regions.len()));
+                    processed_audio.to_stereo_with_regions(pan, &pan_regions)
+                }
+                None => processed_audio,
+            };
+            
+            // Вызываем пользовательскую обработку, если она задана
+            let mut processed_audio = processed_audio;
+            if let Some(hook) = post_hook {
+                log_info("Применение пользовательской обработки аудио");
+                hook(&mut processed_audio);
+            }
+            
             // Создаем новый аудио трек с одним сегментом
-            audio_track = AudioTrack::default();
+            audio_track = AudioTrack::new(processed_audio.sample_rate, processed_audio.channels);
             audio_track.add_segment(AudioSegment::new(
                 processed_audio.clone(),
                 0.0,
@@ -293,22 +1433,60 @@ impl TtsSync {
             ));
         }
         
-        // Генерируем имя выходного файла
-        let output_path = format!("{}.{}", vtt_path.replace(".vtt", "_tts"), 
+        // Отмена во время обработки эффектов: результат не сохраняем
+        if self.cancellation_token.as_ref().is_some_and(|token| token.is_cancelled()) {
+            log_info("Синхронизация отменена, результат не сохранен");
+            return Err(Error::new(ErrorType::Cancelled, "синхронизация прервана по запросу"));
+        }
+        
+        let output_path = match source {
+            SubtitleSource::File(vtt_path) => self.output_path_for(vtt_path),
+            SubtitleSource::Track { output_path, .. } => output_path.to_string(),
+        };
+        let output_path = self.save_track(&sync_core, &audio_track, output_path).await?;
+        Ok(SyncResult {
+            output_path,
+            total_duration: audio_track.merged_duration(),
+            segments: sync_core.report().segments,
+            failed_segments: sync_core.take_segment_errors(),
+        })
+    }
+    
+    /// Строит трек из тишины по таймингам субтитров и сохраняет его в файл
+    ///
+    /// Не обращается к TTS провайдеру, поэтому позволяет бесплатно проверить
+    /// тайминги субтитров и весь конвейер вывода.
+    pub async fn synchronize_silent(&self, vtt_path: &str, video_duration: f64) -> Result<String> {
+        log_info(&format!("Построение трека из тишины для файла: {}", vtt_path));
+        
+        let sync_core = self.create_sync_core()?;
+        let audio_track = sync_core.synchronize_silent(vtt_path, video_duration)?;
+        
+        self.save_track(&sync_core, &audio_track, self.output_path_for(vtt_path)).await
+    }
+    
+    /// Возвращает путь выходного файла рядом с VTT файлом
+    fn output_path_for(&self, vtt_path: &str) -> String {
+        format!("{}.{}", vtt_path.replace(".vtt", "_tts"), 
             match self.options.output_format {
                 AudioFormat::Mp3 => "mp3",
                 AudioFormat::Wav => "wav",
                 AudioFormat::Ogg => "ogg",
+                AudioFormat::Flac => "flac",
+                AudioFormat::Opus => "opus",
             }
-        );
-        
+        )
+    }
+    
+    /// Сохраняет итоговый трек в файл `output_path`
+    async fn save_track(&self, sync_core: &SyncCore, audio_track: &AudioTrack, output_path: String) -> Result<String> {
         // Сохраняем аудио в файл
         self.progress_tracker.update(95.0, "Сохранение аудио файла")?;
-        match sync_core.save_to_file(&audio_track, &output_path).await {
-            Ok(_) => {
-                log_info(&format!("Аудио успешно сохранено в файл: {}", output_path));
+        match sync_core.save_to_file(audio_track, &output_path).await {
+            Ok(saved_path) => {
+                log_info(&format!("Аудио успешно сохранено в файл: {}", saved_path));
                 self.progress_tracker.update(100.0, "Синхронизация завершена")?;
-                Ok(output_path)
+                Ok(saved_path)
             },
             Err(e) => {
                 log_error::<(), _>(&e, &format!("Ошибка при сохранении аудио в файл: {}", output_path))?;
@@ -318,34 +1496,66 @@ impl TtsSync {
     }
     
     /// Синхронизирует TTS с видео и субтитрами, возвращая аудио данные
+    ///
+    /// Возвращает только сэмплы; частоту дискретизации и количество каналов
+    /// можно получить через [`TtsSync::synchronize_to_audiodata`].
     pub async fn synchronize_to_memory(
         &self,
         vtt_path: &str,
         video_duration: f64,
         api_key: &str,
     ) -> Result<Vec<f32>> {
-        log_info(&format!("Начало синхронизации TTS в память для файла: {}", vtt_path));
-        
+        Ok(self.synchronize_to_audiodata(vtt_path, video_duration, api_key).await?.samples)
+    }
+    
+    /// Синхронизирует TTS с видео и субтитрами, возвращая аудио данные
+    /// вместе с частотой дискретизации и количеством каналов
+    pub async fn synchronize_to_audiodata(
+        &self,
+        vtt_path: &str,
+        video_duration: f64,
+        api_key: &str,
+    ) -> Result<AudioData> {
         // Создаем TTS провайдер
-        let tts_options = TtsOptions {
-            model: OpenAiTtsModel::Tts1Hd,
-            voice: OpenAiVoice::from_str(&self.options.voice)?,
-            speed: 1.0,
-            response_format: OpenAiAudioFormat::Mp3,
-        };
+        let tts_provider = self.create_tts_provider(api_key)?;
+        
+        self.synchronize_to_audiodata_with_provider(vtt_path, video_duration, &tts_provider).await
+    }
+    
+    /// Синхронизирует TTS с видео и субтитрами, возвращая 16-битный PCM
+    /// (little-endian) для прямой передачи в аудио устройство
+    ///
+    /// Частота дискретизации равна [`SyncOptions::output_sample_rate`], аудио моно.
+    pub async fn synchronize_to_pcm16(
+        &self,
+        vtt_path: &str,
+        video_duration: f64,
+        api_key: &str,
+    ) -> Result<Vec<u8>> {
+        let audio = self.synchronize_to_audiodata(vtt_path, video_duration, api_key).await?;
+        let (pcm_data, clipped_samples) = audio.to_pcm16_le_dithered(self.options.dither);
         
-        let tts_provider = OpenAiTts::new(api_key.to_string(), tts_options);
+        if clipped_samples > 0 {
+            log_warning(&format!("При преобразовании в PCM ограничено {} сэмплов", clipped_samples));
+        }
+        
+        Ok(pcm_data)
+    }
+    
+    /// Синхронизирует TTS с видео и субтитрами в память, используя указанный TTS провайдер
+    pub async fn synchronize_to_audiodata_with_provider<P: TtsProvider + Send + Sync + ?Sized>(
+        &self,
+        vtt_path: &str,
+        video_duration: f64,
+        tts_provider: &P,
+    ) -> Result<AudioData> {
+        log_info(&format!("Начало синхронизации TTS в память для файла: {}", vtt_path));
         
         // Создаем ядро синхронизации
-        let sync_core = SyncCore::new(
-            self.progress_tracker.clone(),
-            self.options.sample_rate,
-            1, // Моно аудио
-            self.options.normalize_volume,
-        );
+        let sync_core = self.create_sync_core()?;
         
         // Синхронизируем TTS с субтитрами
-        let audio_track = match sync_core.synchronize(vtt_path, video_duration, &tts_provider).await {
+        let audio_track = match sync_core.synchronize(vtt_path, video_duration, tts_provider).await {
             Ok(track) => track,
             Err(e) => {
                 log_error::<(), _>(&e, "Ошибка при синхронизации TTS")?;
@@ -354,10 +1564,11 @@ impl TtsSync {
         };
         
         // Объединяем все сегменты в один аудио файл
-        match audio_track.merge() {
+        match audio_track.merge_with(self.options.merge_policy) {
             Ok(merged_audio) => {
                 log_info(&format!("Аудио успешно синхронизировано, длительность: {} секунд", merged_audio.duration()));
-                Ok(merged_audio.samples)
+                self.progress_tracker.update(100.0, "Синхронизация завершена")?;
+                Ok(merged_audio)
             },
             Err(e) => {
                 log_error::<(), _>(&e, "Ошибка при объединении аудио сегментов")?;
@@ -1,3 +1,3 @@
 mod tracker;
 
-pub use tracker::{ProgressTracker, ProgressCallback, ChildProgressTracker};
+pub use tracker::{ProgressTracker, ProgressCallback, ChildProgressTracker, DetailedProgressCallback, ProgressUpdate};
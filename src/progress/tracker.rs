@@ -1,9 +1,113 @@
 use crate::error::Result;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
 
 /// Тип для функций обратного вызова прогресса
 pub type ProgressCallback = Box<dyn Fn(f32, &str) + Send + 'static>;
 
+/// Тип для функций обратного вызова прогресса с оценкой оставшегося времени
+pub type DetailedProgressCallback = Box<dyn Fn(&ProgressUpdate) + Send + Sync + 'static>;
+
+/// Постоянная времени сглаживания скорости прогресса в секундах
+const RATE_SMOOTHING_SECONDS: f64 = 10.0;
+
+/// Количество обновлений, которые подписчик может не прочитать до потери
+/// самых старых
+const PROGRESS_CHANNEL_CAPACITY: usize = 256;
+
+/// Обновление прогресса с оценками времени
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressUpdate {
+    /// Прогресс от 0.0 до 100.0
+    pub progress: f32,
+    /// Статус
+    pub status: String,
+    /// Оценка оставшегося времени (None - пока недостаточно данных)
+    pub eta: Option<Duration>,
+    /// Средняя скорость генерации TTS сегментов в секунду на последнем этапе
+    /// генерации (None до первого сегмента)
+    pub segments_per_second: Option<f64>,
+}
+
+/// Данные для оценки оставшегося времени и скорости
+#[derive(Debug, Default)]
+struct ProgressStats {
+    /// Время и значение последнего обновления прогресса
+    last: Option<(Instant, f32)>,
+    /// Сглаженная скорость прогресса в процентах в секунду
+    rate: Option<f64>,
+    /// Начало отсчета сегментов: время и количество готовых сегментов
+    segments_start: Option<(Instant, usize)>,
+    /// Средняя скорость генерации сегментов в секунду
+    segments_per_second: Option<f64>,
+}
+
+impl ProgressStats {
+    /// Учитывает новое значение прогресса
+    fn record(&mut self, progress: f32, now: Instant) {
+        match self.last {
+            // Прогресс пошел назад - началась новая задача
+            Some((_, last_progress)) if progress < last_progress => {
+                self.rate = None;
+                self.segments_start = None;
+                self.segments_per_second = None;
+            }
+            Some((last_time, last_progress)) => {
+                let elapsed = now.saturating_duration_since(last_time).as_secs_f64();
+                if elapsed <= 0.0 {
+                    // Обновления в один момент времени объединяются
+                    if let Some((_, previous)) = &mut self.last {
+                        *previous = progress;
+                    }
+                    return;
+                }
+                let rate = (progress - last_progress) as f64 / elapsed;
+                // Экспоненциальное сглаживание с весом, зависящим от интервала,
+                // чтобы частые мелкие обновления не перевешивали редкие
+                let weight = 1.0 - (-elapsed / RATE_SMOOTHING_SECONDS).exp();
+                self.rate = Some(match self.rate {
+                    Some(smoothed) => smoothed + weight * (rate - smoothed),
+                    None => rate,
+                });
+            }
+            None => {}
+        }
+        self.last = Some((now, progress));
+    }
+    
+    /// Учитывает количество готовых сегментов
+    fn record_segments(&mut self, completed: usize, now: Instant) {
+        match self.segments_start {
+            Some((start, start_completed)) if completed >= start_completed => {
+                let elapsed = now.saturating_duration_since(start).as_secs_f64();
+                if elapsed > 0.0 {
+                    self.segments_per_second = Some((completed - start_completed) as f64 / elapsed);
+                }
+            }
+            _ => {
+                self.segments_start = Some((now, completed));
+                self.segments_per_second = None;
+            }
+        }
+    }
+    
+    /// Возвращает оценку оставшегося времени
+    fn eta(&self) -> Option<Duration> {
+        let (_, progress) = self.last?;
+        if progress >= 100.0 {
+            return Some(Duration::ZERO);
+        }
+        let rate = self.rate.filter(|&rate| rate > 0.0)?;
+        let seconds = (100.0 - progress) as f64 / rate;
+        if seconds.is_finite() && seconds < u64::MAX as f64 {
+            Some(Duration::from_secs_f64(seconds))
+        } else {
+            None
+        }
+    }
+}
+
 /// Трекер прогресса
 #[derive(Clone)]
 pub struct ProgressTracker {
@@ -13,6 +117,12 @@ pub struct ProgressTracker {
     status: Arc<Mutex<String>>,
     /// Функция обратного вызова для отслеживания прогресса
     callback: Option<Arc<ProgressCallback>>,
+    /// Функция обратного вызова, получающая также оценки времени
+    detailed_callback: Option<Arc<DetailedProgressCallback>>,
+    /// Данные для оценки оставшегося времени
+    stats: Arc<Mutex<ProgressStats>>,
+    /// Канал рассылки обновлений подписчикам
+    sender: broadcast::Sender<(f32, String)>,
 }
 
 impl ProgressTracker {
@@ -22,6 +132,9 @@ impl ProgressTracker {
             progress: Arc::new(Mutex::new(0.0)),
             status: Arc::new(Mutex::new(String::new())),
             callback: None,
+            detailed_callback: None,
+            stats: Arc::new(Mutex::new(ProgressStats::default())),
+            sender: broadcast::channel(PROGRESS_CHANNEL_CAPACITY).0,
         }
     }
     
@@ -31,6 +144,9 @@ impl ProgressTracker {
             progress: Arc::new(Mutex::new(0.0)),
             status: Arc::new(Mutex::new(String::new())),
             callback: Some(Arc::new(callback)),
+            detailed_callback: None,
+            stats: Arc::new(Mutex::new(ProgressStats::default())),
+            sender: broadcast::channel(PROGRESS_CHANNEL_CAPACITY).0,
         }
     }
     
@@ -39,10 +155,35 @@ impl ProgressTracker {
         self.callback = Some(Arc::new(callback));
     }
     
+    /// Устанавливает функцию обратного вызова, получающую вместе с прогрессом
+    /// оценку оставшегося времени и скорость генерации сегментов
+    ///
+    /// Вызывается при каждом обновлении вместе с обычной функцией обратного вызова.
+    pub fn set_detailed_callback(&mut self, callback: DetailedProgressCallback) {
+        self.detailed_callback = Some(Arc::new(callback));
+    }
+    
+    /// Подписывается на обновления прогресса
+    ///
+    /// Получатель принимает пары (прогресс, статус) всех последующих вызовов
+    /// `update` этого трекера, его копий и дочерних трекеров в порядке
+    /// вызова, независимо от функции обратного вызова. Если подписчик
+    /// отстает больше чем на 256 обновлений, самые старые теряются и `recv`
+    /// возвращает [`broadcast::error::RecvError::Lagged`].
+    pub fn subscribe(&self) -> broadcast::Receiver<(f32, String)> {
+        self.sender.subscribe()
+    }
+    
     /// Обновляет прогресс
     pub fn update(&self, progress: f32, status: &str) -> Result<()> {
+        self.update_at(progress, status, Instant::now())
+    }
+    
+    /// Обновляет прогресс, считая, что обновление произошло в момент `now`
+    pub(crate) fn update_at(&self, progress: f32, status: &str, now: Instant) -> Result<()> {
         // Ограничиваем прогресс от 0 до 100
         let clamped_progress = progress.max(0.0).min(100.0);
+        self.stats.lock().unwrap().record(clamped_progress, now);
         
         // Обновляем прогресс и статус
         {
@@ -59,6 +200,12 @@ impl ProgressTracker {
         if let Some(callback) = &self.callback {
             callback(clamped_progress, status);
         }
+        if let Some(callback) = &self.detailed_callback {
+            callback(&self.get_update());
+        }
+        
+        // Рассылаем обновление подписчикам (ошибка означает, что их нет)
+        let _ = self.sender.send((clamped_progress, status.to_string()));
         
         Ok(())
     }
@@ -73,6 +220,45 @@ impl ProgressTracker {
         self.status.lock().unwrap().clone()
     }
     
+    /// Возвращает оценку оставшегося времени
+    ///
+    /// Оценка строится по скорости роста прогресса, сглаженной с постоянной
+    /// времени 10 секунд, поэтому короткие задержки (например, медленный
+    /// ответ провайдера) не вызывают скачков. None до второго обновления
+    /// и пока прогресс не растет.
+    pub fn get_eta(&self) -> Option<Duration> {
+        self.stats.lock().unwrap().eta()
+    }
+    
+    /// Возвращает среднюю скорость генерации TTS сегментов в секунду
+    pub fn get_segments_per_second(&self) -> Option<f64> {
+        self.stats.lock().unwrap().segments_per_second
+    }
+    
+    /// Возвращает текущий прогресс вместе с оценками времени
+    pub fn get_update(&self) -> ProgressUpdate {
+        let stats = self.stats.lock().unwrap();
+        ProgressUpdate {
+            progress: self.get_progress(),
+            status: self.get_status(),
+            eta: stats.eta(),
+            segments_per_second: stats.segments_per_second,
+        }
+    }
+    
+    /// Сообщает количество готовых сегментов текущего этапа
+    ///
+    /// Первый вызов (или вызов с меньшим количеством) начинает отсчет;
+    /// скорость считается как среднее с начала отсчета.
+    pub fn record_segments(&self, completed: usize) {
+        self.record_segments_at(completed, Instant::now());
+    }
+    
+    /// Сообщает количество готовых сегментов на момент `now`
+    pub(crate) fn record_segments_at(&self, completed: usize, now: Instant) {
+        self.stats.lock().unwrap().record_segments(completed, now);
+    }
+    
     /// Создает дочерний трекер прогресса с заданным диапазоном
     pub fn create_child(&self, start: f32, end: f32) -> ChildProgressTracker {
         ChildProgressTracker {
@@ -112,6 +298,29 @@ impl ChildProgressTracker {
         // Обновляем прогресс в родительском трекере
         self.parent.update(parent_progress, status)
     }
+    
+    /// Сообщает количество готовых сегментов текущего этапа родительскому трекеру
+    pub fn record_segments(&self, completed: usize) {
+        self.parent.record_segments(completed);
+    }
+    
+    /// Создает вложенный дочерний трекер с диапазоном внутри диапазона этого трекера
+    pub fn create_child(&self, start: f32, end: f32) -> ChildProgressTracker {
+        let scale = |progress: f32| self.start + (self.end - self.start) * progress.clamp(0.0, 100.0) / 100.0;
+        
+        ChildProgressTracker {
+            parent: self.parent.clone(),
+            start: scale(start),
+            end: scale(end),
+        }
+    }
+}
+
+impl From<ProgressTracker> for ChildProgressTracker {
+    /// Создает дочерний трекер, занимающий весь диапазон родительского
+    fn from(parent: ProgressTracker) -> Self {
+        parent.create_child(0.0, 100.0)
+    }
 }
 
 #[cfg(test)]
@@ -161,6 +370,98 @@ mod tests {
         assert_eq!(status, "Quarter done");
     }
     
+    #[tokio::test]
+    async fn test_progress_subscription() {
+        let (tx, rx) = mpsc::channel();
+        let tracker = ProgressTracker::with_callback(Box::new(move |progress: f32, _status: &str| {
+            tx.send(progress).unwrap();
+        }));
+        let mut receiver = tracker.subscribe();
+        
+        // Обновления до подписки не приходят
+        let late_tracker = tracker.clone();
+        tracker.update(10.0, "Старт").unwrap();
+        let mut late_receiver = late_tracker.subscribe();
+        
+        let producer = async {
+            tokio::task::yield_now().await;
+            tracker.update(20.0, "Синтез").unwrap();
+            tokio::task::yield_now().await;
+            tracker.create_child(50.0, 100.0).update(50.0, "Корректировка").unwrap();
+            tracker.update(100.0, "Готово").unwrap();
+        };
+        let consumer = async {
+            let mut updates = Vec::new();
+            while updates.len() < 4 {
+                updates.push(receiver.recv().await.unwrap());
+            }
+            updates
+        };
+        let ((), updates) = tokio::join!(producer, consumer);
+        
+        assert_eq!(updates, [
+            (10.0, "Старт".to_string()),
+            (20.0, "Синтез".to_string()),
+            (75.0, "Корректировка".to_string()),
+            (100.0, "Готово".to_string()),
+        ]);
+        assert_eq!(late_receiver.recv().await.unwrap().0, 20.0);
+        
+        // Функция обратного вызова получает те же обновления
+        assert_eq!(rx.iter().take(4).collect::<Vec<_>>(), [10.0, 20.0, 75.0, 100.0]);
+    }
+    
+    #[test]
+    fn test_progress_eta() {
+        let (tx, rx) = mpsc::channel();
+        let mut tracker = ProgressTracker::new();
+        tracker.set_detailed_callback(Box::new(move |update: &ProgressUpdate| {
+            tx.send(update.clone()).unwrap();
+        }));
+        let start = Instant::now();
+        let at = |seconds: f64| start + Duration::from_secs_f64(seconds);
+        
+        // Одного обновления недостаточно для оценки
+        tracker.update_at(0.0, "Старт", at(0.0)).unwrap();
+        assert_eq!(tracker.get_eta(), None);
+        
+        // 10% в секунду: оценка точна и убывает
+        let mut previous = Duration::MAX;
+        for second in 1..=5 {
+            tracker.update_at(10.0 * second as f32, "Синтез", at(second as f64)).unwrap();
+            let eta = tracker.get_eta().unwrap();
+            assert!((eta.as_secs_f64() - (10.0 - second as f64)).abs() < 1e-6, "{:?}", eta);
+            assert!(eta < previous);
+            previous = eta;
+        }
+        
+        // После замедления вдвое оценка растет плавно, а не скачком к 10 секундам
+        tracker.update_at(55.0, "Синтез", at(6.0)).unwrap();
+        let slowed = tracker.get_eta().unwrap().as_secs_f64();
+        assert!(slowed > 4.5 && slowed < 5.0, "{}", slowed);
+        for second in 7..=14 {
+            tracker.update_at(55.0 + 5.0 * (second - 6) as f32, "Синтез", at(second as f64)).unwrap();
+            let remaining_at_slow_rate = (100.0 - tracker.get_progress() as f64) / 5.0;
+            let eta = tracker.get_eta().unwrap().as_secs_f64();
+            assert!(eta > 0.0 && eta <= remaining_at_slow_rate + 1e-6, "{} > {}", eta, remaining_at_slow_rate);
+        }
+        
+        // Скорость генерации сегментов считается с начала отсчета
+        tracker.record_segments_at(0, at(20.0));
+        tracker.record_segments_at(8, at(22.0));
+        assert_eq!(tracker.get_segments_per_second(), Some(4.0));
+        
+        tracker.update_at(100.0, "Готово", at(23.0)).unwrap();
+        assert_eq!(tracker.get_eta(), Some(Duration::ZERO));
+        let last = rx.try_iter().last().unwrap();
+        assert_eq!((last.progress, last.eta, last.segments_per_second), (100.0, Some(Duration::ZERO), Some(4.0)));
+        
+        // Новая задача начинает оценку заново
+        tracker.update_at(0.0, "Старт", at(30.0)).unwrap();
+        assert_eq!(tracker.get_eta(), None);
+        assert_eq!(tracker.get_segments_per_second(), None);
+    }
+    
     #[test]
     fn test_child_progress_tracker() {
         let parent = ProgressTracker::new();
@@ -173,4 +474,17 @@ mod tests {
         assert_eq!(parent.get_progress(), 62.5); // 50 + (75-50) * 0.5 = 62.5
         assert_eq!(parent.get_status(), "Child halfway");
     }
+    
+    #[test]
+    fn test_nested_child_progress_tracker() {
+        let parent = ProgressTracker::new();
+        let child = parent.create_child(0.0, 80.0);
+        let nested = child.create_child(50.0, 100.0);
+        
+        nested.update(0.0, "Начало").unwrap();
+        assert_eq!(parent.get_progress(), 40.0);
+        
+        nested.update(100.0, "Конец").unwrap();
+        assert_eq!(parent.get_progress(), 80.0);
+    }
 }
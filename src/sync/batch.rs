@@ -0,0 +1,102 @@
+//! Пакетная синхронизация и экспорт нескольких аудио треков.
+
+use crate::audio::AudioTrack;
+use crate::error::Result;
+use crate::logging::log_info;
+use crate::sync::core::SyncCore;
+use futures_util::future::join_all;
+use tokio::sync::Semaphore;
+
+/// Параметры пакетного экспорта
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    /// Максимальное количество одновременно запущенных процессов кодирования (ffmpeg)
+    pub max_concurrent_encodes: usize,
+    /// Доля неудачных заданий (от 0.0 до 1.0), при превышении которой пакет
+    /// прерывается, а оставшиеся задания не выполняются (None - выполнять все)
+    pub max_failure_rate: Option<f64>,
+    /// Количество выполненных заданий, после которого проверяется доля неудач
+    pub failure_check_after: usize,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self {
+            max_concurrent_encodes: 2,
+            max_failure_rate: None,
+            failure_check_after: 4,
+        }
+    }
+}
+
+impl BatchOptions {
+    /// Проверяет, нужно ли прервать пакет после `attempted` заданий,
+    /// из которых `failed` завершились ошибкой
+    ///
+    /// Массовые ошибки в начале пакета обычно означают общую проблему
+    /// (например, неверный API ключ), и продолжать бессмысленно.
+    pub fn should_abort(&self, attempted: usize, failed: usize) -> bool {
+        match self.max_failure_rate {
+            Some(max_rate) if attempted > 0 && attempted >= self.failure_check_after => {
+                failed as f64 / attempted as f64 > max_rate
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Задание пакетной синхронизации: VTT файл и длительность видео
+#[derive(Debug, Clone)]
+pub struct SyncJob {
+    /// Путь к VTT файлу (результат сохраняется рядом с ним)
+    pub vtt_path: String,
+    /// Длительность видео в секундах
+    pub video_duration: f64,
+}
+
+impl SyncJob {
+    /// Создает новое задание синхронизации
+    pub fn new(vtt_path: impl Into<String>, video_duration: f64) -> Self {
+        Self {
+            vtt_path: vtt_path.into(),
+            video_duration,
+        }
+    }
+}
+
+/// Задание пакетного экспорта: трек и путь выходного файла
+#[derive(Debug, Clone)]
+pub struct BatchJob {
+    /// Аудио трек для сохранения
+    pub track: AudioTrack,
+    /// Путь выходного файла (формат определяется по расширению)
+    pub output_path: String,
+}
+
+impl BatchJob {
+    /// Создает новое задание экспорта
+    pub fn new(track: AudioTrack, output_path: impl Into<String>) -> Self {
+        Self {
+            track,
+            output_path: output_path.into(),
+        }
+    }
+}
+
+impl SyncCore {
+    /// Сохраняет несколько треков параллельно
+    ///
+    /// Задания выполняются одновременно, но число процессов ffmpeg ограничено
+    /// `max_concurrent_encodes`. Результаты (пути сохраненных файлов, см.
+    /// [`SyncCore::save_to_file`]) возвращаются в порядке заданий.
+    pub async fn export_batch(&self, jobs: &[BatchJob], options: &BatchOptions) -> Vec<Result<String>> {
+        let max_concurrent_encodes = options.max_concurrent_encodes.max(1);
+        let encode_slots = Semaphore::new(max_concurrent_encodes);
+        log_info(&format!("Пакетный экспорт {} треков (одновременных кодирований: {})",
+            jobs.len(), max_concurrent_encodes));
+
+        join_all(jobs.iter().map(|job| {
+            self.save_to_file_limited(&job.track, &job.output_path, Some(&encode_slots))
+        })).await
+    }
+}
@@ -0,0 +1,29 @@
+//! Запуск внешних программ, используемых при сохранении аудио (ffmpeg).
+
+use std::process::{Command, Output};
+use std::sync::Arc;
+
+/// Исполнитель внешних команд
+///
+/// Позволяет подменить запуск внешних программ, например, чтобы проверить
+/// поведение при отсутствии ffmpeg.
+pub trait CommandRunner: Send + Sync {
+    /// Запускает программу с аргументами и возвращает результат ее выполнения
+    fn run(&self, program: &str, args: &[&str]) -> std::io::Result<Output>;
+}
+
+/// Исполнитель, запускающий команды в системе
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemCommandRunner;
+
+impl CommandRunner for SystemCommandRunner {
+    fn run(&self, program: &str, args: &[&str]) -> std::io::Result<Output> {
+        Command::new(program).args(args).output()
+    }
+}
+
+impl<R: CommandRunner + ?Sized> CommandRunner for Arc<R> {
+    fn run(&self, program: &str, args: &[&str]) -> std::io::Result<Output> {
+        (**self).run(program, args)
+    }
+}
@@ -1,19 +1,28 @@
 use crate::error::{Error, Result, ErrorType};
-use crate::vtt::{SubtitleTrack, VttParser};
-use crate::tts::{TtsProvider, TtsSegment};
+use crate::vtt::{EmptyVttBehavior, Subtitle, SubtitleTrack, VttParser};
+use crate::tts::{TtsProvider, TtsSegment, WordTiming};
 use crate::audio::{
-    AudioData, AudioSegment, AudioTrack,
-    AudioAnalyzer, TempoAdjuster,
-    TempoAlgorithm
+    AudioData, AudioSegment, AudioTrack, AudioSink, MergePolicy, SinkWriter,
+    AudioAnalyzer, AudioProcessor, TempoAdjuster,
+    TempoAlgorithm, SampleRateMismatch, DitherType, RawPcmSpec, OutputMetadata,
+    pcm16_wav_header, validate_keep_original_ranges, validate_mp3_bitrate, DEFAULT_MP3_BITRATE
 };
-use crate::progress::ProgressTracker;
+use crate::progress::{ProgressTracker, ChildProgressTracker};
+use crate::sync::command::{CommandRunner, SystemCommandRunner};
+use crate::sync::pacing::PunctuationPauses;
+use crate::sync::report::{Diagnostic, DiagnosticKind, GapCompression, SyncReport, SegmentReport};
+use crate::sync::text_cache::{text_hash, TextCache};
+use crate::sync::tradeoff::split_compression;
 use crate::logging::{log_debug, log_info, log_error, log_warning, log_trace};
 use tokio::fs::File;
 use tokio::io::{AsyncWriteExt, AsyncReadExt};
-use std::collections::HashMap;
-use std::process::Command;
 use std::path::Path;
 use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+use futures_util::stream::StreamExt;
 use std::io::Cursor;
 
 // Используем Symphonia для работы с аудио
@@ -23,10 +32,112 @@ use symphonia::core::io::MediaSourceStream;
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
 
+/// Обработчик скорректированной реплики при потоковой синхронизации
+type AdjustedCallback<'a> = dyn FnMut(&AudioSegment) -> Result<()> + 'a;
+
+/// Количество потоков декодирования сегментов по умолчанию
+pub(crate) const DEFAULT_DECODE_CONCURRENCY: usize = 4;
+
+/// Количество одновременных запросов к TTS провайдеру по умолчанию
+pub(crate) const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 4;
+
+/// Предел декодированных сэмплов одного сегмента по умолчанию
+/// (10 минут стерео 48 кГц)
+pub(crate) const DEFAULT_MAX_DECODED_SAMPLES: usize = 48000 * 2 * 600;
+
+/// Пауза после последней реплики по умолчанию в секундах
+pub(crate) const DEFAULT_SEGMENT_PAUSE: f64 = 0.2;
+
+/// Минимальная длительность речи сегмента по умолчанию в секундах
+/// (0 - без ограничения, как до появления настройки)
+pub(crate) const DEFAULT_MIN_SEGMENT_DURATION: f64 = 0.0;
+
+/// Допустимое отклонение длительности результата от видео по умолчанию в секундах
+pub(crate) const DEFAULT_DURATION_TOLERANCE: f64 = 0.01;
+
+/// Отклонение пика от целевого по умолчанию, при котором нормализация пропускается
+pub(crate) const DEFAULT_NORMALIZATION_TOLERANCE: f32 = 0.001;
+
+/// Поведение, когда итоговый трек длиннее максимальной длительности
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DurationLimitBehavior {
+    /// Обрезать трек до максимальной длительности с предупреждением
+    #[default]
+    Truncate,
+    /// Завершить синхронизацию ошибкой
+    Error,
+}
+
+/// Поведение для субтитров, выходящих за пределы `[0, video_duration]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CueRangeBehavior {
+    /// Обрезать время субтитра по границам видео с предупреждением;
+    /// субтитры целиком за пределами видео пропускаются
+    #[default]
+    Clamp,
+    /// Завершить синхронизацию ошибкой
+    Error,
+}
+
+/// Положение речи в субтитре, когда она короче субтитра
+/// (см. [`Subtitle::speech_duration`]); остаток заполняется тишиной
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpeechAnchor {
+    /// Речь с начала субтитра, тишина в конце
+    #[default]
+    Start,
+    /// Речь посередине, тишина поровну до и после нее
+    Center,
+    /// Речь заканчивается вместе с субтитром, тишина в начале
+    End,
+}
+
+/// Способ добавления синхронизированного аудио в видео
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MuxMode {
+    /// Заменить звуковую дорожку видео
+    #[default]
+    Replace,
+    /// Смешать с исходной звуковой дорожкой видео
+    Mix {
+        /// Громкость исходной дорожки относительно синхронизированного аудио
+        /// (0.0 - тишина, 1.0 - одинаковая громкость)
+        original_volume: f32,
+    },
+}
+
+/// Прогресс этапа `[start, end]`, разделенного на `total` шагов
+///
+/// Шаги могут отмечать несколько одновременно выполняемых частей конвейера,
+/// прогресс при этом не уменьшается.
+struct StageProgress {
+    start: f32,
+    end: f32,
+    total: usize,
+    completed: AtomicUsize,
+}
+
+impl StageProgress {
+    fn new(start: f32, end: f32, total: usize) -> Self {
+        Self {
+            start,
+            end,
+            total,
+            completed: AtomicUsize::new(0),
+        }
+    }
+    
+    /// Возвращает прогресс перед очередным шагом и отмечает шаг выполненным
+    fn step(&self) -> f32 {
+        let completed = self.completed.fetch_add(1, Ordering::SeqCst);
+        SyncCore::stage_progress(self.start, self.end, completed, self.total)
+    }
+}
+
 /// Ядро синхронизации аудио
 pub struct SyncCore {
-    /// Трекер прогресса
-    progress_tracker: ProgressTracker,
+    /// Трекер прогресса (ядро сообщает прогресс 0-100 в свой диапазон)
+    progress_tracker: ChildProgressTracker,
     /// Частота дискретизации
     sample_rate: u32,
     /// Количество каналов
@@ -35,29 +146,115 @@ pub struct SyncCore {
     normalize_volume: bool,
     /// Целевой пик громкости (от 0.0 до 1.0)
     target_peak: f32,
+    /// Учитывать ли межсэмпловые пики при нормализации
+    true_peak_normalization: bool,
     /// Сохранять ли паузы при адаптивном изменении темпа
     preserve_pauses: bool,
     /// Алгоритм изменения темпа
     tempo_algorithm: TempoAlgorithm,
+    /// Глобальное смещение всего дубляжа в секундах
+    global_offset: f64,
+    /// Длительность затухания перед завершающей тишиной в секундах
+    tail_fade: f64,
+    /// Тишина перед началом результата в секундах
+    pre_roll: f64,
+    /// Тишина после окончания результата в секундах
+    post_roll: f64,
+    /// Компромисс между ускорением синтеза (0.0) и изменением темпа (1.0)
+    intelligibility_bias: f32,
+    /// Схлопывать ли переносы строк и пробелы в тексте перед синтезом
+    collapse_whitespace: bool,
+    /// Нормализовать ли текст для синтеза (удаление тегов и описаний звуков)
+    normalize_spoken_text: bool,
+    /// Ожидаемая частота дискретизации аудио от TTS провайдера
+    tts_sample_rate: u32,
+    /// Поведение при несовпадении частоты аудио от провайдера с ожидаемой
+    sample_rate_mismatch: SampleRateMismatch,
+    /// Формат сырого PCM от провайдера (None - аудио в контейнере, например MP3)
+    raw_pcm: Option<RawPcmSpec>,
+    /// Максимальная длительность итогового трека в секундах
+    max_output_duration: Option<f64>,
+    /// Поведение при превышении максимальной длительности
+    duration_limit_behavior: DurationLimitBehavior,
+    /// Промежуток между репликами в секундах, ниже которого пауза убирается
+    micro_gap_threshold: f64,
+    /// Пауза после реплики в секундах, если промежуток до следующей неизвестен
+    pause_between_segments: f64,
+    /// Минимальная длительность речи сегмента в секундах
+    min_segment_duration: f64,
+    /// Допустимое отклонение длительности результата от видео в секундах
+    duration_tolerance: f64,
+    /// Способ объединения перекрывающихся сегментов при сохранении
+    merge_policy: MergePolicy,
+    /// Отклонение пика от целевого, при котором нормализация пропускается
+    normalization_tolerance: f32,
+    /// Переносить ли позицию субтитров в панораму сегментов
+    position_panning: bool,
+    /// Запрашивать ли у провайдера тайминги слов
+    request_timestamps: bool,
+    /// Корректировать ли сегменты одновременно с синтезом следующих
+    pipelined: bool,
+    /// Поведение для субтитров за пределами длительности видео
+    cue_range_behavior: CueRangeBehavior,
+    /// Положение речи, короче субтитра, внутри него
+    speech_anchor: SpeechAnchor,
+    /// Максимальная длительность промежутка между репликами в секундах
+    max_gap_duration: Option<f64>,
+    /// Паузы на знаках препинания (None - паузы определяет провайдер)
+    punctuation_pauses: Option<PunctuationPauses>,
+    /// Тип дизеринга при экспорте в 16-битный PCM
+    dither: DitherType,
+    /// Метаданные выходного файла (пустые - без тегов)
+    output_metadata: OutputMetadata,
+    /// Количество потоков для параллельного декодирования сегментов
+    /// (0 - декодирование в асинхронной задаче без пула потоков)
+    decode_concurrency: usize,
+    /// Максимальное количество одновременных запросов к TTS провайдеру
+    max_concurrent_requests: usize,
+    /// Максимальное количество декодированных сэмплов одного сегмента
+    /// (None - без ограничения)
+    max_decoded_samples: Option<usize>,
+    /// Токен отмены синхронизации
+    cancellation_token: Option<CancellationToken>,
+    /// Завершать ли сохранение ошибкой, если для формата нужен ffmpeg, а он недоступен
+    require_ffmpeg: bool,
+    /// Битрейт MP3 в кбит/с
+    mp3_bitrate: u32,
+    /// Продолжать ли синхронизацию, заменяя тишиной сегменты с ошибкой синтеза
+    continue_on_error: bool,
+    /// Диапазоны времени `(начало, конец)` в секундах, где при смешивании с
+    /// оригиналом звучит только оригинал
+    keep_original_ranges: Vec<(f64, f64)>,
+    /// Исполнитель внешних команд (ffmpeg)
+    command_runner: Arc<dyn CommandRunner>,
+    /// Результат проверки наличия ffmpeg (None - еще не проверялось)
+    ffmpeg_probe: Mutex<Option<bool>>,
+    /// Отчет о последней синхронизации
+    report: Arc<Mutex<SyncReport>>,
+    /// Ошибки синтеза сегментов последней синхронизации, замененных тишиной
+    segment_errors: Mutex<Vec<(usize, Error)>>,
 }
 
 impl SyncCore {
     /// Создает новое ядро синхронизации аудио
+    ///
+    /// Использует целевой пик 0.9, сохранение пауз и алгоритм темпа Sinc,
+    /// см. [`SyncCore::new_with_options`].
     pub fn new(
         progress_tracker: ProgressTracker,
         sample_rate: u32,
         channels: u16,
         normalize_volume: bool,
     ) -> Self {
-        Self {
+        Self::new_with_options(
             progress_tracker,
             sample_rate,
             channels,
             normalize_volume,
-            target_peak: 0.9,
-            preserve_pauses: true,
-            tempo_algorithm: TempoAlgorithm::Sinc,
-        }
+            0.9,
+            true,
+            TempoAlgorithm::Sinc,
+        )
     }
     
     /// Создает новое ядро синхронизации аудио с расширенными параметрами
@@ -71,255 +268,1582 @@ impl SyncCore {
         tempo_algorithm: TempoAlgorithm,
     ) -> Self {
         Self {
-            progress_tracker,
+            progress_tracker: progress_tracker.into(),
             sample_rate,
             channels,
             normalize_volume,
             target_peak,
+            true_peak_normalization: false,
             preserve_pauses,
             tempo_algorithm,
+            global_offset: 0.0,
+            tail_fade: 0.01,
+            pre_roll: 0.0,
+            post_roll: 0.0,
+            intelligibility_bias: 1.0,
+            collapse_whitespace: true,
+            normalize_spoken_text: false,
+            tts_sample_rate: 24000,
+            sample_rate_mismatch: SampleRateMismatch::Resample,
+            raw_pcm: None,
+            max_output_duration: None,
+            duration_limit_behavior: DurationLimitBehavior::Truncate,
+            micro_gap_threshold: 0.08,
+            pause_between_segments: DEFAULT_SEGMENT_PAUSE,
+            min_segment_duration: DEFAULT_MIN_SEGMENT_DURATION,
+            duration_tolerance: DEFAULT_DURATION_TOLERANCE,
+            merge_policy: MergePolicy::default(),
+            normalization_tolerance: DEFAULT_NORMALIZATION_TOLERANCE,
+            position_panning: false,
+            request_timestamps: false,
+            pipelined: false,
+            cue_range_behavior: CueRangeBehavior::default(),
+            speech_anchor: SpeechAnchor::default(),
+            max_gap_duration: None,
+            punctuation_pauses: None,
+            dither: DitherType::None,
+            output_metadata: OutputMetadata::new(),
+            decode_concurrency: DEFAULT_DECODE_CONCURRENCY,
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            max_decoded_samples: Some(DEFAULT_MAX_DECODED_SAMPLES),
+            cancellation_token: None,
+            require_ffmpeg: false,
+            mp3_bitrate: DEFAULT_MP3_BITRATE,
+            continue_on_error: false,
+            keep_original_ranges: Vec::new(),
+            command_runner: Arc::new(SystemCommandRunner),
+            ffmpeg_probe: Mutex::new(None),
+            report: Arc::new(Mutex::new(SyncReport::new())),
+            segment_errors: Mutex::new(Vec::new()),
+        }
+    }
+    
+    /// Ограничивает прогресс ядра диапазоном `[start, end]` трекера
+    ///
+    /// Позволяет встроить синхронизацию в более длинный процесс, сохраняя
+    /// монотонность общего прогресса.
+    pub fn with_progress_range(mut self, start: f32, end: f32) -> Self {
+        self.progress_tracker = self.progress_tracker.create_child(start, end);
+        self
+    }
+    
+    /// Включает нормализацию по истинному пику с учетом межсэмпловых пиков
+    pub fn with_true_peak_normalization(mut self, enabled: bool) -> Self {
+        self.true_peak_normalization = enabled;
+        self
+    }
+    
+    /// Устанавливает глобальное смещение всего дубляжа в миллисекундах
+    ///
+    /// Положительное значение сдвигает дубляж позже (начало заполняется тишиной),
+    /// отрицательное - раньше (выходящая за ноль часть обрезается).
+    pub fn with_global_offset_ms(mut self, offset_ms: i64) -> Self {
+        self.global_offset = offset_ms as f64 / 1000.0;
+        self
+    }
+    
+    /// Устанавливает длительность затухания перед завершающей тишиной в миллисекундах
+    pub fn with_tail_fade_ms(mut self, fade_ms: u64) -> Self {
+        self.tail_fade = fade_ms as f64 / 1000.0;
+        self
+    }
+    
+    /// Устанавливает тишину перед началом результата в миллисекундах
+    ///
+    /// Все содержимое сдвигается на эту величину, длительность результата
+    /// увеличивается относительно видео.
+    pub fn with_pre_roll_ms(mut self, pre_roll_ms: u64) -> Self {
+        self.pre_roll = pre_roll_ms as f64 / 1000.0;
+        self
+    }
+    
+    /// Устанавливает тишину после окончания результата в миллисекундах
+    ///
+    /// Добавляется после дополнения результата до длительности видео.
+    pub fn with_post_roll_ms(mut self, post_roll_ms: u64) -> Self {
+        self.post_roll = post_roll_ms as f64 / 1000.0;
+        self
+    }
+    
+    /// Устанавливает компромисс между ускорением синтеза и изменением темпа
+    ///
+    /// 0.0 - необходимое сжатие достигается ускорением синтеза речи, 1.0 - речь
+    /// всегда синтезируется с естественной скоростью и сжимается изменением темпа.
+    /// Значения вне диапазона 0.0 - 1.0 ограничиваются.
+    ///
+    /// При значении меньше 1.0 реплика, которая не укладывается в субтитр,
+    /// синтезируется повторно с большей скоростью: на такие реплики приходится
+    /// два запроса к провайдеру. Уложившаяся реплика используется как есть.
+    pub fn with_intelligibility_bias(mut self, bias: f32) -> Self {
+        self.intelligibility_bias = bias.clamp(0.0, 1.0);
+        self
+    }
+    
+    /// Включает замену переносов строк и повторяющихся пробелов в тексте
+    /// субтитров одиночными пробелами перед отправкой провайдеру
+    ///
+    /// Исходный текст субтитров при этом не изменяется.
+    pub fn with_whitespace_normalization(mut self, enabled: bool) -> Self {
+        self.collapse_whitespace = enabled;
+        self
+    }
+    
+    /// Включает нормализацию текста субтитров для синтеза речи
+    ///
+    /// Провайдер получает `spoken_text` без тегов и описаний звуков, а отчеты
+    /// по-прежнему содержат исходный текст субтитров.
+    pub fn with_spoken_text_normalization(mut self, enabled: bool) -> Self {
+        self.normalize_spoken_text = enabled;
+        self
+    }
+    
+    /// Устанавливает ожидаемую частоту дискретизации аудио от TTS провайдера
+    ///
+    /// По умолчанию 24000 Гц - частота аудио OpenAI TTS.
+    pub fn with_tts_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.tts_sample_rate = sample_rate;
+        self
+    }
+    
+    /// Устанавливает поведение при несовпадении частоты аудио от провайдера с ожидаемой
+    pub fn with_sample_rate_mismatch(mut self, behavior: SampleRateMismatch) -> Self {
+        self.sample_rate_mismatch = behavior;
+        self
+    }
+    
+    /// Указывает, что провайдер возвращает сырой PCM без заголовка в формате `spec`
+    ///
+    /// Частота из `spec` считается фактической частотой аудио провайдера.
+    pub fn with_raw_pcm(mut self, spec: RawPcmSpec) -> Self {
+        self.raw_pcm = Some(spec);
+        self
+    }
+    
+    /// Ограничивает длительность итогового трека
+    ///
+    /// Защищает от слишком длинного результата, например при ошибочно указанной
+    /// длительности видео. Предел включает тишину pre-roll и post-roll. Если
+    /// длительность видео или субтитры выходят за предел, трек обрезается
+    /// (реплики за пределом не синтезируются) или синхронизация завершается
+    /// ошибкой в зависимости от `behavior`.
+    pub fn with_max_output_duration(mut self, max_duration: f64, behavior: DurationLimitBehavior) -> Self {
+        self.max_output_duration = Some(max_duration);
+        self.duration_limit_behavior = behavior;
+        self
+    }
+    
+    /// Устанавливает порог микро-пауз между репликами в миллисекундах
+    ///
+    /// Если между концом речи одной реплики и началом следующей (с другим
+    /// текстом) меньше порога, следующая реплика сдвигается вплотную к
+    /// предыдущей и пауза между ними не добавляется. 0 отключает объединение.
+    pub fn with_micro_gap_threshold_ms(mut self, threshold_ms: u64) -> Self {
+        self.micro_gap_threshold = threshold_ms as f64 / 1000.0;
+        self
+    }
+    
+    /// Устанавливает паузу после реплики в секундах для случаев, когда
+    /// промежуток в исходных субтитрах неизвестен (после последней реплики)
+    ///
+    /// Промежутки между репликами воспроизводятся по исходным субтитрам,
+    /// 0 отключает добавление искусственной паузы.
+    pub fn with_segment_pause(mut self, pause: f64) -> Self {
+        self.pause_between_segments = if pause.is_finite() { pause.max(0.0) } else { 0.0 };
+        self
+    }
+    
+    /// Аналог [`SyncCore::with_segment_pause`] с паузой в миллисекундах
+    pub fn with_default_pause_ms(self, pause_ms: u64) -> Self {
+        self.with_segment_pause(pause_ms as f64 / 1000.0)
+    }
+    
+    /// Устанавливает поведение для субтитров с отрицательным временем или
+    /// временем после конца видео
+    pub fn with_cue_range_behavior(mut self, behavior: CueRangeBehavior) -> Self {
+        self.cue_range_behavior = behavior;
+        self
+    }
+    
+    /// Устанавливает положение речи внутри субтитра, если она короче него
+    ///
+    /// Действует на реплики с `target_speech_duration`: тишина добавляется
+    /// после речи ([`SpeechAnchor::Start`]), поровну с обеих сторон или перед ней.
+    pub fn with_speech_anchor(mut self, anchor: SpeechAnchor) -> Self {
+        self.speech_anchor = anchor;
+        self
+    }
+    
+    /// Ограничивает длительность промежутков между репликами ("сжатое"
+    /// повествование без привязки к таймингам видео)
+    ///
+    /// Промежутки длиннее `max_gap` сокращаются до `max_gap`, последующие
+    /// реплики сдвигаются раньше. Сокращения записываются в
+    /// [`SyncReport::gap_compressions`] для обратного сопоставления времени.
+    pub fn with_max_gap_duration(mut self, max_gap: Option<f64>) -> Self {
+        self.max_gap_duration = max_gap;
+        self
+    }
+    
+    /// Включает явные паузы на знаках препинания
+    ///
+    /// Реплики с несколькими фразами синтезируются по фразам, между которыми
+    /// вставляется тишина из `pauses`, что дает предсказуемый темп речи.
+    /// Компромисс скорости синтеза (`intelligibility_bias`) к таким репликам
+    /// не применяется.
+    pub fn with_punctuation_pauses(mut self, pauses: Option<PunctuationPauses>) -> Self {
+        self.punctuation_pauses = pauses;
+        self
+    }
+    
+    /// Устанавливает минимальную длительность речи сегмента в секундах
+    ///
+    /// Речь очень коротких реплик (например, 20 мс) подгоняется не короче
+    /// этой длительности и может заходить за конец субтитра: изменение темпа
+    /// до нескольких сотен сэмплов дает искаженный звук. По умолчанию 0 -
+    /// ограничение отключено.
+    pub fn with_min_segment_duration(mut self, duration: f64) -> Self {
+        self.min_segment_duration = duration.max(0.0);
+        self
+    }
+    
+    /// Устанавливает допустимое отклонение длительности результата от видео в секундах
+    ///
+    /// После синхронизации фактическая длительность трека сравнивается
+    /// с длительностью видео; разница записывается в отчет, а при
+    /// превышении допуска добавляется предупреждение.
+    pub fn with_duration_tolerance(mut self, tolerance: f64) -> Self {
+        self.duration_tolerance = tolerance.max(0.0);
+        self
+    }
+    
+    /// Устанавливает отклонение пика от целевого, при котором нормализация пропускается
+    ///
+    /// Сегменты, пик которых уже отличается от целевого не больше чем на
+    /// `tolerance`, остаются без изменений (в том числе сохраняют исходные
+    /// данные провайдера). 0 - нормализуются все сегменты.
+    pub fn with_normalization_tolerance(mut self, tolerance: f32) -> Self {
+        self.normalization_tolerance = tolerance.max(0.0);
+        self
+    }
+    
+    /// Включает перенос позиции субтитров (`position:20%`) в панораму сегментов
+    ///
+    /// Панорама сохраняется в [`AudioSegment::pan`] и применяется при
+    /// преобразовании результата в стерео (см. [`AudioTrack::pan_regions`]).
+    pub fn with_position_panning(mut self, enabled: bool) -> Self {
+        self.position_panning = enabled;
+        self
+    }
+    
+    /// Включает запрос таймингов слов у провайдера
+    ///
+    /// Тайминги переносятся на шкалу результата (с учетом изменения темпа,
+    /// сокращения промежутков, удаления микро-пауз и глобального смещения) и
+    /// сохраняются в [`SegmentReport::word_timings`]. Их возвращают только провайдеры с
+    /// данными выравнивания; при синтезе по фразам и повторном синтезе с
+    /// другой скоростью тайминги не запрашиваются.
+    pub fn with_request_timestamps(mut self, enabled: bool) -> Self {
+        self.request_timestamps = enabled;
+        self
+    }
+    
+    /// Включает конвейерную обработку: декодирование и корректировка
+    /// длительности сегмента начинаются сразу после его синтеза, пока
+    /// синтезируются следующие сегменты
+    ///
+    /// Результат совпадает с последовательной обработкой, но общее время
+    /// сокращается, когда синтез ограничен ожиданием ответа провайдера.
+    pub fn with_pipelining(mut self, enabled: bool) -> Self {
+        self.pipelined = enabled;
+        self
+    }
+    
+    /// Устанавливает способ объединения перекрывающихся сегментов при сохранении
+    pub fn with_merge_policy(mut self, policy: MergePolicy) -> Self {
+        self.merge_policy = policy;
+        self
+    }
+    
+    /// Устанавливает тип дизеринга при экспорте в 16-битный PCM
+    pub fn with_dither(mut self, dither: DitherType) -> Self {
+        self.dither = dither;
+        self
+    }
+    
+    /// Устанавливает метаданные (название, комментарий) выходного файла
+    ///
+    /// Теги записываются в MP3, OGG, Opus и FLAC; WAV сохраняется без тегов.
+    pub fn with_output_metadata(mut self, metadata: OutputMetadata) -> Self {
+        self.output_metadata = metadata;
+        self
+    }
+    
+    /// Устанавливает количество потоков для декодирования сегментов
+    ///
+    /// Декодирование выполняется в пуле блокирующих потоков tokio и не
+    /// занимает асинхронный исполнитель. 0 - декодирование последовательно
+    /// прямо в асинхронной задаче.
+    pub fn with_decode_concurrency(mut self, concurrency: usize) -> Self {
+        self.decode_concurrency = concurrency;
+        self
+    }
+    
+    /// Возвращает количество потоков для декодирования сегментов
+    pub fn decode_concurrency(&self) -> usize {
+        self.decode_concurrency
+    }
+    
+    /// Устанавливает максимальное количество одновременных запросов к TTS провайдеру
+    ///
+    /// Порядок сегментов не зависит от порядка завершения запросов.
+    /// 0 и 1 - реплики синтезируются последовательно.
+    pub fn with_max_concurrent_requests(mut self, max_requests: usize) -> Self {
+        self.max_concurrent_requests = max_requests;
+        self
+    }
+    
+    /// Возвращает максимальное количество одновременных запросов к TTS провайдеру
+    pub fn max_concurrent_requests(&self) -> usize {
+        self.max_concurrent_requests
+    }
+    
+    /// Устанавливает максимальное количество декодированных сэмплов одного сегмента
+    ///
+    /// Декодирование аудио провайдера прерывается с ошибкой при превышении
+    /// предела, что защищает от неограниченного расхода памяти на поврежденных
+    /// данных. None - без ограничения.
+    pub fn with_max_decoded_samples(mut self, max_samples: Option<usize>) -> Self {
+        self.max_decoded_samples = max_samples;
+        self
+    }
+    
+    /// Устанавливает токен отмены синхронизации
+    ///
+    /// Отмена проверяется между сегментами при генерации TTS и корректировке
+    /// длительности, а также прерывает ожидание ответа провайдера. Синхронизация
+    /// завершается ошибкой [`Error::Cancelled`], временные файлы удаляются.
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+    
+    /// Возвращает ошибку [`Error::Cancelled`], если синхронизация отменена
+    fn check_cancelled(&self) -> Result<()> {
+        match &self.cancellation_token {
+            Some(token) if token.is_cancelled() => {
+                log_info("Синхронизация отменена");
+                Err(Error::new(ErrorType::Cancelled, "синхронизация прервана по запросу"))
+            }
+            _ => Ok(()),
+        }
+    }
+    
+    /// Требует наличия ffmpeg для форматов, которые нельзя закодировать без него
+    ///
+    /// Если ffmpeg недоступен, сохранение в MP3, OGG или Opus без встроенного
+    /// кодировщика (функции `mp3-encoder`, `vorbis` и `opus`) завершается
+    /// ошибкой вместо записи WAV файла рядом с запрошенным путем. FLAC и
+    /// форматы со встроенным кодировщиком от этого параметра не зависят.
+    pub fn with_require_ffmpeg(mut self, required: bool) -> Self {
+        self.require_ffmpeg = required;
+        self
+    }
+    
+    /// Устанавливает битрейт MP3 в кбит/с
+    ///
+    /// Используется и ffmpeg, и встроенным кодировщиком LAME. Допустимы
+    /// значения 8, 16, 24, 32, 40, 48, 64, 80, 96, 112, 128, 160, 192, 224,
+    /// 256 и 320; другие значения приводят к ошибке при сохранении в MP3.
+    /// Если битрейт слишком велик для частоты дискретизации, кодировщик
+    /// использует наибольший допустимый.
+    pub fn with_mp3_bitrate(mut self, bitrate: u32) -> Self {
+        self.mp3_bitrate = bitrate;
+        self
+    }
+    
+    /// Устанавливает, продолжать ли синхронизацию при ошибке синтеза сегмента
+    ///
+    /// Если включено, ошибка провайдера для реплики не прерывает синхронизацию:
+    /// вместо речи вставляется тишина длительности реплики, в отчет добавляется
+    /// предупреждение `SegmentFailed`, а сама ошибка доступна через
+    /// [`SyncCore::take_segment_errors`]. Реплики с тем же текстом также
+    /// получают тишину и собственную запись об ошибке. Отмена синхронизации
+    /// по-прежнему прерывает ее.
+    pub fn with_continue_on_error(mut self, continue_on_error: bool) -> Self {
+        self.continue_on_error = continue_on_error;
+        self
+    }
+    
+    /// Устанавливает диапазоны времени `(начало, конец)` в секундах, где
+    /// оригинальная звуковая дорожка звучит с полной громкостью без дубляжа
+    ///
+    /// Учитываются при смешивании с оригиналом: в [`SyncCore::mux_into_video`]
+    /// в режиме [`MuxMode::Mix`] и в [`SyncCore::mix_with_original`]. Например,
+    /// так оставляют без перевода песни.
+    pub fn with_keep_original_ranges(mut self, ranges: Vec<(f64, f64)>) -> Self {
+        self.keep_original_ranges = ranges;
+        self
+    }
+    
+    /// Устанавливает исполнитель внешних команд, используемый для запуска ffmpeg
+    pub fn with_command_runner<R: CommandRunner + 'static>(mut self, runner: R) -> Self {
+        self.command_runner = Arc::new(runner);
+        self.ffmpeg_probe = Mutex::new(None);
+        self
+    }
+    
+    /// Проверяет, доступен ли ffmpeg
+    ///
+    /// `ffmpeg -version` запускается один раз, результат запоминается до
+    /// смены исполнителя команд.
+    pub fn ffmpeg_available(&self) -> bool {
+        let mut probe = self.ffmpeg_probe.lock().unwrap();
+        *probe.get_or_insert_with(|| {
+            let available = matches!(self.command_runner.run("ffmpeg", &["-version"]), Ok(output) if output.status.success());
+            log_debug(&format!("Проверка ffmpeg: {}", if available { "найден" } else { "не найден" }));
+            available
+        })
+    }
+    
+    /// Добавляет синхронизированное аудио в видео с помощью ffmpeg
+    ///
+    /// Видеопоток копируется без перекодирования, аудио кодируется кодеком по
+    /// умолчанию для контейнера `output_path`. В режиме [`MuxMode::Replace`]
+    /// исходная звуковая дорожка отбрасывается, в режиме [`MuxMode::Mix`]
+    /// смешивается с новой, а длительность результата определяется видео.
+    pub async fn mux_into_video(&self, audio_path: &str, video_path: &str, output_path: &str, mode: MuxMode) -> Result<()> {
+        log_info(&format!("Добавление аудио {} в видео {}: {}", audio_path, video_path, output_path));
+        
+        let mut args = vec![
+            "-y".to_string(),
+            "-i".to_string(), video_path.to_string(),
+            "-i".to_string(), audio_path.to_string(),
+        ];
+        match mode {
+            MuxMode::Replace => {
+                args.extend(["-map", "0:v", "-map", "1:a:0"].iter().map(|arg| arg.to_string()));
+            }
+            MuxMode::Mix { original_volume } => {
+                if !original_volume.is_finite() || original_volume < 0.0 {
+                    return Err(Error::new(
+                        ErrorType::InvalidParameters,
+                        &format!("Громкость исходной дорожки должна быть неотрицательной, получено {}", original_volume),
+                    ));
+                }
+                validate_keep_original_ranges(&self.keep_original_ranges)?;
+                let filter = if self.keep_original_ranges.is_empty() {
+                    format!("[0:a:0][1:a:0]amix=inputs=2:duration=first:weights='{} 1':normalize=0[mixed]", original_volume)
+                } else {
+                    // В диапазонах оригинала исходная дорожка звучит полностью, а дубляж заглушается
+                    let keep = self.keep_original_ranges.iter()
+                        .map(|(start, end)| format!("between(t,{},{})", start, end))
+                        .collect::<Vec<_>>()
+                        .join("+");
+                    format!(
+                        "[0:a:0]volume='if(gt({keep},0),1,{volume})':eval=frame[original];\
+                        [1:a:0]volume='if(gt({keep},0),0,1)':eval=frame[dub];\
+                        [original][dub]amix=inputs=2:duration=first:normalize=0[mixed]",
+                        keep = keep, volume = original_volume,
+                    )
+                };
+                args.extend([
+                    "-filter_complex".to_string(),
+                    filter,
+                    "-map".to_string(), "0:v".to_string(),
+                    "-map".to_string(), "[mixed]".to_string(),
+                ]);
+            }
+        }
+        args.extend(["-c:v".to_string(), "copy".to_string(), output_path.to_string()]);
+        
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.run_ffmpeg(&args, None).await
+            .map_err(|e| Error::new(ErrorType::AudioProcessingError, &format!("Не удалось добавить аудио в видео: {}", e)))?;
+        
+        log_info(&format!("Видео с синхронизированным аудио сохранено: {}", output_path));
+        Ok(())
+    }
+    
+    /// Смешивает дубляж с оригинальной звуковой дорожкой в памяти
+    ///
+    /// Аналог [`AudioProcessor::mix_with_original`] с диапазонами оригинала,
+    /// заданными через [`SyncCore::with_keep_original_ranges`].
+    pub fn mix_with_original(&self, dub: &AudioData, original: &AudioData, original_gain_db: f32) -> Result<AudioData> {
+        validate_keep_original_ranges(&self.keep_original_ranges)?;
+        AudioProcessor::mix_with_original(dub, original, original_gain_db, &self.keep_original_ranges)
+    }
+    
+    /// Возвращает отчет о последней синхронизации
+    pub fn report(&self) -> SyncReport {
+        self.report.lock().unwrap().clone()
+    }
+    
+    /// Забирает ошибки синтеза сегментов последней синхронизации
+    ///
+    /// Возвращает пары `(номер субтитра, ошибка)` в порядке субтитров для
+    /// сегментов, замененных тишиной (см. [`SyncCore::with_continue_on_error`]).
+    pub fn take_segment_errors(&self) -> Vec<(usize, Error)> {
+        let mut errors = std::mem::take(&mut *self.segment_errors.lock().unwrap());
+        // Запросы завершаются в произвольном порядке
+        errors.sort_by_key(|(index, _)| *index);
+        errors
+    }
+    
+    /// Выводит предупреждение в лог и добавляет его в отчет
+    fn warn(&self, kind: DiagnosticKind, segment: Option<usize>, message: String) {
+        log_warning(&message);
+        self.report.lock().unwrap().diagnostics.push(Diagnostic { kind, segment, message });
+    }
+    
+    /// Синхронизирует TTS с субтитрами
+    pub async fn synchronize<P: TtsProvider + Send + Sync + ?Sized>(
+        &self,
+        vtt_path: &str,
+        video_duration: f64,
+        tts_provider: &P,
+    ) -> Result<AudioTrack> {
+        // Шаг 1: Парсинг VTT файла
+        self.progress_tracker.update(0.0, "Парсинг субтитров")?;
+        let subtitles = VttParser::parse_file_with(vtt_path, EmptyVttBehavior::Error)?;
+        
+        self.synchronize_track(&subtitles, video_duration, tts_provider).await
+    }
+    
+    /// Синхронизирует TTS с субтитрами, уже загруженными в память
+    ///
+    /// Аналог [`SyncCore::synchronize`] без чтения VTT файла: субтитры
+    /// обрабатываются так же, включая сортировку по времени начала и
+    /// нормализацию произносимого текста. Пустой трек дает ошибку синхронизации.
+    pub async fn synchronize_track<P: TtsProvider + Send + Sync + ?Sized>(
+        &self,
+        subtitles: &SubtitleTrack,
+        video_duration: f64,
+        tts_provider: &P,
+    ) -> Result<AudioTrack> {
+        let mut subtitles = subtitles.clone();
+        subtitles.sort();
+        if self.normalize_spoken_text {
+            subtitles.normalize_spoken_text();
+        }
+        
+        self.synchronize_subtitles(&subtitles, video_duration, tts_provider).await
+    }
+    
+    /// Синхронизирует TTS с субтитрами, передавая реплики в `sink` по мере готовности
+    ///
+    /// Каждая реплика передается сразу после синтеза и корректировки длительности,
+    /// не дожидаясь остальных, поэтому дубляж можно слушать во время синхронизации.
+    /// Синтез и корректировка при этом всегда идут одновременно (как при
+    /// [`SyncCore::with_pipelining`]). Сегменты передаются по порядку
+    /// воспроизведения начиная с нуля с учетом смещения и тишины pre-roll,
+    /// промежутки между ними - сегментами тишины; по завершении передается
+    /// тишина до конца трека и вызывается [`AudioSink::finish`].
+    ///
+    /// Переданный звук - предпрослушивание: обработка, требующая всего трека
+    /// (сдвиг реплик к предыдущей при микро-паузах, затухание в конце и
+    /// обрезка по длительности), применяется только к возвращаемому треку.
+    pub async fn synchronize_streaming<P, S>(
+        &self,
+        vtt_path: &str,
+        video_duration: f64,
+        tts_provider: &P,
+        sink: &mut S,
+    ) -> Result<AudioTrack>
+    where
+        P: TtsProvider + Send + Sync + ?Sized,
+        S: AudioSink + Send,
+    {
+        self.progress_tracker.update(0.0, "Парсинг субтитров")?;
+        let mut subtitles = VttParser::parse_file_with(vtt_path, EmptyVttBehavior::Error)?;
+        if self.normalize_spoken_text {
+            subtitles.normalize_spoken_text();
+        }
+        
+        let mut writer = SinkWriter::new(sink, self.sample_rate, self.channels);
+        let shift = self.global_offset + self.pre_roll;
+        let mut on_adjusted = |segment: &AudioSegment| {
+            let segment = if self.normalize_volume { self.normalize_segment(segment) } else { segment.clone() };
+            writer.write_at(&segment, segment.start_time + shift)
+        };
+        let audio_track = self.synchronize_subtitles_with(
+            &subtitles, video_duration, tts_provider, Some(&mut on_adjusted)
+        ).await?;
+        
+        writer.finish_at(audio_track.merged_duration())?;
+        Ok(audio_track)
+    }
+    
+    /// Синхронизирует TTS с уже разобранными субтитрами
+    ///
+    /// Позволяет изменить субтитры перед синтезом, например задать
+    /// `target_speech_duration` отдельным репликам.
+    pub async fn synchronize_subtitles<P: TtsProvider + Send + Sync + ?Sized>(
+        &self,
+        subtitles: &SubtitleTrack,
+        video_duration: f64,
+        tts_provider: &P,
+    ) -> Result<AudioTrack> {
+        self.synchronize_subtitles_with(subtitles, video_duration, tts_provider, None).await
+    }
+    
+    /// Синхронизирует TTS с субтитрами, передавая каждую скорректированную
+    /// реплику в `on_adjusted` (если задан) сразу после ее корректировки
+    async fn synchronize_subtitles_with<P: TtsProvider + Send + Sync + ?Sized>(
+        &self,
+        subtitles: &SubtitleTrack,
+        video_duration: f64,
+        tts_provider: &P,
+        on_adjusted: Option<&mut AdjustedCallback<'_>>,
+    ) -> Result<AudioTrack> {
+        *self.report.lock().unwrap() = SyncReport::new();
+        self.segment_errors.lock().unwrap().clear();
+        
+        if subtitles.is_empty() {
+            return Err(Error::new(
+                ErrorType::Synchronization,
+                "Субтитры не найдены"
+            ));
+        }
+        let subtitles = &self.check_cue_range(subtitles, video_duration)?;
+        let (subtitles, video_duration) = &self.compress_gaps(subtitles, video_duration);
+        let (subtitles, video_duration) = &self.limit_output_duration(subtitles, *video_duration)?;
+        let video_duration = *video_duration;
+        
+        // Шаги 2 и 3: Генерация TTS для каждого субтитра, анализ и корректировка длительности
+        let audio_segments = if self.pipelined || on_adjusted.is_some() {
+            self.progress_tracker.update(10.0, "Генерация TTS и корректировка длительности")?;
+            self.generate_and_adjust_pipelined(subtitles, tts_provider, on_adjusted).await?
+        } else {
+            self.progress_tracker.update(10.0, "Генерация TTS")?;
+            let progress = StageProgress::new(10.0, 50.0, subtitles.len());
+            let mut tts_segments = self.generate_tts_segments(subtitles, tts_provider, &progress, |_, _| Ok(())).await?;
+            
+            self.progress_tracker.update(50.0, "Анализ и корректировка длительности")?;
+            self.analyze_and_adjust_segments(&mut tts_segments, subtitles).await?
+        };
+        
+        // Шаг 4: Синхронизация аудио с субтитрами
+        self.progress_tracker.update(70.0, "Синхронизация аудио с субтитрами")?;
+        let mut audio_track = self.synchronize_with_subtitles(&audio_segments, subtitles, video_duration)?;
+        
+        // Шаг 5: Добавление пауз между сегментами
+        self.progress_tracker.update(80.0, "Добавление пауз между сегментами")?;
+        audio_track = self.add_pauses_between_segments(&audio_track, subtitles)?;
+        
+        // Шаг 6: Нормализация громкости
+        if self.normalize_volume {
+            self.progress_tracker.update(90.0, "Нормализация громкости")?;
+            audio_track = self.normalize_track(&audio_track)?;
+        }
+        
+        // Шаг 7: Проверка общей длительности
+        self.progress_tracker.update(95.0, "Проверка общей длительности")?;
+        audio_track = self.ensure_duration(&audio_track, video_duration)?;
+        audio_track = self.apply_roll(&audio_track);
+        self.verify_duration(&audio_track, video_duration + self.pre_roll + self.post_roll);
+        self.report_track_layout(&audio_track);
+        
+        self.progress_tracker.update(100.0, "Синхронизация завершена")?;
+        
+        Ok(audio_track)
+    }
+    
+    /// Строит трек из тишины по таймингам субтитров без обращения к TTS провайдеру
+    ///
+    /// Каждому субтитру соответствует сегмент тишины его длительности, начало
+    /// трека и промежуток после последнего субтитра также заполняются тишиной.
+    /// Позволяет дешево проверить тайминги и весь конвейер вывода.
+    pub fn synchronize_silent(&self, vtt_path: &str, video_duration: f64) -> Result<AudioTrack> {
+        *self.report.lock().unwrap() = SyncReport::new();
+        
+        self.progress_tracker.update(0.0, "Парсинг субтитров")?;
+        let subtitles = VttParser::parse_file_with(vtt_path, EmptyVttBehavior::Error)?;
+        
+        if subtitles.is_empty() {
+            return Err(Error::new(
+                ErrorType::Synchronization,
+                "Субтитры не найдены"
+            ));
+        }
+        let subtitles = self.check_cue_range(&subtitles, video_duration)?;
+        let (subtitles, video_duration) = self.compress_gaps(&subtitles, video_duration);
+        let (subtitles, video_duration) = self.limit_output_duration(&subtitles, video_duration)?;
+        
+        self.progress_tracker.update(50.0, "Построение трека из тишины")?;
+        let mut audio_track = AudioTrack::new(self.sample_rate, self.channels);
+        let silence = |duration: f64| AudioData::new(
+            vec![0.0f32; (self.sample_rate as f64 * duration.max(0.0)) as usize * self.channels as usize],
+            self.sample_rate,
+            self.channels
+        );
+        
+        // Заполняем начало трека тишиной, чтобы тайминги отсчитывались от нуля
+        let first_start = subtitles.iter().map(|s| s.start_time).fold(f64::INFINITY, f64::min);
+        if first_start > 0.0 {
+            audio_track.add_segment(AudioSegment::new(silence(first_start), 0.0, first_start, String::new()));
+        }
+        
+        for (i, subtitle) in subtitles.iter().enumerate() {
+            let audio = silence(subtitle.duration());
+            
+            self.report.lock().unwrap().segments.push(SegmentReport {
+                index: i,
+                id: subtitle.id.clone(),
+                text: subtitle.display_text.clone(),
+                start_time: subtitle.start_time,
+                end_time: subtitle.end_time,
+                tts_duration: subtitle.duration(),
+                adjusted_duration: audio.duration(),
+                stretch_factor: 1.0,
+                word_timings: None,
+            });
+            
+            audio_track.add_segment(AudioSegment::new(
+                audio,
+                subtitle.start_time,
+                subtitle.end_time,
+                subtitle.display_text.clone()
+            ).with_id(subtitle.id.clone()).with_cue(Some(i)));
+        }
+        
+        if self.global_offset != 0.0 {
+            audio_track = self.apply_global_offset(&audio_track);
+        }
+        
+        audio_track = self.ensure_duration(&audio_track, video_duration)?;
+        audio_track = self.apply_roll(&audio_track);
+        self.verify_duration(&audio_track, video_duration + self.pre_roll + self.post_roll);
+        self.report_track_layout(&audio_track);
+        
+        self.progress_tracker.update(100.0, "Синхронизация завершена")?;
+        
+        Ok(audio_track)
+    }
+    
+    /// Записывает в отчет области речи и положение субтитров в итоговом треке
+    fn report_track_layout(&self, audio_track: &AudioTrack) {
+        let mut report = self.report.lock().unwrap();
+        report.speech_regions = audio_track.speech_regions();
+        report.sample_map = audio_track.cue_sample_map();
+    }
+    
+    /// Проверяет, что время субтитров лежит в пределах `[0, video_duration]`
+    ///
+    /// Ошибочные метки (например, `99:59:59.999`) или отрицательное время
+    /// нарушают расчет позиций сэмплов, поэтому такие субтитры обрезаются
+    /// или синхронизация завершается ошибкой в зависимости от `cue_range_behavior`.
+    fn check_cue_range(&self, subtitles: &SubtitleTrack, video_duration: f64) -> Result<SubtitleTrack> {
+        let mut result = SubtitleTrack::new();
+        
+        for (i, subtitle) in subtitles.iter().enumerate() {
+            if subtitle.start_time >= 0.0 && subtitle.end_time <= video_duration {
+                result.add(subtitle.clone());
+                continue;
+            }
+            
+            let message = format!(
+                "Субтитр '{}' ({:.3}-{:.3}с) выходит за пределы видео 0-{:.3}с",
+                subtitle.text, subtitle.start_time, subtitle.end_time, video_duration
+            );
+            if self.cue_range_behavior == CueRangeBehavior::Error {
+                return Err(Error::new(ErrorType::Synchronization, &message));
+            }
+            
+            let mut clamped = subtitle.clone();
+            clamped.start_time = subtitle.start_time.clamp(0.0, video_duration.max(0.0));
+            clamped.end_time = subtitle.end_time.clamp(0.0, video_duration.max(0.0));
+            if clamped.end_time <= clamped.start_time {
+                self.warn(DiagnosticKind::CueOutOfRange, Some(i), format!("{}, субтитр пропущен", message));
+                continue;
+            }
+            
+            self.warn(DiagnosticKind::CueOutOfRange, Some(i),
+                format!("{}, время обрезано до {:.3}-{:.3}с", message, clamped.start_time, clamped.end_time));
+            result.add(clamped);
+        }
+        
+        if result.is_empty() {
+            return Err(Error::new(
+                ErrorType::Synchronization,
+                "Нет субтитров в пределах длительности видео"
+            ));
+        }
+        
+        Ok(result)
+    }
+    
+    /// Сокращает промежутки между репликами длиннее `max_gap_duration`
+    ///
+    /// Возвращает субтитры с новыми таймингами и длительность результата,
+    /// уменьшенную на суммарно удаленное время.
+    fn compress_gaps(&self, subtitles: &SubtitleTrack, video_duration: f64) -> (SubtitleTrack, f64) {
+        let max_gap = match self.max_gap_duration {
+            Some(max_gap) if max_gap >= 0.0 => max_gap,
+            _ => return (subtitles.clone(), video_duration),
+        };
+        
+        let mut result = subtitles.clone();
+        result.sort();
+        
+        let mut compressions = Vec::new();
+        let mut removed = 0.0;
+        let mut content_end: Option<f64> = None;
+        for subtitle in &mut result.subtitles {
+            if let Some(end) = content_end {
+                let gap = subtitle.start_time - end;
+                if gap > max_gap {
+                    compressions.push(GapCompression {
+                        original_start: end,
+                        original_duration: gap,
+                        compressed_duration: max_gap,
+                    });
+                    removed += gap - max_gap;
+                }
+            }
+            content_end = Some(content_end.map_or(subtitle.end_time, |end| end.max(subtitle.end_time)));
+            
+            subtitle.start_time -= removed;
+            subtitle.end_time -= removed;
+        }
+        
+        if !compressions.is_empty() {
+            log_info(&format!("Сокращено промежутков между репликами: {}, удалено {:.2}с",
+                compressions.len(), removed));
+        }
+        self.report.lock().unwrap().gap_compressions = compressions;
+        
+        (result, (video_duration - removed).max(0.0))
+    }
+    
+    /// Применяет ограничение длительности итогового трека
+    ///
+    /// Предел распространяется на весь результат вместе с тишиной pre-roll и
+    /// post-roll. Возвращает субтитры и длительность, до которой строится
+    /// трек; при обрезке реплики, начинающиеся за пределом, удаляются до
+    /// синтеза.
+    fn limit_output_duration(&self, subtitles: &SubtitleTrack, video_duration: f64) -> Result<(SubtitleTrack, f64)> {
+        let max_duration = match self.max_output_duration {
+            Some(max_duration) => max_duration,
+            None => return Ok((subtitles.clone(), video_duration)),
+        };
+        
+        let roll = self.pre_roll.max(0.0) + self.post_roll.max(0.0);
+        let content_end = subtitles.iter().map(|s| s.end_time).fold(video_duration, f64::max) + roll;
+        if content_end <= max_duration {
+            return Ok((subtitles.clone(), video_duration));
+        }
+        
+        let message = format!(
+            "Длительность результата {:.2}с превышает максимальную {:.2}с",
+            content_end, max_duration
+        );
+        match self.duration_limit_behavior {
+            DurationLimitBehavior::Truncate => {
+                let limit = video_duration.min(max_duration - roll).max(0.0);
+                let mut result = subtitles.clone();
+                result.subtitles.retain(|subtitle| subtitle.start_time < limit);
+                self.warn(DiagnosticKind::Truncation, None, format!("{}, трек будет обрезан, удалено реплик: {}",
+                    message, subtitles.len() - result.len()));
+                if result.is_empty() {
+                    return Err(Error::new(
+                        ErrorType::Synchronization,
+                        "Нет субтитров в пределах максимальной длительности результата"
+                    ));
+                }
+                Ok((result, limit))
+            }
+            DurationLimitBehavior::Error => Err(Error::new(ErrorType::Synchronization, &message)),
+        }
+    }
+    
+    /// Вычисляет прогресс этапа `[start, end]` по количеству завершенных элементов
+    ///
+    /// Не накапливает ошибку округления и не выходит за границы этапа,
+    /// в том числе при нулевом количестве элементов.
+    fn stage_progress(start: f32, end: f32, completed: usize, total: usize) -> f32 {
+        if total == 0 {
+            return end;
+        }
+        
+        let fraction = (completed as f32 / total as f32).clamp(0.0, 1.0);
+        start + (end - start) * fraction
+    }
+    
+    /// Генерирует TTS сегменты для субтитров
+    ///
+    /// Каждый готовый сегмент передается в `on_segment` вместе с его номером;
+    /// ошибка `on_segment` прерывает генерацию.
+    async fn generate_tts_segments<P, F>(
+        &self,
+        subtitles: &SubtitleTrack,
+        tts_provider: &P,
+        progress: &StageProgress,
+        mut on_segment: F,
+    ) -> Result<Vec<TtsSegment>>
+    where
+        P: TtsProvider + Send + Sync + ?Sized,
+        F: FnMut(usize, &TtsSegment) -> Result<()>,
+    {
+        let total = subtitles.len();
+        
+        // Текст, отправляемый провайдеру, и его хэш вычисляем один раз для каждой реплики
+        let speech_texts: Vec<(String, u64)> = subtitles.iter()
+            .map(|subtitle| {
+                let speech_text = if let Some(spoken_text) = &subtitle.spoken_text {
+                    spoken_text.clone()
+                } else if self.collapse_whitespace {
+                    subtitle.normalized_text()
+                } else {
+                    subtitle.text.clone()
+                };
+                let hash = text_hash(&speech_text);
+                (speech_text, hash)
+            })
+            .collect();
+        
+        // Для каждой реплики находим первую реплику с тем же текстом:
+        // одинаковый текст синтезируется один раз
+        let mut first_by_text: TextCache<usize> = TextCache::new();
+        let mut sources = Vec::with_capacity(total);
+        for (i, (speech_text, speech_hash)) in speech_texts.iter().enumerate() {
+            let source = match first_by_text.get(*speech_hash, speech_text) {
+                Some(&first) => first,
+                None => {
+                    first_by_text.insert(*speech_hash, speech_text, i);
+                    i
+                }
+            };
+            sources.push(source);
+        }
+        
+        log_info(&format!("Начало генерации {} TTS сегментов (одновременных запросов: {})",
+            total, self.max_concurrent_requests));
+        
+        // Временная директория для сохранения и проверки TTS данных
+        let temp_dir = std::env::temp_dir().join("tts_sync_temp");
+        if !temp_dir.exists() {
+            std::fs::create_dir_all(&temp_dir).map_err(|e| 
+                Error::new(ErrorType::Io, &format!("Не удалось создать временную директорию: {}", e)))?;
+        }
+        
+        // Запросы уникальных текстов выполняются одновременно, не более
+        // max_concurrent_requests за раз, и завершаются в произвольном порядке
+        let temp_dir_ref = &temp_dir;
+        let speech_texts_ref = &speech_texts;
+        let mut requests = futures_util::stream::iter((0..total).filter(|&i| sources[i] == i))
+            .map(|i| async move {
+                let segment = self.synthesize_cue(
+                    tts_provider, i, total, &subtitles.subtitles[i], &speech_texts_ref[i].0, temp_dir_ref
+                ).await;
+                (i, segment)
+            })
+            .buffer_unordered(self.max_concurrent_requests.max(1));
+        
+        // Сегменты выдаются строго по порядку реплик, чтобы прогресс
+        // и обработка готовых сегментов шли монотонно
+        self.progress_tracker.record_segments(0);
+        let generation = async {
+            let mut generated: Vec<Option<TtsSegment>> = vec![None; total];
+            let mut failed = vec![false; total];
+            let mut tts_segments: Vec<TtsSegment> = Vec::with_capacity(total);
+            while tts_segments.len() < total {
+                self.check_cancelled()?;
+                
+                let i = tts_segments.len();
+                let segment = if sources[i] < i {
+                    log_debug(&format!("Использован кэшированный TTS для сегмента {}/{}", i + 1, total));
+                    if failed[sources[i]] {
+                        self.record_duplicate_failure(i, sources[i], total);
+                    }
+                    tts_segments[sources[i]].clone()
+                } else if let Some(segment) = generated[i].take() {
+                    segment
+                } else {
+                    // Ожидание ответа провайдера прерывается отменой
+                    let next = match &self.cancellation_token {
+                        Some(token) => match token.run_until_cancelled(requests.next()).await {
+                            Some(next) => next,
+                            None => continue,
+                        },
+                        None => requests.next().await,
+                    };
+                    let (ready, segment) = next.ok_or_else(|| Error::new(
+                        ErrorType::Synchronization,
+                        &format!("Не получен TTS сегмент {}/{}", i + 1, total)
+                    ))?;
+                    let segment = match segment {
+                        Ok(segment) => segment,
+                        Err(e) if self.continue_on_error && !matches!(e, Error::Cancelled(_)) => {
+                            failed[ready] = true;
+                            self.failed_segment_placeholder(ready, total, &subtitles.subtitles[ready], &speech_texts[ready].0, e)
+                        }
+                        Err(e) => return Err(e),
+                    };
+                    generated[ready] = Some(segment);
+                    continue;
+                };
+                
+                self.progress_tracker.record_segments(i + 1);
+                self.progress_tracker.update(
+                    progress.step(),
+                    &format!("Генерация TTS {}/{}", i + 1, total)
+                )?;
+                
+                on_segment(i, &segment)?;
+                tts_segments.push(segment);
+            }
+            Ok::<_, Error>(tts_segments)
+        };
+        let result = generation.await;
+        
+        // Попытка очистки временной директории, в том числе после ошибки или отмены
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        
+        let tts_segments = result?;
+        log_info(&format!("Сгенерировано {} TTS сегментов, из них уникальных: {}", 
+            tts_segments.len(), first_by_text.len()));
+        
+        Ok(tts_segments)
+    }
+    
+    /// Синтезирует реплику `i` и проверяет полученные от провайдера данные
+    async fn synthesize_cue<P: TtsProvider + Send + Sync + ?Sized>(
+        &self,
+        tts_provider: &P,
+        i: usize,
+        total: usize,
+        subtitle: &Subtitle,
+        speech_text: &str,
+        temp_dir: &Path,
+    ) -> Result<TtsSegment> {
+        // Реплика без текста для синтеза (например, только описание звука)
+        // заменяется тишиной без запроса к провайдеру
+        if speech_text.trim().is_empty() {
+            log_info(&format!("Сегмент {}/{} '{}' не содержит текста для синтеза, вставлена тишина",
+                i + 1, total, subtitle.display_text));
+            return Ok(self.silent_segment(subtitle, speech_text));
+        }
+        
+        log_debug(&format!("Генерация нового TTS для сегмента {}/{}: '{}' (длительность: {:.2}с)",
+            i + 1, total, subtitle.display_text, subtitle.duration()));
+        let start = std::time::Instant::now();
+        
+        // Генерируем TTS: по фразам, если заданы паузы на знаках препинания
+        let phrases = self.punctuation_pauses
+            .map(|pauses| pauses.split_phrases(speech_text))
+            .unwrap_or_default();
+        let segment = if phrases.len() > 1 {
+            self.generate_phrased_segment(tts_provider, speech_text, &phrases, subtitle.speech_duration()).await?
+        } else {
+            let segment = if self.request_timestamps {
+                tts_provider.generate_segment_with_timestamps(speech_text, subtitle.speech_duration()).await?
+            } else {
+                tts_provider.generate_segment(speech_text, subtitle.speech_duration()).await?
+            };
+            
+            // Переносим часть сжатия на скорость синтеза, если это разрешено
+            if self.intelligibility_bias < 1.0 {
+                self.apply_speed_tradeoff(tts_provider, segment, subtitle.speech_duration()).await?
+            } else {
+                segment
+            }
+        };
+        let duration = start.elapsed();
+        
+        // Проверяем полученные данные
+        let audio_size = segment.audio_data.len();
+        log_debug(&format!("TTS сегмент {}/{} сгенерирован за {:.2?}, размер данных: {} байт",
+            i + 1, total, duration, audio_size));
+        
+        if audio_size < 100 {
+            self.warn(DiagnosticKind::TinyTtsData, Some(i), format!(
+                "Подозрительно маленький размер TTS данных для сегмента {}: {} байт", i + 1, audio_size));
+        }
+        
+        // Для отладки: сохраним полученные TTS данные во временный файл и проверим их
+        let temp_file = temp_dir.join(format!("tts_segment_{}.mp3", i + 1));
+        let temp_path = temp_file.to_str().unwrap_or("temp.mp3");
+        
+        // Сохраняем во временный файл
+        let mut file = File::create(temp_path).await
+            .map_err(|e| Error::new(ErrorType::Io, &format!("Не удалось создать временный файл: {}", e)))?;
+        file.write_all(&segment.audio_data).await
+            .map_err(|e| Error::new(ErrorType::Io, &format!("Не удалось записать TTS данные: {}", e)))?;
+        
+        // Проверяем формат полученных данных
+        if let Err(e) = self.validate_tts_data(temp_path, i).await {
+            self.warn(DiagnosticKind::InvalidTtsData, Some(i), format!("Проблема с TTS данными: {}", e));
+        }
+        
+        Ok(segment)
+    }
+    
+    /// Записывает ошибку для реплики `i`, получившей тишину реплики `source`
+    /// с тем же текстом, синтез которой завершился ошибкой
+    fn record_duplicate_failure(&self, i: usize, source: usize, total: usize) {
+        let mut errors = self.segment_errors.lock().unwrap();
+        let source_error = errors.iter()
+            .find(|(index, _)| *index == source)
+            .map(|(_, error)| error.to_string())
+            .unwrap_or_default();
+        let error = Error::new(ErrorType::Synchronization, &format!(
+            "Текст совпадает с сегментом {}, синтез которого не удался: {}", source + 1, source_error));
+        errors.push((i, error));
+        drop(errors);
+        
+        self.warn(DiagnosticKind::SegmentFailed, Some(i), format!(
+            "Сегмент {}/{} повторяет текст сегмента {} с ошибкой синтеза, вставлена тишина",
+            i + 1, total, source + 1));
+    }
+    
+    /// Записывает ошибку синтеза реплики `i` и возвращает сегмент тишины вместо нее
+    ///
+    /// Тишина кодируется в формате провайдера (сырой PCM или WAV с частотой
+    /// провайдера) и имеет целевую длительность речи, поэтому при корректировке
+    /// не растягивается.
+    fn failed_segment_placeholder(
+        &self,
+        i: usize,
+        total: usize,
+        subtitle: &Subtitle,
+        speech_text: &str,
+        error: Error,
+    ) -> TtsSegment {
+        self.warn(DiagnosticKind::SegmentFailed, Some(i), format!(
+            "Не удалось синтезировать сегмент {}/{}, вставлена тишина: {}", i + 1, total, error));
+        self.segment_errors.lock().unwrap().push((i, error));
+        self.silent_segment(subtitle, speech_text)
+    }
+    
+    /// Создает сегмент тишины длительностью речи субтитра в формате провайдера
+    fn silent_segment(&self, subtitle: &Subtitle, speech_text: &str) -> TtsSegment {
+        let duration = subtitle.speech_duration().max(self.min_segment_duration);
+        let audio_data = match &self.raw_pcm {
+            Some(spec) => {
+                let frame_size = spec.bytes_per_sample() * spec.channels.max(1) as usize;
+                let silence_byte = if spec.bits == 8 { 128 } else { 0 };
+                let frames = (duration * spec.sample_rate as f64).round() as usize;
+                vec![silence_byte; frames * frame_size]
+            }
+            None => {
+                let samples = (duration * self.tts_sample_rate as f64).round() as usize;
+                AudioData::new(vec![0.0f32; samples], self.tts_sample_rate, 1).to_wav_bytes()
+            }
+        };
+        
+        TtsSegment {
+            text: speech_text.to_string(),
+            audio_data,
+            duration: Some(duration),
+            target_duration: subtitle.speech_duration(),
+            stretch_factor: None,
+            word_timings: None,
+        }
+    }
+    
+    /// Синтезирует реплику по фразам и объединяет их с паузами между ними
+    ///
+    /// Целевая длительность речи без пауз распределяется между фразами
+    /// пропорционально количеству символов. Сырой PCM объединяется напрямую,
+    /// остальные форматы декодируются и кодируются в WAV с частотой провайдера.
+    async fn generate_phrased_segment<P: TtsProvider + Send + Sync + ?Sized>(
+        &self,
+        tts_provider: &P,
+        text: &str,
+        phrases: &[(String, f64)],
+        target_duration: f64,
+    ) -> Result<TtsSegment> {
+        let total_pause: f64 = phrases.iter().map(|(_, pause)| pause).sum();
+        let total_chars: usize = phrases.iter().map(|(phrase, _)| phrase.chars().count()).sum();
+        let speech_duration = (target_duration - total_pause).max(0.0);
+        log_debug(&format!("Синтез по фразам: {} фраз, паузы {:.2}с", phrases.len(), total_pause));
+        
+        let mut parts = Vec::with_capacity(phrases.len());
+        for (phrase, pause) in phrases {
+            let share = phrase.chars().count() as f64 / total_chars.max(1) as f64;
+            let segment = tts_provider.generate_segment(phrase, speech_duration * share).await?;
+            parts.push((segment.audio_data, *pause));
+        }
+        
+        let (audio_data, duration) = match &self.raw_pcm {
+            Some(spec) => {
+                let frame_size = spec.bytes_per_sample() * spec.channels.max(1) as usize;
+                let silence_byte = if spec.bits == 8 { 128 } else { 0 };
+                let mut bytes = Vec::new();
+                for (data, pause) in parts {
+                    bytes.extend_from_slice(&data[..data.len() / frame_size * frame_size]);
+                    let frames = (pause * spec.sample_rate as f64).round() as usize;
+                    bytes.resize(bytes.len() + frames * frame_size, silence_byte);
+                }
+                let duration = (bytes.len() / frame_size) as f64 / spec.sample_rate as f64;
+                (bytes, duration)
+            }
+            None => {
+                let mut chunks = Vec::with_capacity(parts.len() * 2);
+                for (data, pause) in parts {
+                    let audio = Self::decode_provider_audio(
+                        &data, None, self.tts_sample_rate, self.tts_sample_rate, self.sample_rate_mismatch,
+                        self.max_decoded_samples
+                    )?;
+                    let silence_samples = (pause * audio.sample_rate as f64).round() as usize * audio.channels as usize;
+                    let silence = AudioData::new(vec![0.0f32; silence_samples], audio.sample_rate, audio.channels);
+                    chunks.push(audio);
+                    chunks.push(silence);
+                }
+                let joined = AudioData::concat(&chunks)?;
+                let duration = joined.duration();
+                (joined.to_wav_bytes(), duration)
+            }
+        };
+        
+        Ok(TtsSegment {
+            text: text.to_string(),
+            audio_data,
+            duration: Some(duration),
+            target_duration,
+            stretch_factor: None,
+            word_timings: None,
+        })
+    }
+    
+    /// Декодирует аудио от TTS провайдера и приводит его к частоте конвейера
+    fn decode_tts_data(&self, data: &[u8]) -> Result<AudioData> {
+        Self::decode_provider_audio(
+            data,
+            self.raw_pcm.as_ref(),
+            self.tts_sample_rate,
+            self.sample_rate,
+            self.sample_rate_mismatch,
+            self.max_decoded_samples
+        )
+    }
+    
+    /// Декодирует аудио провайдера как сырой PCM (если задан формат) или
+    /// как аудио в контейнере
+    fn decode_provider_audio(
+        data: &[u8],
+        raw_pcm: Option<&RawPcmSpec>,
+        expected_rate: u32,
+        target_rate: u32,
+        on_mismatch: SampleRateMismatch,
+        max_samples: Option<usize>,
+    ) -> Result<AudioData> {
+        match raw_pcm {
+            Some(spec) => crate::audio::utils::decode_raw_tts_audio(data, spec, expected_rate, target_rate, on_mismatch),
+            None => crate::audio::utils::decode_tts_audio_with_limit(data, expected_rate, target_rate, on_mismatch, max_samples),
+        }
+    }
+    
+    /// Декодирует аудио всех сегментов, сохраняя их порядок
+    ///
+    /// При `decode_concurrency > 0` сегменты декодируются в пуле блокирующих
+    /// потоков: одновременно выполняется не более `decode_concurrency` задач,
+    /// и следующая запускается, как только завершается любая из них.
+    /// Иначе сегменты декодируются последовательно в текущей задаче.
+    pub async fn decode_segments(&self, segments: &[TtsSegment]) -> Result<Vec<Result<AudioData>>> {
+        if self.decode_concurrency == 0 {
+            return Ok(segments.iter().map(|segment| self.decode_tts_data(&segment.audio_data)).collect());
+        }
+        
+        let (raw_pcm, expected_rate, target_rate, on_mismatch, max_samples) = (
+            self.raw_pcm, self.tts_sample_rate, self.sample_rate, self.sample_rate_mismatch,
+            self.max_decoded_samples
+        );
+        let mut tasks = futures_util::stream::iter(segments.iter().enumerate())
+            .map(|(i, segment)| {
+                let data = segment.audio_data.clone();
+                let task = tokio::task::spawn_blocking(move || {
+                    Self::decode_provider_audio(&data, raw_pcm.as_ref(), expected_rate, target_rate, on_mismatch, max_samples)
+                });
+                async move { (i, task.await) }
+            })
+            .buffer_unordered(self.decode_concurrency);
+        
+        // Задачи завершаются в произвольном порядке
+        let mut decoded: Vec<Option<Result<AudioData>>> = segments.iter().map(|_| None).collect();
+        while let Some((i, result)) = tasks.next().await {
+            decoded[i] = Some(result.map_err(|e| Error::new(
+                ErrorType::AudioProcessingError,
+                &format!("Ошибка потока декодирования: {}", e)
+            ))?);
         }
+        
+        Ok(decoded.into_iter().flatten().collect())
     }
     
-    /// Синхронизирует TTS с субтитрами
-    pub async fn synchronize<P: TtsProvider + Send + Sync>(
+    /// Повторно синтезирует сегмент с большей скоростью речи согласно компромиссу
+    /// между скоростью синтеза и изменением темпа
+    async fn apply_speed_tradeoff<P: TtsProvider + Send + Sync + ?Sized>(
         &self,
-        vtt_path: &str,
-        video_duration: f64,
         tts_provider: &P,
-    ) -> Result<AudioTrack> {
-        // Шаг 1: Парсинг VTT файла
-        self.progress_tracker.update(0.0, "Парсинг субтитров")?;
-        let subtitles = VttParser::parse_file(vtt_path)?;
-        
-        if subtitles.is_empty() {
-            return Err(Error::new(
-                ErrorType::Synchronization,
-                "Субтитры не найдены"
-            ));
+        segment: TtsSegment,
+        target_duration: f64,
+    ) -> Result<TtsSegment> {
+        let natural_duration = match self.decode_tts_data(&segment.audio_data) {
+            Ok(data) => data.duration(),
+            Err(_) => return Ok(segment),
+        };
+        
+        if target_duration <= 0.0 || natural_duration <= 0.0 {
+            return Ok(segment);
         }
         
-        // Шаг 2: Генерация TTS для каждого субтитра
-        self.progress_tracker.update(10.0, "Генерация TTS")?;
-        let tts_segments = self.generate_tts_segments(&subtitles, tts_provider).await?;
-        
-        // Шаг 3: Анализ и корректировка длительности
-        self.progress_tracker.update(50.0, "Анализ и корректировка длительности")?;
-        let audio_segments = self.analyze_and_adjust_segments(&tts_segments, &subtitles).await?;
-        
-        // Шаг 4: Синхронизация аудио с субтитрами
-        self.progress_tracker.update(70.0, "Синхронизация аудио с субтитрами")?;
-        let mut audio_track = self.synchronize_with_subtitles(&audio_segments, &subtitles, video_duration)?;
-        
-        // Шаг 5: Добавление пауз между сегментами
-        self.progress_tracker.update(80.0, "Добавление пауз между сегментами")?;
-        audio_track = self.add_pauses_between_segments(&audio_track, &subtitles)?;
-        
-        // Шаг 6: Нормализация громкости
-        if self.normalize_volume {
-            self.progress_tracker.update(90.0, "Нормализация громкости")?;
-            audio_track = self.normalize_track(&audio_track)?;
+        let split = split_compression(natural_duration / target_duration, self.intelligibility_bias);
+        if split.speed <= 1.01 {
+            return Ok(segment);
         }
         
-        // Шаг 7: Проверка общей длительности
-        self.progress_tracker.update(95.0, "Проверка общей длительности")?;
-        audio_track = self.ensure_duration(&audio_track, video_duration)?;
-        
-        self.progress_tracker.update(100.0, "Синхронизация завершена")?;
+        log_debug(&format!("Повторный синтез '{}' со скоростью x{:.2}, оставшееся сжатие темпом: x{:.2}",
+            segment.text, split.speed, split.stretch));
         
-        Ok(audio_track)
+        tts_provider.generate_segment_with_speed(&segment.text, target_duration, split.speed as f32).await
     }
     
-    /// Генерирует TTS сегменты для субтитров
-    async fn generate_tts_segments<P: TtsProvider + Send + Sync>(
+    /// Генерирует TTS сегменты и корректирует их длительность конвейером
+    ///
+    /// Готовые сегменты передаются через канал на декодирование и корректировку,
+    /// пока синтезируются следующие. Порядок сегментов и отчет совпадают с
+    /// последовательной обработкой.
+    async fn generate_and_adjust_pipelined<P: TtsProvider + Send + Sync + ?Sized>(
         &self,
         subtitles: &SubtitleTrack,
         tts_provider: &P,
-    ) -> Result<Vec<TtsSegment>> {
-        let mut tts_segments = Vec::with_capacity(subtitles.len());
-        
-        let progress_step = 40.0f32 / subtitles.len() as f32;
-        let mut current_progress = 10.0f32;
-        
-        // Создаем кэш для хранения уже сгенерированных TTS сегментов
-        let mut segments_cache: HashMap<String, TtsSegment> = HashMap::new();
-        
-        log_info(&format!("Начало генерации {} TTS сегментов", subtitles.len()));
-        
-        // Временная директория для сохранения и проверки TTS данных
-        let temp_dir = std::env::temp_dir().join("tts_sync_temp");
-        if !temp_dir.exists() {
-            std::fs::create_dir_all(&temp_dir).map_err(|e| 
-                Error::new(ErrorType::Io, &format!("Не удалось создать временную директорию: {}", e)))?;
-        }
-        
-        for (i, subtitle) in subtitles.iter().enumerate() {
-            // Обновляем прогресс
-            self.progress_tracker.update(
-                current_progress,
-                &format!("Генерация TTS {}/{}", i + 1, subtitles.len())
-            )?;
-            
-            log_debug(&format!("Обработка сегмента {}/{}: '{}' (длительность: {:.2}с)",
-                i + 1, subtitles.len(), subtitle.text, subtitle.duration()));
-            
-            // Проверяем, есть ли сегмент в кэше
-            let cache_key = subtitle.text.clone();
-            let segment = if let Some(cached_segment) = segments_cache.get(&cache_key) {
-                log_debug(&format!("Использован кэшированный TTS для сегмента {}/{}", i + 1, subtitles.len()));
-                cached_segment.clone()
-            } else {
-                // Если нет в кэше, генерируем новый
-                log_debug(&format!("Генерация нового TTS для сегмента {}/{}", i + 1, subtitles.len()));
-                let start = std::time::Instant::now();
-                
-                // Генерируем TTS
-                let mut segment = tts_provider.generate_segment(&subtitle.text, subtitle.duration()).await?;
-                let duration = start.elapsed();
-                
-                // Проверяем полученные данные
-                let audio_size = segment.audio_data.len();
-                log_debug(&format!("TTS сегмент {}/{} сгенерирован за {:.2?}, размер данных: {} байт",
-                    i + 1, subtitles.len(), duration, audio_size));
-                
-                if audio_size < 100 {
-                    log_warning(&format!("Подозрительно маленький размер TTS данных для сегмента {}: {} байт", 
-                        i + 1, audio_size));
-                }
-                
-                // Для отладки: сохраним полученные TTS данные во временный файл и проверим их
-                let temp_file = temp_dir.join(format!("tts_segment_{}.mp3", i + 1));
-                let temp_path = temp_file.to_str().unwrap_or("temp.mp3");
-                
-                // Сохраняем во временный файл
-                let mut file = File::create(temp_path).await
-                    .map_err(|e| Error::new(ErrorType::Io, &format!("Не удалось создать временный файл: {}", e)))?;
-                file.write_all(&segment.audio_data).await
-                    .map_err(|e| Error::new(ErrorType::Io, &format!("Не удалось записать TTS данные: {}", e)))?;
+        mut on_adjusted: Option<&mut AdjustedCallback<'_>>,
+    ) -> Result<Vec<AudioSegment>> {
+        let total = subtitles.len();
+        let progress = StageProgress::new(10.0, 70.0, total * 2);
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel::<(usize, TtsSegment)>();
+        
+        let producer = async {
+            // Канал закрывается по завершении генерации, и корректировка заканчивается
+            let sender = sender;
+            self.generate_tts_segments(subtitles, tts_provider, &progress, |i, segment| {
+                sender.send((i, segment.clone())).map_err(|_| Error::new(
+                    ErrorType::Synchronization,
+                    "Корректировка сегментов остановлена, генерация прервана"
+                ))
+            }).await
+        };
+        
+        let consumer = async {
+            // При ошибке корректировки канал закрывается, и генерация прерывается
+            let mut receiver = receiver;
+            let mut adjusted_segments = Vec::with_capacity(total);
+            while let Some((i, mut segment)) = receiver.recv().await {
+                self.check_cancelled()?;
+                self.progress_tracker.update(
+                    progress.step(),
+                    &format!("Анализ и корректировка сегмента {}/{}", i + 1, total)
+                )?;
                 
-                // Проверяем формат полученных данных
-                if let Err(e) = self.validate_tts_data(temp_path).await {
-                    log_warning(&format!("Проблема с TTS данными: {}", e));
+                let decoded = self.decode_segments(std::slice::from_ref(&segment)).await?
+                    .pop()
+                    .unwrap_or_else(|| Err(Error::new(ErrorType::AudioProcessingError, "Сегмент не декодирован")));
+                let adjusted = self.adjust_segment(i, total, &mut segment, &subtitles.subtitles[i], decoded)?;
+                if let Some(on_adjusted) = on_adjusted.as_mut() {
+                    on_adjusted(&adjusted)?;
                 }
-                
-                // Добавляем в кэш
-                segments_cache.insert(cache_key, segment.clone());
-                segment
-            };
-            
-            tts_segments.push(segment);
-            current_progress += progress_step;
-        }
-        
-        log_info(&format!("Сгенерировано {} TTS сегментов, из них уникальных: {}", 
-            tts_segments.len(), segments_cache.len()));
+                adjusted_segments.push(adjusted);
+            }
+            Ok::<_, Error>(adjusted_segments)
+        };
         
-        // Попытка очистки временной директории
-        let _ = std::fs::remove_dir_all(&temp_dir);
+        let (generated, adjusted) = futures_util::future::join(producer, consumer).await;
+        // Ошибка корректировки первична: из-за нее прерывается генерация
+        let adjusted_segments = adjusted?;
+        generated?;
         
-        Ok(tts_segments)
+        Ok(adjusted_segments)
     }
     
     /// Анализирует и корректирует длительность сегментов
     async fn analyze_and_adjust_segments(
         &self,
-        tts_segments: &[TtsSegment],
+        tts_segments: &mut [TtsSegment],
         subtitles: &SubtitleTrack,
     ) -> Result<Vec<AudioSegment>> {
         let mut adjusted_segments = Vec::with_capacity(tts_segments.len());
         
-        let progress_step = 20.0f32 / tts_segments.len() as f32;
-        let mut current_progress = 50.0f32;
-        
-        for (i, (segment, subtitle)) in tts_segments.iter().zip(subtitles.iter()).enumerate() {
+        log_debug(&format!("Декодирование {} сегментов (потоков: {})",
+            tts_segments.len(), self.decode_concurrency));
+        let decoded_segments = self.decode_segments(tts_segments).await?;
+        let progress = StageProgress::new(50.0, 70.0, tts_segments.len());
+        
+        let total = tts_segments.len();
+        for (i, ((segment, subtitle), decoded)) in tts_segments.iter_mut()
+            .zip(subtitles.iter())
+            .zip(decoded_segments)
+            .enumerate()
+        {
+            self.check_cancelled()?;
+            
             // Обновляем прогресс
             self.progress_tracker.update(
-                current_progress,
-                &format!("Анализ и корректировка сегмента {}/{}", i + 1, tts_segments.len())
+                progress.step(),
+                &format!("Анализ и корректировка сегмента {}/{}", i + 1, total)
             )?;
             
-            // Сохраняем исходные MP3 данные для последующего прямого сохранения
-            let raw_audio_data = segment.audio_data.clone();
-            
-            // Декодируем MP3 данные в сэмплы с использованием Symphonia
-            log_debug(&format!("Декодирование сегмента {}/{} размером {} байт", 
-                i + 1, tts_segments.len(), raw_audio_data.len()));
-            
-            let audio_data = match crate::audio::utils::decode_mp3_to_samples(
-                &raw_audio_data, 
-                self.sample_rate, 
-                self.channels
-            ) {
-                Ok(data) => {
-                    log_debug(&format!("Успешно декодирован MP3 сегмент {}/{}: {} сэмплов, длительность {:.2}с", 
-                        i + 1, tts_segments.len(), data.samples.len(), data.duration()));
-                    data
-                },
-                Err(err) => {
-                    log_warning(&format!("Ошибка при декодировании MP3 сегмента {}/{}: {}", 
-                        i + 1, tts_segments.len(), err));
-                    
-                    // Создаем заглушку если декодирование не удалось (предотвращаем полную остановку процесса)
-                    log_warning("Создаем пустой сегмент как заглушку");
-                    let empty_samples = vec![0.0f32; (self.sample_rate as f64 * subtitle.duration()) as usize];
-                    AudioData::new(
-                        empty_samples,
-                        self.sample_rate,
-                        self.channels,
-                    )
-                }
-            };
-            
-            // Анализируем аудио для определения характеристик
-            let analysis = AudioAnalyzer::analyze(&audio_data)?;
-            log_debug(&format!("Анализ аудио сегмента {}/{}: пик громкости: {:.2}, средняя громкость: {:.2}",
-                i + 1, tts_segments.len(), analysis.peak, analysis.rms));
-            
-            // Рассчитываем целевую длительность из субтитров
-            let target_duration = subtitle.duration() as f32;
-            let current_duration = audio_data.duration() as f32;
-            
-            log_debug(&format!("Сегмент {}/{}: текущая длительность: {:.2}с, целевая: {:.2}с, разница: {:.2}с",
-                i + 1, tts_segments.len(), current_duration, target_duration, 
-                current_duration - target_duration));
-            
-            // Корректируем длительность аудио, если необходимо
-            let adjusted_audio = if (current_duration - target_duration).abs() > 0.05 {
-                // Используем адаптивное изменение темпа с сохранением пауз
-                log_debug(&format!("Корректировка длительности сегмента {}/{} с {:.2}с до {:.2}с", 
-                    i + 1, tts_segments.len(), current_duration, target_duration));
+            adjusted_segments.push(self.adjust_segment(i, total, segment, subtitle, decoded)?);
+        }
+        
+        Ok(adjusted_segments)
+    }
+    
+    /// Корректирует длительность декодированного сегмента `i` из `total`
+    /// под субтитр и создает аудио сегмент
+    ///
+    /// Заполняет длительность и коэффициент изменения длительности `segment`.
+    fn adjust_segment(
+        &self,
+        i: usize,
+        total: usize,
+        segment: &mut TtsSegment,
+        subtitle: &Subtitle,
+        decoded: Result<AudioData>,
+    ) -> Result<AudioSegment> {
+        // Сохраняем исходные MP3 данные для последующего прямого сохранения
+        let raw_audio_data = segment.audio_data.clone();
+        
+        let audio_data = match decoded {
+            Ok(data) => {
+                log_debug(&format!("Успешно декодирован MP3 сегмент {}/{}: {} сэмплов, длительность {:.2}с", 
+                    i + 1, total, data.samples.len(), data.duration()));
+                data
+            },
+            Err(err) => {
+                self.warn(DiagnosticKind::InvalidTtsData, Some(i), format!(
+                    "Ошибка при декодировании MP3 сегмента {}/{}: {}", i + 1, total, err));
                 
-                TempoAdjuster::adaptive_tempo_adjustment(
-                    &audio_data,
-                    target_duration,
-                    self.tempo_algorithm,
-                    self.preserve_pauses
-                )?
-            } else {
-                // Если разница незначительная, оставляем как есть
-                log_debug(&format!("Разница длительности сегмента {}/{} незначительна ({:.2}с), оставляем без изменений", 
-                    i + 1, tts_segments.len(), current_duration - target_duration));
-                audio_data
-            };
-            
-            // Создаем аудио сегмент с сохранением исходных данных
-            let raw_audio_size = raw_audio_data.len();
-            let audio_segment = AudioSegment::new_with_raw_data(
-                adjusted_audio,
-                subtitle.start_time,
-                subtitle.end_time,
-                subtitle.text.clone(),
-                raw_audio_data
-            );
-            
-            adjusted_segments.push(audio_segment);
-            log_debug(&format!("Добавлен сегмент {}/{} с сохранением исходных MP3 данных ({} байт)",
-                i + 1, tts_segments.len(), raw_audio_size));
+                // Создаем заглушку если декодирование не удалось (предотвращаем полную остановку процесса)
+                log_warning("Создаем пустой сегмент как заглушку");
+                let empty_samples = vec![0.0f32; (self.sample_rate as f64 * subtitle.duration()) as usize];
+                AudioData::new(
+                    empty_samples,
+                    self.sample_rate,
+                    self.channels,
+                )
+            }
+        };
+        
+        // Анализируем аудио для определения характеристик
+        let analysis = AudioAnalyzer::analyze(&audio_data)?;
+        log_debug(&format!("Анализ аудио сегмента {}/{}: пик громкости: {:.2}, средняя громкость: {:.2}",
+            i + 1, total, analysis.peak, analysis.rms));
+        
+        // Рассчитываем целевую длительность речи из субтитров
+        let speech_target = subtitle.speech_duration();
+        if speech_target < self.min_segment_duration {
+            self.warn(DiagnosticKind::ShortSegment, Some(i), format!(
+                "Сегмент {}/{}: длительность речи {:.3}с меньше минимальной, используется {:.3}с",
+                i + 1, total, speech_target, self.min_segment_duration));
+        }
+        let target_duration = speech_target.max(self.min_segment_duration) as f32;
+        let current_duration = audio_data.duration() as f32;
+        
+        log_debug(&format!("Сегмент {}/{}: текущая длительность: {:.2}с, целевая: {:.2}с, разница: {:.2}с",
+            i + 1, total, current_duration, target_duration, 
+            current_duration - target_duration));
+        
+        // Корректируем длительность аудио, если необходимо
+        let adjusted_audio = if (current_duration - target_duration).abs() > 0.05 {
+            // Используем адаптивное изменение темпа с сохранением пауз
+            log_debug(&format!("Корректировка длительности сегмента {}/{} с {:.2}с до {:.2}с", 
+                i + 1, total, current_duration, target_duration));
             
-            current_progress += progress_step;
+            TempoAdjuster::adaptive_tempo_adjustment(
+                &audio_data,
+                target_duration,
+                self.tempo_algorithm,
+                self.preserve_pauses
+            )?
+        } else {
+            // Если разница незначительная, оставляем как есть
+            log_debug(&format!("Разница длительности сегмента {}/{} незначительна ({:.2}с), оставляем без изменений", 
+                i + 1, total, current_duration - target_duration));
+            audio_data
+        };
+        let speech_duration = adjusted_audio.duration();
+        
+        // Если речь короче субтитра, остаток субтитра заполняется тишиной
+        // по обе стороны речи в соответствии с speech_anchor
+        let (adjusted_audio, speech_offset) = if subtitle.speech_duration() < subtitle.duration() {
+            log_debug(&format!("Сегмент {}/{}: речь {:.2}с, тишина до длительности субтитра ({:.2}с, {:?})",
+                i + 1, total, speech_duration, subtitle.duration(), self.speech_anchor));
+            Self::pad_to_duration(adjusted_audio, subtitle.duration(), self.speech_anchor)
+        } else {
+            (adjusted_audio, 0.0)
+        };
+        
+        // Переносим тайминги слов на шкалу результата с учетом изменения темпа
+        let tempo_scale = if current_duration > 0.0 { speech_duration / current_duration as f64 } else { 1.0 };
+        segment.duration = Some(current_duration as f64);
+        segment.stretch_factor = Some(tempo_scale);
+        let word_timings = segment.word_timings.as_ref().map(|timings| {
+            timings.iter()
+                .map(|timing| WordTiming::new(
+                    timing.word.clone(),
+                    subtitle.start_time + speech_offset + timing.start * tempo_scale,
+                    subtitle.start_time + speech_offset + timing.end * tempo_scale,
+                ))
+                .collect()
+        });
+        
+        self.report.lock().unwrap().segments.push(SegmentReport {
+            index: i,
+            id: subtitle.id.clone(),
+            text: subtitle.display_text.clone(),
+            start_time: subtitle.start_time,
+            end_time: subtitle.end_time,
+            tts_duration: current_duration as f64,
+            adjusted_duration: speech_duration,
+            stretch_factor: tempo_scale,
+            word_timings,
+        });
+        
+        // Создаем аудио сегмент с сохранением исходных данных
+        let raw_audio_size = raw_audio_data.len();
+        let audio_segment = AudioSegment::new_with_raw_data(
+            adjusted_audio,
+            subtitle.start_time,
+            subtitle.end_time,
+            subtitle.display_text.clone(),
+            raw_audio_data
+        )
+        .with_id(subtitle.id.clone())
+        .with_pan(if self.position_panning { subtitle.position_pan() } else { None })
+        .with_cue(Some(i));
+        
+        log_debug(&format!("Добавлен сегмент {}/{} с сохранением исходных MP3 данных ({} байт)",
+            i + 1, total, raw_audio_size));
+        
+        Ok(audio_segment)
+    }
+    
+    /// Дополняет аудио тишиной до указанной длительности
+    ///
+    /// Тишина добавляется после речи, поровну до и после нее или перед ней
+    /// в зависимости от `anchor`. Возвращает аудио и смещение начала речи
+    /// в секундах.
+    fn pad_to_duration(mut audio: AudioData, duration: f64, anchor: SpeechAnchor) -> (AudioData, f64) {
+        let channels = audio.channels.max(1) as usize;
+        let total_frames = (duration * audio.sample_rate as f64) as usize;
+        let frames = audio.samples.len() / channels;
+        if frames >= total_frames {
+            return (audio, 0.0);
         }
         
-        Ok(adjusted_segments)
+        let lead_frames = match anchor {
+            SpeechAnchor::Start => 0,
+            SpeechAnchor::Center => (total_frames - frames) / 2,
+            SpeechAnchor::End => total_frames - frames,
+        };
+        let mut samples = vec![0.0; lead_frames * channels];
+        samples.append(&mut audio.samples);
+        samples.resize(total_frames * channels, 0.0);
+        audio.samples = samples;
+        
+        let offset = lead_frames as f64 / audio.sample_rate.max(1) as f64;
+        (audio, offset)
     }
     
     /// Синхронизирует аудио сегменты с субтитрами и видео
@@ -336,6 +1860,16 @@ impl SyncCore {
             audio_track.add_segment(segment.clone());
         }
         
+        // Убираем микро-паузы между соседними репликами
+        if self.micro_gap_threshold > 0.0 {
+            self.close_micro_gaps(&mut audio_track);
+        }
+        
+        // Применяем глобальное смещение
+        if self.global_offset != 0.0 {
+            audio_track = self.apply_global_offset(&audio_track);
+        }
+        
         // Проверяем, что все сегменты находятся в пределах длительности видео
         if let Some(last_segment) = audio_track.segments.last() {
             if last_segment.end_time > video_duration {
@@ -352,7 +1886,99 @@ impl SyncCore {
         Ok(audio_track)
     }
     
+    /// Сдвигает реплики, начинающиеся менее чем через `micro_gap_threshold`
+    /// после окончания речи предыдущей реплики, вплотную к ней
+    fn close_micro_gaps(&self, audio_track: &mut AudioTrack) {
+        for i in 1..audio_track.segments.len() {
+            let (previous, rest) = audio_track.segments.split_at_mut(i);
+            let previous = &previous[i - 1];
+            let current = &mut rest[0];
+            
+            if previous.text == current.text {
+                continue;
+            }
+            
+            let speech_end = previous.start_time + previous.audio.duration();
+            let gap = current.start_time - speech_end;
+            if gap > 0.0 && gap < self.micro_gap_threshold {
+                log_debug(&format!("Микро-пауза {:.0}мс перед '{}' удалена", gap * 1000.0, current.text));
+                current.start_time -= gap;
+                current.end_time -= gap;
+                if let Some(cue) = current.cue {
+                    self.shift_word_timings(cue, -gap);
+                }
+            }
+        }
+    }
+    
+    /// Сдвигает тайминги слов реплики `cue` в отчете на `delta` секунд
+    ///
+    /// Тайминги следуют за аудио реплики при сдвигах трека; слова,
+    /// оказавшиеся целиком до начала трека, удаляются.
+    fn shift_word_timings(&self, cue: usize, delta: f64) {
+        let mut report = self.report.lock().unwrap();
+        let timings = report.segments.iter_mut()
+            .find(|segment| segment.index == cue)
+            .and_then(|segment| segment.word_timings.as_mut());
+        if let Some(timings) = timings {
+            timings.retain(|timing| timing.end + delta > 0.0);
+            for timing in timings.iter_mut() {
+                timing.start = (timing.start + delta).max(0.0);
+                timing.end += delta;
+            }
+        }
+    }
+    
+    /// Сдвигает все сегменты трека на глобальное смещение
+    fn apply_global_offset(&self, audio_track: &AudioTrack) -> AudioTrack {
+        let offset = self.global_offset;
+        log_debug(&format!("Применение глобального смещения: {:.3}с", offset));
+        
+        let mut result_track = AudioTrack::new(self.sample_rate, self.channels);
+        
+        // При положительном смещении заполняем начало тишиной
+        if offset > 0.0 {
+            let frames = (offset * self.sample_rate as f64).round() as usize;
+            let silence_samples = vec![0.0f32; frames * self.channels as usize];
+            let silence_data = AudioData::new(silence_samples, self.sample_rate, self.channels);
+            result_track.add_segment(AudioSegment::new(silence_data, 0.0, offset, String::new()));
+        }
+        
+        for segment in &audio_track.segments {
+            let mut shifted = segment.clone();
+            shifted.start_time += offset;
+            shifted.end_time += offset;
+            if let Some(cue) = segment.cue {
+                self.shift_word_timings(cue, offset);
+            }
+            
+            if shifted.end_time <= 0.0 {
+                log_debug(&format!("Сегмент '{}' полностью вышел за начало трека и удален", segment.text));
+                continue;
+            }
+            
+            if shifted.start_time < 0.0 {
+                // Обрезаем часть сегмента, оказавшуюся до нуля
+                let trim_samples = (-shifted.start_time * shifted.audio.sample_rate as f64) as usize
+                    * shifted.audio.channels as usize;
+                let trim_samples = trim_samples.min(shifted.audio.samples.len());
+                shifted.audio.samples.drain(..trim_samples);
+                shifted.start_time = 0.0;
+                // Исходные данные больше не соответствуют обрезанному аудио
+                shifted.raw_data = None;
+            }
+            
+            result_track.add_segment(shifted);
+        }
+        
+        result_track
+    }
+    
     /// Добавляет паузы между сегментами для более естественного звучания
+    ///
+    /// Пауза после реплики повторяет промежуток до следующей реплики в
+    /// исходных субтитрах (с учетом сдвигов и сокращения промежутков), после
+    /// последней реплики добавляется пауза `pause_between_segments`.
     fn add_pauses_between_segments(
         &self,
         audio_track: &AudioTrack,
@@ -361,18 +1987,32 @@ impl SyncCore {
         let mut result_track = AudioTrack::new(self.sample_rate, self.channels);
         
         // Добавляем сегменты с паузами между ними
-        for segment in &audio_track.segments {
+        for (i, segment) in audio_track.segments.iter().enumerate() {
             result_track.add_segment(segment.clone());
             
+            let pause_end = match audio_track.segments.get(i + 1) {
+                // Вплотную идущие реплики (после удаления микро-пауз) не разделяем
+                Some(next) if self.micro_gap_threshold > 0.0
+                    && next.start_time - (segment.start_time + segment.audio.duration()) < self.micro_gap_threshold => continue,
+                // Естественный промежуток между репликами
+                Some(next) => next.start_time,
+                None => segment.end_time + self.pause_between_segments,
+            };
+            
+            let silence_duration = pause_end - segment.end_time;
+            if silence_duration <= 0.0 {
+                continue;
+            }
+            
             // Добавляем паузу после сегмента
-            let silence_duration = 0.2; // 200ms пауза
-            let silence_samples = vec![0.0f32; (self.sample_rate as f64 * silence_duration) as usize];
+            let silence_frames = (self.sample_rate as f64 * silence_duration).round() as usize;
+            let silence_samples = vec![0.0f32; silence_frames * self.channels as usize];
             let silence_data = AudioData::new(silence_samples, self.sample_rate, self.channels);
             
             let silence_segment = AudioSegment::new(
                 silence_data,
                 segment.end_time,
-                segment.end_time + silence_duration,
+                pause_end,
                 String::new()
             );
             
@@ -388,22 +2028,43 @@ impl SyncCore {
         
         // Нормализуем каждый сегмент
         for segment in &audio_track.segments {
-            let mut normalized_audio = segment.audio.clone();
-            normalized_audio.normalize(self.target_peak);
-            
-            let normalized_segment = AudioSegment::new(
-                normalized_audio,
-                segment.start_time,
-                segment.end_time,
-                segment.text.clone()
-            );
-            
-            result_track.add_segment(normalized_segment);
+            result_track.add_segment(self.normalize_segment(segment));
         }
         
         Ok(result_track)
     }
     
+    /// Нормализует пиковый уровень сегмента к целевому
+    fn normalize_segment(&self, segment: &AudioSegment) -> AudioSegment {
+        let peak = if self.true_peak_normalization {
+            segment.audio.true_peak()
+        } else {
+            segment.audio.samples.iter().fold(0.0f32, |max, &sample| max.max(sample.abs()))
+        };
+        if self.normalization_tolerance > 0.0 && (peak - self.target_peak).abs() <= self.normalization_tolerance {
+            log_debug(&format!("Пик сегмента {:.4} близок к целевому {:.4}, нормализация пропущена",
+                peak, self.target_peak));
+            return segment.clone();
+        }
+        
+        let mut normalized_audio = segment.audio.clone();
+        if self.true_peak_normalization {
+            normalized_audio.normalize_true_peak(self.target_peak);
+        } else {
+            normalized_audio.normalize(self.target_peak);
+        }
+        
+        AudioSegment::new(
+            normalized_audio,
+            segment.start_time,
+            segment.end_time,
+            segment.text.clone()
+        )
+        .with_id(segment.id.clone())
+        .with_pan(segment.pan)
+        .with_cue(segment.cue)
+    }
+    
     /// Проверяет и корректирует общую длительность аудио трека
     fn ensure_duration(&self, audio_track: &AudioTrack, video_duration: f64) -> Result<AudioTrack> {
         let mut result_track = AudioTrack::new(self.sample_rate, self.channels);
@@ -426,31 +2087,127 @@ impl SyncCore {
             }
         }
         
-        // Если аудио короче видео, добавляем тишину в конец
-        if let Some(last_segment) = result_track.segments.last() {
-            if last_segment.end_time < video_duration {
-                let silence_duration = video_duration - last_segment.end_time;
-                let silence_samples = vec![0.0f32; (self.sample_rate as f64 * silence_duration) as usize];
-                let silence_data = AudioData::new(silence_samples, self.sample_rate, self.channels);
-                
-                let silence_segment = AudioSegment::new(
-                    silence_data,
-                    last_segment.end_time,
-                    video_duration,
-                    String::new()
-                );
-                
-                result_track.add_segment(silence_segment);
-            }
+        // Тишина до первой реплики добавляется отдельным сегментом, чтобы
+        // сегменты трека непрерывно покрывали его с нуля
+        let first_start = result_track.segments.iter()
+            .map(|segment| segment.start_time)
+            .fold(f64::INFINITY, f64::min);
+        if first_start.is_finite() && first_start > 0.0 {
+            let silence_samples = vec![0.0f32; (self.sample_rate as f64 * first_start) as usize * self.channels as usize];
+            result_track.segments.insert(0, AudioSegment::new(
+                AudioData::new(silence_samples, self.sample_rate, self.channels),
+                0.0,
+                first_start,
+                String::new()
+            ));
         }
         
+        // Если аудио короче видео, добавляем тишину в конец с плавным затуханием
+        result_track.pad_with_silence(video_duration, self.tail_fade);
+        
         Ok(result_track)
     }
     
-    /// Сохраняет аудио трек в файл
-    pub async fn save_to_file(&self, audio_track: &AudioTrack, path: &str) -> Result<()> {
+    /// Добавляет тишину перед началом (pre-roll) и после окончания (post-roll) трека
+    ///
+    /// Трек должен начинаться с нуля (см. [`SyncCore::ensure_duration`]);
+    /// все сегменты, а также тайминги реплик и слов в отчете сдвигаются на
+    /// длительность pre-roll.
+    fn apply_roll(&self, audio_track: &AudioTrack) -> AudioTrack {
+        if self.pre_roll <= 0.0 && self.post_roll <= 0.0 {
+            return audio_track.clone();
+        }
+        
+        log_debug(&format!("Добавление тишины: {:.3}с в начале, {:.3}с в конце", self.pre_roll, self.post_roll));
+        let silence = |duration: f64| AudioData::new(
+            vec![0.0f32; (duration * self.sample_rate as f64).round() as usize * self.channels as usize],
+            self.sample_rate,
+            self.channels
+        );
+        
+        let mut result_track = AudioTrack::new(self.sample_rate, self.channels);
+        if self.pre_roll > 0.0 {
+            result_track.add_segment(AudioSegment::new(silence(self.pre_roll), 0.0, self.pre_roll, String::new()));
+        }
+        
+        for segment in &audio_track.segments {
+            let mut shifted = segment.clone();
+            shifted.start_time += self.pre_roll;
+            shifted.end_time += self.pre_roll;
+            result_track.add_segment(shifted);
+        }
+        
+        // Отчет описывает реплики на шкале результата, поэтому сдвигается вместе с треком
+        if self.pre_roll > 0.0 {
+            let mut report = self.report.lock().unwrap();
+            for segment in &mut report.segments {
+                segment.start_time += self.pre_roll;
+                segment.end_time += self.pre_roll;
+                for timing in segment.word_timings.iter_mut().flatten() {
+                    timing.start += self.pre_roll;
+                    timing.end += self.pre_roll;
+                }
+            }
+        }
+        
+        if self.post_roll > 0.0 {
+            let end_time = result_track.segments.iter().map(|segment| segment.end_time).fold(self.pre_roll, f64::max);
+            result_track.add_segment(AudioSegment::new(
+                silence(self.post_roll),
+                end_time,
+                end_time + self.post_roll,
+                String::new()
+            ));
+        }
+        
+        result_track
+    }
+    
+    /// Сравнивает фактическую длительность трека с ожидаемой `video_duration`
+    /// (длительность видео вместе с тишиной pre-roll и post-roll)
+    ///
+    /// Записывает разницу в отчет и предупреждает, если она превышает допуск.
+    fn verify_duration(&self, audio_track: &AudioTrack, video_duration: f64) {
+        let duration_error = audio_track.merged_duration() - video_duration;
+        self.report.lock().unwrap().duration_error = duration_error;
+        
+        if duration_error.abs() > self.duration_tolerance {
+            self.warn(DiagnosticKind::DurationMismatch, None, format!(
+                "Длительность результата отличается от видео на {:.4}с (допустимо {:.4}с)",
+                duration_error, self.duration_tolerance));
+        } else {
+            log_debug(&format!("Длительность результата отличается от видео на {:.6}с", duration_error));
+        }
+    }
+    
+    /// Сохраняет аудио трек в файл и возвращает путь сохраненного файла
+    ///
+    /// FLAC кодируется встроенным кодировщиком, MP3, OGG и Opus - с помощью
+    /// ffmpeg. Без ffmpeg или после его ошибки они кодируются встроенными
+    /// кодировщиками, если включены соответствующие функции: `mp3-encoder`
+    /// (LAME, см. [`SyncCore::with_mp3_bitrate`]), `vorbis` и `opus`. Если
+    /// встроенного кодировщика нет, трек сохраняется в WAV с тем же именем и
+    /// расширением `.wav` с предупреждением в отчете, а возвращаемый путь
+    /// указывает на этот файл (см. [`SyncCore::with_require_ffmpeg`]).
+    pub async fn save_to_file(&self, audio_track: &AudioTrack, path: &str) -> Result<String> {
+        self.save_to_file_limited(audio_track, path, None).await
+    }
+    
+    /// Сохраняет аудио трек в файл, ограничивая запуск ffmpeg семафором `encode_slots`
+    pub(crate) async fn save_to_file_limited(
+        &self,
+        audio_track: &AudioTrack,
+        path: &str,
+        encode_slots: Option<&Semaphore>,
+    ) -> Result<String> {
         // Объединяем все сегменты
-        let merged_audio = audio_track.merge()?;
+        let (merged_audio, overruns) = audio_track.merge_with_overruns(self.merge_policy)?;
+        for index in overruns {
+            let segment = &audio_track.segments[index];
+            self.warn(DiagnosticKind::MergeOverrun, None, format!(
+                "Сегмент '{}' ({:.3}-{:.3}с) не поместился в трек и обрезан",
+                segment.text, segment.start_time, segment.end_time));
+        }
         
         let num_segments = audio_track.segments.len();
         let total_samples = merged_audio.samples.len();
@@ -465,8 +2222,10 @@ impl SyncCore {
             return Err(Error::new(ErrorType::AudioProcessingError, "Аудио трек пуст или не содержит сэмплов"));
         }
         
+        self.report.lock().unwrap().headroom_db = Some(merged_audio.headroom_db());
+        
         // Определяем формат по расширению файла
-        let ext = Path::new(path).extension()
+        let mut ext = Path::new(path).extension()
             .and_then(|os_str| os_str.to_str())
             .unwrap_or("mp3")
             .to_lowercase();
@@ -474,7 +2233,32 @@ impl SyncCore {
         // Проверяем, есть ли у нас исходные MP3 данные, которые можно сохранить напрямую
         if ext == "mp3" && self.try_direct_mp3_save(audio_track, path).await? {
             log_info(&format!("Финальный аудио файл создан напрямую: {}, формат: MP3", path));
-            return Ok(());
+            return Ok(path.to_string());
+        }
+        
+        if ext == "mp3" {
+            validate_mp3_bitrate(self.mp3_bitrate)?;
+        }
+        
+        // Если для формата нет встроенного кодировщика, без ffmpeg сохраняем
+        // корректный WAV файл вместо файла с неверным расширением
+        let mut output_path = path.to_string();
+        if matches!(ext.as_str(), "mp3" | "ogg" | "opus")
+            && !self.ffmpeg_available()
+            && !Self::has_native_encoder(&ext)
+        {
+            if self.require_ffmpeg {
+                return Err(Error::new(
+                    ErrorType::AudioProcessingError,
+                    &format!("Для сохранения в формате {} требуется ffmpeg, но он не найден. \
+                        Установите ffmpeg или выберите формат WAV", ext.to_uppercase())
+                ));
+            }
+            output_path = Self::wav_fallback_path(path);
+            self.warn(DiagnosticKind::EncoderFallback, None, format!(
+                "ffmpeg не найден, аудио сохранено в формате WAV: {}. Установите ffmpeg для сохранения в формате {}",
+                output_path, ext.to_uppercase()));
+            ext = "wav".to_string();
         }
         
         // Всегда сначала сохраняем в WAV, так как с ним проще работать
@@ -500,48 +2284,100 @@ impl SyncCore {
         
         // Теперь конвертируем в нужный формат
         match ext.as_str() {
+            "mp3" | "ogg" | "opus" if !self.ffmpeg_available() => {
+                log_info(&format!("ffmpeg не найден, {} кодируется встроенным кодировщиком", ext.to_uppercase()));
+                self.write_native_encoded(&merged_audio, path, &ext).await?;
+            },
             "mp3" => {
                 log_debug("Конвертация WAV в MP3...");
                 
-                // Пробуем использовать ffmpeg
-                let result = self.convert_with_ffmpeg(&temp_wav_path, path, "mp3", &[
-                    "-codec:a", "libmp3lame", 
-                    "-q:a", "2", // Высокое качество (0-9, где 0 - лучшее)
-                    "-b:a", "192k" // Битрейт
-                ]);
+                // Постоянный битрейт, как у встроенного кодировщика
+                let mut codec_args = vec![
+                    "-codec:a".to_string(), "libmp3lame".to_string(),
+                    "-b:a".to_string(), format!("{}k", self.mp3_bitrate),
+                ];
+                if !self.output_metadata.is_empty() {
+                    codec_args.extend(["-id3v2_version".to_string(), "3".to_string()]);
+                    codec_args.extend(self.output_metadata.ffmpeg_args());
+                }
+                let codec_args: Vec<&str> = codec_args.iter().map(String::as_str).collect();
+                let result = self.convert_with_ffmpeg(&temp_wav_path, path, "mp3", &codec_args, encode_slots).await;
                 
                 match result {
                     Ok(_) => log_debug(&format!("Файл MP3 успешно создан с помощью ffmpeg: {}", path)),
-                    Err(e) => {
-                        log_warning(&format!("Ошибка ffmpeg: {}, пробую резервный метод", e));
-                        self.convert_with_symphonia(&temp_wav_path, path, "mp3").await?;
-                    }
+                    Err(e) if Self::has_native_encoder("mp3") => {
+                        self.warn(DiagnosticKind::EncoderFallback, None, format!(
+                            "Ошибка ffmpeg: {}, MP3 закодирован встроенным кодировщиком", e));
+                        self.write_native_encoded(&merged_audio, path, "mp3").await?;
+                    },
+                    Err(e) => output_path = self.save_wav_fallback(&temp_wav_path, path, &e).await?,
                 }
             },
             "wav" => {
                 // WAV уже создан, просто переименовываем
-                if temp_wav_path != path {
-                    tokio::fs::copy(&temp_wav_path, path).await
+                if temp_wav_path != output_path {
+                    tokio::fs::copy(&temp_wav_path, &output_path).await
                         .map_err(|e| Error::new(ErrorType::Io, &format!("Ошибка при копировании WAV файла: {}", e)))?;
-                    log_debug(&format!("WAV файл скопирован в: {}", path));
+                    log_debug(&format!("WAV файл скопирован в: {}", output_path));
                 }
             },
             "ogg" => {
                 log_debug("Конвертация WAV в OGG...");
                 
                 // Пробуем использовать ffmpeg
-                let result = self.convert_with_ffmpeg(&temp_wav_path, path, "ogg", &[
-                    "-codec:a", "libvorbis",
-                    "-q:a", "6" // Качество (0-10, где 10 - лучшее)
-                ]);
+                let mut codec_args = vec![
+                    "-codec:a".to_string(), "libvorbis".to_string(),
+                    "-q:a".to_string(), "6".to_string(), // Качество (0-10, где 10 - лучшее)
+                ];
+                codec_args.extend(self.output_metadata.ffmpeg_args());
+                let codec_args: Vec<&str> = codec_args.iter().map(String::as_str).collect();
+                let result = self.convert_with_ffmpeg(&temp_wav_path, path, "ogg", &codec_args, encode_slots).await;
                 
                 match result {
                     Ok(_) => log_debug(&format!("Файл OGG успешно создан с помощью ffmpeg: {}", path)),
-                    Err(e) => {
-                        log_warning(&format!("Ошибка ffmpeg: {}, пробую резервный метод", e));
-                        self.convert_with_symphonia(&temp_wav_path, path, "ogg").await?;
-                    }
+                    Err(e) if Self::has_native_encoder("ogg") => {
+                        self.warn(DiagnosticKind::EncoderFallback, None, format!(
+                            "Ошибка ffmpeg: {}, OGG закодирован встроенным кодировщиком", e));
+                        self.write_native_encoded(&merged_audio, path, "ogg").await?;
+                    },
+                    Err(e) => output_path = self.save_wav_fallback(&temp_wav_path, path, &e).await?,
+                }
+            },
+            "opus" => {
+                log_debug("Конвертация WAV в Opus...");
+                
+                // Кодировщик Opus работает с частотой 48 кГц
+                let mut codec_args = vec![
+                    "-codec:a".to_string(), "libopus".to_string(),
+                    "-b:a".to_string(), "96k".to_string(),
+                    "-ar".to_string(), "48000".to_string(),
+                ];
+                codec_args.extend(self.output_metadata.ffmpeg_args());
+                let codec_args: Vec<&str> = codec_args.iter().map(String::as_str).collect();
+                let result = self.convert_with_ffmpeg(&temp_wav_path, path, "opus", &codec_args, encode_slots).await;
+                
+                match result {
+                    Ok(_) => log_debug(&format!("Файл Opus успешно создан с помощью ffmpeg: {}", path)),
+                    Err(e) if Self::has_native_encoder("opus") => {
+                        self.warn(DiagnosticKind::EncoderFallback, None, format!(
+                            "Ошибка ffmpeg: {}, Opus закодирован встроенным кодировщиком", e));
+                        self.write_native_encoded(&merged_audio, path, "opus").await?;
+                    },
+                    Err(e) => output_path = self.save_wav_fallback(&temp_wav_path, path, &e).await?,
+                }
+            },
+            "flac" => {
+                // FLAC кодируется встроенным кодировщиком без ffmpeg
+                if merged_audio.channels > 8 {
+                    return Err(Error::new(
+                        ErrorType::AudioProcessingError,
+                        &format!("FLAC поддерживает не более 8 каналов, получено {}", merged_audio.channels)
+                    ));
                 }
+                let (flac_data, _) = merged_audio.to_flac_bytes_with(self.dither, &self.output_metadata);
+                tokio::fs::write(path, &flac_data).await
+                    .map_err(|e| Error::new(ErrorType::Io, &format!("Ошибка при записи FLAC файла: {}", e)))?;
+                log_debug(&format!("Файл FLAC создан: {}, {} байт", path, flac_data.len()));
             },
             _ => {
                 return Err(Error::new(
@@ -558,13 +2394,13 @@ impl SyncCore {
         }
         
         // Проверяем, что выходной файл существует и содержит данные
-        match tokio::fs::metadata(path).await {
+        match tokio::fs::metadata(&output_path).await {
             Ok(metadata) => {
                 let file_size = metadata.len();
-                log_info(&format!("Финальный аудио файл создан: {}, размер: {} байт", path, file_size));
+                log_info(&format!("Финальный аудио файл создан: {}, размер: {} байт", output_path, file_size));
                 
                 if file_size <= 100 { // Подозрительно маленький файл
-                    log_warning(&format!("Финальный файл подозрительно мал: {} байт", file_size));
+                    self.warn(DiagnosticKind::TinyOutputFile, None, format!("Финальный файл подозрительно мал: {} байт", file_size));
                 }
             },
             Err(e) => {
@@ -575,11 +2411,72 @@ impl SyncCore {
             }
         }
         
+        Ok(output_path)
+    }
+    
+    /// Кодирует аудио встроенным кодировщиком формата `ext` (MP3, OGG или
+    /// Opus) и записывает в `path`
+    ///
+    /// Кодирование выполняется в пуле блокирующих потоков tokio.
+    async fn write_native_encoded(&self, audio: &AudioData, path: &str, ext: &str) -> Result<()> {
+        let audio = audio.clone();
+        let format = ext.to_string();
+        let bitrate = self.mp3_bitrate;
+        let metadata = self.output_metadata.clone();
+        let encode = move || match format.as_str() {
+            #[cfg(feature = "vorbis")]
+            "ogg" => audio.to_ogg_bytes_with(&metadata),
+            #[cfg(feature = "opus")]
+            "opus" => audio.to_opus_bytes_with(&metadata),
+            _ => audio.to_mp3_bytes_with(bitrate, &metadata),
+        };
+        let data = tokio::task::spawn_blocking(encode)
+            .await
+            .map_err(|e| Error::new(ErrorType::AudioProcessingError, &format!("Ошибка кодирования {}: {}", ext.to_uppercase(), e)))??;
+        tokio::fs::write(path, &data).await
+            .map_err(|e| Error::new(ErrorType::Io, &format!("Ошибка при записи {} файла: {}", ext.to_uppercase(), e)))?;
+        log_debug(&format!("Файл {} создан встроенным кодировщиком: {}, {} байт", ext.to_uppercase(), path, data.len()));
         Ok(())
     }
     
+    /// Проверяет, собран ли встроенный кодировщик для формата `ext`
+    fn has_native_encoder(ext: &str) -> bool {
+        (ext == "mp3" && cfg!(feature = "mp3-encoder"))
+            || (ext == "ogg" && cfg!(feature = "vorbis"))
+            || (ext == "opus" && cfg!(feature = "opus"))
+    }
+    
+    /// Возвращает путь WAV файла, сохраняемого вместо `path`, если ffmpeg недоступен
+    fn wav_fallback_path(path: &str) -> String {
+        Path::new(path).with_extension("wav").to_string_lossy().into_owned()
+    }
+    
+    /// Сохраняет временный WAV файл рядом с `path` после ошибки кодирования ffmpeg
+    ///
+    /// Возвращает путь сохраненного WAV файла.
+    async fn save_wav_fallback(&self, temp_wav_path: &str, path: &str, error: &std::io::Error) -> Result<String> {
+        let wav_path = Self::wav_fallback_path(path);
+        self.warn(DiagnosticKind::EncoderFallback, None, format!(
+            "Ошибка ffmpeg: {}, аудио сохранено в формате WAV: {}", error, wav_path));
+        tokio::fs::copy(temp_wav_path, &wav_path).await
+            .map_err(|e| Error::new(ErrorType::Io, &format!("Ошибка при копировании WAV файла: {}", e)))?;
+        Ok(wav_path)
+    }
+
+    
     /// Конвертирует аудио файл с помощью ffmpeg
-    fn convert_with_ffmpeg(&self, input_path: &str, output_path: &str, format: &str, codec_args: &[&str]) -> std::io::Result<()> {
+    ///
+    /// Если передан `encode_slots`, процесс ffmpeg запускается только после
+    /// получения разрешения семафора, что ограничивает число одновременных
+    /// процессов кодирования.
+    async fn convert_with_ffmpeg(
+        &self,
+        input_path: &str,
+        output_path: &str,
+        format: &str,
+        codec_args: &[&str],
+        encode_slots: Option<&Semaphore>,
+    ) -> std::io::Result<()> {
         log_debug(&format!("Запуск ffmpeg для конвертации в {}: {} -> {}", format, input_path, output_path));
         
         // Базовые аргументы
@@ -595,11 +2492,31 @@ impl SyncCore {
         // Добавляем выходной файл
         args.push(output_path);
         
+        self.run_ffmpeg(&args, encode_slots).await
+    }
+    
+    /// Запускает ffmpeg с аргументами и возвращает ошибку с выводом stderr при неудаче
+    ///
+    /// Если передан `encode_slots`, процесс запускается только после получения
+    /// разрешения семафора.
+    async fn run_ffmpeg(&self, args: &[&str], encode_slots: Option<&Semaphore>) -> std::io::Result<()> {
         log_debug(&format!("Команда ffmpeg: ffmpeg {}", args.join(" ")));
         
-        let output = Command::new("ffmpeg")
-            .args(&args)
-            .output()?;
+        let _permit = match encode_slots {
+            Some(slots) => Some(slots.acquire().await
+                .map_err(std::io::Error::other)?),
+            None => None,
+        };
+        
+        // Процесс ffmpeg блокирует поток, поэтому запускаем его вне асинхронного исполнителя
+        let runner = self.command_runner.clone();
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        let output = tokio::task::spawn_blocking(move || {
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            runner.run("ffmpeg", &args)
+        })
+        .await
+        .map_err(std::io::Error::other)??;
         
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -610,30 +2527,16 @@ impl SyncCore {
         Ok(())
     }
     
-    /// Конвертирует аудио файл с помощью библиотеки symphonia
-    async fn convert_with_symphonia(&self, input_path: &str, output_path: &str, format: &str) -> Result<()> {
-        log_debug(&format!("Использую библиотеку symphonia для конвертации в {}: {} -> {}", format, input_path, output_path));
-        
-        // Вместо этого используем прямую копию WAV файла с предупреждением
-        
-        log_warning(&format!("Полная конвертация в {} с помощью библиотеки не реализована", format));
-        log_warning("Копирую WAV файл с новым расширением как временное решение");
-        log_warning("Для корректного перекодирования аудио установите ffmpeg");
-        
-        tokio::fs::copy(input_path, output_path).await
-            .map_err(|e| Error::new(ErrorType::Io, &format!("Ошибка при копировании файла: {}", e)))?;
-        
-        Ok(())
-    }
-
     /// Записывает данные аудио в формате WAV
+    ///
+    /// Сэмплы многоканального аудио должны быть перемежены по кадрам
+    /// (`L R L R ...` для стерео) и записываются в файл в том же порядке.
+    /// Если количество сэмплов не кратно количеству каналов, последний кадр
+    /// был бы неполным, поэтому возвращается ошибка и файл не создается.
     async fn write_wav_file(&self, audio_data: &AudioData, path: &str) -> Result<()> {
         log_debug(&format!("Запись WAV файла: {}, {} сэмплов, {} каналов, {}Hz", 
             path, audio_data.samples.len(), audio_data.channels, audio_data.sample_rate));
         
-        let mut file = File::create(path).await
-            .map_err(|e| Error::new(ErrorType::Io, &format!("Не удалось создать WAV файл: {}", e)))?;
-        
         let total_samples = audio_data.samples.len();
         let num_channels = audio_data.channels;
         let sample_rate = audio_data.sample_rate;
@@ -643,39 +2546,21 @@ impl SyncCore {
             return Err(Error::new(ErrorType::AudioProcessingError, "Попытка записать пустые аудио данные"));
         }
         
+        if num_channels == 0 || total_samples % num_channels as usize != 0 {
+            return Err(Error::new(
+                ErrorType::AudioProcessingError,
+                &format!("Количество сэмплов {} не кратно количеству каналов {}: последний кадр неполный",
+                    total_samples, num_channels)
+            ));
+        }
+        
+        let mut file = File::create(path).await
+            .map_err(|e| Error::new(ErrorType::Io, &format!("Не удалось создать WAV файл: {}", e)))?;
+        
         // Создаем заголовок WAV
         let bytes_per_sample = 2; // 16-bit PCM = 2 байта на сэмпл
         let data_size = (total_samples * bytes_per_sample) as u32;
-        let file_size = data_size + 36; // 44 байта заголовка - 8 байтов
-        
-        // Создаем подробный заголовок WAV
-        let mut header = Vec::new();
-        
-        // RIFF chunk
-        header.extend_from_slice(b"RIFF");
-        header.extend_from_slice(&file_size.to_le_bytes());
-        header.extend_from_slice(b"WAVE");
-        
-        // fmt subchunk
-        header.extend_from_slice(b"fmt ");
-        header.extend_from_slice(&16u32.to_le_bytes()); // размер подчанка fmt (16 байтов)
-        header.extend_from_slice(&1u16.to_le_bytes()); // аудио формат (1 = PCM)
-        header.extend_from_slice(&num_channels.to_le_bytes()); // количество каналов
-        header.extend_from_slice(&sample_rate.to_le_bytes()); // частота дискретизации
-        
-        // Байт рейт = SampleRate * NumChannels * BitsPerSample / 8
-        let byte_rate = sample_rate * num_channels as u32 * 16 / 8;
-        header.extend_from_slice(&byte_rate.to_le_bytes());
-        
-        // Блок выравнивания = NumChannels * BitsPerSample / 8
-        let block_align = num_channels as u16 * 16 / 8;
-        header.extend_from_slice(&block_align.to_le_bytes());
-        
-        header.extend_from_slice(&16u16.to_le_bytes()); // биты на сэмпл
-        
-        // data subchunk
-        header.extend_from_slice(b"data");
-        header.extend_from_slice(&data_size.to_le_bytes());
+        let header = pcm16_wav_header(sample_rate, num_channels, data_size);
         
         // Записываем заголовок
         file.write_all(&header).await
@@ -683,15 +2568,13 @@ impl SyncCore {
         
         log_debug(&format!("Записан заголовок WAV: {} байт", header.len()));
         
-        // Конвертируем float сэмплы в 16-bit PCM
-        let mut pcm_data = Vec::with_capacity(total_samples * bytes_per_sample);
+        // Конвертируем float сэмплы в 16-bit PCM, считая ограниченные сэмплы
+        let (pcm_data, clipped_samples) = audio_data.to_pcm16_le_dithered(self.dither);
+        self.report.lock().unwrap().clipped_samples = clipped_samples;
         
-        for &sample in &audio_data.samples {
-            // Преобразуем float в int16, важно нормализовать значения правильно
-            let pcm_sample = (sample.max(-1.0).min(1.0) * 32767.0) as i16;
-            
-            // Записываем в порядке little-endian (младший байт, затем старший)
-            pcm_data.extend_from_slice(&pcm_sample.to_le_bytes());
+        if clipped_samples > 0 {
+            self.warn(DiagnosticKind::Clipping, None, format!("При экспорте ограничено {} сэмплов из {} ({:.2}%), увеличьте запас громкости",
+                clipped_samples, total_samples, clipped_samples as f64 * 100.0 / total_samples as f64));
         }
         
         // Записываем PCM данные
@@ -719,7 +2602,7 @@ impl SyncCore {
     }
 
     /// Проверяет аудиоданные TTS перед использованием
-    async fn validate_tts_data(&self, file_path: &str) -> Result<()> {
+    async fn validate_tts_data(&self, file_path: &str, index: usize) -> Result<()> {
         log_debug(&format!("Проверка аудиофайла: {}", file_path));
         
         // Читаем часть файла для проверки
@@ -757,7 +2640,7 @@ impl SyncCore {
         } else if is_ogg {
             log_debug("Файл определен как OGG");
         } else {
-            log_warning("Неизвестный формат файла");
+            self.warn(DiagnosticKind::InvalidTtsData, Some(index), "Неизвестный формат файла".to_string());
             // Вывод первых байтов для отладки
             let hex_display: Vec<String> = header.iter().take(16).map(|b| format!("{:02X}", b)).collect();
             log_debug(&format!("Первые 16 байт: {}", hex_display.join(" ")));
@@ -768,8 +2651,13 @@ impl SyncCore {
 
     /// Пытается сохранить MP3 файл напрямую из исходных сегментов, если они в MP3 формате
     async fn try_direct_mp3_save(&self, audio_track: &AudioTrack, path: &str) -> Result<bool> {
-        // Проверяем, есть ли у нас доступ к исходным MP3 данным в сегментах
-        let has_raw_mp3 = audio_track.segments.iter().any(|segment| {
+        // Проверяем, есть ли у нас доступ к исходным MP3 данным во всех сегментах
+        // реплик (сегменты тишины вместо ошибок синтеза хранят WAV); паузы
+        // исходных данных не имеют
+        let mut cue_segments = audio_track.segments.iter()
+            .filter(|segment| segment.cue.is_some() || !segment.text.is_empty())
+            .peekable();
+        let has_raw_mp3 = cue_segments.peek().is_some() && cue_segments.all(|segment| {
             // Здесь проверка наличия исходных MP3 данных в сегменте
             // В текущей реализации мы используем эвристику - проверяем первые байты
             if let Some(original_data) = self.get_raw_segment_data(segment) {
@@ -793,6 +2681,12 @@ impl SyncCore {
         // Собираем все MP3 сегменты в один файл
         let mut output_file = tokio::fs::File::create(path).await
             .map_err(|e| Error::new(ErrorType::Io, &format!("Не удалось создать выходной файл: {}", e)))?;
+        
+        // Теги ID3 записываются перед первым MP3 кадром
+        if !self.output_metadata.is_empty() {
+            output_file.write_all(&self.output_metadata.id3v2_tag()).await
+                .map_err(|e| Error::new(ErrorType::Io, &format!("Ошибка записи тега ID3: {}", e)))?;
+        }
 
         // Копируем данные каждого сегмента напрямую в выходной файл
         for (idx, segment) in audio_track.segments.iter().enumerate() {
@@ -810,7 +2704,7 @@ impl SyncCore {
             .map_err(|e| Error::new(ErrorType::Io, &format!("Не удалось получить метаданные файла: {}", e)))?;
         
         if file_metadata.len() == 0 {
-            log_warning("Созданный MP3 файл пуст, возвращаюсь к стандартному методу");
+            self.warn(DiagnosticKind::EncoderFallback, None, "Созданный MP3 файл пуст, возвращаюсь к стандартному методу".to_string());
             tokio::fs::remove_file(path).await.ok(); // Удаляем пустой файл
             return Ok(false);
         }
@@ -1,3 +1,14 @@
+pub mod batch;
+pub mod command;
 pub mod core;
+pub mod pacing;
+pub mod report;
+pub(crate) mod text_cache;
+pub mod tradeoff;
 
-pub use core::SyncCore;
+pub use batch::{BatchJob, BatchOptions, SyncJob};
+pub use command::{CommandRunner, SystemCommandRunner};
+pub use core::{CueRangeBehavior, DurationLimitBehavior, MuxMode, SpeechAnchor, SyncCore};
+pub use pacing::PunctuationPauses;
+pub use report::{Diagnostic, DiagnosticKind, GapCompression, SyncReport, SegmentReport};
+pub use tradeoff::{CompressionSplit, split_compression};
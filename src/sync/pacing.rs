@@ -0,0 +1,104 @@
+//! Паузы на знаках препинания при синтезе речи.
+
+/// Длительности пауз в секундах, вставляемых после знаков препинания
+///
+/// Реплика разбивается на фразы по знакам препинания, за которыми следует
+/// пробел; фразы синтезируются по отдельности, а между ними вставляется
+/// тишина заданной длительности. Паузы растягиваются или сжимаются вместе
+/// с речью при подгонке длительности сегмента.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PunctuationPauses {
+    /// Пауза после запятой
+    pub comma: f64,
+    /// Пауза после точки с запятой, двоеточия и тире
+    pub semicolon: f64,
+    /// Пауза после точки, восклицательного и вопросительного знаков, многоточия
+    pub period: f64,
+}
+
+impl Default for PunctuationPauses {
+    fn default() -> Self {
+        Self {
+            comma: 0.15,
+            semicolon: 0.25,
+            period: 0.35,
+        }
+    }
+}
+
+impl PunctuationPauses {
+    /// Возвращает длительность паузы после знака `c` (None - не знак паузы)
+    pub fn pause_after(&self, c: char) -> Option<f64> {
+        match c {
+            ',' => Some(self.comma),
+            ';' | ':' | '—' | '–' => Some(self.semicolon),
+            '.' | '!' | '?' | '…' => Some(self.period),
+            _ => None,
+        }
+    }
+
+    /// Разбивает текст на фразы с паузами после них
+    ///
+    /// Знаки препинания остаются в тексте фраз. Для последовательности знаков
+    /// (например, `?!` или `...`) используется наибольшая пауза. После
+    /// последней фразы пауза не добавляется.
+    pub fn split_phrases(&self, text: &str) -> Vec<(String, f64)> {
+        let mut phrases = Vec::new();
+        let mut current = String::new();
+        let mut pending_pause: Option<f64> = None;
+
+        for c in text.chars() {
+            if c.is_whitespace() {
+                if let Some(pause) = pending_pause.take() {
+                    let phrase = current.trim().to_string();
+                    if !phrase.is_empty() {
+                        phrases.push((phrase, pause));
+                    }
+                    current.clear();
+                    continue;
+                }
+            } else {
+                pending_pause = self.pause_after(c)
+                    .map(|pause| pending_pause.map_or(pause, |p| p.max(pause)));
+            }
+            current.push(c);
+        }
+
+        let phrase = current.trim().to_string();
+        if !phrase.is_empty() {
+            phrases.push((phrase, 0.0));
+        } else if let Some(last) = phrases.last_mut() {
+            last.1 = 0.0;
+        }
+
+        phrases
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_phrases() {
+        let pauses = PunctuationPauses { comma: 0.1, semicolon: 0.2, period: 0.3 };
+        let phrases = pauses.split_phrases("Well, I think; maybe... Really?! Yes.");
+
+        assert_eq!(phrases, vec![
+            ("Well,".to_string(), 0.1),
+            ("I think;".to_string(), 0.2),
+            ("maybe...".to_string(), 0.3),
+            ("Really?!".to_string(), 0.3),
+            ("Yes.".to_string(), 0.0),
+        ]);
+    }
+
+    #[test]
+    fn test_split_phrases_ignores_punctuation_inside_words() {
+        let pauses = PunctuationPauses::default();
+
+        assert_eq!(pauses.split_phrases("Pi is 3.14 today"), vec![("Pi is 3.14 today".to_string(), 0.0)]);
+        assert_eq!(pauses.split_phrases("Trailing, "), vec![("Trailing,".to_string(), 0.0)]);
+        assert!(pauses.split_phrases("  ").is_empty());
+    }
+}
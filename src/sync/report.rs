@@ -0,0 +1,153 @@
+use crate::tts::WordTiming;
+
+/// Отчет о синхронизации отдельного сегмента
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentReport {
+    /// Порядковый номер субтитра
+    pub index: usize,
+    /// Идентификатор субтитра из VTT файла (если указан)
+    pub id: Option<String>,
+    /// Текст субтитра
+    pub text: String,
+    /// Время начала субтитра в секундах (с учетом тишины pre-roll в начале трека)
+    pub start_time: f64,
+    /// Время окончания субтитра в секундах (с учетом тишины pre-roll в начале трека)
+    pub end_time: f64,
+    /// Длительность сгенерированной речи в секундах
+    pub tts_duration: f64,
+    /// Длительность речи после корректировки темпа в секундах
+    pub adjusted_duration: f64,
+    /// Коэффициент изменения длительности речи (`adjusted_duration / tts_duration`,
+    /// меньше 1 - речь ускорена)
+    pub stretch_factor: f64,
+    /// Тайминги слов в секундах итогового трека, если провайдер их вернул
+    pub word_timings: Option<Vec<WordTiming>>,
+}
+
+impl SegmentReport {
+    /// Возвращает целевую длительность сегмента в секундах
+    pub fn target_duration(&self) -> f64 {
+        self.end_time - self.start_time
+    }
+}
+
+/// Сокращенный длинный промежуток между репликами
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GapCompression {
+    /// Время начала промежутка в исходной шкале (по субтитрам) в секундах
+    pub original_start: f64,
+    /// Исходная длительность промежутка в секундах
+    pub original_duration: f64,
+    /// Длительность промежутка в результате в секундах
+    pub compressed_duration: f64,
+}
+
+impl GapCompression {
+    /// Возвращает, на сколько секунд сокращен промежуток
+    pub fn removed_duration(&self) -> f64 {
+        self.original_duration - self.compressed_duration
+    }
+}
+
+/// Вид предупреждения, возникшего при синхронизации
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// Подозрительно малый размер данных от TTS провайдера
+    TinyTtsData,
+    /// Данные TTS провайдера не удалось распознать или декодировать
+    InvalidTtsData,
+    /// Синтез реплики завершился ошибкой, вместо речи вставлена тишина
+    SegmentFailed,
+    /// Субтитр выходит за пределы видео и обрезан или пропущен
+    CueOutOfRange,
+    /// Речь реплики подогнана под минимальную длительность сегмента
+    ShortSegment,
+    /// Сегмент не поместился в трек при объединении и обрезан
+    MergeOverrun,
+    /// Трек обрезан по максимальной длительности результата
+    Truncation,
+    /// Сэмплы ограничены при экспорте в PCM
+    Clipping,
+    /// ffmpeg недоступен или кодирование не удалось, трек сохранен в WAV
+    /// или закодирован встроенным кодировщиком
+    EncoderFallback,
+    /// Подозрительно малый размер выходного файла
+    TinyOutputFile,
+    /// Длительность результата отличается от видео больше допустимого
+    DurationMismatch,
+}
+
+/// Предупреждение, собранное во время синхронизации
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// Вид предупреждения
+    pub kind: DiagnosticKind,
+    /// Порядковый номер субтитра, к которому относится предупреждение
+    pub segment: Option<usize>,
+    /// Текст предупреждения, совпадающий с сообщением в логе
+    pub message: String,
+}
+
+/// Отчет о синхронизации
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncReport {
+    /// Отчеты по сегментам в порядке субтитров
+    pub segments: Vec<SegmentReport>,
+    /// Количество сэмплов, ограниченных при преобразовании в PCM во время экспорта
+    pub clipped_samples: usize,
+    /// Запас громкости экспортированного аудио в дБ (см. [`AudioData::headroom_db`])
+    /// или `None`, если экспорт не выполнялся
+    ///
+    /// [`AudioData::headroom_db`]: crate::audio::AudioData::headroom_db
+    pub headroom_db: Option<f32>,
+    /// Интервалы речи `(начало, конец)` в секундах итогового трека
+    pub speech_regions: Vec<(f64, f64)>,
+    /// Положение реплик в объединенном буфере итогового трека:
+    /// `(номер субтитра, первый сэмпл, конец)`, см. [`AudioTrack::cue_sample_map`]
+    ///
+    /// [`AudioTrack::cue_sample_map`]: crate::audio::AudioTrack::cue_sample_map
+    pub sample_map: Vec<(usize, usize, usize)>,
+    /// Сокращенные промежутки между репликами в порядке времени
+    pub gap_compressions: Vec<GapCompression>,
+    /// Предупреждения в порядке возникновения
+    pub diagnostics: Vec<Diagnostic>,
+    /// Разница между длительностью результата и видео (вместе с тишиной
+    /// pre-roll и post-roll) в секундах (положительная - результат длиннее)
+    pub duration_error: f64,
+}
+
+impl SyncReport {
+    /// Создает новый пустой отчет
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Проверяет, есть ли в отчете предупреждение указанного вида
+    pub fn has_diagnostic(&self, kind: DiagnosticKind) -> bool {
+        self.diagnostics.iter().any(|diagnostic| diagnostic.kind == kind)
+    }
+
+    /// Переводит время исходной шкалы (субтитров) во время результата
+    ///
+    /// Учитывает сокращенные промежутки; время внутри сокращенного промежутка
+    /// не выходит за его конец в результате.
+    pub fn to_output_time(&self, original_time: f64) -> f64 {
+        let mut removed = 0.0;
+        for gap in &self.gap_compressions {
+            if original_time <= gap.original_start {
+                break;
+            }
+            let offset = original_time - gap.original_start;
+            if offset < gap.original_duration {
+                return gap.original_start - removed + offset.min(gap.compressed_duration);
+            }
+            removed += gap.removed_duration();
+        }
+        original_time - removed
+    }
+
+    /// Находит отчет сегмента по идентификатору субтитра
+    pub fn segment_by_id(&self, id: &str) -> Option<&SegmentReport> {
+        self.segments.iter().find(|segment| segment.id.as_deref() == Some(id))
+    }
+}
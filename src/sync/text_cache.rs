@@ -0,0 +1,81 @@
+//! Кэш синтезированных сегментов по тексту реплики.
+
+use std::collections::HashMap;
+
+/// Вычисляет хэш текста (FNV-1a) для ключа кэша
+pub(crate) fn text_hash(text: &str) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    text.bytes().fold(FNV_OFFSET, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+/// Кэш значений по тексту с заранее вычисленным хэшем
+///
+/// Ключом служит хэш текста, поэтому длинные тексты не хэшируются повторно
+/// при каждом обращении. При совпадении хэшей разных текстов значения
+/// хранятся в одной корзине и различаются сравнением текста.
+#[derive(Debug)]
+pub(crate) struct TextCache<V> {
+    buckets: HashMap<u64, Vec<(String, V)>>,
+}
+
+impl<V> TextCache<V> {
+    /// Создает пустой кэш
+    pub(crate) fn new() -> Self {
+        Self {
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// Возвращает значение для текста `text` с хэшем `hash`
+    pub(crate) fn get(&self, hash: u64, text: &str) -> Option<&V> {
+        self.buckets
+            .get(&hash)?
+            .iter()
+            .find(|(cached_text, _)| cached_text == text)
+            .map(|(_, value)| value)
+    }
+
+    /// Сохраняет значение для текста `text` с хэшем `hash`
+    pub(crate) fn insert(&mut self, hash: u64, text: &str, value: V) {
+        let bucket = self.buckets.entry(hash).or_default();
+        match bucket.iter_mut().find(|(cached_text, _)| cached_text == text) {
+            Some((_, cached)) => *cached = value,
+            None => bucket.push((text.to_string(), value)),
+        }
+    }
+
+    /// Возвращает количество сохраненных текстов
+    pub(crate) fn len(&self) -> usize {
+        self.buckets.values().map(Vec::len).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_hash_is_stable() {
+        assert_eq!(text_hash("Hello"), text_hash("Hello"));
+        assert_ne!(text_hash("Hello"), text_hash("Hello!"));
+    }
+
+    #[test]
+    fn test_cache_separates_colliding_texts() {
+        let mut cache = TextCache::new();
+        cache.insert(42, "first", 1);
+        cache.insert(42, "second", 2);
+
+        assert_eq!(cache.get(42, "first"), Some(&1));
+        assert_eq!(cache.get(42, "second"), Some(&2));
+        assert_eq!(cache.get(42, "third"), None);
+        assert_eq!(cache.get(7, "first"), None);
+        assert_eq!(cache.len(), 2);
+
+        cache.insert(42, "first", 3);
+        assert_eq!(cache.get(42, "first"), Some(&3));
+        assert_eq!(cache.len(), 2);
+    }
+}
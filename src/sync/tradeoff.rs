@@ -0,0 +1,90 @@
+/// Минимальная скорость синтеза речи
+const MIN_SPEED: f64 = 1.0;
+/// Максимальная скорость синтеза речи (ограничение OpenAI TTS)
+const MAX_SPEED: f64 = 4.0;
+
+/// Распределение необходимого сжатия между скоростью синтеза и растяжением по времени
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompressionSplit {
+    /// Множитель скорости синтеза речи
+    pub speed: f64,
+    /// Оставшееся сжатие, выполняемое изменением темпа
+    pub stretch: f64,
+}
+
+impl CompressionSplit {
+    /// Возвращает общее сжатие (произведение скорости и растяжения)
+    pub fn total(&self) -> f64 {
+        self.speed * self.stretch
+    }
+}
+
+/// Распределяет сжатие `compression` (отношение длительности речи к целевой)
+/// между скоростью синтеза и растяжением по времени
+///
+/// `intelligibility_bias` задает компромисс: 0.0 - все сжатие за счет ускорения
+/// синтеза (точное попадание в тайминг без артефактов растяжения), 1.0 - синтез
+/// всегда на естественной скорости, а сжатие выполняется изменением темпа.
+/// Промежуточные значения делят сжатие в логарифмической шкале, поэтому
+/// произведение скорости и растяжения всегда равно исходному сжатию.
+/// Растяжение (сжатие меньше 1.0) всегда выполняется изменением темпа.
+pub fn split_compression(compression: f64, intelligibility_bias: f32) -> CompressionSplit {
+    if !compression.is_finite() || compression <= 1.0 {
+        return CompressionSplit {
+            speed: 1.0,
+            stretch: if compression.is_finite() && compression > 0.0 { compression } else { 1.0 },
+        };
+    }
+
+    let bias = intelligibility_bias.clamp(0.0, 1.0) as f64;
+    let speed = compression.powf(1.0 - bias).clamp(MIN_SPEED, MAX_SPEED);
+
+    CompressionSplit {
+        speed,
+        stretch: compression / speed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_shifts_with_bias() {
+        let fast = split_compression(1.6, 0.0);
+        let balanced = split_compression(1.6, 0.5);
+        let natural = split_compression(1.6, 1.0);
+
+        // Без смещения все сжатие за счет скорости синтеза
+        assert!((fast.speed - 1.6).abs() < 1e-9);
+        assert!((fast.stretch - 1.0).abs() < 1e-9);
+
+        // Посередине сжатие делится поровну в логарифмической шкале
+        assert!((balanced.speed - 1.6f64.sqrt()).abs() < 1e-9);
+        assert!((balanced.stretch - 1.6f64.sqrt()).abs() < 1e-9);
+
+        // При максимальном смещении синтез на естественной скорости
+        assert!((natural.speed - 1.0).abs() < 1e-9);
+        assert!((natural.stretch - 1.6).abs() < 1e-9);
+
+        // Чем больше смещение, тем меньше скорость и больше растяжение
+        assert!(fast.speed > balanced.speed && balanced.speed > natural.speed);
+        assert!(fast.stretch < balanced.stretch && balanced.stretch < natural.stretch);
+        for split in [fast, balanced, natural] {
+            assert!((split.total() - 1.6).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_split_expansion_and_speed_limit() {
+        // Растяжение не требует изменения скорости синтеза
+        let expansion = split_compression(0.8, 0.0);
+        assert_eq!(expansion.speed, 1.0);
+        assert!((expansion.stretch - 0.8).abs() < 1e-9);
+
+        // Скорость ограничена сверху, остаток уходит в изменение темпа
+        let extreme = split_compression(6.0, 0.0);
+        assert_eq!(extreme.speed, 4.0);
+        assert!((extreme.stretch - 1.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,215 @@
+//! Объединение одновременных запросов синтеза одинакового текста.
+
+use crate::error::Result;
+use crate::logging::log_debug;
+use crate::tts::{TtsProvider, TtsSegment};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+
+/// Ключ запроса: текст, множитель скорости (None - без управления скоростью)
+/// и признак запроса таймингов слов
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct RequestKey {
+    text: String,
+    speed_factor: Option<u32>,
+    timestamps: bool,
+}
+
+impl RequestKey {
+    fn new(text: &str, speed_factor: Option<f32>) -> Self {
+        Self {
+            text: text.to_string(),
+            speed_factor: speed_factor.map(f32::to_bits),
+            timestamps: false,
+        }
+    }
+
+    fn with_timestamps(text: &str) -> Self {
+        Self {
+            timestamps: true,
+            ..Self::new(text, None)
+        }
+    }
+}
+
+/// Провайдер, объединяющий одновременные запросы одинакового текста
+///
+/// Если несколько задач одновременно запрашивают сегмент для одного и того же
+/// текста, провайдер `inner` вызывается один раз, а остальные задачи ожидают
+/// его результат. Целевая длительность в ключ не входит, как и в кэше сегментов
+/// ядра синхронизации, и подставляется в результат для каждой задачи.
+///
+/// Объединяются только выполняющиеся запросы: завершенный результат не
+/// сохраняется, повторный запрос того же текста снова обращается к `inner`.
+/// Ошибка не разделяется между задачами - следующая ожидающая задача
+/// повторяет запрос сама.
+pub struct DedupTtsProvider<P: TtsProvider> {
+    inner: P,
+    enabled: bool,
+    in_flight: Mutex<HashMap<RequestKey, Arc<OnceCell<TtsSegment>>>>,
+}
+
+impl<P: TtsProvider> DedupTtsProvider<P> {
+    /// Создает провайдер, объединяющий одновременные запросы к `inner`
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            enabled: true,
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Включает или отключает объединение запросов
+    ///
+    /// При отключении каждый запрос передается `inner` без изменений.
+    pub fn with_dedup(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Возвращает исходный провайдер
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Возвращает количество выполняющихся в данный момент уникальных запросов
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.lock().unwrap().len()
+    }
+
+    /// Выполняет запрос `generate` или ожидает уже выполняющийся запрос с тем же ключом
+    async fn dedup<F, Fut>(&self, key: RequestKey, target_duration: f64, generate: F) -> Result<TtsSegment>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<TtsSegment>>,
+    {
+        if !self.enabled {
+            return generate().await;
+        }
+
+        let cell = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            if in_flight.contains_key(&key) {
+                log_debug(&format!("Ожидание выполняющегося запроса TTS для текста '{}'", key.text));
+            }
+            in_flight.entry(key.clone()).or_default().clone()
+        };
+
+        let result = match cell.get_or_try_init(generate).await {
+            Ok(segment) => Ok(segment.clone()),
+            Err(e) => Err(e),
+        };
+
+        // Удаляем запись, если ее не заменил более новый запрос
+        {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            if in_flight.get(&key).is_some_and(|current| Arc::ptr_eq(current, &cell)) {
+                in_flight.remove(&key);
+            }
+        }
+
+        result.map(|mut segment| {
+            segment.target_duration = target_duration;
+            segment
+        })
+    }
+}
+
+impl<P: TtsProvider> TtsProvider for DedupTtsProvider<P> {
+    fn generate_speech(&self, text: &str) -> impl Future<Output = Result<Vec<u8>>> {
+        self.inner.generate_speech(text)
+    }
+
+    fn generate_speech_to_file<Q: AsRef<Path> + Send>(&self, text: &str, path: Q) -> impl Future<Output = Result<()>> {
+        self.inner.generate_speech_to_file(text, path)
+    }
+
+    async fn generate_segment(&self, text: &str, target_duration: f64) -> Result<TtsSegment> {
+        self.dedup(RequestKey::new(text, None), target_duration, || {
+            self.inner.generate_segment(text, target_duration)
+        }).await
+    }
+
+    async fn generate_segment_with_speed(&self, text: &str, target_duration: f64, speed_factor: f32) -> Result<TtsSegment> {
+        self.dedup(RequestKey::new(text, Some(speed_factor)), target_duration, || {
+            self.inner.generate_segment_with_speed(text, target_duration, speed_factor)
+        }).await
+    }
+
+    async fn generate_segment_with_timestamps(&self, text: &str, target_duration: f64) -> Result<TtsSegment> {
+        self.dedup(RequestKey::with_timestamps(text), target_duration, || {
+            self.inner.generate_segment_with_timestamps(text, target_duration)
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Провайдер, считающий вызовы и отвечающий с задержкой
+    struct CountingProvider {
+        calls: AtomicUsize,
+    }
+
+    impl TtsProvider for CountingProvider {
+        async fn generate_speech(&self, _text: &str) -> Result<Vec<u8>> {
+            Ok(Vec::new())
+        }
+
+        async fn generate_speech_to_file<Q: AsRef<Path> + Send>(&self, _text: &str, _path: Q) -> Result<()> {
+            Ok(())
+        }
+
+        async fn generate_segment(&self, text: &str, target_duration: f64) -> Result<TtsSegment> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(TtsSegment {
+                text: text.to_string(),
+                audio_data: vec![0u8; 1000],
+                duration: Some(target_duration),
+                target_duration,
+                stretch_factor: None,
+                word_timings: None,
+            })
+        }
+    }
+
+    fn counting_provider() -> CountingProvider {
+        CountingProvider { calls: AtomicUsize::new(0) }
+    }
+
+    #[tokio::test]
+    async fn test_dedup_concurrent_identical_requests() {
+        let provider = DedupTtsProvider::new(counting_provider());
+
+        let (first, second) = tokio::join!(
+            provider.generate_segment("Hello", 1.0),
+            provider.generate_segment("Hello", 2.0),
+        );
+
+        assert_eq!(provider.inner().calls.load(Ordering::SeqCst), 1);
+        assert_eq!(first.unwrap().target_duration, 1.0);
+        assert_eq!(second.unwrap().target_duration, 2.0);
+        assert_eq!(provider.in_flight(), 0);
+
+        // Разные тексты и отключенное объединение синтезируются отдельно
+        let _ = tokio::join!(
+            provider.generate_segment("Hello", 1.0),
+            provider.generate_segment("World", 1.0),
+        );
+        assert_eq!(provider.inner().calls.load(Ordering::SeqCst), 3);
+
+        let provider = DedupTtsProvider::new(counting_provider()).with_dedup(false);
+        let _ = tokio::join!(
+            provider.generate_segment("Hello", 1.0),
+            provider.generate_segment("Hello", 1.0),
+        );
+        assert_eq!(provider.inner().calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -1,36 +1,141 @@
 use crate::error::Result;
+use futures_util::future::BoxFuture;
 use std::path::Path;
 use std::future::Future;
 
+mod dedup;
 mod openai;
+mod rate_limit;
+mod retry;
+#[cfg(feature = "test-util")]
+mod replay;
+#[cfg(feature = "test-util")]
+mod tone;
 
 pub use openai::{
-    OpenAiTts, TtsOptions, TtsSegment, 
+    OpenAiTts, TtsOptions, TtsSegment, WordTiming,
     OpenAiVoice, OpenAiTtsModel, OpenAiAudioFormat
 };
+pub(crate) use openai::validate_speed;
+pub use dedup::DedupTtsProvider;
+pub use rate_limit::{RateLimiter, RateLimitConfig, RateLimitPermit};
+pub use retry::RetryPolicy;
+#[cfg(feature = "test-util")]
+pub use replay::{RecordingTtsProvider, ReplayTtsProvider};
 
 /// Интерфейс для TTS провайдеров
+///
+/// Трейт не объектно-совместим; для выбора провайдера во время выполнения
+/// (`Box<dyn ...>`, `Arc<dyn ...>`) используйте [`DynTtsProvider`].
 pub trait TtsProvider: Send + Sync {
     /// Генерирует TTS для текста
-    fn generate_speech(&self, text: &str) -> impl Future<Output = Result<Vec<u8>>>;
+    fn generate_speech(&self, text: &str) -> impl Future<Output = Result<Vec<u8>>> + Send;
     
     /// Генерирует TTS для текста и сохраняет в файл
-    fn generate_speech_to_file<P: AsRef<Path>>(&self, text: &str, path: P) -> impl Future<Output = Result<()>>;
+    fn generate_speech_to_file<P: AsRef<Path> + Send>(&self, text: &str, path: P) -> impl Future<Output = Result<()>> + Send;
     
     /// Генерирует TTS для сегмента субтитров
-    fn generate_segment(&self, text: &str, target_duration: f64) -> impl Future<Output = Result<TtsSegment>>;
+    fn generate_segment(&self, text: &str, target_duration: f64) -> impl Future<Output = Result<TtsSegment>> + Send;
+    
+    /// Генерирует TTS для сегмента субтитров, ускоряя речь в `speed_factor` раз
+    ///
+    /// Реализация по умолчанию не управляет скоростью и игнорирует `speed_factor`.
+    fn generate_segment_with_speed(&self, text: &str, target_duration: f64, speed_factor: f32) -> impl Future<Output = Result<TtsSegment>> + Send {
+        let _ = speed_factor;
+        self.generate_segment(text, target_duration)
+    }
+    
+    /// Генерирует TTS для сегмента субтитров, запрашивая тайминги слов
+    ///
+    /// Провайдеры, возвращающие данные выравнивания, заполняют
+    /// `TtsSegment::word_timings`. Реализация по умолчанию таймингов
+    /// не запрашивает и совпадает с [`TtsProvider::generate_segment`].
+    fn generate_segment_with_timestamps(&self, text: &str, target_duration: f64) -> impl Future<Output = Result<TtsSegment>> + Send {
+        self.generate_segment(text, target_duration)
+    }
+}
+
+/// Объектно-совместимый вариант [`TtsProvider`]
+///
+/// Реализован для всех провайдеров [`TtsProvider`], поэтому провайдер можно
+/// сохранить как `Box<dyn DynTtsProvider>` или `Arc<dyn DynTtsProvider>`.
+/// В свою очередь `dyn DynTtsProvider` реализует [`TtsProvider`] и
+/// передается в ядро синхронизации как обычный провайдер.
+pub trait DynTtsProvider: Send + Sync {
+    /// См. [`TtsProvider::generate_speech`]
+    fn generate_speech_boxed<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<u8>>>;
+    
+    /// См. [`TtsProvider::generate_speech_to_file`]
+    fn generate_speech_to_file_boxed<'a>(&'a self, text: &'a str, path: &'a Path) -> BoxFuture<'a, Result<()>>;
+    
+    /// См. [`TtsProvider::generate_segment`]
+    fn generate_segment_boxed<'a>(&'a self, text: &'a str, target_duration: f64) -> BoxFuture<'a, Result<TtsSegment>>;
+    
+    /// См. [`TtsProvider::generate_segment_with_speed`]
+    fn generate_segment_with_speed_boxed<'a>(&'a self, text: &'a str, target_duration: f64, speed_factor: f32) -> BoxFuture<'a, Result<TtsSegment>>;
+    
+    /// См. [`TtsProvider::generate_segment_with_timestamps`]
+    fn generate_segment_with_timestamps_boxed<'a>(&'a self, text: &'a str, target_duration: f64) -> BoxFuture<'a, Result<TtsSegment>>;
+}
+
+impl<T: TtsProvider> DynTtsProvider for T {
+    fn generate_speech_boxed<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<u8>>> {
+        Box::pin(self.generate_speech(text))
+    }
+    
+    fn generate_speech_to_file_boxed<'a>(&'a self, text: &'a str, path: &'a Path) -> BoxFuture<'a, Result<()>> {
+        Box::pin(self.generate_speech_to_file(text, path))
+    }
+    
+    fn generate_segment_boxed<'a>(&'a self, text: &'a str, target_duration: f64) -> BoxFuture<'a, Result<TtsSegment>> {
+        Box::pin(self.generate_segment(text, target_duration))
+    }
+    
+    fn generate_segment_with_speed_boxed<'a>(&'a self, text: &'a str, target_duration: f64, speed_factor: f32) -> BoxFuture<'a, Result<TtsSegment>> {
+        Box::pin(self.generate_segment_with_speed(text, target_duration, speed_factor))
+    }
+    
+    fn generate_segment_with_timestamps_boxed<'a>(&'a self, text: &'a str, target_duration: f64) -> BoxFuture<'a, Result<TtsSegment>> {
+        Box::pin(self.generate_segment_with_timestamps(text, target_duration))
+    }
+}
+
+impl TtsProvider for dyn DynTtsProvider {
+    async fn generate_speech(&self, text: &str) -> Result<Vec<u8>> {
+        self.generate_speech_boxed(text).await
+    }
+    
+    async fn generate_speech_to_file<P: AsRef<Path> + Send>(&self, text: &str, path: P) -> Result<()> {
+        self.generate_speech_to_file_boxed(text, path.as_ref()).await
+    }
+    
+    async fn generate_segment(&self, text: &str, target_duration: f64) -> Result<TtsSegment> {
+        self.generate_segment_boxed(text, target_duration).await
+    }
+    
+    async fn generate_segment_with_speed(&self, text: &str, target_duration: f64, speed_factor: f32) -> Result<TtsSegment> {
+        self.generate_segment_with_speed_boxed(text, target_duration, speed_factor).await
+    }
+    
+    async fn generate_segment_with_timestamps(&self, text: &str, target_duration: f64) -> Result<TtsSegment> {
+        self.generate_segment_with_timestamps_boxed(text, target_duration).await
+    }
 }
 
 impl TtsProvider for OpenAiTts {
-    fn generate_speech(&self, text: &str) -> impl Future<Output = Result<Vec<u8>>> {
+    fn generate_speech(&self, text: &str) -> impl Future<Output = Result<Vec<u8>>> + Send {
         self.generate_speech(text)
     }
     
-    fn generate_speech_to_file<P: AsRef<Path>>(&self, text: &str, path: P) -> impl Future<Output = Result<()>> {
+    fn generate_speech_to_file<P: AsRef<Path> + Send>(&self, text: &str, path: P) -> impl Future<Output = Result<()>> + Send {
         self.generate_speech_to_file(text, path)
     }
     
-    fn generate_segment(&self, text: &str, target_duration: f64) -> impl Future<Output = Result<TtsSegment>> {
+    fn generate_segment(&self, text: &str, target_duration: f64) -> impl Future<Output = Result<TtsSegment>> + Send {
         self.generate_segment(text, target_duration)
     }
+    
+    fn generate_segment_with_speed(&self, text: &str, target_duration: f64, speed_factor: f32) -> impl Future<Output = Result<TtsSegment>> + Send {
+        self.generate_segment_with_speed(text, target_duration, speed_factor)
+    }
 }
\ No newline at end of file
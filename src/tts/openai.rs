@@ -1,9 +1,37 @@
-use crate::error::{Error, Result, ErrorType};
+use crate::error::{Error, OpenAiApiError, OpenAiErrorKind, Result, ErrorType};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
 use crate::logging::{log_debug, log_info, log_error, log_warning, log_trace};
+use crate::tts::rate_limit::RateLimiter;
+use crate::tts::retry::{parse_retry_after, RetryPolicy};
+use crate::sync::text_cache::text_hash;
+
+/// Базовый адрес OpenAI API
+const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// Максимальная длина текста одного запроса синтеза речи в символах
+const MAX_INPUT_CHARS: usize = 4096;
+
+/// Минимальная скорость речи, которую принимает OpenAI TTS API
+const MIN_SPEED: f32 = 0.25;
+
+/// Максимальная скорость речи, которую принимает OpenAI TTS API
+const MAX_SPEED: f32 = 4.0;
+
+/// Проверяет, что скорость речи входит в допустимый диапазон OpenAI (0.25 - 4.0)
+pub(crate) fn validate_speed(speed: f32) -> Result<()> {
+    if (MIN_SPEED..=MAX_SPEED).contains(&speed) {
+        Ok(())
+    } else {
+        Err(Error::new(
+            ErrorType::InvalidParameters,
+            &format!("Скорость речи {} вне допустимого диапазона {} - {}", speed, MIN_SPEED, MAX_SPEED),
+        ))
+    }
+}
 
 /// Модели голосов OpenAI TTS
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -56,11 +84,33 @@ impl OpenAiVoice {
             "onyx" => Ok(Self::Onyx),
             "nova" => Ok(Self::Nova),
             "shimmer" => Ok(Self::Shimmer),
-            _ => Err(Error::InvalidParameters(format!("Unknown voice: {}", s))),
+            _ => Err(Error::new(ErrorType::InvalidParameters, &format!("Unknown voice: {}", s))),
         }
     }
 }
 
+impl std::str::FromStr for OpenAiVoice {
+    type Err = Error;
+    
+    fn from_str(s: &str) -> Result<Self> {
+        OpenAiVoice::from_str(s)
+    }
+}
+
+impl TryFrom<&str> for OpenAiVoice {
+    type Error = Error;
+    
+    fn try_from(s: &str) -> Result<Self> {
+        OpenAiVoice::from_str(s)
+    }
+}
+
+impl std::fmt::Display for OpenAiVoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Модели для TTS OpenAI
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OpenAiTtsModel {
@@ -92,11 +142,33 @@ impl OpenAiTtsModel {
         match s.to_lowercase().as_str() {
             "tts-1" => Ok(Self::Tts1),
             "tts-1-hd" => Ok(Self::Tts1Hd),
-            _ => Err(Error::InvalidParameters(format!("Unknown model: {}", s))),
+            _ => Err(Error::new(ErrorType::InvalidParameters, &format!("Unknown model: {}", s))),
         }
     }
 }
 
+impl std::str::FromStr for OpenAiTtsModel {
+    type Err = Error;
+    
+    fn from_str(s: &str) -> Result<Self> {
+        OpenAiTtsModel::from_str(s)
+    }
+}
+
+impl TryFrom<&str> for OpenAiTtsModel {
+    type Error = Error;
+    
+    fn try_from(s: &str) -> Result<Self> {
+        OpenAiTtsModel::from_str(s)
+    }
+}
+
+impl std::fmt::Display for OpenAiTtsModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Формат аудио для OpenAI TTS
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OpenAiAudioFormat {
@@ -143,10 +215,19 @@ impl OpenAiAudioFormat {
             "flac" => Ok(Self::Flac),
             "opus" => Ok(Self::Opus),
             "pcm" => Ok(Self::Pcm),
-            _ => Err(Error::InvalidParameters(format!("Unknown audio format: {}", s))),
+            _ => Err(Error::new(ErrorType::InvalidParameters, &format!("Unknown audio format: {}", s))),
         }
     }
     
+    /// Можно ли объединить несколько ответов в этом формате простой конкатенацией
+    ///
+    /// MP3 и AAC (ADTS) - последовательности независимых кадров, PCM - сырые
+    /// сэмплы. FLAC и Opus (Ogg) содержат заголовок потока, поэтому их
+    /// конкатенация дает некорректный файл.
+    pub fn is_concatenable(&self) -> bool {
+        matches!(self, Self::Mp3 | Self::Aac | Self::Pcm)
+    }
+    
     /// Возвращает расширение файла для формата
     pub fn file_extension(&self) -> &'static str {
         match self {
@@ -159,6 +240,28 @@ impl OpenAiAudioFormat {
     }
 }
 
+impl std::str::FromStr for OpenAiAudioFormat {
+    type Err = Error;
+    
+    fn from_str(s: &str) -> Result<Self> {
+        OpenAiAudioFormat::from_str(s)
+    }
+}
+
+impl TryFrom<&str> for OpenAiAudioFormat {
+    type Error = Error;
+    
+    fn try_from(s: &str) -> Result<Self> {
+        OpenAiAudioFormat::from_str(s)
+    }
+}
+
+impl std::fmt::Display for OpenAiAudioFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Настройки для генерации TTS
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TtsOptions {
@@ -170,6 +273,9 @@ pub struct TtsOptions {
     pub speed: f32,
     /// Формат аудио
     pub response_format: OpenAiAudioFormat,
+    /// Повтор запросов при ограничении частоты (429) и ошибках сервера (5xx)
+    #[serde(default)]
+    pub retry: RetryPolicy,
 }
 
 impl Default for TtsOptions {
@@ -179,6 +285,7 @@ impl Default for TtsOptions {
             voice: OpenAiVoice::default(),
             speed: 1.0,
             response_format: OpenAiAudioFormat::default(),
+            retry: RetryPolicy::default(),
         }
     }
 }
@@ -193,6 +300,28 @@ struct TtsRequest {
     speed: f32,
 }
 
+/// Время произнесения слова внутри сегмента TTS
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordTiming {
+    /// Слово
+    pub word: String,
+    /// Начало слова в секундах от начала аудио сегмента
+    pub start: f64,
+    /// Конец слова в секундах от начала аудио сегмента
+    pub end: f64,
+}
+
+impl WordTiming {
+    /// Создает тайминг слова
+    pub fn new(word: impl Into<String>, start: f64, end: f64) -> Self {
+        Self {
+            word: word.into(),
+            start,
+            end,
+        }
+    }
+}
+
 /// Сегмент TTS
 #[derive(Debug, Clone)]
 pub struct TtsSegment {
@@ -206,6 +335,8 @@ pub struct TtsSegment {
     pub target_duration: f64,
     /// Коэффициент растяжения/сжатия
     pub stretch_factor: Option<f64>,
+    /// Тайминги слов, если провайдер их вернул (OpenAI их не возвращает)
+    pub word_timings: Option<Vec<WordTiming>>,
 }
 
 /// Клиент для работы с OpenAI TTS API
@@ -213,6 +344,12 @@ pub struct OpenAiTts {
     api_key: String,
     options: TtsOptions,
     client: reqwest::Client,
+    rate_limiter: Option<RateLimiter>,
+    base_url: String,
+    cache_dir: Option<PathBuf>,
+    cache_enabled: bool,
+    cache_hits: AtomicUsize,
+    cache_misses: AtomicUsize,
 }
 
 impl OpenAiTts {
@@ -222,6 +359,12 @@ impl OpenAiTts {
             api_key,
             options,
             client: reqwest::Client::new(),
+            rate_limiter: None,
+            base_url: OPENAI_BASE_URL.to_string(),
+            cache_dir: None,
+            cache_enabled: true,
+            cache_hits: AtomicUsize::new(0),
+            cache_misses: AtomicUsize::new(0),
         }
     }
     
@@ -230,8 +373,183 @@ impl OpenAiTts {
         Self::new(api_key, TtsOptions::default())
     }
     
+    /// Устанавливает ограничитель частоты запросов
+    ///
+    /// Ограничитель можно разделять между несколькими клиентами, передавая его клоны.
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+    
+    /// Устанавливает базовый адрес API
+    ///
+    /// Позволяет использовать совместимые с OpenAI серверы и шлюзы
+    /// (Azure OpenAI, LiteLLM, vLLM). Запрос отправляется на
+    /// `{base_url}/audio/speech`, например `http://localhost:8000/v1`.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+    
+    /// Устанавливает HTTP клиент для запросов
+    ///
+    /// Позволяет заранее настроить прокси, таймауты и корневые сертификаты.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+    
+    /// Устанавливает директорию кэша синтезированной речи на диске
+    ///
+    /// Ответ API сохраняется в файл, имя которого - хэш адреса API, текста,
+    /// голоса, модели, скорости и формата, и при повторном запросе читается
+    /// из файла без обращения к сети. Вместе с ответом в файле хранится
+    /// полный ключ, и файл с другим ключом не используется.
+    /// Директория создается при первой записи.
+    pub fn with_cache_dir(mut self, cache_dir: impl AsRef<Path>) -> Self {
+        self.cache_dir = Some(cache_dir.as_ref().to_path_buf());
+        self
+    }
+    
+    /// Включает или отключает кэш синтезированной речи
+    ///
+    /// Без директории кэша ([`OpenAiTts::with_cache_dir`]) кэш не используется.
+    pub fn with_cache(mut self, enabled: bool) -> Self {
+        self.cache_enabled = enabled;
+        self
+    }
+    
+    /// Возвращает количество попаданий и промахов кэша синтезированной речи
+    pub fn cache_stats(&self) -> (usize, usize) {
+        (self.cache_hits.load(Ordering::SeqCst), self.cache_misses.load(Ordering::SeqCst))
+    }
+    
+    /// Возвращает базовый адрес API
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+    
+    /// Возвращает ключ кэша и путь его файла для текста и скорости, если кэш включен
+    ///
+    /// Ключ включает адрес API, поэтому ответы разных серверов не смешиваются.
+    fn cache_entry(&self, text: &str, speed: f32) -> Option<(String, PathBuf)> {
+        let cache_dir = self.cache_dir.as_ref().filter(|_| self.cache_enabled)?;
+        let key = format!("{}\n{}\n{}\n{}\n{}\n{}",
+            self.base_url.trim_end_matches('/'), self.options.model.as_str(), self.options.voice.as_str(),
+            speed.to_bits(), self.options.response_format.as_str(), text);
+        let path = cache_dir.join(format!("{:016x}.{}", text_hash(&key), self.options.response_format.as_str()));
+        Some((key, path))
+    }
+    
+    /// Извлекает аудио данные из файла кэша, если он записан для ключа `key`
+    ///
+    /// Файл начинается с длины ключа (u64, little-endian) и самого ключа,
+    /// поэтому совпадение хэшей разных ключей и файлы старого формата
+    /// считаются промахом.
+    fn read_cache_entry(key: &str, entry: Vec<u8>) -> Option<Vec<u8>> {
+        let header: [u8; 8] = entry.get(..8)?.try_into().ok()?;
+        let key_len = usize::try_from(u64::from_le_bytes(header)).ok()?;
+        let key_end = key_len.checked_add(8)?;
+        if entry.get(8..key_end)? != key.as_bytes() {
+            return None;
+        }
+        Some(entry[key_end..].to_vec())
+    }
+    
+    /// Сохраняет ответ API в кэш; ошибка записи только логируется
+    async fn write_cache(path: &Path, key: &str, audio_data: &[u8]) {
+        // Запись во временный файл и переименование, чтобы параллельные
+        // запросы не прочитали недописанный файл
+        let temp_path = path.with_extension("tmp");
+        let result = async {
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            let mut entry = Vec::with_capacity(8 + key.len() + audio_data.len());
+            entry.extend_from_slice(&(key.len() as u64).to_le_bytes());
+            entry.extend_from_slice(key.as_bytes());
+            entry.extend_from_slice(audio_data);
+            tokio::fs::write(&temp_path, entry).await?;
+            tokio::fs::rename(&temp_path, path).await
+        }.await;
+        
+        if let Err(e) = result {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            log_warning(&format!("Не удалось сохранить ответ TTS в кэш {}: {}", path.display(), e));
+        }
+    }
+    
+    /// Возвращает адрес запроса синтеза речи
+    fn speech_url(&self) -> String {
+        format!("{}/audio/speech", self.base_url.trim_end_matches('/'))
+    }
+    
     /// Генерирует TTS для указанного текста
     pub async fn generate_speech(&self, text: &str) -> Result<Vec<u8>> {
+        self.request_speech(text, self.options.speed).await
+    }
+    
+    /// Генерирует речь с указанной скоростью, используя кэш на диске, если он задан
+    ///
+    /// Скорость вне диапазона 0.25 - 4.0 отклоняется до обращения к API.
+    async fn request_speech(&self, text: &str, speed: f32) -> Result<Vec<u8>> {
+        validate_speed(speed)?;
+        let cache_entry = self.cache_entry(text, speed);
+        if let Some((key, path)) = &cache_entry {
+            let cached = tokio::fs::read(path).await.ok()
+                .and_then(|entry| Self::read_cache_entry(key, entry));
+            if let Some(audio_data) = cached {
+                let hits = self.cache_hits.fetch_add(1, Ordering::SeqCst) + 1;
+                log_debug(&format!("Кэш TTS: попадание для '{}' (попаданий: {}, промахов: {})",
+                    text, hits, self.cache_misses.load(Ordering::SeqCst)));
+                return Ok(audio_data);
+            }
+            let misses = self.cache_misses.fetch_add(1, Ordering::SeqCst) + 1;
+            log_debug(&format!("Кэш TTS: промах для '{}' (попаданий: {}, промахов: {})",
+                text, self.cache_hits.load(Ordering::SeqCst), misses));
+        }
+        
+        let audio_data = self.synthesize_speech(text, speed).await?;
+        if let Some((key, path)) = &cache_entry {
+            Self::write_cache(path, key, &audio_data).await;
+        }
+        
+        Ok(audio_data)
+    }
+    
+    /// Синтезирует речь с указанной скоростью
+    ///
+    /// Текст длиннее допустимого для одного запроса делится на части по
+    /// границам предложений (или слов), части синтезируются по очереди,
+    /// и аудио данные объединяются. Объединение корректно для потоковых
+    /// форматов (mp3, aac, pcm).
+    async fn synthesize_speech(&self, text: &str, speed: f32) -> Result<Vec<u8>> {
+        let chunks = split_input(text, MAX_INPUT_CHARS);
+        if chunks.len() <= 1 {
+            return self.request_chunk(text, speed).await;
+        }
+        
+        if !self.options.response_format.is_concatenable() {
+            return Err(Error::new(
+                ErrorType::InvalidParameters,
+                &format!("Текст длиной {} символов требует {} запросов, но ответы в формате {} нельзя объединить. \
+                    Используйте формат mp3, aac или pcm либо сократите реплику до {} символов",
+                    text.chars().count(), chunks.len(), self.options.response_format.as_str(), MAX_INPUT_CHARS)
+            ));
+        }
+        
+        log_info(&format!("Текст длиной {} символов разделен на {} запросов",
+            text.chars().count(), chunks.len()));
+        let mut audio_data = Vec::new();
+        for chunk in chunks {
+            audio_data.extend(self.request_chunk(chunk, speed).await?);
+        }
+        
+        Ok(audio_data)
+    }
+    
+    /// Отправляет запрос на генерацию речи с указанной скоростью
+    async fn request_chunk(&self, text: &str, speed: f32) -> Result<Vec<u8>> {
         log_debug(&format!("OpenAI TTS запрос: '{}' с использованием голоса {} и модели {}", 
             text, self.options.voice.as_str(), self.options.model.as_str()));
         
@@ -241,48 +559,63 @@ impl OpenAiTts {
             input: text.to_string(),
             voice: self.options.voice.as_str().to_string(),
             response_format: self.options.response_format.as_str().to_string(),
-            speed: self.options.speed,
+            speed,
         };
 
         log_debug(&format!("Отправляем запрос к OpenAI TTS API: model={}, voice={}, format={}, speed={}",
             request.model, request.voice, request.response_format, request.speed));
         
-        let client = reqwest::Client::new();
-        
-        let response = client.post("https://api.openai.com/v1/audio/speech")
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&request) // Используем JSON вместо multipart/form-data
-            .send()
-            .await
-            .map_err(|e| Error::new(
-                ErrorType::OpenAi,
-                &format!("Ошибка при отправке запроса к OpenAI TTS API: {}", e)
-            ))?;
+        let url = self.speech_url();
+        let retry = self.options.retry;
+        let mut attempt = 0;
+        let response = loop {
+            // Соблюдаем ограничение частоты запросов, если оно задано
+            let permit = match &self.rate_limiter {
+                Some(rate_limiter) => Some(rate_limiter.acquire().await),
+                None => None,
+            };
             
-        let status = response.status();
-        log_debug(&format!("Получен ответ от OpenAI API, статус: {}", status));
-        
-        if !status.is_success() {
+            let response = self.client.post(&url)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .header("Content-Type", "application/json")
+                .json(&request) // Используем JSON вместо multipart/form-data
+                .send()
+                .await?;
+                
+            let status = response.status();
+            log_debug(&format!("Получен ответ от OpenAI API, статус: {}", status));
+            
+            if status.is_success() {
+                break response;
+            }
+            
+            let retry_after = response.headers().get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_retry_after);
             let error_text = response.text().await
                 .unwrap_or_else(|_| "Не удалось получить текст ошибки".to_string());
+            let api_error = OpenAiApiError::from_response(status.as_u16(), &error_text);
             
-            log_error::<(), _>(
-                &Error::new(ErrorType::OpenAi, &error_text),
-                &format!("OpenAI API вернул ошибку: {}", status)
-            )?;
+            // Повторяем только временные ошибки: исчерпанная квота к ним не относится
+            let transient = matches!(api_error.kind(), OpenAiErrorKind::RateLimit | OpenAiErrorKind::Server);
+            if transient && attempt < retry.max_retries {
+                let delay = retry.delay(attempt, retry_after);
+                attempt += 1;
+                log_warning(&format!("OpenAI API вернул ошибку {}, повтор {}/{} через {:.1?}",
+                    api_error, attempt, retry.max_retries, delay));
+                // Слот одновременных запросов не удерживается на время ожидания
+                drop(permit);
+                tokio::time::sleep(delay).await;
+                continue;
+            }
             
-            return Err(Error::new(
-                ErrorType::OpenAi,
-                &format!("Ошибка OpenAI API: {}. {}", status, error_text)
-            ));
-        }
+            // Ошибка только логируется: вызывающему возвращается разобранная ошибка API
+            let _ = log_error::<(), _>(&api_error, "OpenAI API вернул ошибку");
+            
+            return Err(Error::OpenAiApi(api_error));
+        };
         
-        let audio_data = response.bytes().await
-            .map_err(|e| Error::new(
-                ErrorType::OpenAi,
-                &format!("Ошибка при получении данных от OpenAI TTS API: {}", e)
-            ))?;
+        let audio_data = response.bytes().await?;
             
         let bytes = audio_data.to_vec();
         let size = bytes.len();
@@ -306,11 +639,8 @@ impl OpenAiTts {
     pub async fn generate_speech_to_file<P: AsRef<Path>>(&self, text: &str, path: P) -> Result<()> {
         let audio_data = self.generate_speech(text).await?;
         
-        let mut file = File::create(path).await
-            .map_err(|e| Error::Io(e))?;
-        
-        file.write_all(&audio_data).await
-            .map_err(|e| Error::Io(e))?;
+        let mut file = File::create(path).await?;
+        file.write_all(&audio_data).await?;
         
         Ok(())
     }
@@ -326,16 +656,95 @@ impl OpenAiTts {
             duration: None,
             target_duration,
             stretch_factor: None,
+            word_timings: None,
         };
         
         Ok(segment)
     }
+    
+    /// Генерирует TTS для сегмента субтитров, ускоряя речь в `speed_factor` раз
+    /// относительно настроенной скорости
+    ///
+    /// Итоговая скорость ограничивается допустимым диапазоном OpenAI (0.25 - 4.0),
+    /// но настроенная скорость вне этого диапазона считается ошибкой.
+    pub async fn generate_segment_with_speed(&self, text: &str, target_duration: f64, speed_factor: f32) -> Result<TtsSegment> {
+        validate_speed(self.options.speed)?;
+        let speed = (self.options.speed * speed_factor).clamp(MIN_SPEED, MAX_SPEED);
+        let audio_data = self.request_speech(text, speed).await?;
+        
+        Ok(TtsSegment {
+            text: text.to_string(),
+            audio_data,
+            duration: None,
+            target_duration,
+            stretch_factor: None,
+            word_timings: None,
+        })
+    }
+}
+
+/// Делит текст на части не длиннее `max_chars` символов
+///
+/// Часть заканчивается на последнем конце предложения (`.`, `!`, `?`),
+/// который в нее помещается, иначе на последнем пробеле. Слово делится,
+/// только если оно само длиннее `max_chars`.
+fn split_input(text: &str, max_chars: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = text.trim();
+    
+    while rest.chars().count() > max_chars {
+        // Байтовая граница первых max_chars символов
+        let limit = rest.char_indices().nth(max_chars).map_or(rest.len(), |(index, _)| index);
+        let window = &rest[..limit];
+        let next_char = rest[limit..].chars().next();
+        
+        // Конец предложения: знак препинания, за которым идет пробел или конец окна
+        let sentence_end = window.char_indices()
+            .rev()
+            .find(|&(index, c)| {
+                let after = window[index + c.len_utf8()..].chars().next().or(next_char);
+                matches!(c, '.' | '!' | '?') && after.map_or(true, char::is_whitespace)
+            })
+            .map(|(index, c)| index + c.len_utf8());
+        // Иначе граница слова; окно, оканчивающееся ровно перед пробелом, целиком
+        let word_end = if next_char.is_some_and(char::is_whitespace) {
+            Some(limit)
+        } else {
+            window.rfind(char::is_whitespace).filter(|&index| index > 0)
+        };
+        let end = sentence_end.or(word_end).unwrap_or(limit);
+        
+        chunks.push(rest[..end].trim_end());
+        rest = rest[end..].trim_start();
+    }
+    if !rest.is_empty() {
+        chunks.push(rest);
+    }
+    
+    chunks
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     
+    #[test]
+    fn test_split_input_on_sentence_and_word_boundaries() {
+        assert_eq!(split_input("Short text.", 20), vec!["Short text."]);
+        assert_eq!(
+            split_input("First sentence. Second one! Third?", 30),
+            vec!["First sentence. Second one!", "Third?"]
+        );
+        // Без конца предложения текст делится по пробелу, слова не разрываются
+        assert_eq!(split_input("alpha beta gamma delta", 12), vec!["alpha beta", "gamma delta"]);
+        // Многобайтовые символы считаются по символам
+        assert_eq!(split_input("привет мир. пока", 12), vec!["привет мир.", "пока"]);
+        // Слово длиннее предела делится принудительно
+        assert_eq!(split_input("abcdefgh", 3), vec!["abc", "def", "gh"]);
+        // Точка внутри числа не считается концом предложения
+        assert_eq!(split_input("Pi is 3.14 and e is 2.71", 16), vec!["Pi is 3.14 and e", "is 2.71"]);
+    }
+    
     #[test]
     fn test_voice_as_str() {
         assert_eq!(OpenAiVoice::Alloy.as_str(), "alloy");
@@ -362,6 +771,23 @@ mod tests {
         assert!(OpenAiVoice::from_str("unknown").is_err());
     }
     
+    #[test]
+    fn test_voice_parse_and_display() {
+        assert_eq!("nova".parse::<OpenAiVoice>().unwrap(), OpenAiVoice::Nova);
+        assert_eq!(OpenAiVoice::try_from("Shimmer").unwrap(), OpenAiVoice::Shimmer);
+        assert!("unknown".parse::<OpenAiVoice>().is_err());
+        
+        for voice in [OpenAiVoice::Alloy, OpenAiVoice::Echo, OpenAiVoice::Fable,
+            OpenAiVoice::Onyx, OpenAiVoice::Nova, OpenAiVoice::Shimmer] {
+            assert_eq!(voice.to_string().parse::<OpenAiVoice>().unwrap(), voice);
+        }
+        
+        assert_eq!(OpenAiTtsModel::Tts1Hd.to_string(), "tts-1-hd");
+        assert_eq!("tts-1".parse::<OpenAiTtsModel>().unwrap(), OpenAiTtsModel::Tts1);
+        assert_eq!(OpenAiAudioFormat::Opus.to_string(), "opus");
+        assert_eq!(OpenAiAudioFormat::try_from("pcm").unwrap(), OpenAiAudioFormat::Pcm);
+    }
+    
     #[test]
     fn test_model_as_str() {
         assert_eq!(OpenAiTtsModel::Tts1.as_str(), "tts-1");
@@ -412,4 +838,289 @@ mod tests {
         assert_eq!(OpenAiAudioFormat::Opus.file_extension(), "opus");
         assert_eq!(OpenAiAudioFormat::Pcm.file_extension(), "wav");
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_speech_url_from_base_url() {
+        let tts = OpenAiTts::with_api_key("key".to_string());
+        assert_eq!(tts.speech_url(), "https://api.openai.com/v1/audio/speech");
+        
+        let tts = tts.with_base_url("http://localhost:4000/v1/");
+        assert_eq!(tts.base_url(), "http://localhost:4000/v1/");
+        assert_eq!(tts.speech_url(), "http://localhost:4000/v1/audio/speech");
+    }
+    
+    /// Запускает HTTP сервер, отвечающий на запросы по очереди ответами `responses`
+    ///
+    /// Возвращает адрес сервера и счетчик принятых запросов.
+    async fn mock_server(responses: Vec<String>) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::AsyncReadExt;
+        
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}/v1", listener.local_addr().unwrap());
+        let requests = std::sync::Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                counter.fetch_add(1, Ordering::SeqCst);
+                
+                // Читаем запрос целиком: заголовки и тело длиной Content-Length
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 4096];
+                loop {
+                    let read = stream.read(&mut buffer).await.unwrap();
+                    request.extend_from_slice(&buffer[..read]);
+                    let text = String::from_utf8_lossy(&request).to_lowercase();
+                    if let Some(headers_end) = text.find("\r\n\r\n") {
+                        let body_length = text.lines()
+                            .find_map(|line| line.strip_prefix("content-length:"))
+                            .and_then(|value| value.trim().parse::<usize>().ok())
+                            .unwrap_or(0);
+                        if request.len() >= headers_end + 4 + body_length {
+                            break;
+                        }
+                    }
+                    if read == 0 {
+                        break;
+                    }
+                }
+                
+                stream.write_all(response.as_bytes()).await.unwrap();
+                stream.shutdown().await.unwrap();
+            }
+        });
+        
+        (address, requests)
+    }
+    
+    /// Формирует HTTP ответ с заданным статусом, заголовками и телом
+    fn http_response(status: &str, headers: &str, body: &str) -> String {
+        format!("HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}", status, headers, body.len(), body)
+    }
+    
+    #[tokio::test]
+    async fn test_generate_speech_retries_rate_limit_and_server_errors() {
+        use std::sync::atomic::Ordering;
+        
+        let rate_limited = http_response(
+            "429 Too Many Requests",
+            "Retry-After: 0\r\n",
+            r#"{"error": {"message": "Rate limit reached", "type": "requests"}}"#,
+        );
+        let audio = "A".repeat(200);
+        let (base_url, requests) = mock_server(vec![
+            rate_limited.clone(),
+            rate_limited,
+            http_response("200 OK", "", &audio),
+        ]).await;
+        
+        let options = TtsOptions { retry: RetryPolicy::new(3, 1), ..TtsOptions::default() };
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .unwrap();
+        let tts = OpenAiTts::new("key".to_string(), options)
+            .with_base_url(base_url)
+            .with_client(client);
+        let data = tts.generate_speech("Hello").await.unwrap();
+        
+        assert_eq!(data, audio.as_bytes());
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+        
+        // После исчерпания повторов возвращается последняя ошибка
+        let server_error = http_response("500 Internal Server Error", "", "upstream failed");
+        let (base_url, requests) = mock_server(vec![server_error.clone(), server_error]).await;
+        let options = TtsOptions { retry: RetryPolicy::new(1, 1), ..TtsOptions::default() };
+        let tts = OpenAiTts::new("key".to_string(), options).with_base_url(base_url);
+        
+        match tts.generate_speech("Hello").await {
+            Err(Error::OpenAiApi(error)) => assert_eq!(error.status, 500),
+            other => panic!("Ожидалась ошибка API, получено {:?}", other.map(|data| data.len())),
+        }
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+        
+        // Исчерпанная квота не повторяется
+        let quota = http_response(
+            "429 Too Many Requests",
+            "",
+            r#"{"error": {"message": "Quota exceeded", "type": "insufficient_quota", "code": "insufficient_quota"}}"#,
+        );
+        let (base_url, requests) = mock_server(vec![quota]).await;
+        let tts = OpenAiTts::with_api_key("key".to_string()).with_base_url(base_url);
+        assert!(tts.generate_speech("Hello").await.is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_retry_wait_releases_rate_limit_slot() {
+        use crate::tts::rate_limit::RateLimitConfig;
+        use std::sync::atomic::Ordering;
+        use std::time::Duration;
+    
+        let first = "A".repeat(200);
+        let second = "B".repeat(200);
+        let (base_url, requests) = mock_server(vec![
+            http_response(
+                "429 Too Many Requests",
+                "Retry-After: 1\r\n",
+                r#"{"error": {"message": "Rate limit reached", "type": "requests"}}"#,
+            ),
+            http_response("200 OK", "", &second),
+            http_response("200 OK", "", &first),
+        ]).await;
+    
+        // Один слот одновременных запросов на оба вызова
+        let options = TtsOptions { retry: RetryPolicy::new(1, 1), ..TtsOptions::default() };
+        let tts = OpenAiTts::new("key".to_string(), options)
+            .with_base_url(base_url)
+            .with_rate_limiter(RateLimiter::new(RateLimitConfig::new(0, 1)));
+    
+        let retried = tts.generate_speech("First");
+        let concurrent = async {
+            while requests.load(Ordering::SeqCst) == 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            // Пока первый запрос ждет повтора, слот свободен для второго
+            tokio::time::timeout(Duration::from_millis(700), tts.generate_speech("Second")).await
+        };
+        let (retried, concurrent) = tokio::join!(retried, concurrent);
+    
+        assert_eq!(concurrent.expect("второй запрос ждал слот").unwrap(), second.as_bytes());
+        assert_eq!(retried.unwrap(), first.as_bytes());
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+    
+    #[tokio::test]
+    async fn test_generate_speech_splits_long_text() {
+        use std::sync::atomic::Ordering;
+        
+        let first = "A".repeat(200);
+        let second = "B".repeat(200);
+        let (base_url, requests) = mock_server(vec![
+            http_response("200 OK", "", &first),
+            http_response("200 OK", "", &second),
+        ]).await;
+        
+        // 5000 символов: предложения по 25 символов
+        let text = "This is a test sentence. ".repeat(200);
+        assert_eq!(text.chars().count(), 5000);
+        
+        let tts = OpenAiTts::with_api_key("key".to_string()).with_base_url(base_url);
+        let data = tts.generate_speech(&text).await.unwrap();
+        
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+        assert_eq!(data, format!("{}{}", first, second).as_bytes());
+    }
+    
+    #[tokio::test]
+    async fn test_generate_speech_rejects_long_text_in_stream_formats() {
+        use std::sync::atomic::Ordering;
+        
+        let (base_url, requests) = mock_server(Vec::new()).await;
+        let text = "This is a test sentence. ".repeat(200);
+        
+        // Ответы FLAC и Opus нельзя склеить, поэтому запросы не отправляются
+        for format in [OpenAiAudioFormat::Flac, OpenAiAudioFormat::Opus] {
+            let options = TtsOptions { response_format: format.clone(), ..TtsOptions::default() };
+            let tts = OpenAiTts::new("key".to_string(), options).with_base_url(base_url.clone());
+            match tts.generate_speech(&text).await {
+                Err(Error::InvalidParameters(_)) => {}
+                other => panic!("{:?}: ожидалась ошибка параметров, получено {:?}", format, other.map(|data| data.len())),
+            }
+        }
+        assert_eq!(requests.load(Ordering::SeqCst), 0);
+        assert!(OpenAiAudioFormat::Aac.is_concatenable());
+        assert!(!OpenAiAudioFormat::Flac.is_concatenable());
+    }
+    
+    #[tokio::test]
+    async fn test_cache_dir_avoids_repeated_requests() {
+        use std::sync::atomic::Ordering;
+        
+        let audio = "A".repeat(200);
+        let (base_url, requests) = mock_server(vec![http_response("200 OK", "", &audio)]).await;
+        let cache_dir = tempfile::tempdir().unwrap();
+        
+        // Первый запуск обращается к API и сохраняет ответ в кэш
+        let tts = OpenAiTts::with_api_key("key".to_string())
+            .with_base_url(base_url.clone())
+            .with_cache_dir(cache_dir.path());
+        assert_eq!(tts.generate_speech("Hello").await.unwrap(), audio.as_bytes());
+        assert_eq!(tts.cache_stats(), (0, 1));
+        
+        // Второй запуск с теми же параметрами не обращается к сети
+        let tts = OpenAiTts::with_api_key("key".to_string())
+            .with_base_url(base_url.clone())
+            .with_cache_dir(cache_dir.path());
+        assert_eq!(tts.generate_segment("Hello", 1.0).await.unwrap().audio_data, audio.as_bytes());
+        assert_eq!(tts.cache_stats(), (1, 0));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        
+        // Другая скорость и отключенный кэш требуют запроса (сервер уже не отвечает)
+        assert!(tts.generate_segment_with_speed("Hello", 1.0, 1.5).await.is_err());
+        let tts = OpenAiTts::with_api_key("key".to_string())
+            .with_base_url(base_url)
+            .with_cache_dir(cache_dir.path())
+            .with_cache(false);
+        assert!(tts.generate_speech("Hello").await.is_err());
+        assert_eq!(tts.cache_stats(), (0, 0));
+    }
+    
+    #[tokio::test]
+    async fn test_cache_entry_is_checked_against_key() {
+        use std::sync::atomic::Ordering;
+        
+        let (first, second, third) = ("A".repeat(200), "B".repeat(200), "C".repeat(200));
+        let (first_url, first_requests) = mock_server(vec![
+            http_response("200 OK", "", &first),
+            http_response("200 OK", "", &third),
+        ]).await;
+        let (second_url, second_requests) = mock_server(vec![http_response("200 OK", "", &second)]).await;
+        let cache_dir = tempfile::tempdir().unwrap();
+        let tts_for = |base_url: &str| OpenAiTts::with_api_key("key".to_string())
+            .with_base_url(base_url.to_string())
+            .with_cache_dir(cache_dir.path());
+        
+        // Ответ другого сервера не берется из кэша
+        assert_eq!(tts_for(&first_url).generate_speech("Hello").await.unwrap(), first.as_bytes());
+        let tts = tts_for(&second_url);
+        assert_eq!(tts.generate_speech("Hello").await.unwrap(), second.as_bytes());
+        assert_eq!(tts.cache_stats(), (0, 1));
+        assert_eq!(second_requests.load(Ordering::SeqCst), 1);
+        
+        // Файл кэша без совпадающего ключа считается промахом
+        let (_, path) = tts_for(&first_url).cache_entry("Hello", 1.0).unwrap();
+        std::fs::write(&path, first.as_bytes()).unwrap();
+        let tts = tts_for(&first_url);
+        assert_eq!(tts.generate_speech("Hello").await.unwrap(), third.as_bytes());
+        assert_eq!(tts.cache_stats(), (0, 1));
+        assert_eq!(first_requests.load(Ordering::SeqCst), 2);
+        
+        // Перезаписанный файл снова дает попадание
+        assert_eq!(tts.generate_speech("Hello").await.unwrap(), third.as_bytes());
+        assert_eq!(tts.cache_stats(), (1, 1));
+    }
+    
+    #[tokio::test]
+    async fn test_speed_out_of_range_fails_before_request() {
+        use std::sync::atomic::Ordering;
+        
+        for speed in [0.25, 1.0, 4.0] {
+            assert!(validate_speed(speed).is_ok(), "скорость {}", speed);
+        }
+        
+        let (base_url, requests) = mock_server(Vec::new()).await;
+        for speed in [0.0, 0.24, 4.01, 10.0, f32::NAN] {
+            assert!(validate_speed(speed).is_err(), "скорость {}", speed);
+            
+            let options = TtsOptions { speed, ..TtsOptions::default() };
+            let tts = OpenAiTts::new("key".to_string(), options).with_base_url(base_url.clone());
+            assert!(matches!(tts.generate_speech("Hello").await, Err(Error::InvalidParameters(_))));
+            assert!(matches!(tts.generate_segment_with_speed("Hello", 1.0, 1.0).await, Err(Error::InvalidParameters(_))));
+        }
+        assert_eq!(requests.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,169 @@
+//! Ограничение частоты запросов к TTS провайдерам.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use crate::logging::log_trace;
+
+/// Настройки ограничения частоты запросов
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitConfig {
+    /// Максимальное количество запросов в минуту (0 - без ограничения)
+    pub requests_per_minute: u32,
+    /// Максимальное количество одновременных запросов (0 - без ограничения)
+    pub max_concurrent_requests: usize,
+    /// Количество запросов, которые можно отправить подряд без ожидания
+    pub burst: u32,
+}
+
+impl RateLimitConfig {
+    /// Создает настройки с заданным лимитом запросов в минуту и одновременных запросов
+    pub fn new(requests_per_minute: u32, max_concurrent_requests: usize) -> Self {
+        Self {
+            requests_per_minute,
+            max_concurrent_requests,
+            burst: 1,
+        }
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self::new(0, 0)
+    }
+}
+
+/// Состояние "ведра токенов"
+#[derive(Debug)]
+struct TokenBucket {
+    /// Доступное количество токенов
+    tokens: f64,
+    /// Время последнего пополнения
+    last_refill: Instant,
+}
+
+/// Ограничитель частоты запросов на основе "ведра токенов"
+///
+/// Клоны ограничителя разделяют общее состояние, поэтому один экземпляр
+/// можно передать нескольким провайдерам или заданиям пакетной обработки.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    bucket: Arc<Mutex<TokenBucket>>,
+    semaphore: Option<Arc<Semaphore>>,
+}
+
+/// Разрешение на выполнение запроса; освобождает слот при удалении
+#[derive(Debug)]
+pub struct RateLimitPermit {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl RateLimiter {
+    /// Создает новый ограничитель с заданными настройками
+    pub fn new(config: RateLimitConfig) -> Self {
+        let burst = config.burst.max(1) as f64;
+        let semaphore = if config.max_concurrent_requests > 0 {
+            Some(Arc::new(Semaphore::new(config.max_concurrent_requests)))
+        } else {
+            None
+        };
+
+        Self {
+            config,
+            bucket: Arc::new(Mutex::new(TokenBucket {
+                tokens: burst,
+                last_refill: Instant::now(),
+            })),
+            semaphore,
+        }
+    }
+
+    /// Возвращает настройки ограничителя
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Ожидает, пока запрос можно будет выполнить, не нарушая ограничений
+    pub async fn acquire(&self) -> RateLimitPermit {
+        // Сначала занимаем слот одновременных запросов
+        let permit = match &self.semaphore {
+            Some(semaphore) => semaphore.clone().acquire_owned().await.ok(),
+            None => None,
+        };
+
+        if self.config.requests_per_minute > 0 {
+            loop {
+                let wait = self.try_take_token();
+                match wait {
+                    None => break,
+                    Some(wait) => {
+                        log_trace(&format!("Ограничение частоты запросов: ожидание {:.0?}", wait));
+                        tokio::time::sleep(wait).await;
+                    }
+                }
+            }
+        }
+
+        RateLimitPermit { _permit: permit }
+    }
+
+    /// Пытается взять токен; возвращает время ожидания, если токенов нет
+    fn try_take_token(&self) -> Option<Duration> {
+        let rate = self.config.requests_per_minute as f64 / 60.0;
+        let capacity = self.config.burst.max(1) as f64;
+
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rate_limiter_spaces_requests() {
+        // 600 запросов в минуту - один запрос каждые 100 мс
+        let limiter = RateLimiter::new(RateLimitConfig::new(600, 0));
+        let start = Instant::now();
+
+        let mut timestamps = Vec::new();
+        for _ in 0..4 {
+            let _permit = limiter.acquire().await;
+            timestamps.push(start.elapsed());
+        }
+
+        // Первый запрос проходит сразу, остальные разнесены минимум на ~100 мс
+        assert!(timestamps[0] < Duration::from_millis(50));
+        for pair in timestamps.windows(2) {
+            let gap = pair[1] - pair[0];
+            assert!(gap >= Duration::from_millis(90), "Слишком маленький интервал: {:?}", gap);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_limits_concurrency() {
+        let limiter = RateLimiter::new(RateLimitConfig::new(0, 1));
+
+        let first = limiter.acquire().await;
+
+        // Пока первое разрешение удерживается, второе получить нельзя
+        let second = tokio::time::timeout(Duration::from_millis(50), limiter.acquire()).await;
+        assert!(second.is_err());
+
+        drop(first);
+        let third = tokio::time::timeout(Duration::from_millis(50), limiter.acquire()).await;
+        assert!(third.is_ok());
+    }
+}
@@ -0,0 +1,231 @@
+//! Запись ответов TTS провайдера на диск и их воспроизведение.
+//!
+//! Позволяет один раз записать ответы настоящего провайдера и затем
+//! выполнять синхронизацию без сети с одинаковым результатом, например
+//! в интеграционных тестах.
+
+use crate::error::{Error, ErrorType, Result};
+use crate::logging::log_debug;
+use crate::tts::{TtsProvider, TtsSegment, WordTiming};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Идентификатор формата файла записи
+const RECORDING_FORMAT: &str = "tts-sync-recording";
+/// Текущая версия формата файла записи
+const RECORDING_VERSION: u32 = 1;
+
+/// Записанный ответ провайдера
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    /// Текст запроса
+    text: String,
+    /// Множитель скорости речи (None - запрос без управления скоростью)
+    speed_factor: Option<f32>,
+    /// Аудио данные ответа
+    #[serde(with = "bytes_base64")]
+    audio_data: Vec<u8>,
+    /// Длительность аудио, сообщенная провайдером
+    duration: Option<f64>,
+    /// Тайминги слов, если провайдер их вернул
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    word_timings: Option<Vec<WordTiming>>,
+}
+
+/// Содержимое файла записи
+#[derive(Serialize, Deserialize)]
+struct RecordingFile {
+    format: String,
+    version: u32,
+    responses: Vec<RecordedResponse>,
+}
+
+/// Находит записанный ответ для текста и скорости
+fn find_response<'a>(responses: &'a [RecordedResponse], text: &str, speed_factor: Option<f32>) -> Option<&'a RecordedResponse> {
+    responses.iter().find(|response| response.text == text && response.speed_factor == speed_factor)
+}
+
+/// Провайдер, передающий запросы другому провайдеру и записывающий ответы
+///
+/// Записанные ответы сохраняются в файл методом [`RecordingTtsProvider::save`]
+/// и воспроизводятся [`ReplayTtsProvider`].
+pub struct RecordingTtsProvider<P: TtsProvider> {
+    inner: P,
+    path: PathBuf,
+    responses: Mutex<Vec<RecordedResponse>>,
+}
+
+impl<P: TtsProvider> RecordingTtsProvider<P> {
+    /// Создает провайдер, записывающий ответы `inner` в файл `path`
+    pub fn new(inner: P, path: impl AsRef<Path>) -> Self {
+        Self {
+            inner,
+            path: path.as_ref().to_path_buf(),
+            responses: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Возвращает количество записанных ответов
+    pub fn len(&self) -> usize {
+        self.responses.lock().unwrap().len()
+    }
+
+    /// Проверяет, что записанных ответов нет
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Сохраняет записанные ответы в файл
+    pub fn save(&self) -> Result<()> {
+        let recording = RecordingFile {
+            format: RECORDING_FORMAT.to_string(),
+            version: RECORDING_VERSION,
+            responses: self.responses.lock().unwrap().clone(),
+        };
+
+        let json = serde_json::to_string_pretty(&recording)?;
+        std::fs::write(&self.path, json)?;
+        log_debug(&format!("Записано ответов TTS: {} в файл {}", recording.responses.len(), self.path.display()));
+        Ok(())
+    }
+
+    /// Запоминает ответ, если для этого запроса он еще не записан
+    fn record(
+        &self,
+        text: &str,
+        speed_factor: Option<f32>,
+        audio_data: &[u8],
+        duration: Option<f64>,
+        word_timings: Option<&Vec<WordTiming>>,
+    ) {
+        let mut responses = self.responses.lock().unwrap();
+        if find_response(&responses, text, speed_factor).is_none() {
+            responses.push(RecordedResponse {
+                text: text.to_string(),
+                speed_factor,
+                audio_data: audio_data.to_vec(),
+                duration,
+                word_timings: word_timings.cloned(),
+            });
+        }
+    }
+}
+
+impl<P: TtsProvider> TtsProvider for RecordingTtsProvider<P> {
+    async fn generate_speech(&self, text: &str) -> Result<Vec<u8>> {
+        let audio_data = self.inner.generate_speech(text).await?;
+        self.record(text, None, &audio_data, None, None);
+        Ok(audio_data)
+    }
+
+    async fn generate_speech_to_file<Q: AsRef<Path> + Send>(&self, text: &str, path: Q) -> Result<()> {
+        let audio_data = self.generate_speech(text).await?;
+        std::fs::write(path, audio_data)?;
+        Ok(())
+    }
+
+    async fn generate_segment(&self, text: &str, target_duration: f64) -> Result<TtsSegment> {
+        let segment = self.inner.generate_segment(text, target_duration).await?;
+        self.record(text, None, &segment.audio_data, segment.duration, segment.word_timings.as_ref());
+        Ok(segment)
+    }
+
+    async fn generate_segment_with_speed(&self, text: &str, target_duration: f64, speed_factor: f32) -> Result<TtsSegment> {
+        let segment = self.inner.generate_segment_with_speed(text, target_duration, speed_factor).await?;
+        self.record(text, Some(speed_factor), &segment.audio_data, segment.duration, segment.word_timings.as_ref());
+        Ok(segment)
+    }
+
+    async fn generate_segment_with_timestamps(&self, text: &str, target_duration: f64) -> Result<TtsSegment> {
+        let segment = self.inner.generate_segment_with_timestamps(text, target_duration).await?;
+        self.record(text, None, &segment.audio_data, segment.duration, segment.word_timings.as_ref());
+        Ok(segment)
+    }
+}
+
+/// Провайдер, воспроизводящий ответы, записанные [`RecordingTtsProvider`]
+///
+/// Не обращается к сети; запрос, для которого нет записанного ответа,
+/// завершается ошибкой.
+pub struct ReplayTtsProvider {
+    responses: Vec<RecordedResponse>,
+}
+
+impl ReplayTtsProvider {
+    /// Загружает записанные ответы из файла
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let json = std::fs::read_to_string(path.as_ref())?;
+        let recording: RecordingFile = serde_json::from_str(&json).map_err(|e| Error::new(
+            ErrorType::InvalidParameters,
+            &format!("Ошибка чтения записи TTS: {}", e)
+        ))?;
+
+        if recording.format != RECORDING_FORMAT || recording.version > RECORDING_VERSION {
+            return Err(Error::new(
+                ErrorType::InvalidParameters,
+                &format!("Неподдерживаемый формат записи TTS: {} v{}", recording.format, recording.version)
+            ));
+        }
+
+        log_debug(&format!("Загружено ответов TTS: {} из файла {}", recording.responses.len(), path.as_ref().display()));
+        Ok(Self { responses: recording.responses })
+    }
+
+    /// Возвращает записанный ответ или ошибку, если его нет
+    fn response(&self, text: &str, speed_factor: Option<f32>) -> Result<&RecordedResponse> {
+        find_response(&self.responses, text, speed_factor).ok_or_else(|| Error::new(
+            ErrorType::InvalidParameters,
+            &format!("Нет записанного ответа TTS для текста: '{}'", text)
+        ))
+    }
+
+    /// Создает сегмент из записанного ответа
+    fn segment(&self, text: &str, target_duration: f64, speed_factor: Option<f32>) -> Result<TtsSegment> {
+        let response = self.response(text, speed_factor)?;
+        Ok(TtsSegment {
+            text: text.to_string(),
+            audio_data: response.audio_data.clone(),
+            duration: response.duration,
+            target_duration,
+            stretch_factor: None,
+            word_timings: response.word_timings.clone(),
+        })
+    }
+}
+
+impl TtsProvider for ReplayTtsProvider {
+    async fn generate_speech(&self, text: &str) -> Result<Vec<u8>> {
+        self.response(text, None).map(|response| response.audio_data.clone())
+    }
+
+    async fn generate_speech_to_file<Q: AsRef<Path> + Send>(&self, text: &str, path: Q) -> Result<()> {
+        let audio_data = self.generate_speech(text).await?;
+        std::fs::write(path, audio_data)?;
+        Ok(())
+    }
+
+    async fn generate_segment(&self, text: &str, target_duration: f64) -> Result<TtsSegment> {
+        self.segment(text, target_duration, None)
+    }
+
+    async fn generate_segment_with_speed(&self, text: &str, target_duration: f64, speed_factor: f32) -> Result<TtsSegment> {
+        self.segment(text, target_duration, Some(speed_factor))
+    }
+}
+
+/// Сериализация бинарных данных в base64
+mod bytes_base64 {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(data))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        STANDARD.decode(encoded).map_err(de::Error::custom)
+    }
+}
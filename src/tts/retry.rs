@@ -0,0 +1,130 @@
+//! Повтор запросов к TTS провайдерам при временных ошибках.
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Настройки повтора запросов с экспоненциальной задержкой
+///
+/// Задержка перед повтором `n` (с нуля) равна `base_delay_ms * 2^n`, но не
+/// больше `max_delay_ms`, и увеличивается на случайную долю до `jitter`.
+/// Если сервер указал `Retry-After`, используется не меньшая из задержек,
+/// но задержка из заголовка ограничивается `max_delay_ms`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Максимальное количество повторов (0 - без повторов)
+    pub max_retries: u32,
+    /// Задержка перед первым повтором в миллисекундах
+    pub base_delay_ms: u64,
+    /// Максимальная задержка между попытками в миллисекундах
+    pub max_delay_ms: u64,
+    /// Случайная добавка к задержке в долях от нее (0.0 - 1.0)
+    pub jitter: f64,
+}
+
+impl RetryPolicy {
+    /// Создает настройки с заданным количеством повторов и начальной задержкой
+    pub fn new(max_retries: u32, base_delay_ms: u64) -> Self {
+        Self {
+            max_retries,
+            base_delay_ms,
+            ..Self::default()
+        }
+    }
+
+    /// Создает настройки без повторов
+    pub fn none() -> Self {
+        Self::new(0, 0)
+    }
+
+    /// Устанавливает максимальную задержку между попытками
+    pub fn with_max_delay_ms(mut self, max_delay_ms: u64) -> Self {
+        self.max_delay_ms = max_delay_ms;
+        self
+    }
+
+    /// Устанавливает случайную добавку к задержке
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Возвращает задержку перед повтором `retry` (с нуля)
+    ///
+    /// `retry_after` - задержка из заголовка `Retry-After`, если он был;
+    /// она не превышает `max_delay_ms`.
+    pub fn delay(&self, retry: u32, retry_after: Option<Duration>) -> Duration {
+        let backoff = self.base_delay_ms
+            .saturating_mul(1u64.checked_shl(retry).unwrap_or(u64::MAX))
+            .min(self.max_delay_ms);
+        let jitter = (backoff as f64 * self.jitter * random_fraction()) as u64;
+        let delay = Duration::from_millis(backoff.saturating_add(jitter));
+
+        match retry_after {
+            Some(retry_after) if retry_after > delay => {
+                retry_after.min(Duration::from_millis(self.max_delay_ms)).max(delay)
+            }
+            _ => delay,
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay_ms: 500,
+            max_delay_ms: 30_000,
+            jitter: 0.2,
+        }
+    }
+}
+
+/// Разбирает значение заголовка `Retry-After` в секундах
+///
+/// Формат с датой HTTP не поддерживается и дает `None`.
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    let seconds: f64 = value.trim().parse().ok()?;
+    if seconds.is_finite() && seconds >= 0.0 {
+        Some(Duration::from_secs_f64(seconds))
+    } else {
+        None
+    }
+}
+
+/// Возвращает псевдослучайное число в диапазоне [0, 1) для разброса задержек
+fn random_fraction() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.subsec_nanos())
+        .unwrap_or(0);
+    // Перемешивание битов (xorshift), чтобы соседние значения времени не давали близких чисел
+    let mut x = nanos as u64 | 1;
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    (x % 1_000_000) as f64 / 1_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_grows_exponentially_and_respects_retry_after() {
+        let policy = RetryPolicy::new(5, 100).with_max_delay_ms(1000).with_jitter(0.0);
+
+        assert_eq!(policy.delay(0, None), Duration::from_millis(100));
+        assert_eq!(policy.delay(2, None), Duration::from_millis(400));
+        assert_eq!(policy.delay(10, None), Duration::from_millis(1000));
+        assert_eq!(policy.delay(0, Some(Duration::from_millis(700))), Duration::from_millis(700));
+        // Задержка из заголовка не превышает максимальную
+        assert_eq!(policy.delay(0, Some(Duration::from_secs(3600))), Duration::from_millis(1000));
+        assert_eq!(policy.delay(2, Some(Duration::from_millis(10))), Duration::from_millis(400));
+
+        let jittered = RetryPolicy::new(1, 100).with_jitter(0.5).delay(0, None);
+        assert!(jittered >= Duration::from_millis(100) && jittered <= Duration::from_millis(150));
+
+        assert_eq!(parse_retry_after(" 2 "), Some(Duration::from_secs(2)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+    }
+}
@@ -0,0 +1,34 @@
+//! Синтетические сегменты TTS для тестов, не требующие обращения к провайдеру.
+
+use crate::audio::AudioData;
+use crate::tts::TtsSegment;
+
+/// Амплитуда тестового тона
+const TONE_AMPLITUDE: f32 = 0.5;
+
+impl TtsSegment {
+    /// Создает сегмент с синусоидальным тоном вместо речи
+    ///
+    /// Аудио данные сегмента - WAV (16-битный PCM, моно) с тоном частотой
+    /// `frequency` Гц и длительностью `duration` секунд, которые декодируются
+    /// так же, как ответ настоящего провайдера.
+    pub fn test_tone(text: &str, duration: f64, frequency: f32, sample_rate: u32) -> Self {
+        let num_samples = (duration.max(0.0) * sample_rate as f64).round() as usize;
+        let samples = (0..num_samples)
+            .map(|i| {
+                let t = i as f32 / sample_rate as f32;
+                TONE_AMPLITUDE * (2.0 * std::f32::consts::PI * frequency * t).sin()
+            })
+            .collect();
+        let audio = AudioData::new(samples, sample_rate, 1);
+
+        Self {
+            text: text.to_string(),
+            audio_data: audio.to_wav_bytes(),
+            duration: Some(audio.duration()),
+            target_duration: duration,
+            stretch_factor: None,
+            word_timings: None,
+        }
+    }
+}
@@ -2,4 +2,4 @@ mod models;
 mod parser;
 
 pub use models::{Subtitle, SubtitleTrack};
-pub use parser::VttParser;
+pub use parser::{EmptyVttBehavior, VttParser};
@@ -7,8 +7,21 @@ pub struct Subtitle {
     pub start_time: f64,
     /// Время окончания субтитра в секундах
     pub end_time: f64,
-    /// Текст субтитра
+    /// Текст субтитра из VTT файла
     pub text: String,
+    /// Исходный текст субтитра для отчетов и сопроводительных файлов;
+    /// нормализация текста для синтеза его не изменяет
+    pub display_text: String,
+    /// Идентификатор субтитра из VTT файла (если указан)
+    pub id: Option<String>,
+    /// Длительность речи в секундах, если она должна занимать только часть
+    /// субтитра; остаток заполняется тишиной (см. `SpeechAnchor`)
+    pub target_speech_duration: Option<f64>,
+    /// Текст для синтеза речи после нормализации (если не задан, используется `text`)
+    pub spoken_text: Option<String>,
+    /// Горизонтальная позиция из настроек субтитра (`position:20%`) в процентах
+    /// ширины кадра от 0 до 100
+    pub position: Option<f32>,
 }
 
 impl Subtitle {
@@ -17,10 +30,71 @@ impl Subtitle {
         Self {
             start_time,
             end_time,
+            display_text: text.clone(),
             text,
+            id: None,
+            target_speech_duration: None,
+            spoken_text: None,
+            position: None,
         }
     }
 
+    /// Устанавливает идентификатор субтитра
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Устанавливает длительность речи, меньшую длительности субтитра
+    pub fn with_target_speech_duration(mut self, duration: f64) -> Self {
+        self.target_speech_duration = Some(duration);
+        self
+    }
+
+    /// Устанавливает горизонтальную позицию субтитра в процентах ширины кадра
+    pub fn with_position(mut self, position: f32) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    /// Возвращает панораму в стерео, соответствующую позиции субтитра
+    ///
+    /// Позиция 0% соответствует -1.0 (слева), 50% - центру, 100% - 1.0 (справа).
+    pub fn position_pan(&self) -> Option<f32> {
+        self.position.map(|position| (position / 50.0 - 1.0).clamp(-1.0, 1.0))
+    }
+
+    /// Возвращает длительность, под которую подгоняется речь
+    ///
+    /// Равна `target_speech_duration`, если она задана и положительна, но не
+    /// больше длительности субтитра; иначе - длительности субтитра.
+    pub fn speech_duration(&self) -> f64 {
+        match self.target_speech_duration {
+            Some(duration) if duration > 0.0 => duration.min(self.duration()),
+            _ => self.duration(),
+        }
+    }
+
+    /// Возвращает исходный текст субтитра без изменений
+    pub fn display_text(&self) -> &str {
+        &self.display_text
+    }
+
+    /// Заполняет `spoken_text` нормализованным для синтеза текстом
+    ///
+    /// Удаляются теги разметки (`<i>`, `<v Имя>` и т.п.), описания звуков
+    /// в квадратных скобках (`[музыка]`) и символы `♪`, пробелы схлопываются.
+    /// Исходный текст `text` не изменяется.
+    pub fn normalize_spoken_text(&mut self) {
+        self.spoken_text = Some(spoken_form(&self.text));
+    }
+
+    /// Возвращает текст для синтеза речи: переносы строк и повторяющиеся
+    /// пробелы заменены одиночными пробелами
+    pub fn normalized_text(&self) -> String {
+        self.text.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
     /// Возвращает длительность субтитра в секундах
     pub fn duration(&self) -> f64 {
         self.end_time - self.start_time
@@ -32,6 +106,36 @@ impl Subtitle {
     }
 }
 
+/// Приводит текст субтитра к виду для синтеза речи
+fn spoken_form(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut closing: Option<char> = None;
+
+    for c in text.chars() {
+        match closing {
+            Some(end) if c == end => {
+                closing = None;
+                result.push(' ');
+            }
+            Some(_) => {}
+            None => match c {
+                '<' => closing = Some('>'),
+                '[' => closing = Some(']'),
+                '♪' => result.push(' '),
+                _ => result.push(c),
+            },
+        }
+    }
+
+    // Пробелы перед знаками препинания остаются после удаления тегов и звуков
+    let collapsed = result.split_whitespace().collect::<Vec<_>>().join(" ");
+    collapsed
+        .replace(" ,", ",")
+        .replace(" .", ".")
+        .replace(" !", "!")
+        .replace(" ?", "?")
+}
+
 /// Коллекция субтитров
 #[derive(Debug, Clone, Default)]
 pub struct SubtitleTrack {
@@ -62,14 +166,31 @@ impl SubtitleTrack {
         self.subtitles.is_empty()
     }
 
+    /// Возвращает субтитр по индексу или `None`, если индекс вне диапазона
+    pub fn get(&self, index: usize) -> Option<&Subtitle> {
+        self.subtitles.get(index)
+    }
+
     /// Возвращает итератор по субтитрам
     pub fn iter(&self) -> impl Iterator<Item = &Subtitle> {
         self.subtitles.iter()
     }
 
+    /// Заполняет `spoken_text` всех субтитров, см. [`Subtitle::normalize_spoken_text`]
+    pub fn normalize_spoken_text(&mut self) {
+        for subtitle in &mut self.subtitles {
+            subtitle.normalize_spoken_text();
+        }
+    }
+
     /// Сортирует субтитры по времени начала
+    ///
+    /// Субтитры с некорректным временем начала (NaN) перемещаются в конец.
     pub fn sort(&mut self) {
-        self.subtitles.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
+        self.subtitles.sort_by(|a, b| {
+            a.start_time.partial_cmp(&b.start_time)
+                .unwrap_or_else(|| a.start_time.is_nan().cmp(&b.start_time.is_nan()))
+        });
     }
 
     /// Возвращает общую длительность всех субтитров
@@ -88,6 +209,32 @@ impl SubtitleTrack {
             
         max_end - min_start
     }
+
+    /// Возвращает субтитры, пересекающиеся с диапазоном времени `[start, end)`
+    ///
+    /// Время субтитров не изменяется; для обрезки по границам диапазона
+    /// используйте [`SubtitleTrack::slice_clipped`].
+    pub fn slice(&self, start: f64, end: f64) -> SubtitleTrack {
+        let subtitles = self.subtitles.iter()
+            .filter(|s| s.start_time < end && s.end_time > start)
+            .cloned()
+            .collect();
+        
+        SubtitleTrack { subtitles }
+    }
+
+    /// Возвращает субтитры, пересекающиеся с диапазоном времени `[start, end)`,
+    /// обрезая их время по границам диапазона
+    pub fn slice_clipped(&self, start: f64, end: f64) -> SubtitleTrack {
+        let mut track = self.slice(start, end);
+        
+        for subtitle in &mut track.subtitles {
+            subtitle.start_time = subtitle.start_time.max(start);
+            subtitle.end_time = subtitle.end_time.min(end);
+        }
+        
+        track
+    }
 }
 
 impl std::ops::Index<usize> for SubtitleTrack {
@@ -1,29 +1,58 @@
 use crate::error::{Error, Result};
+use crate::logging::log_warning;
 use crate::vtt::models::{Subtitle, SubtitleTrack};
 use regex::Regex;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
+/// Поведение парсера для файлов без субтитров
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmptyVttBehavior {
+    /// Вернуть пустой трек
+    #[default]
+    Allow,
+    /// Вернуть ошибку парсинга с указанием причины: пустой файл без
+    /// заголовка или файл только с заголовком и комментариями
+    Error,
+}
+
 /// Парсер VTT файлов
 pub struct VttParser;
 
 impl VttParser {
     /// Парсит VTT файл и возвращает коллекцию субтитров
     pub fn parse_file<P: AsRef<Path>>(path: P) -> Result<SubtitleTrack> {
+        Self::parse_file_with(path, EmptyVttBehavior::Allow)
+    }
+
+    /// Парсит VTT файл с указанным поведением для файлов без субтитров
+    pub fn parse_file_with<P: AsRef<Path>>(path: P, empty_behavior: EmptyVttBehavior) -> Result<SubtitleTrack> {
         let file = File::open(path).map_err(|e| Error::Io(e))?;
         let reader = BufReader::new(file);
-        Self::parse_reader(reader)
+        Self::parse_reader_with(reader, empty_behavior)
     }
 
     /// Парсит VTT из строки и возвращает коллекцию субтитров
     pub fn parse_str(content: &str) -> Result<SubtitleTrack> {
+        Self::parse_str_with(content, EmptyVttBehavior::Allow)
+    }
+
+    /// Парсит VTT из строки с указанным поведением для файлов без субтитров
+    pub fn parse_str_with(content: &str, empty_behavior: EmptyVttBehavior) -> Result<SubtitleTrack> {
         let reader = BufReader::new(content.as_bytes());
-        Self::parse_reader(reader)
+        Self::parse_reader_with(reader, empty_behavior)
     }
 
     /// Парсит VTT из любого источника, реализующего BufRead
+    ///
+    /// Пустой источник дает пустой трек.
     pub fn parse_reader<R: BufRead>(reader: R) -> Result<SubtitleTrack> {
+        Self::parse_reader_with(reader, EmptyVttBehavior::Allow)
+    }
+
+    /// Парсит VTT из источника с указанным поведением для файлов без субтитров
+    pub fn parse_reader_with<R: BufRead>(reader: R, empty_behavior: EmptyVttBehavior) -> Result<SubtitleTrack> {
         let mut subtitle_track = SubtitleTrack::new();
         let mut lines = reader.lines();
         
@@ -32,6 +61,8 @@ impl VttParser {
             if !first_line.trim().starts_with("WEBVTT") {
                 return Err(Error::VttParsing("Invalid WebVTT file: missing WEBVTT header".to_string()));
             }
+        } else if empty_behavior == EmptyVttBehavior::Error {
+            return Err(Error::VttParsing("Invalid WebVTT file: file is empty, missing WEBVTT header".to_string()));
         } else {
             // Пустой файл - возвращаем пустой трек
             return Ok(subtitle_track);
@@ -43,7 +74,11 @@ impl VttParser {
         
         let mut current_start_time: Option<f64> = None;
         let mut current_end_time: Option<f64> = None;
+        let mut current_position: Option<f32> = None;
         let mut current_text = String::new();
+        // Идентификатор текущего субтитра и кандидат в идентификатор следующего
+        let mut current_id: Option<String> = None;
+        let mut pending_id: Option<String> = None;
         
         // Парсинг субтитров
         for line_result in lines {
@@ -53,12 +88,18 @@ impl VttParser {
             if let Some(captures) = timestamp_regex.captures(trimmed_line) {
                 // Если у нас уже есть временные метки и текст, добавляем предыдущий субтитр
                 if current_start_time.is_some() && !current_text.trim().is_empty() {
-                    subtitle_track.add(Subtitle::new(
-                        current_start_time.unwrap(),
-                        current_end_time.unwrap(),
-                        current_text.trim().to_string(),
-                    ));
+                    subtitle_track.add(Subtitle {
+                        id: current_id.take(),
+                        position: current_position,
+                        ..Subtitle::new(
+                            current_start_time.unwrap(),
+                            current_end_time.unwrap(),
+                            current_text.trim().to_string(),
+                        )
+                    });
                     current_text.clear();
+                } else if let (Some(start), Some(end)) = (current_start_time, current_end_time) {
+                    Self::warn_dangling_timestamp(start, end);
                 }
                 
                 // Парсинг новых временных меток
@@ -72,14 +113,23 @@ impl VttParser {
                 
                 current_start_time = Some(start_time);
                 current_end_time = Some(end_time);
+                current_position = Self::parse_position(&trimmed_line[captures.get(0).map_or(0, |m| m.end())..]);
+                current_id = pending_id.take();
             } else if trimmed_line.is_empty() {
+                // Пустая строка вне субтитра сбрасывает кандидата в идентификаторы
+                pending_id = None;
+                
                 // Пустая строка может означать конец субтитра
                 if current_start_time.is_some() && !current_text.trim().is_empty() {
-                    subtitle_track.add(Subtitle::new(
-                        current_start_time.unwrap(),
-                        current_end_time.unwrap(),
-                        current_text.trim().to_string(),
-                    ));
+                    subtitle_track.add(Subtitle {
+                        id: current_id.take(),
+                        position: current_position,
+                        ..Subtitle::new(
+                            current_start_time.unwrap(),
+                            current_end_time.unwrap(),
+                            current_text.trim().to_string(),
+                        )
+                    });
                     current_text.clear();
                     current_start_time = None;
                     current_end_time = None;
@@ -90,15 +140,31 @@ impl VttParser {
                     current_text.push('\n');
                 }
                 current_text.push_str(trimmed_line);
+            } else {
+                // Строка перед временными метками - идентификатор субтитра
+                pending_id = Some(trimmed_line.to_string());
             }
         }
         
         // Добавляем последний субтитр, если он есть
         if current_start_time.is_some() && !current_text.trim().is_empty() {
-            subtitle_track.add(Subtitle::new(
-                current_start_time.unwrap(),
-                current_end_time.unwrap(),
-                current_text.trim().to_string(),
+            subtitle_track.add(Subtitle {
+                id: current_id.take(),
+                position: current_position,
+                ..Subtitle::new(
+                    current_start_time.unwrap(),
+                    current_end_time.unwrap(),
+                    current_text.trim().to_string(),
+                )
+            });
+        } else if let (Some(start), Some(end)) = (current_start_time, current_end_time) {
+            // Файл закончился сразу после временных меток
+            Self::warn_dangling_timestamp(start, end);
+        }
+        
+        if subtitle_track.is_empty() && empty_behavior == EmptyVttBehavior::Error {
+            return Err(Error::VttParsing(
+                "WebVTT file contains no cues: only the header and comments were found".to_string()
             ));
         }
         
@@ -108,6 +174,24 @@ impl VttParser {
         Ok(subtitle_track)
     }
     
+    /// Сообщает о временных метках без текста, которые не попадают в трек
+    fn warn_dangling_timestamp(start: f64, end: f64) {
+        log_warning(&format!(
+            "Субтитр {:.3} --> {:.3} не содержит текста и будет пропущен",
+            start, end
+        ));
+    }
+    
+    /// Извлекает позицию в процентах из настроек субтитра (`position:20%,line-left`)
+    fn parse_position(settings: &str) -> Option<f32> {
+        settings.split_whitespace()
+            .find_map(|setting| setting.strip_prefix("position:"))
+            .and_then(|value| value.split(',').next())
+            .and_then(|value| value.strip_suffix('%'))
+            .and_then(|value| value.parse::<f32>().ok())
+            .filter(|position| (0.0..=100.0).contains(position))
+    }
+    
     /// Парсит временную метку и возвращает время в секундах
     fn parse_timestamp(hours: &str, minutes: &str, seconds: &str, milliseconds: &str) -> Result<f64> {
         let hours: u32 = hours.parse()
@@ -176,7 +260,9 @@ mod tests {
         
         assert_eq!(track.len(), 2);
         assert_eq!(track.subtitles[0].text, "Hello, world!");
+        assert_eq!(track.subtitles[0].id.as_deref(), Some("1"));
         assert_eq!(track.subtitles[1].text, "This is a test.");
+        assert_eq!(track.subtitles[1].id.as_deref(), Some("2"));
     }
     
     #[test]
@@ -186,6 +272,7 @@ mod tests {
         
         assert_eq!(track.len(), 1);
         assert_eq!(track.subtitles[0].text, "Hello, world!");
+        assert_eq!(track.subtitles[0].id, None);
     }
     
     #[test]
@@ -1,4 +1,5 @@
-use tts_sync::{AudioData, AudioProcessor, TempoAdjuster, audio::TempoAlgorithm, Result};
+use tts_sync::{AudioData, AudioSegment, AudioTrack, AudioProcessor, DitherType, MergePolicy, TempoAdjuster, audio::TempoAlgorithm, Result};
+use tts_sync::{Endianness, RawPcmSpec, SampleRateMismatch, TtsSegment, audio::{decode_raw_tts_audio, decode_tts_audio}};
 
 #[test]
 fn test_audio_data_creation() {
@@ -73,64 +74,66 @@ fn test_audio_compression() -> Result<()> {
     Ok(())
 }
 
+/// Синусоида частоты `freq` длительностью одна секунда
+fn sine_tone(freq: f32, amplitude: f32, sample_rate: u32) -> AudioData {
+    let samples = (0..sample_rate)
+        .map(|i| amplitude * (2.0 * std::f32::consts::PI * freq * i as f32 / sample_rate as f32).sin())
+        .collect();
+    AudioData::new(samples, sample_rate, 1)
+}
+
+/// Усиление тона в дБ после переходного процесса фильтров
+fn tone_gain_db(original: &AudioData, processed: &AudioData) -> f32 {
+    let settled = original.sample_rate as usize / 4;
+    let rms = |samples: &[f32]| (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt();
+    20.0 * (rms(&processed.samples[settled..]) / rms(&original.samples[settled..])).log10()
+}
+
 #[test]
 fn test_audio_equalization() -> Result<()> {
-    // Создаем тестовые данные с разными частотами
-    let mut samples = Vec::new();
-    for i in 0..44100 { // 1 second at 44.1kHz
-        let t = i as f32 / 44100.0;
-        // Mix of low, mid, and high frequencies
-        // Using frequencies well-separated from crossover points (300 Hz and 3 kHz)
-        samples.push(
-            0.3 * (t * 100.0).sin() +  // Low frequency (100 Hz)
-            0.3 * (t * 1500.0).sin() + // Mid frequency (1.5 kHz)
-            0.3 * (t * 5000.0).sin()   // High frequency (5 kHz)
-        );
-    }
-    let audio = AudioData::new(samples, 44100, 1);
-    
-    // Применяем эквализацию
-    let equalized = AudioProcessor::apply_equalization(
-        &audio,
-        3.0,    // усиление низких частот в дБ
-        0.0,    // усиление средних частот в дБ
-        -3.0,   // усиление высоких частот в дБ
-        300.0,  // частота разделения низких и средних частот в Гц
-        3000.0  // частота разделения средних и высоких частот в Гц
-    )?;
-    
-    // Проверяем, что длина не изменилась
-    assert_eq!(equalized.samples.len(), audio.samples.len());
-    
-    // Проверяем, что эквализация применена (значения изменились)
-    let mut has_changes = false;
-    for (&original, &equalized) in audio.samples.iter().zip(equalized.samples.iter()) {
-        if (original - equalized).abs() > 0.001 {
-            has_changes = true;
-            break;
+    // Тоны в центре каждой полосы при разделении на 300 Гц и 3 кГц
+    let tones = [100.0, 1500.0, 5000.0];
+
+    // Усиление одной полосы применяется к тону в ней
+    for band in 0..3 {
+        for gain in [6.0, -6.0] {
+            let mut gains = [0.0; 3];
+            gains[band] = gain;
+
+            let audio = sine_tone(tones[band], 0.25, 44100);
+            let equalized = AudioProcessor::apply_equalization(&audio, gains[0], gains[1], gains[2], 300.0, 3000.0)?;
+            assert_eq!(equalized.samples.len(), audio.samples.len());
+
+            let gain_db = tone_gain_db(&audio, &equalized);
+            assert!((gain_db - gain).abs() < 1.0,
+                "Тон {} Гц при усилении полос {:?}: {:.2} дБ", tones[band], gains, gain_db);
         }
     }
-    assert!(has_changes, "Эквализация не изменила аудио данные");
-    
-    // Проверяем, что эквализация не исказила сигнал слишком сильно
-    // (форма сигнала должна быть сохранена)
-    let mut max_diff: f32 = 0.0;
-    let mut max_amplitude: f32 = 0.0;
-    for (&original, &equalized) in audio.samples.iter().zip(equalized.samples.iter()) {
-        max_diff = max_diff.max((original - equalized).abs());
-        max_amplitude = max_amplitude.max(original.abs());
-    }
-    
-    // Проверяем, что максимальная разница не превышает 40% от максимальной амплитуды
-    assert!(max_diff <= 0.4 * max_amplitude, 
-        "Эквализация слишком сильно исказила сигнал: max_diff={}, max_amplitude={}", 
-        max_diff, max_amplitude);
-    
-    // Проверяем, что амплитуда не превышает 1.0
-    for &sample in &equalized.samples {
-        assert!(sample.abs() <= 1.0);
+
+    // При настройке всех полос каждый тон получает усиление своей полосы
+    let gains = [3.0, 0.0, -3.0];
+    for (&freq, &expected_db) in tones.iter().zip(gains.iter()) {
+        let audio = sine_tone(freq, 0.25, 44100);
+        let equalized = AudioProcessor::apply_equalization(&audio, gains[0], gains[1], gains[2], 300.0, 3000.0)?;
+        let gain_db = tone_gain_db(&audio, &equalized);
+        assert!((gain_db - expected_db).abs() < 1.0,
+            "Тон {} Гц при усилении полос {:?}: {:.2} дБ", freq, gains, gain_db);
     }
-    
+
+    // На частоте разделения полка дает половину усиления
+    let audio = sine_tone(300.0, 0.25, 44100);
+    let equalized = AudioProcessor::apply_equalization(&audio, 6.0, 0.0, 0.0, 300.0, 3000.0)?;
+    assert!((tone_gain_db(&audio, &equalized) - 3.0).abs() < 0.5);
+
+    // Узкая средняя полоса почти не затрагивает тоны у частот разделения
+    let audio = sine_tone(5000.0, 0.25, 44100);
+    let narrow = AudioProcessor::apply_equalization_with_q(&audio, 0.0, 6.0, 0.0, 300.0, 3000.0, 4.0)?;
+    assert!(tone_gain_db(&audio, &narrow).abs() < 0.5);
+
+    assert!(AudioProcessor::apply_equalization_with_q(&audio, 0.0, 6.0, 0.0, 300.0, 3000.0, 0.0).is_err());
+    assert!(AudioProcessor::apply_equalization(&audio, 0.0, 0.0, 0.0, 3000.0, 300.0).is_err());
+    assert!(AudioProcessor::apply_equalization(&audio, 0.0, 0.0, 0.0, 300.0, 30000.0).is_err());
+
     Ok(())
 }
 
@@ -242,4 +245,712 @@ fn test_adaptive_tempo_adjustment() -> Result<()> {
     );
     
     Ok(())
-}
\ No newline at end of file
+}
+#[test]
+fn test_track_merge_policies() -> Result<()> {
+    // Кадр = 0.1 с при частоте 10 Гц; трек начинается с нуля
+    let mut track = AudioTrack::new(10, 1);
+    track.add_segment(AudioSegment::new(AudioData::new(vec![0.5; 4], 10, 1), 1.0, 1.4, "A".to_string()));
+    track.add_segment(AudioSegment::new(AudioData::new(vec![0.25; 2], 10, 1), 1.6, 1.8, "B".to_string()));
+    
+    // Без перекрытия способы совпадают, промежутки заполнены тишиной
+    let mut expected = vec![0.0; 10];
+    expected.extend([0.5, 0.5, 0.5, 0.5, 0.0, 0.0, 0.25, 0.25]);
+    assert_eq!(track.merge_with(MergePolicy::Overwrite)?.samples, expected);
+    assert_eq!(track.merge_with(MergePolicy::Sum)?.samples, expected);
+    assert_eq!(track.merge()?.samples, expected);
+    
+    // С перекрытием последний сегмент заменяет предыдущий или складывается с ним
+    track.add_segment(AudioSegment::new(AudioData::new(vec![0.125; 3], 10, 1), 1.2, 1.5, "C".to_string()));
+    assert_eq!(track.merge_with(MergePolicy::Overwrite)?.samples[10..],
+        [0.5, 0.5, 0.125, 0.125, 0.125, 0.0, 0.25, 0.25]);
+    assert_eq!(track.merge_with(MergePolicy::Sum)?.samples[10..],
+        [0.5, 0.5, 0.625, 0.625, 0.125, 0.0, 0.25, 0.25]);
+    
+    // По умолчанию перекрывающиеся сегменты складываются
+    assert_eq!(track.merge()?, track.merge_with(MergePolicy::Sum)?);
+    
+    Ok(())
+}
+
+#[test]
+fn test_track_merge_places_segments_on_timeline() -> Result<()> {
+    let sample_rate = 1000;
+    let mut track = AudioTrack::new(sample_rate, 1);
+    track.add_segment(AudioSegment::new(AudioData::new(vec![0.5; 500], sample_rate, 1), 1.0, 1.5, "A".to_string()));
+    track.add_segment(AudioSegment::new(AudioData::new(vec![0.25; 500], sample_rate, 1), 6.0, 6.5, "B".to_string()));
+    
+    // Буфер начинается с нуля, сэмплы сегментов попадают на свои секунды
+    let (merged, overruns) = track.merge_with_overruns(MergePolicy::Sum)?;
+    assert_eq!(merged.samples.len(), 6500);
+    assert!(merged.samples[..1000].iter().all(|&s| s == 0.0));
+    assert!(merged.samples[1000..1500].iter().all(|&s| s == 0.5));
+    assert!(merged.samples[1500..6000].iter().all(|&s| s == 0.0));
+    assert!(merged.samples[6000..].iter().all(|&s| s == 0.25));
+    assert!(overruns.is_empty());
+    
+    // Сегмент длиннее своего интервала и сегмент до нуля обрезаются, а не пропускаются
+    track.add_segment(AudioSegment::new(AudioData::new(vec![0.125; 1000], sample_rate, 1), 6.0, 6.5, "C".to_string()));
+    track.add_segment(AudioSegment::new(AudioData::new(vec![0.125; 200], sample_rate, 1), -0.1, 0.1, "D".to_string()));
+    let (merged, overruns) = track.merge_with_overruns(MergePolicy::Sum)?;
+    assert_eq!(merged.samples.len(), 6500);
+    assert!(merged.samples[..100].iter().all(|&s| s == 0.125));
+    assert_eq!(merged.samples[100], 0.0);
+    assert!(merged.samples[6000..].iter().all(|&s| s == 0.375));
+    assert_eq!(overruns, vec![2, 3]);
+    
+    Ok(())
+}
+
+#[test]
+fn test_track_merge_places_stereo_segments_on_frames() -> Result<()> {
+    // Начало 0.05 с при 10 Гц приходится на середину кадра; сегмент
+    // размещается с целого кадра, и каналы не меняются местами
+    let mut track = AudioTrack::new(10, 2);
+    track.add_segment(AudioSegment::new(AudioData::new(vec![0.0; 4], 10, 2), 0.0, 0.2, String::new()));
+    track.add_segment(AudioSegment::new(AudioData::new(vec![0.5, -0.5], 10, 2), 0.05, 0.2, "S".to_string()));
+    
+    let merged = track.merge()?;
+    assert_eq!(merged.samples, vec![0.0, 0.0, 0.5, -0.5]);
+    
+    Ok(())
+}
+
+#[test]
+fn test_track_sorting_tolerates_nan_start_time() {
+    // Сегмент с NaN во времени начала не должен приводить к панике при сортировке
+    let mut track = AudioTrack::new(10, 1);
+    track.add_segment(AudioSegment::new(AudioData::new(vec![0.5; 2], 10, 1), 2.0, 2.2, "B".to_string()));
+    track.add_segment(AudioSegment::new(AudioData::new(vec![0.5; 2], 10, 1), f64::NAN, 1.0, "N".to_string()));
+    track.add_segment(AudioSegment::new(AudioData::new(vec![0.5; 2], 10, 1), 1.0, 1.2, "A".to_string()));
+    
+    assert_eq!(track.speech_regions(), vec![(1.0, 1.2), (2.0, 2.2)]);
+    
+    track.sort_by_start_time();
+    let texts: Vec<&str> = track.segments.iter().map(|segment| segment.text.as_str()).collect();
+    assert_eq!(texts, vec!["A", "B", "N"]);
+}
+
+#[test]
+fn test_pan_regions_use_track_time() -> Result<()> {
+    // Первая реплика начинается не с нуля: объединение отсчитывает время от нуля
+    let mut track = AudioTrack::new(44100, 1);
+    track.add_segment(AudioSegment::new(AudioData::new(vec![0.5; 44100], 44100, 1), 1.0, 2.0, "Left".to_string())
+        .with_pan(Some(-1.0)));
+    track.add_segment(AudioSegment::new(AudioData::new(vec![0.5; 44100], 44100, 1), 2.0, 3.0, "Center".to_string()));
+    
+    assert_eq!(track.pan_regions(), vec![(1.0, 2.0, -1.0)]);
+    
+    // Реплика панорамирована влево, соседняя - по центру
+    let stereo = track.merge()?.to_stereo_with_regions(0.0, &track.pan_regions());
+    let frame = |seconds: f64| {
+        let index = (seconds * 44100.0) as usize * 2;
+        (stereo.samples[index], stereo.samples[index + 1])
+    };
+    let (left, right) = frame(1.5);
+    assert!(left > 0.49 && right.abs() < 1e-6, "{} {}", left, right);
+    let (left, right) = frame(2.5);
+    assert!((left - right).abs() < 1e-6 && left > 0.3, "{} {}", left, right);
+    
+    Ok(())
+}
+
+#[test]
+fn test_pad_with_silence_fades_out_last_segment() -> Result<()> {
+    // Сегмент с постоянным смещением (DC), последний сэмпл не равен нулю
+    let audio = AudioData::new(vec![0.5; 44100], 44100, 1);
+    let mut track = AudioTrack::new(44100, 1);
+    track.add_segment(AudioSegment::new(audio, 0.0, 1.0, "Speech".to_string()));
+    
+    // Дополняем тишиной до 2 секунд с затуханием 10 мс
+    track.pad_with_silence(2.0, 0.01);
+    assert_eq!(track.len(), 2);
+    assert_eq!(track.segments[1].start_time, 1.0);
+    assert_eq!(track.segments[1].end_time, 2.0);
+    
+    let merged = track.merge()?;
+    
+    // На границе сегмента и тишины нет скачка амплитуды
+    let boundary = 44100;
+    let max_jump = merged.samples[boundary - 500..boundary + 500]
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).abs())
+        .fold(0.0f32, f32::max);
+    assert!(max_jump < 0.01, "Скачок на границе: {}", max_jump);
+    
+    // Начало сегмента не затронуто затуханием
+    assert_eq!(merged.samples[0], 0.5);
+    
+    Ok(())
+}
+
+#[test]
+fn test_audio_track_project_round_trip() {
+    let mut track = AudioTrack::new(22050, 1);
+    track.add_segment(AudioSegment::new(
+        AudioData::new(vec![0.0, 0.25, -0.5, 1.0, f32::MIN_POSITIVE], 22050, 1),
+        0.5,
+        1.5,
+        "Привет".to_string(),
+    ).with_id(Some("intro".to_string())));
+    track.add_segment(AudioSegment::new_with_raw_data(
+        AudioData::new(vec![0.125; 32], 22050, 1),
+        2.0,
+        3.0,
+        "World".to_string(),
+        vec![0xFF, 0xFB, 0x90, 0x00],
+    ));
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let project_path = temp_dir.path().join("render.ttsproj");
+    track.save_project(&project_path).unwrap();
+
+    let restored = AudioTrack::load_project(&project_path).unwrap();
+    assert_eq!(restored, track);
+
+    // Файл другого формата не загружается
+    assert!(AudioTrack::from_project_json("{\"format\":\"other\",\"version\":1,\"track\":{\"segments\":[],\"sample_rate\":1,\"channels\":1}}").is_err());
+}
+
+/// Логгер, сохраняющий предупреждения для проверки в тестах
+struct WarningCapture {
+    messages: std::sync::Mutex<Vec<String>>,
+}
+
+impl log::Log for WarningCapture {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Warn
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            self.messages.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn warning_capture() -> &'static WarningCapture {
+    static CAPTURE: std::sync::OnceLock<&'static WarningCapture> = std::sync::OnceLock::new();
+    CAPTURE.get_or_init(|| {
+        let capture: &'static WarningCapture = Box::leak(Box::new(WarningCapture {
+            messages: std::sync::Mutex::new(Vec::new()),
+        }));
+        let _ = log::set_logger(capture);
+        log::set_max_level(log::LevelFilter::Warn);
+        capture
+    })
+}
+
+#[test]
+fn test_decode_resamples_unexpected_provider_rate() {
+    use tts_sync::audio::{decode_tts_audio, SampleRateMismatch};
+
+    let capture = warning_capture();
+
+    // Провайдер вернул 0.5с аудио с частотой 22050 Гц вместо ожидаемых 24000 Гц
+    let samples: Vec<f32> = (0..11025)
+        .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 22050.0).sin())
+        .collect();
+    let wav = AudioData::new(samples, 22050, 1).to_wav_bytes();
+
+    let audio = decode_tts_audio(&wav, 24000, 44100, SampleRateMismatch::Resample).unwrap();
+    assert_eq!(audio.sample_rate, 44100);
+    assert_eq!(audio.channels, 1);
+    assert_eq!(audio.samples.len(), 22050);
+    assert!((audio.duration() - 0.5).abs() < 1e-3);
+
+    assert!(capture.messages.lock().unwrap().iter()
+        .any(|message| message.contains("22050") && message.contains("24000")));
+
+    // Прежнее поведение: данные считаются записанными с ожидаемой частотой
+    let ignored = decode_tts_audio(&wav, 24000, 24000, SampleRateMismatch::Ignore).unwrap();
+    assert_eq!(ignored.sample_rate, 24000);
+    assert_eq!(ignored.samples.len(), 11025);
+
+    assert!(decode_tts_audio(&wav, 24000, 44100, SampleRateMismatch::Error).is_err());
+}
+
+#[test]
+fn test_decode_aborts_when_sample_limit_exceeded() {
+    use tts_sync::audio::decode_tts_audio_with_limit;
+
+    // 10 секунд аудио при пределе в 1000 сэмплов
+    let wav = TtsSegment::test_tone("", 10.0, 440.0, 24000).audio_data;
+
+    let result = decode_tts_audio_with_limit(&wav, 24000, 24000, SampleRateMismatch::Resample, Some(1000));
+    assert!(result.is_err());
+
+    let audio = decode_tts_audio_with_limit(&wav, 24000, 24000, SampleRateMismatch::Resample, None).unwrap();
+    assert_eq!(audio.samples.len(), 240000);
+}
+
+#[test]
+fn test_audio_data_file_round_trip() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("narration.WAV");
+
+    // Стерео: разные тоны в левом и правом каналах
+    let samples: Vec<f32> = (0..4410)
+        .flat_map(|i| {
+            let t = i as f32 / 44100.0;
+            [0.5 * (2.0 * std::f32::consts::PI * 440.0 * t).sin(), 0.25 * (2.0 * std::f32::consts::PI * 660.0 * t).sin()]
+        })
+        .collect();
+    let audio = AudioData::new(samples, 44100, 2);
+    audio.to_file(&path).unwrap();
+
+    let loaded = AudioData::from_file(&path).unwrap();
+    assert_eq!(loaded.sample_rate, 44100);
+    assert_eq!(loaded.channels, 2);
+    assert_eq!(loaded.samples.len(), audio.samples.len());
+    assert!(loaded.samples.iter().zip(&audio.samples).all(|(a, b)| (a - b).abs() < 1e-4));
+
+    // MP3 кодируется с потерями: совпадают параметры и длительность с учетом
+    // задержки кодировщика
+    let mp3_path = temp_dir.path().join("narration.mp3");
+    audio.to_file(&mp3_path).unwrap();
+    let loaded = AudioData::from_file(&mp3_path).unwrap();
+    assert_eq!((loaded.sample_rate, loaded.channels), (44100, 2));
+    assert!(loaded.duration() >= audio.duration() && loaded.duration() < audio.duration() + 0.1);
+
+    // OGG и Opus кодируются встроенными кодировщиками: количество сэмплов
+    // сохраняется, Opus передискретизируется в 48 кГц
+    let ogg_path = temp_dir.path().join("narration.ogg");
+    audio.to_file(&ogg_path).unwrap();
+    let loaded = AudioData::from_file(&ogg_path).unwrap();
+    assert_eq!((loaded.sample_rate, loaded.channels), (44100, 2));
+    assert_eq!(loaded.samples.len(), audio.samples.len());
+
+    let opus_path = temp_dir.path().join("narration.opus");
+    audio.to_file(&opus_path).unwrap();
+    let loaded = AudioData::from_file(&opus_path).unwrap();
+    assert_eq!((loaded.sample_rate, loaded.channels), (48000, 2));
+    assert!((loaded.duration() - audio.duration()).abs() < 1e-9, "{}", loaded.duration());
+
+    // Неподдерживаемые форматы отклоняются
+    assert!(matches!(AudioData::from_file(temp_dir.path().join("narration.aac")), Err(tts_sync::Error::InvalidParameters(_))));
+    assert!(matches!(audio.to_file(temp_dir.path().join("narration.aac")), Err(tts_sync::Error::InvalidParameters(_))));
+    assert!(AudioData::from_file(temp_dir.path().join("missing.wav")).is_err());
+}
+
+#[test]
+fn test_audio_data_flac_round_trip() {
+    let temp_dir = tempfile::tempdir().unwrap();
+
+    // Тон, тишина и шум: несколько кадров FLAC с разными способами кодирования
+    let mut noise_state = 12345u32;
+    let mut noise = move || {
+        noise_state = noise_state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        (noise_state >> 16) as f32 / 32768.0 - 1.0
+    };
+    let mono: Vec<f32> = (0..10001)
+        .map(|i| match i {
+            0..=4999 => 0.5 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 44100.0).sin(),
+            5000..=8999 => 0.0,
+            _ => 0.9 * noise(),
+        })
+        .collect();
+    let stereo: Vec<f32> = (0..4410)
+        .flat_map(|i| {
+            let t = i as f32 / 48000.0;
+            [0.5 * (2.0 * std::f32::consts::PI * 440.0 * t).sin(), -0.25]
+        })
+        .collect();
+
+    for (audio, name) in [(AudioData::new(mono, 44100, 1), "mono.flac"), (AudioData::new(stereo, 48000, 2), "stereo.FLAC")] {
+        let path = temp_dir.path().join(name);
+        audio.to_file(&path).unwrap();
+        assert_eq!(&std::fs::read(&path).unwrap()[0..4], b"fLaC");
+
+        // Сжатие без потерь: декодированные сэмплы совпадают с 16-битным PCM
+        let loaded = AudioData::from_file(&path).unwrap();
+        assert_eq!(loaded.sample_rate, audio.sample_rate);
+        assert_eq!(loaded.channels, audio.channels);
+        assert_eq!(loaded.samples.len(), audio.samples.len());
+        assert!((loaded.duration() - audio.duration()).abs() < 1e-9);
+        let wav_path = path.with_extension("wav");
+        audio.to_file(&wav_path).unwrap();
+        let expected = AudioData::from_file(&wav_path).unwrap();
+        assert!(loaded.samples.iter().zip(&expected.samples).all(|(a, b)| (a - b).abs() < 1e-6), "{}", name);
+    }
+}
+
+#[test]
+fn test_audio_segment_adjust_tempo() -> Result<()> {
+    let samples: Vec<f32> = (0..16000)
+        .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 220.0 * i as f32 / 16000.0).sin())
+        .collect();
+    let segment = AudioSegment::new(AudioData::new(samples, 16000, 1), 1.0, 2.0, "Hello".to_string())
+        .with_id(Some("intro".to_string()));
+
+    // Ускорение в 2 раза вдвое сокращает аудио, не меняя тайминг и текст
+    let faster = segment.adjust_tempo(2.0)?;
+    assert!((faster.audio.duration() - 0.5).abs() < 0.01, "Длительность: {}", faster.audio.duration());
+    assert_eq!((faster.start_time, faster.end_time), (1.0, 2.0));
+    assert_eq!(faster.text, "Hello");
+    assert_eq!(faster.id.as_deref(), Some("intro"));
+
+    let linear = segment.adjust_tempo_with(2.0, TempoAlgorithm::Linear)?;
+    assert_eq!(linear.audio.samples.len(), 8000);
+
+    // Почти единичный коэффициент оставляет сегмент без изменений
+    assert_eq!(segment.adjust_tempo(1.005)?, segment);
+    assert!(matches!(segment.adjust_tempo(0.0), Err(tts_sync::Error::InvalidParameters(_))));
+    assert!(matches!(segment.adjust_tempo(-1.0), Err(tts_sync::Error::InvalidParameters(_))));
+    Ok(())
+}
+
+#[test]
+fn test_audio_data_resample() {
+    let audio = AudioData::new(vec![0.0, 1.0, 0.0, -1.0], 4, 1);
+    let upsampled = audio.resample(8).unwrap();
+
+    assert_eq!(upsampled.sample_rate, 8);
+    assert_eq!(upsampled.samples.len(), 8);
+    assert!((upsampled.samples[1] - 0.5).abs() < 1e-6);
+    assert!((upsampled.duration() - audio.duration()).abs() < 1e-9);
+}
+
+/// Возвращает ошибку квантования (в младших разрядах) для каждого сэмпла
+fn quantization_errors(audio: &AudioData, dither: DitherType) -> Vec<f64> {
+    let (bytes, _) = audio.to_pcm16_le_dithered(dither);
+    bytes.chunks_exact(2)
+        .zip(&audio.samples)
+        .map(|(pcm, &sample)| i16::from_le_bytes([pcm[0], pcm[1]]) as f64 - sample as f64 * 32767.0)
+        .collect()
+}
+
+/// Возвращает среднее, дисперсию и коэффициент автокорреляции с шагом 1
+fn error_statistics(errors: &[f64]) -> (f64, f64, f64) {
+    let mean = errors.iter().sum::<f64>() / errors.len() as f64;
+    let variance = errors.iter().map(|e| (e - mean).powi(2)).sum::<f64>() / errors.len() as f64;
+    let lag1 = errors.windows(2).map(|w| (w[0] - mean) * (w[1] - mean)).sum::<f64>()
+        / (errors.len() - 1) as f64;
+    (mean, variance, lag1 / variance)
+}
+
+#[test]
+fn test_pcm16_dither_noise_characteristics() {
+    // Без дизеринга вывод не меняется
+    let sine = tone(440.0, 1.0, 44100);
+    assert_eq!(sine.to_pcm16_le_dithered(DitherType::None), sine.to_pcm16_le());
+
+    // TPDF: ошибка несмещена, ее дисперсия не зависит от сигнала
+    // (1/12 от квантования + 1/6 от треугольного шума = 0.25 LSB^2)
+    for offset in [0.0f32, 0.25, 0.5, 0.75] {
+        let dc = AudioData::new(vec![(1000.0 + offset) / 32767.0; 50000], 44100, 1);
+        let errors = quantization_errors(&dc, DitherType::Triangular);
+        let (mean, variance, lag1) = error_statistics(&errors);
+
+        assert!(mean.abs() < 0.02, "Смещение {}: среднее {}", offset, mean);
+        assert!((variance - 0.25).abs() < 0.02, "Смещение {}: дисперсия {}", offset, variance);
+        assert!(lag1.abs() < 0.05, "Смещение {}: автокорреляция {}", offset, lag1);
+        assert!(errors.iter().all(|e| e.abs() <= 1.5 + 1e-3));
+    }
+
+    // Формирование спектра: ошибка соседних сэмплов отрицательно коррелирована,
+    // то есть энергия шума смещена к высоким частотам
+    let quiet = AudioData::new(sine.samples.iter().map(|s| s * 0.01).collect(), 44100, 1);
+    let (_, _, tpdf_lag1) = error_statistics(&quantization_errors(&quiet, DitherType::Triangular));
+    let (_, _, shaped_lag1) = error_statistics(&quantization_errors(&quiet, DitherType::NoiseShaped));
+    assert!(tpdf_lag1.abs() < 0.05, "TPDF: {}", tpdf_lag1);
+    assert!(shaped_lag1 < -0.3, "Формирование спектра: {}", shaped_lag1);
+}
+
+#[test]
+fn test_pcm16_quantization_of_quiet_ramp() {
+    // Медленный нарастающий сигнал амплитудой в несколько младших разрядов
+    let ramp = AudioData::new((0..44100).map(|i| (i as f32 / 44100.0 * 4.0 - 2.0) / 32767.0).collect(), 44100, 1);
+    
+    // Без дизеринга сэмплы округляются: ошибка несмещена и не превышает
+    // половины младшего разряда, но повторяет форму сигнала (пилообразна)
+    let errors = quantization_errors(&ramp, DitherType::None);
+    let (mean, _, plain_lag1) = error_statistics(&errors);
+    assert!(mean.abs() < 0.02, "Среднее {}", mean);
+    assert!(errors.iter().all(|e| e.abs() <= 0.5 + 1e-3));
+    assert!(plain_lag1 > 0.9, "Без дизеринга: {}", plain_lag1);
+    
+    // TPDF разрушает корреляцию ошибки с сигналом
+    let (mean, _, tpdf_lag1) = error_statistics(&quantization_errors(&ramp, DitherType::Triangular));
+    assert!(mean.abs() < 0.02, "Среднее {}", mean);
+    assert!(tpdf_lag1.abs() < 0.05, "TPDF: {}", tpdf_lag1);
+    
+    // Полная шкала симметрична и не переполняется
+    let extremes = AudioData::new(vec![1.0, -1.0, 1.5, -1.5], 44100, 1);
+    for dither in [DitherType::None, DitherType::Triangular] {
+        let (bytes, clipped) = extremes.to_pcm16_le_dithered(dither);
+        let pcm: Vec<i16> = bytes.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]])).collect();
+        assert_eq!(clipped, 2);
+        assert!(pcm[0] >= 32766 && pcm[2] >= 32766, "{:?}", pcm);
+        assert!(pcm[1] <= -32766 && pcm[3] <= -32766 && pcm[1] > i16::MIN, "{:?}", pcm);
+    }
+}
+
+#[test]
+fn test_audio_data_to_mono() {
+    let stereo = AudioData::new(vec![0.2, 0.4, -1.0, 1.0, 0.5, 0.0], 44100, 2);
+    let mono = stereo.to_mono();
+
+    assert_eq!(mono.channels, 1);
+    assert_eq!(mono.sample_rate, 44100);
+    assert_eq!(mono.samples.len(), stereo.samples.len() / 2);
+    for (actual, expected) in mono.samples.iter().zip([0.3, 0.0, 0.25]) {
+        assert!((actual - expected).abs() < 1e-6);
+    }
+    assert!((mono.duration() - stereo.duration()).abs() < 1e-9);
+}
+
+#[test]
+fn test_audio_data_normalized_leaves_original_unchanged() {
+    let original = AudioData::new(vec![0.1, -0.4, 0.2, 0.0], 44100, 1);
+    let normalized = original.normalized(0.8);
+
+    assert_eq!(original.samples, vec![0.1, -0.4, 0.2, 0.0]);
+
+    let mut in_place = original.clone();
+    in_place.normalize(0.8);
+    assert_eq!(normalized, in_place);
+    assert!((normalized.samples[1] + 0.8).abs() < 1e-6);
+}
+
+#[test]
+fn test_audio_data_to_stereo_pan_law() {
+    let mono = AudioData::new(vec![0.5, -0.25, 1.0], 44100, 1);
+
+    // По центру каналы одинаковы и ослаблены на 3 дБ
+    let center = mono.to_stereo(0.0);
+    assert_eq!(center.channels, 2);
+    assert_eq!(center.samples.len(), 6);
+    assert!((center.duration() - mono.duration()).abs() < 1e-9);
+    for (frame, &sample) in center.samples.chunks(2).zip(&mono.samples) {
+        assert_eq!(frame[0], frame[1]);
+        assert!((frame[0] - sample * std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+    }
+
+    // Крайние положения - только один канал
+    let left = mono.to_stereo(-1.0);
+    assert!((left.samples[0] - 0.5).abs() < 1e-6 && left.samples[1].abs() < 1e-6);
+    let right = mono.to_stereo(1.0);
+    assert!(right.samples[0].abs() < 1e-6 && (right.samples[1] - 0.5).abs() < 1e-6);
+
+    // Смещение вправо переносит энергию в правый канал, сохраняя общую мощность
+    let energy = |audio: &AudioData, channel: usize| -> f32 {
+        audio.samples.iter().skip(channel).step_by(2).map(|s| s * s).sum()
+    };
+    let total: f32 = mono.samples.iter().map(|s| s * s).sum();
+    let panned = mono.to_stereo(0.5);
+    let angle = 1.5 * std::f32::consts::FRAC_PI_4;
+    assert!((energy(&panned, 0) - total * angle.cos().powi(2)).abs() < 1e-5);
+    assert!((energy(&panned, 1) - total * angle.sin().powi(2)).abs() < 1e-5);
+    assert!(energy(&panned, 1) > energy(&panned, 0));
+    assert!((energy(&panned, 0) + energy(&panned, 1) - total).abs() < 1e-5);
+}
+
+#[test]
+fn test_audio_data_approx_eq_and_content_hash() {
+    let original = tone(440.0, 0.1, 44100);
+
+    // Отличия меньше допуска
+    let mut nearly_same = original.clone();
+    for sample in nearly_same.samples.iter_mut() {
+        *sample += 0.0005;
+    }
+    assert!(original.approx_eq(&nearly_same, 0.001));
+    assert!(!original.approx_eq(&nearly_same, 0.0001));
+
+    // Формат и длина должны совпадать
+    let other_rate = AudioData::new(original.samples.clone(), 48000, 1);
+    assert!(!original.approx_eq(&other_rate, 1.0));
+    let shorter = AudioData::new(original.samples[1..].to_vec(), 44100, 1);
+    assert!(!original.approx_eq(&shorter, 1.0));
+
+    // Хэш детерминирован и различается для разного аудио
+    assert_eq!(original.content_hash(), original.clone().content_hash());
+    let louder = AudioData::new(original.samples.iter().map(|s| s * 1.5).collect(), 44100, 1);
+    let other_tone = tone(880.0, 0.1, 44100);
+    assert_ne!(original.content_hash(), louder.content_hash());
+    assert_ne!(original.content_hash(), other_tone.content_hash());
+    assert_ne!(original.content_hash(), other_rate.content_hash());
+}
+
+#[test]
+fn test_raw_pcm_decoding_bit_depths_and_endianness() {
+    let expected = [0.0f32, 0.5, -0.5, 0.25, -1.0];
+    let spec_16 = RawPcmSpec { bits: 16, endian: Endianness::Little, channels: 1, sample_rate: 24000 };
+    let spec_24 = RawPcmSpec { bits: 24, ..spec_16 };
+    let spec_24_be = RawPcmSpec { endian: Endianness::Big, ..spec_24 };
+    
+    let pcm_16: Vec<u8> = expected.iter()
+        .flat_map(|&s| ((s * 32768.0) as i16).to_le_bytes())
+        .collect();
+    let pcm_24: Vec<u8> = expected.iter()
+        .flat_map(|&s| ((s * 8388608.0) as i32).to_le_bytes().into_iter().take(3))
+        .collect();
+    let pcm_24_be: Vec<u8> = expected.iter()
+        .flat_map(|&s| ((s * 8388608.0) as i32).to_be_bytes().into_iter().skip(1))
+        .collect();
+    
+    let decoded_16 = spec_16.decode(&pcm_16).unwrap();
+    let decoded_24 = spec_24.decode(&pcm_24).unwrap();
+    let decoded_24_be = spec_24_be.decode(&pcm_24_be).unwrap();
+    
+    assert_eq!(decoded_16.samples, expected);
+    assert_eq!(decoded_24.samples, decoded_16.samples);
+    assert_eq!(decoded_24_be.samples, decoded_16.samples);
+    assert_eq!((decoded_24.sample_rate, decoded_24.channels), (24000, 1));
+    
+    // Неполный последний сэмпл отбрасывается, неподдерживаемая разрядность - ошибка
+    assert_eq!(spec_24.decode(&pcm_24[..7]).unwrap().samples.len(), 2);
+    assert!(RawPcmSpec { bits: 12, ..spec_16 }.decode(&pcm_16).is_err());
+    
+    // Сырой PCM от провайдера приводится к частоте конвейера
+    let audio = decode_raw_tts_audio(&pcm_24, &spec_24, 24000, 24000, SampleRateMismatch::Resample).unwrap();
+    assert_eq!(audio.samples, expected);
+}
+
+#[test]
+fn test_audio_data_split_and_concat() {
+    let samples: Vec<f32> = (0..1001 * 2).map(|i| (i as f32 * 0.01).sin()).collect();
+    let audio = AudioData::new(samples, 22050, 2);
+
+    // Размер части выравнивается по кадрам стерео аудио
+    let chunks = audio.split_into_chunks(257);
+    assert_eq!(chunks.len(), 8);
+    for chunk in &chunks {
+        assert_eq!(chunk.sample_rate, 22050);
+        assert_eq!(chunk.channels, 2);
+        assert_eq!(chunk.samples.len() % 2, 0);
+        assert!(chunk.samples.len() <= 256);
+    }
+
+    let restored = AudioData::concat(&chunks).unwrap();
+    assert_eq!(restored, audio);
+
+    // Части с разными параметрами не объединяются
+    let mono = AudioData::new(vec![0.0; 4], 22050, 1);
+    assert!(AudioData::concat(&[chunks[0].clone(), mono]).is_err());
+    assert!(AudioData::concat(&[]).is_err());
+}
+
+/// Генерирует синусоидальный тон заданной частоты
+fn tone(frequency: f32, duration: f32, sample_rate: u32) -> AudioData {
+    let samples = (0..(duration * sample_rate as f32) as usize)
+        .map(|i| 0.5 * (2.0 * std::f32::consts::PI * frequency * i as f32 / sample_rate as f32).sin())
+        .collect();
+    AudioData::new(samples, sample_rate, 1)
+}
+
+#[test]
+fn test_tempo_pitch_check_warns_for_resampling_algorithms() {
+    let capture = warning_capture();
+    let pitch_warnings = |algorithm: &str| {
+        capture.messages.lock().unwrap().iter()
+            .filter(|message| message.contains("Сдвиг высоты тона") && message.contains(algorithm))
+            .count()
+    };
+
+    let audio = tone(220.0, 0.25, 16000);
+    let estimated = TempoAdjuster::estimate_pitch(&audio).unwrap();
+    assert!((estimated - 220.0).abs() < 2.0, "Оценка частоты: {}", estimated);
+
+    // Алгоритмы на основе передискретизации смещают высоту тона
+    for algorithm in [TempoAlgorithm::Sinc, TempoAlgorithm::Fir, TempoAlgorithm::Linear] {
+        assert!(!algorithm.preserves_pitch());
+        let before = pitch_warnings(&format!("{:?}", algorithm));
+        let adjusted = TempoAdjuster::adjust_tempo_checked(&audio, 1.5, algorithm).unwrap();
+        assert_eq!(pitch_warnings(&format!("{:?}", algorithm)), before + 1);
+        assert!(!TempoAdjuster::verify_pitch(&audio, &adjusted, algorithm));
+    }
+
+    // Сжатие с сохранением высоты тона проверку проходит без предупреждения
+    let before = pitch_warnings("Linear");
+    let pitch_preserved = tone(220.0, 0.25 / 1.5, 16000);
+    assert!(TempoAdjuster::verify_pitch(&audio, &pitch_preserved, TempoAlgorithm::Linear));
+    assert_eq!(pitch_warnings("Linear"), before);
+}
+
+#[test]
+fn test_true_peak_normalization_leaves_more_headroom() {
+    // Синусоида fs/4 со сдвигом фазы 45°: сэмплы равны ±0.5, а истинный пик ≈ 0.707
+    let samples: Vec<f32> = (0..4096).map(|i| if (i / 2) % 2 == 0 { 0.5 } else { -0.5 }).collect();
+    let audio = AudioData::new(samples, 44100, 1);
+
+    assert_eq!(audio.sample_peak(), 0.5);
+    let true_peak = audio.true_peak();
+    assert!((true_peak - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.02, "Истинный пик: {}", true_peak);
+
+    let mut sample_normalized = audio.clone();
+    sample_normalized.normalize(0.9);
+    let mut true_peak_normalized = audio.clone();
+    true_peak_normalized.normalize_true_peak(0.9);
+
+    // Нормализация по сэмплам дает межсэмпловые пики выше полной шкалы
+    assert!(sample_normalized.true_peak() > 1.0);
+    // Нормализация по истинному пику оставляет запас
+    assert!(true_peak_normalized.sample_peak() < sample_normalized.sample_peak());
+    assert!((true_peak_normalized.true_peak() - 0.9).abs() < 0.01);
+}
+
+#[test]
+fn test_audio_data_loudness_over_time() {
+    // Меандр с амплитудой, растущей каждые 100 мс: RMS окна равен амплитуде
+    let sample_rate = 8000;
+    let samples: Vec<f32> = (0..8400).map(|i| {
+        let amplitude = (i / 800 + 1) as f32 * 0.05;
+        if i % 2 == 0 { amplitude } else { -amplitude }
+    }).collect();
+    let audio = AudioData::new(samples, sample_rate, 1);
+
+    // 10 полных окон и одно неполное
+    let meter = audio.loudness_over_time(100);
+    assert_eq!(meter.len(), 11);
+    for (k, value) in meter.iter().enumerate() {
+        let expected = (k + 1) as f32 * 0.05;
+        assert!((value - expected).abs() < 1e-4, "Окно {}: {} != {}", k, value, expected);
+    }
+
+    // Окно больше аудио дает одно значение, нулевое окно - ни одного
+    assert_eq!(audio.loudness_over_time(5000).len(), 1);
+    assert!(audio.loudness_over_time(0).is_empty());
+}
+
+#[test]
+fn test_audio_data_headroom_and_clip_count() {
+    // Синусоида с пиком -6 дБ
+    let amplitude = 10.0f32.powf(-6.0 / 20.0);
+    let sine: Vec<f32> = (0..4410)
+        .map(|i| amplitude * (2.0 * std::f32::consts::PI * 441.0 * i as f32 / 44100.0).sin())
+        .collect();
+    let audio = AudioData::new(sine.clone(), 44100, 1);
+    assert!((audio.headroom_db() - 6.0).abs() < 0.01, "Запас: {}", audio.headroom_db());
+    assert_eq!(audio.clip_count(), 0);
+
+    // Усиленный сигнал с ограничением по полной шкале
+    let clipped: Vec<f32> = sine.iter().map(|s| (s * 4.0).clamp(-1.0, 1.0)).collect();
+    let expected_clips = sine.iter().filter(|s| (*s * 4.0).abs() >= 1.0).count();
+    let audio = AudioData::new(clipped, 44100, 1);
+    assert!(expected_clips > 0);
+    assert_eq!(audio.clip_count(), expected_clips);
+    assert_eq!(audio.headroom_db(), 0.0);
+
+    // У тишины запас бесконечный
+    assert!(AudioData::new(vec![0.0; 100], 44100, 1).headroom_db().is_infinite());
+}
+
+#[test]
+fn test_tts_segment_test_tone_decodes_to_tone() {
+    let segment = TtsSegment::test_tone("Tone", 0.5, 440.0, 24000);
+    assert_eq!(segment.text, "Tone");
+    assert_eq!(segment.duration, Some(0.5));
+
+    let audio = decode_tts_audio(&segment.audio_data, 24000, 24000, SampleRateMismatch::Error).unwrap();
+    assert_eq!(audio.sample_rate, 24000);
+    assert_eq!(audio.samples.len(), 12000);
+
+    // Частота по количеству переходов через ноль снизу вверх
+    let rising = audio.samples.windows(2).filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0).count();
+    let frequency = rising as f64 / audio.duration();
+    assert!((frequency - 440.0).abs() < 4.0, "{}", frequency);
+}
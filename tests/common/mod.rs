@@ -0,0 +1,148 @@
+//! Общие вспомогательные типы интеграционных тестов
+#![allow(dead_code)]
+
+use std::future::Future;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tts_sync::audio::AudioData;
+use tts_sync::error::{Error, ErrorType, Result};
+use tts_sync::tts::{TtsProvider, TtsSegment, WordTiming};
+
+/// Общий журнал запросов к провайдеру
+pub type RequestLog = Arc<Mutex<Vec<String>>>;
+
+/// Настраиваемый мок TtsProvider, возвращающий тон 440 Гц в формате WAV
+///
+/// По умолчанию отвечает сразу тоном амплитудой 0.5; задержка ответа,
+/// ошибка для заданного текста, журнал запросов, щелчки и тайминги слов
+/// включаются методами `with_*`.
+#[derive(Clone)]
+pub struct ToneTtsProvider {
+    duration: f64,
+    sample_rate: u32,
+    amplitude: f32,
+    click_interval: Option<usize>,
+    delay: Option<Duration>,
+    failing_text: Option<String>,
+    log: Option<(RequestLog, &'static str)>,
+    word_timings: Option<Vec<WordTiming>>,
+}
+
+impl ToneTtsProvider {
+    /// Создает провайдер, возвращающий тон длительностью `duration` секунд
+    pub fn new(duration: f64, sample_rate: u32) -> Self {
+        Self {
+            duration,
+            sample_rate,
+            amplitude: 0.5,
+            click_interval: None,
+            delay: None,
+            failing_text: None,
+            log: None,
+            word_timings: None,
+        }
+    }
+
+    /// Устанавливает амплитуду тона
+    pub fn with_amplitude(mut self, amplitude: f32) -> Self {
+        self.amplitude = amplitude;
+        self
+    }
+
+    /// Добавляет щелчки амплитудой 0.9 каждые `interval` сэмплов
+    pub fn with_clicks(mut self, interval: usize) -> Self {
+        self.click_interval = Some(interval);
+        self
+    }
+
+    /// Задерживает каждый ответ на `delay_ms` миллисекунд
+    pub fn with_delay_ms(mut self, delay_ms: u64) -> Self {
+        self.delay = Some(Duration::from_millis(delay_ms));
+        self
+    }
+
+    /// Завершает ошибкой OpenAI синтез текста `text`
+    pub fn failing_on(mut self, text: &str) -> Self {
+        self.failing_text = Some(text.to_string());
+        self
+    }
+
+    /// Записывает текст каждого запроса с префиксом `prefix` в журнал `log`
+    pub fn with_log(mut self, log: RequestLog, prefix: &'static str) -> Self {
+        self.log = Some((log, prefix));
+        self
+    }
+
+    /// Возвращает тайминги слов при запросе с таймингами
+    pub fn with_word_timings(mut self, word_timings: Vec<WordTiming>) -> Self {
+        self.word_timings = Some(word_timings);
+        self
+    }
+
+    /// Возвращает WAV с тоном
+    pub fn wav_bytes(&self) -> Vec<u8> {
+        let num_samples = (self.duration * self.sample_rate as f64).round() as usize;
+        let samples = (0..num_samples)
+            .map(|i| match self.click_interval {
+                Some(interval) if i % interval == 100 => 0.9,
+                _ => {
+                    let t = i as f32 / self.sample_rate as f32;
+                    self.amplitude * (2.0 * std::f32::consts::PI * 440.0 * t).sin()
+                }
+            })
+            .collect();
+        AudioData::new(samples, self.sample_rate, 1).to_wav_bytes()
+    }
+
+    /// Записывает запрос в журнал, выдерживает задержку и возвращает сегмент
+    async fn respond(&self, text: &str, target_duration: f64) -> Result<TtsSegment> {
+        if let Some((log, prefix)) = &self.log {
+            log.lock().unwrap().push(format!("{}{}", prefix, text));
+        }
+        if let Some(delay) = self.delay {
+            tokio::time::sleep(delay).await;
+        }
+        if self.failing_text.as_deref() == Some(text) {
+            return Err(Error::new(ErrorType::OpenAi, "сервер недоступен"));
+        }
+
+        Ok(TtsSegment {
+            text: text.to_string(),
+            audio_data: self.wav_bytes(),
+            duration: Some(self.duration),
+            target_duration,
+            stretch_factor: None,
+            word_timings: None,
+        })
+    }
+}
+
+impl TtsProvider for ToneTtsProvider {
+    fn generate_speech(&self, text: &str) -> impl Future<Output = Result<Vec<u8>>> {
+        async move {
+            Ok(self.respond(text, self.duration).await?.audio_data)
+        }
+    }
+
+    fn generate_speech_to_file<P: AsRef<Path> + Send>(&self, text: &str, path: P) -> impl Future<Output = Result<()>> {
+        async move {
+            std::fs::write(path, self.generate_speech(text).await?)?;
+            Ok(())
+        }
+    }
+
+    fn generate_segment(&self, text: &str, target_duration: f64) -> impl Future<Output = Result<TtsSegment>> {
+        self.respond(text, target_duration)
+    }
+
+    fn generate_segment_with_timestamps(&self, text: &str, target_duration: f64) -> impl Future<Output = Result<TtsSegment>> {
+        async move {
+            Ok(TtsSegment {
+                word_timings: self.word_timings.clone(),
+                ..self.respond(text, target_duration).await?
+            })
+        }
+    }
+}
@@ -3,12 +3,17 @@ use std::path::Path;
 use std::future::Future;
 
 use tts_sync::{
-    sync::core::SyncCore,
+    sync::{core::SyncCore, BatchJob, CueRangeBehavior, DiagnosticKind, DurationLimitBehavior, BatchOptions, CommandRunner, MuxMode, PunctuationPauses, SpeechAnchor},
+    audio::{decode_tts_audio, AudioData, AudioSegment, AudioSink, AudioTrack, SampleRateMismatch, TempoAlgorithm, OutputMetadata},
     progress::ProgressTracker,
-    tts::{TtsProvider, TtsSegment},
-    error::Result,
+    vtt::{Subtitle, SubtitleTrack},
+    tts::{DynTtsProvider, TtsProvider, TtsSegment, WordTiming},
+    error::{Error, Result},
 };
 
+mod common;
+use common::ToneTtsProvider;
+
 // Мок для TtsProvider для тестирования
 struct MockTtsProvider;
 
@@ -44,11 +49,12 @@ impl TtsProvider for MockTtsProvider {
                 duration: Some(target_duration),
                 target_duration,
                 stretch_factor: None,
+                word_timings: None,
             })
         }
     }
 
-    fn generate_speech_to_file<P: AsRef<Path>>(&self, _text: &str, path: P) -> impl Future<Output = Result<()>> {
+    fn generate_speech_to_file<P: AsRef<Path> + Send>(&self, _text: &str, path: P) -> impl Future<Output = Result<()>> {
         async move {
             // Для тестов просто создаем пустой файл
             std::fs::File::create(path)?;
@@ -97,3 +103,1718 @@ async fn test_sync_core_with_valid_subtitles() {
     assert_eq!(audio_track.sample_rate, 44100);
     assert_eq!(audio_track.channels, 1);
 }
+
+#[tokio::test]
+async fn test_sync_core_with_global_offset() {
+    // Создаем файл с несколькими субтитрами
+    let temp_file = NamedTempFile::new().unwrap();
+    let vtt_content = "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\nFirst\n\n00:00:03.000 --> 00:00:04.000\nSecond\n";
+    std::fs::write(temp_file.path(), vtt_content).unwrap();
+    let vtt_path = temp_file.path().to_str().unwrap();
+    
+    let base_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    let base_track = base_core.synchronize(vtt_path, 10.0, &MockTtsProvider).await.unwrap();
+    
+    let shifted_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true)
+        .with_global_offset_ms(120);
+    let shifted_track = shifted_core.synchronize(vtt_path, 10.0, &MockTtsProvider).await.unwrap();
+    
+    // Сравниваем только речевые сегменты
+    let speech_starts = |track: &tts_sync::AudioTrack| -> Vec<f64> {
+        track.segments.iter()
+            .filter(|s| !s.text.is_empty())
+            .map(|s| s.start_time)
+            .collect()
+    };
+    
+    let base_starts = speech_starts(&base_track);
+    let shifted_starts = speech_starts(&shifted_track);
+    
+    assert_eq!(base_starts.len(), 2);
+    assert_eq!(base_starts.len(), shifted_starts.len());
+    for (base, shifted) in base_starts.iter().zip(shifted_starts.iter()) {
+        assert!((shifted - base - 0.12).abs() < 1e-9, "Ожидалось смещение 0.12с: {} -> {}", base, shifted);
+    }
+    
+    // Начало трека заполнено тишиной длительностью смещения
+    let first = &shifted_track.segments[0];
+    assert_eq!(first.start_time, 0.0);
+    assert!((first.end_time - 0.12).abs() < 1e-9);
+    assert!(first.audio.samples.iter().all(|&s| s == 0.0));
+}
+
+#[tokio::test]
+async fn test_sync_core_global_offset_stereo_lead_in() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(1.0, 2.0, "First".to_string()));
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 2, true)
+        .with_global_offset_ms(250);
+    let track = sync_core.synchronize_subtitles(&subtitles, 5.0, &wav_tone()).await.unwrap();
+    
+    // Тишина в начале содержит оба канала и занимает ровно длительность смещения
+    let lead_in = &track.segments[0];
+    assert_eq!(lead_in.audio.channels, 2);
+    assert_eq!(lead_in.audio.duration(), 0.25);
+    assert!((lead_in.end_time - lead_in.audio.duration()).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_sync_core_fades_last_cue_before_tail_silence() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(0.0, 2.0, "First".to_string()));
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    let track = sync_core.synchronize_subtitles(&subtitles, 5.0, &wav_tone()).await.unwrap();
+    
+    // После реплики идут паузы и тишина, но затухает именно реплика
+    let cue_index = track.segments.iter().position(|segment| segment.cue.is_some()).unwrap();
+    assert!(track.segments[cue_index + 1..].iter().all(|segment| segment.cue.is_none()));
+    let cue = &track.segments[cue_index];
+    
+    // На границе реплики и тишины нет скачка амплитуды
+    let merged = track.merge().unwrap();
+    let boundary = (cue.start_time * 44100.0).round() as usize + cue.audio.samples.len();
+    let jump = (merged.samples[boundary - 1] - merged.samples[boundary]).abs();
+    assert!(jump < 0.01, "Скачок на границе: {}", jump);
+}
+
+#[tokio::test]
+async fn test_sync_core_progress_with_single_subtitle() {
+    use std::sync::{Arc, Mutex};
+    
+    let progress_values = Arc::new(Mutex::new(Vec::new()));
+    let progress_values_clone = progress_values.clone();
+    let progress_tracker = ProgressTracker::with_callback(Box::new(move |progress, _status| {
+        progress_values_clone.lock().unwrap().push(progress);
+    }));
+    let sync_core = SyncCore::new(progress_tracker, 44100, 1, true);
+    
+    // Создаем файл с единственным субтитром
+    let temp_file = NamedTempFile::new().unwrap();
+    let vtt_content = "WEBVTT\n\n00:00:00.000 --> 00:00:01.000\nTest subtitle\n";
+    std::fs::write(temp_file.path(), vtt_content).unwrap();
+    
+    sync_core.synchronize(temp_file.path().to_str().unwrap(), 10.0, &MockTtsProvider).await.unwrap();
+    
+    let values = progress_values.lock().unwrap();
+    assert!(!values.is_empty());
+    
+    // Прогресс остается в пределах [0, 100], не убывает и доходит до 100
+    for &value in values.iter() {
+        assert!(value.is_finite() && (0.0..=100.0).contains(&value), "Некорректный прогресс: {}", value);
+    }
+    for pair in values.windows(2) {
+        assert!(pair[1] >= pair[0], "Прогресс уменьшился: {} -> {}", pair[0], pair[1]);
+    }
+    assert_eq!(*values.last().unwrap(), 100.0);
+}
+
+#[tokio::test]
+async fn test_sync_core_preserves_cue_identifiers() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let vtt_content = "WEBVTT\n\nintro\n00:00:01.000 --> 00:00:02.000\nFirst\n\n00:00:03.000 --> 00:00:04.000\nSecond\n\nscene-2\n00:00:05.000 --> 00:00:06.000\nThird\n";
+    std::fs::write(temp_file.path(), vtt_content).unwrap();
+    let vtt_path = temp_file.path().to_str().unwrap();
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    let track = sync_core.synchronize(vtt_path, 10.0, &MockTtsProvider).await.unwrap();
+    
+    // Идентификаторы сохраняются в речевых сегментах трека
+    let ids: Vec<Option<&str>> = track.segments.iter()
+        .filter(|s| !s.text.is_empty())
+        .map(|s| s.id.as_deref())
+        .collect();
+    assert_eq!(ids, vec![Some("intro"), None, Some("scene-2")]);
+    
+    // И попадают в отчет о синхронизации
+    let report = sync_core.report();
+    assert_eq!(report.segments.len(), 3);
+    assert_eq!(report.segments[0].id.as_deref(), Some("intro"));
+    assert_eq!(report.segments[1].id, None);
+    assert_eq!(report.segment_by_id("scene-2").unwrap().text, "Third");
+    assert_eq!(report.segment_by_id("scene-2").unwrap().index, 2);
+}
+
+#[tokio::test]
+async fn test_sync_core_reports_clipped_samples_on_export() {
+    // Намеренно "горячий" сигнал: часть сэмплов выходит за пределы -1.0..=1.0
+    let samples: Vec<f32> = (0..4410).map(|i| 1.5 * (i as f32 * 0.05).sin()).collect();
+    let out_of_range = samples.iter().filter(|s| s.abs() > 1.0).count();
+    assert!(out_of_range > 0);
+    
+    let mut track = tts_sync::AudioTrack::new(44100, 1);
+    track.add_segment(tts_sync::AudioSegment::new(
+        tts_sync::AudioData::new(samples, 44100, 1),
+        0.0,
+        0.1,
+        "Hot".to_string(),
+    ));
+    
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output_path = temp_dir.path().join("hot.wav");
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false);
+    sync_core.save_to_file(&track, output_path.to_str().unwrap()).await.unwrap();
+    
+    assert_eq!(sync_core.report().clipped_samples, out_of_range);
+    // Пик выше полной шкалы - запас громкости отрицательный
+    let headroom = sync_core.report().headroom_db.unwrap();
+    assert!((headroom + 20.0 * 1.5f32.log10()).abs() < 0.01, "Запас: {}", headroom);
+}
+
+#[tokio::test]
+async fn test_sync_core_collapses_whitespace_for_provider() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let vtt_content = "WEBVTT\n\n00:00:01.000 --> 00:00:03.000\nHello,\n   world!\n";
+    std::fs::write(temp_file.path(), vtt_content).unwrap();
+    let vtt_path = temp_file.path().to_str().unwrap();
+    
+    let texts = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let provider = wav_tone().with_log(texts.clone(), "");
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    let track = sync_core.synchronize(vtt_path, 5.0, &provider).await.unwrap();
+    
+    // Провайдер получает текст без переносов строк и лишних пробелов
+    assert_eq!(*texts.lock().unwrap(), vec!["Hello, world!".to_string()]);
+    
+    // Исходный текст субтитра сохраняется
+    let speech = track.segments.iter().find(|s| !s.text.is_empty()).unwrap();
+    assert_eq!(speech.text, "Hello,\nworld!");
+    assert_eq!(sync_core.report().segments[0].text, "Hello,\nworld!");
+    
+    // Нормализацию можно отключить
+    texts.lock().unwrap().clear();
+    let raw_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true)
+        .with_whitespace_normalization(false);
+    raw_core.synchronize(vtt_path, 5.0, &provider).await.unwrap();
+    assert_eq!(*texts.lock().unwrap(), vec!["Hello,\nworld!".to_string()]);
+}
+
+// Декодирует сегменты параллельно с другой задачей и возвращает, завершилось ли
+// декодирование до первого запуска этой задачи. В однопоточном рантайме задача
+// успевает выполниться раньше, только если декодирование не блокирует исполнитель
+async fn decode_concurrently(
+    core: &SyncCore,
+    segments: &[TtsSegment],
+) -> (Vec<Result<AudioData>>, bool) {
+    let finished = std::sync::atomic::AtomicBool::new(false);
+    tokio::join!(
+        async {
+            let decoded = core.decode_segments(segments).await.unwrap();
+            finished.store(true, std::sync::atomic::Ordering::SeqCst);
+            decoded
+        },
+        async { finished.load(std::sync::atomic::Ordering::SeqCst) }
+    )
+}
+
+#[tokio::test]
+async fn test_sync_core_decodes_segments_off_executor() {
+    let segments: Vec<TtsSegment> = (0..6)
+        .map(|i| TtsSegment::test_tone(&format!("segment {}", i), 2.0, 220.0 + 110.0 * i as f32, 24000))
+        .collect();
+    
+    let inline_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true)
+        .with_decode_concurrency(0);
+    let pooled_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true)
+        .with_decode_concurrency(2);
+    assert_eq!(pooled_core.decode_concurrency(), 2);
+    
+    let (inline, inline_blocked) = decode_concurrently(&inline_core, &segments).await;
+    let (pooled, pooled_blocked) = decode_concurrently(&pooled_core, &segments).await;
+    assert!(inline_blocked);
+    assert!(!pooled_blocked);
+    
+    // Результат совпадает с последовательным декодированием
+    assert_eq!(pooled.len(), segments.len());
+    for (inline, pooled) in inline.into_iter().zip(pooled) {
+        let (inline, pooled) = (inline.unwrap(), pooled.unwrap());
+        assert_eq!(inline.sample_rate, 44100);
+        assert_eq!(inline.samples, pooled.samples);
+    }
+    
+    // Короткие сегменты в конце декодируются раньше длинных, но порядок сохраняется
+    let segments: Vec<TtsSegment> = (0..4).map(|i| {
+        let audio = AudioData::new(vec![0.25; 24000 * (4 - i)], 24000, 1);
+        TtsSegment {
+            text: format!("segment {}", i),
+            audio_data: audio.to_wav_bytes(),
+            duration: Some((4 - i) as f64),
+            target_duration: (4 - i) as f64,
+            stretch_factor: None,
+            word_timings: None,
+        }
+    }).collect();
+    let pooled_core = SyncCore::new(ProgressTracker::new(), 24000, 1, true)
+        .with_decode_concurrency(4);
+    let decoded = pooled_core.decode_segments(&segments).await.unwrap();
+    let lengths: Vec<usize> = decoded.into_iter().map(|audio| audio.unwrap().samples.len()).collect();
+    assert_eq!(lengths, vec![96000, 72000, 48000, 24000]);
+}
+
+// Исполнитель команд, имитирующий отсутствие ffmpeg в системе
+struct MissingFfmpegRunner;
+
+impl CommandRunner for MissingFfmpegRunner {
+    fn run(&self, program: &str, _args: &[&str]) -> std::io::Result<std::process::Output> {
+        Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("{}: not found", program)))
+    }
+}
+
+#[tokio::test]
+async fn test_sync_core_requires_ffmpeg_for_encoded_formats() {
+    let mut track = AudioTrack::new(44100, 1);
+    track.add_segment(AudioSegment::new(
+        AudioData::new(vec![0.1; 4410], 44100, 1),
+        0.0,
+        0.1,
+        "Hello".to_string(),
+    ));
+    
+    let temp_dir = tempfile::tempdir().unwrap();
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true)
+        .with_require_ffmpeg(true)
+        .with_command_runner(MissingFfmpegRunner);
+    assert!(!sync_core.ffmpeg_available());
+    
+    // Без ffmpeg и встроенного кодировщика OGG и Opus не создаются,
+    // ошибка объясняет причину
+    for (ext, native) in [("ogg", cfg!(feature = "vorbis")), ("opus", cfg!(feature = "opus"))] {
+        let path = temp_dir.path().join(format!("out.{}", ext));
+        let result = sync_core.save_to_file(&track, path.to_str().unwrap()).await;
+        if native {
+            assert_eq!(Path::new(&result.unwrap()), path);
+            continue;
+        }
+        let err = result.unwrap_err();
+        assert!(matches!(err, Error::AudioProcessing(_)));
+        assert!(err.to_string().contains("требуется ffmpeg"), "{}", err);
+        assert!(!path.exists());
+    }
+    
+    // WAV, FLAC и MP3 не требуют ffmpeg
+    let mp3_path = temp_dir.path().join("out.mp3");
+    let saved = sync_core.save_to_file(&track, mp3_path.to_str().unwrap()).await.unwrap();
+    assert_eq!(Path::new(&saved), mp3_path);
+    
+    let wav_path = temp_dir.path().join("out.wav");
+    sync_core.save_to_file(&track, wav_path.to_str().unwrap()).await.unwrap();
+    assert!(wav_path.exists());
+    
+    let flac_path = temp_dir.path().join("out.flac");
+    let saved = sync_core.save_to_file(&track, flac_path.to_str().unwrap()).await.unwrap();
+    assert_eq!(Path::new(&saved), flac_path);
+    let decoded = AudioData::from_file(&flac_path).unwrap();
+    assert_eq!((decoded.sample_rate, decoded.channels), (44100, 1));
+    assert_eq!(decoded.samples.len(), 4410);
+    assert!((decoded.duration() - 0.1).abs() < 1e-9);
+    assert!(decoded.samples.iter().all(|s| (s - 0.1).abs() < 1e-3));
+}
+
+// Исполнитель команд без ffmpeg, считающий запуски
+#[derive(Default)]
+struct CountingMissingFfmpegRunner {
+    runs: std::sync::atomic::AtomicUsize,
+}
+
+impl CommandRunner for CountingMissingFfmpegRunner {
+    fn run(&self, program: &str, args: &[&str]) -> std::io::Result<std::process::Output> {
+        self.runs.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        MissingFfmpegRunner.run(program, args)
+    }
+}
+
+#[cfg(not(all(feature = "vorbis", feature = "opus")))]
+#[tokio::test]
+async fn test_sync_core_falls_back_to_wav_without_ffmpeg() {
+    let mut track = AudioTrack::new(44100, 1);
+    track.add_segment(AudioSegment::new(
+        AudioData::new(vec![0.1; 4410], 44100, 1),
+        0.0,
+        0.1,
+        "Hello".to_string(),
+    ));
+    
+    let runner = std::sync::Arc::new(CountingMissingFfmpegRunner::default());
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true)
+        .with_command_runner(std::sync::Arc::clone(&runner));
+    
+    // Вместо OGG и Opus без встроенного кодировщика сохраняется корректный WAV файл
+    let temp_dir = tempfile::tempdir().unwrap();
+    for (ext, _) in [("ogg", cfg!(feature = "vorbis")), ("opus", cfg!(feature = "opus"))].into_iter().filter(|(_, native)| !native) {
+        let path = temp_dir.path().join(format!("out_{}.{}", ext, ext));
+        let saved = sync_core.save_to_file(&track, path.to_str().unwrap()).await.unwrap();
+        assert_eq!(Path::new(&saved), path.with_extension("wav"));
+        assert!(!path.exists());
+        assert_eq!(AudioData::from_file(&saved).unwrap().samples.len(), 4410);
+    }
+    assert!(sync_core.report().diagnostics.iter()
+        .any(|d| d.kind == DiagnosticKind::EncoderFallback && d.message.contains("Установите ffmpeg")));
+    
+    // Наличие ffmpeg проверяется один раз
+    assert!(!sync_core.ffmpeg_available());
+    assert_eq!(runner.runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_sync_core_encodes_mp3_without_ffmpeg() {
+    let samples: Vec<f32> = (0..44100)
+        .map(|n| 0.5 * (2.0 * std::f32::consts::PI * 440.0 * n as f32 / 44100.0).sin())
+        .collect();
+    let mut track = AudioTrack::new(44100, 1);
+    track.add_segment(AudioSegment::new(AudioData::new(samples, 44100, 1), 0.0, 1.0, "Hello".to_string()));
+    
+    let temp_dir = tempfile::tempdir().unwrap();
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false)
+        .with_mp3_bitrate(128)
+        .with_command_runner(MissingFfmpegRunner);
+    
+    // Без ffmpeg сохраняется настоящий MP3, а не WAV
+    let path = temp_dir.path().join("out.mp3");
+    let saved = sync_core.save_to_file(&track, path.to_str().unwrap()).await.unwrap();
+    assert_eq!(Path::new(&saved), path);
+    let bytes = std::fs::read(&path).unwrap();
+    assert!(bytes.len() > 100);
+    assert!(bytes[0] == 0xFF && bytes[1] & 0xE0 == 0xE0, "{:02X?}", &bytes[..4]);
+    assert!(!sync_core.report().diagnostics.iter().any(|d| d.kind == DiagnosticKind::EncoderFallback));
+    
+    // Размер соответствует битрейту 128 кбит/с, длительность - исходной
+    assert!(bytes.len() > 16_000 && bytes.len() < 18_000, "{} байт", bytes.len());
+    let decoded = AudioData::from_file(&path).unwrap();
+    assert_eq!((decoded.sample_rate, decoded.channels), (44100, 1));
+    assert!(decoded.duration() >= 1.0 && decoded.duration() < 1.1, "{}", decoded.duration());
+    
+    // Метаданные записываются в тег ID3v2 перед кадрами
+    let tagged_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false)
+        .with_output_metadata(OutputMetadata::new().with_title("Дубляж"))
+        .with_command_runner(MissingFfmpegRunner);
+    let tagged_path = temp_dir.path().join("tagged.mp3");
+    tagged_core.save_to_file(&track, tagged_path.to_str().unwrap()).await.unwrap();
+    let tagged = std::fs::read(&tagged_path).unwrap();
+    assert_eq!(&tagged[..3], b"ID3");
+    assert!(AudioData::from_file(&tagged_path).unwrap().duration() >= 1.0);
+    
+    // Битрейт вне таблицы MPEG-1 отклоняется
+    let invalid_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false)
+        .with_mp3_bitrate(100)
+        .with_command_runner(MissingFfmpegRunner);
+    let invalid_path = temp_dir.path().join("invalid.mp3");
+    let err = invalid_core.save_to_file(&track, invalid_path.to_str().unwrap()).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidParameters(_)), "{}", err);
+    assert!(!invalid_path.exists());
+}
+
+#[cfg(all(feature = "vorbis", feature = "opus"))]
+#[tokio::test]
+async fn test_sync_core_encodes_ogg_and_opus_without_ffmpeg() {
+    let samples: Vec<f32> = (0..44100)
+        .map(|n| 0.5 * (2.0 * std::f32::consts::PI * 440.0 * n as f32 / 44100.0).sin())
+        .collect();
+    let mut track = AudioTrack::new(44100, 1);
+    track.add_segment(AudioSegment::new(AudioData::new(samples, 44100, 1), 0.0, 1.0, "Hello".to_string()));
+    
+    let temp_dir = tempfile::tempdir().unwrap();
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false)
+        .with_output_metadata(OutputMetadata::new().with_title("Дубляж"))
+        .with_command_runner(MissingFfmpegRunner);
+    
+    // Без ffmpeg сохраняется настоящий OGG Vorbis с исходной частотой
+    let ogg_path = temp_dir.path().join("out.ogg");
+    let saved = sync_core.save_to_file(&track, ogg_path.to_str().unwrap()).await.unwrap();
+    assert_eq!(Path::new(&saved), ogg_path);
+    let bytes = std::fs::read(&ogg_path).unwrap();
+    assert_eq!(&bytes[..4], b"OggS");
+    let tag = "TITLE=Дубляж".as_bytes();
+    assert!(bytes.windows(tag.len()).any(|w| w == tag));
+    let decoded = AudioData::from_file(&ogg_path).unwrap();
+    assert_eq!((decoded.sample_rate, decoded.channels), (44100, 1));
+    assert_eq!(decoded.samples.len(), 44100);
+    assert!((decoded.duration() - 1.0).abs() < 1e-9, "{}", decoded.duration());
+    
+    // Opus кодируется на 48 кГц, длительность сохраняется
+    let opus_path = temp_dir.path().join("out.opus");
+    let saved = sync_core.save_to_file(&track, opus_path.to_str().unwrap()).await.unwrap();
+    assert_eq!(Path::new(&saved), opus_path);
+    let bytes = std::fs::read(&opus_path).unwrap();
+    assert_eq!(&bytes[..4], b"OggS");
+    assert!(bytes.windows(8).any(|w| w == b"OpusHead"));
+    let decoded = AudioData::from_file(&opus_path).unwrap();
+    assert_eq!((decoded.sample_rate, decoded.channels), (48000, 1));
+    assert_eq!(decoded.samples.len(), 48000);
+    assert!((decoded.duration() - 1.0).abs() < 1e-9, "{}", decoded.duration());
+    
+    assert!(!sync_core.report().diagnostics.iter().any(|d| d.kind == DiagnosticKind::EncoderFallback));
+}
+
+#[tokio::test]
+async fn test_sync_core_merges_whole_wav_frames() {
+    // Длительность трека соответствует нечетному количеству сэмплов стерео:
+    // объединение округляет ее до целого кадра
+    let mut track = AudioTrack::new(44100, 2);
+    track.add_segment(AudioSegment::new(
+        AudioData::new(vec![0.1; 44100], 44100, 2),
+        0.0,
+        44101.5 / 88200.0,
+        "Hello".to_string(),
+    ));
+    assert_eq!(track.merge().unwrap().samples.len(), 44102);
+    
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("out.wav");
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 2, false);
+    sync_core.save_to_file(&track, path.to_str().unwrap()).await.unwrap();
+    assert_eq!(AudioData::from_file(&path).unwrap().samples.len(), 44102);
+    
+    // Неполный последний кадр при записи WAV отклоняется
+    let partial = temp_dir.path().join("partial.wav");
+    let err = AudioData::new(vec![0.1; 3], 44100, 2).to_file(&partial).unwrap_err();
+    assert!(matches!(err, Error::AudioProcessing(_)));
+    assert!(err.to_string().contains("не кратно количеству каналов 2"), "{}", err);
+    assert!(!partial.exists());
+}
+
+// Исполнитель команд, имитирующий ffmpeg и запоминающий аргументы
+#[cfg(unix)]
+#[derive(Default)]
+struct RecordingFfmpegRunner {
+    args: std::sync::Mutex<Vec<String>>,
+}
+
+#[cfg(unix)]
+impl CommandRunner for RecordingFfmpegRunner {
+    fn run(&self, _program: &str, args: &[&str]) -> std::io::Result<std::process::Output> {
+        use std::os::unix::process::ExitStatusExt;
+        
+        // Проверка наличия ffmpeg не создает файлов
+        if args != ["-version"] {
+            *self.args.lock().unwrap() = args.iter().map(|arg| arg.to_string()).collect();
+            std::fs::write(args.last().unwrap(), vec![1u8; 1024])?;
+        }
+        Ok(std::process::Output {
+            status: std::process::ExitStatus::from_raw(0),
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_sync_core_writes_id3_metadata() {
+    // Сегмент с исходными MP3 данными сохраняется без перекодирования
+    let mut track = AudioTrack::new(44100, 1);
+    track.add_segment(AudioSegment::new_with_raw_data(
+        AudioData::new(vec![0.1; 4410], 44100, 1),
+        0.0,
+        0.1,
+        "Hello".to_string(),
+        vec![0xFF, 0xFB, 0x90, 0x64, 0, 0, 0, 0],
+    ));
+    
+    let comment = "tts-sync, voice: nova";
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("out.mp3");
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false)
+        .with_output_metadata(OutputMetadata::new().with_title("Episode 1").with_comment(comment));
+    sync_core.save_to_file(&track, path.to_str().unwrap()).await.unwrap();
+    
+    let bytes = std::fs::read(&path).unwrap();
+    assert_eq!(&bytes[0..3], b"ID3");
+    let encoded: Vec<u8> = comment.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    let comm = bytes.windows(4).position(|w| w == b"COMM").expect("нет кадра COMM");
+    assert!(bytes[comm..].windows(encoded.len()).any(|w| w == encoded.as_slice()));
+    // Данные MP3 следуют сразу за тегом
+    assert!(bytes.ends_with(&[0xFF, 0xFB, 0x90, 0x64, 0, 0, 0, 0]));
+    
+    // Без метаданных тег не записывается
+    let plain_path = temp_dir.path().join("plain.mp3");
+    SyncCore::new(ProgressTracker::new(), 44100, 1, false)
+        .save_to_file(&track, plain_path.to_str().unwrap()).await.unwrap();
+    assert_eq!(std::fs::read(&plain_path).unwrap()[0..2], [0xFF, 0xFB]);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_sync_core_passes_metadata_to_encoder() {
+    let mut track = AudioTrack::new(44100, 1);
+    track.add_segment(AudioSegment::new(
+        AudioData::new(vec![0.1; 4410], 44100, 1),
+        0.0,
+        0.1,
+        "Hello".to_string(),
+    ));
+    
+    let runner = std::sync::Arc::new(RecordingFfmpegRunner::default());
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false)
+        .with_output_metadata(OutputMetadata::new().with_comment("tts-sync"))
+        .with_command_runner(std::sync::Arc::clone(&runner));
+    
+    let temp_dir = tempfile::tempdir().unwrap();
+    for ext in ["mp3", "ogg"] {
+        let path = temp_dir.path().join(format!("out.{}", ext));
+        sync_core.save_to_file(&track, path.to_str().unwrap()).await.unwrap();
+        let args = runner.args.lock().unwrap().clone();
+        assert!(args.windows(2).any(|w| w[0] == "-metadata" && w[1] == "comment=tts-sync"), "{:?}", args);
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_sync_core_mux_into_video_arguments() {
+    let runner = std::sync::Arc::new(RecordingFfmpegRunner::default());
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false)
+        .with_command_runner(std::sync::Arc::clone(&runner));
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output = temp_dir.path().join("out.mp4");
+    let output = output.to_str().unwrap();
+    
+    // Замена: видео из первого входа, аудио из второго
+    sync_core.mux_into_video("dub.wav", "video.mp4", output, MuxMode::Replace).await.unwrap();
+    let args = runner.args.lock().unwrap().clone();
+    assert_eq!(args, ["-y", "-i", "video.mp4", "-i", "dub.wav", "-map", "0:v", "-map", "1:a:0", "-c:v", "copy", output]);
+    
+    // Смешивание: исходная дорожка приглушается и складывается с новой
+    sync_core.mux_into_video("dub.wav", "video.mp4", output, MuxMode::Mix { original_volume: 0.3 }).await.unwrap();
+    let args = runner.args.lock().unwrap().clone();
+    let filter = args.iter().position(|arg| arg == "-filter_complex").expect("нет -filter_complex");
+    assert!(args[filter + 1].contains("amix=inputs=2") && args[filter + 1].contains("weights='0.3 1'"), "{:?}", args);
+    assert!(args.windows(2).any(|w| w[0] == "-map" && w[1] == "[mixed]"), "{:?}", args);
+    
+    let err = sync_core.mux_into_video("dub.wav", "video.mp4", output, MuxMode::Mix { original_volume: -1.0 }).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidParameters(_)), "{:?}", err);
+    
+    // В диапазонах оригинала исходная дорожка звучит полностью, а дубляж заглушается
+    let sync_core = sync_core.with_keep_original_ranges(vec![(1.0, 2.5), (4.0, 5.0)]);
+    sync_core.mux_into_video("dub.wav", "video.mp4", output, MuxMode::Mix { original_volume: 0.3 }).await.unwrap();
+    let args = runner.args.lock().unwrap().clone();
+    let filter = &args[args.iter().position(|arg| arg == "-filter_complex").unwrap() + 1];
+    let keep = "between(t,1,2.5)+between(t,4,5)";
+    assert!(filter.contains(&format!("[0:a:0]volume='if(gt({},0),1,0.3)'", keep)), "{}", filter);
+    assert!(filter.contains(&format!("[1:a:0]volume='if(gt({},0),0,1)'", keep)), "{}", filter);
+    assert!(filter.ends_with("amix=inputs=2:duration=first:normalize=0[mixed]"), "{}", filter);
+    
+    let sync_core = sync_core.with_keep_original_ranges(vec![(3.0, 2.0)]);
+    let err = sync_core.mux_into_video("dub.wav", "video.mp4", output, MuxMode::Mix { original_volume: 0.3 }).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidParameters(_)), "{:?}", err);
+}
+
+#[test]
+fn test_sync_core_mix_with_original_keeps_original_in_ranges() {
+    let dub = AudioData::new(vec![0.4; 44100 * 2], 44100, 1);
+    let original = AudioData::new(vec![0.2; 44100 * 2], 44100, 1);
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false)
+        .with_keep_original_ranges(vec![(0.5, 1.5)]);
+    let mixed = sync_core.mix_with_original(&dub, &original, -6.0).unwrap();
+    
+    // Внутри диапазона - только оригинал с полной громкостью, вне его - смесь
+    let at = |seconds: f64| mixed.samples[(seconds * 44100.0) as usize];
+    assert!((at(1.0) - 0.2).abs() < 1e-6, "{}", at(1.0));
+    let ducked = 0.4 + 0.2 * 10.0f32.powf(-6.0 / 20.0);
+    assert!((at(0.25) - ducked).abs() < 1e-5, "{}", at(0.25));
+    assert!((at(1.75) - ducked).abs() < 1e-5, "{}", at(1.75));
+}
+
+// Требует установленного ffmpeg: cargo test -- --ignored
+#[tokio::test]
+#[ignore]
+async fn test_sync_core_mux_into_video_with_ffmpeg() {
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false);
+    if !sync_core.ffmpeg_available() {
+        eprintln!("ffmpeg не найден, тест пропущен");
+        return;
+    }
+    
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = |name: &str| temp_dir.path().join(name).to_str().unwrap().to_string();
+    
+    // Исходное видео длительностью 1 с с тестовой картинкой и тоном
+    let status = std::process::Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error",
+            "-f", "lavfi", "-i", "testsrc=duration=1:size=64x64:rate=10",
+            "-f", "lavfi", "-i", "sine=frequency=220:duration=1",
+            "-shortest", &path("video.mkv")])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    
+    let audio = TtsSegment::test_tone("", 1.0, 440.0, 44100).audio_data;
+    std::fs::write(path("dub.wav"), audio).unwrap();
+    
+    for (mode, name) in [(MuxMode::Replace, "replaced.mkv"), (MuxMode::Mix { original_volume: 0.5 }, "mixed.mkv")] {
+        sync_core.mux_into_video(&path("dub.wav"), &path("video.mkv"), &path(name), mode).await.unwrap();
+        assert!(std::fs::metadata(path(name)).unwrap().len() > 0);
+    }
+    
+    // Ошибка ffmpeg содержит его вывод
+    let err = sync_core.mux_into_video(&path("missing.wav"), &path("video.mkv"), &path("bad.mkv"), MuxMode::Replace)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::AudioProcessing(_)));
+    assert!(err.to_string().contains("missing.wav"), "{}", err);
+}
+
+// Провайдер, возвращающий тон 440 Гц длительностью 1.5 с в формате WAV
+fn wav_tone() -> ToneTtsProvider {
+    ToneTtsProvider::new(1.5, 24000)
+}
+
+// Мок TtsProvider, отменяющий синхронизацию после первого сегмента;
+// остальные запросы выполняются, пока их не прервет отмена
+struct CancellingTtsProvider {
+    token: tts_sync::CancellationToken,
+    calls: std::sync::atomic::AtomicUsize,
+}
+
+impl TtsProvider for CancellingTtsProvider {
+    async fn generate_speech(&self, _text: &str) -> Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+    
+    async fn generate_segment(&self, text: &str, target_duration: f64) -> Result<TtsSegment> {
+        if self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) > 0 {
+            tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+        }
+        let segment = wav_tone().generate_segment(text, target_duration).await;
+        self.token.cancel();
+        segment
+    }
+    
+    async fn generate_speech_to_file<P: AsRef<Path> + Send>(&self, _text: &str, _path: P) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_sync_core_cancellation_after_first_segment() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(0.0, 1.0, "First".to_string()));
+    subtitles.add(Subtitle::new(1.0, 2.0, "Second".to_string()));
+    subtitles.add(Subtitle::new(2.0, 3.0, "Third".to_string()));
+    
+    for max_concurrent_requests in [1, 4] {
+        let token = tts_sync::CancellationToken::new();
+        let provider = CancellingTtsProvider { token: token.clone(), calls: Default::default() };
+        let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true)
+            .with_max_concurrent_requests(max_concurrent_requests)
+            .with_cancellation_token(token);
+        
+        // Отмена прерывает и ожидание зависших запросов
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(10),
+            sync_core.synchronize_subtitles(&subtitles, 3.0, &provider)
+        ).await.expect("Синхронизация не прервана отменой");
+        
+        assert!(matches!(result, Err(Error::Cancelled(_))), "Ожидалась отмена: {:?}", result.err());
+        if max_concurrent_requests == 1 {
+            // Следующий сегмент после отмены не запрашивается
+            assert_eq!(provider.calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_sync_core_continues_on_segment_error() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(0.0, 1.0, "First".to_string()));
+    subtitles.add(Subtitle::new(1.0, 2.0, "Second".to_string()));
+    subtitles.add(Subtitle::new(2.0, 3.0, "Third".to_string()));
+    subtitles.add(Subtitle::new(3.0, 4.0, "Second".to_string()));
+    
+    // По умолчанию ошибка реплики прерывает синхронизацию
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    let result = sync_core.synchronize_subtitles(&subtitles, 4.0, &wav_tone().failing_on("Second")).await;
+    assert!(matches!(result, Err(Error::OpenAi(_))), "{:?}", result.err());
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true)
+        .with_max_concurrent_requests(4)
+        .with_continue_on_error(true);
+    let track = sync_core.synchronize_subtitles(&subtitles, 4.0, &wav_tone().failing_on("Second")).await.unwrap();
+    
+    // Вместо реплики с ошибкой вставлена тишина ее длительности
+    let failed = track.segments.iter().find(|s| s.text == "Second").unwrap();
+    assert!((failed.audio.duration() - 1.0).abs() < 0.01, "{}", failed.audio.duration());
+    assert!(failed.audio.samples.iter().all(|s| s.abs() < 1e-4));
+    let spoken = track.segments.iter().find(|s| s.text == "Third").unwrap();
+    assert!(spoken.audio.samples.iter().any(|s| s.abs() > 0.1));
+    
+    let report = sync_core.report();
+    assert_eq!(report.segments.len(), 4);
+    assert!(report.diagnostics.iter().any(|d| d.kind == DiagnosticKind::SegmentFailed && d.segment == Some(1)));
+    
+    // Повтор текста с ошибкой тоже получает тишину и попадает в список ошибок
+    assert!(report.diagnostics.iter().any(|d| d.kind == DiagnosticKind::SegmentFailed && d.segment == Some(3)));
+    let errors = sync_core.take_segment_errors();
+    assert_eq!(errors.iter().map(|(index, _)| *index).collect::<Vec<_>>(), vec![1, 3]);
+    assert!(matches!(errors[0].1, Error::OpenAi(_)), "{:?}", errors[0].1);
+    assert!(matches!(errors[1].1, Error::Synchronization(_)), "{:?}", errors[1].1);
+}
+
+#[tokio::test]
+async fn test_sync_core_target_speech_duration_leaves_trailing_silence() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(1.0, 3.0, "Hello".to_string()).with_target_speech_duration(1.0));
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    let track = sync_core.synchronize_subtitles(&subtitles, 5.0, &wav_tone()).await.unwrap();
+    
+    // Сегмент занимает весь субтитр
+    let speech = track.segments.iter().find(|s| s.text == "Hello").unwrap();
+    assert!((speech.audio.duration() - 2.0).abs() < 0.01, "{}", speech.audio.duration());
+    
+    // Речь подогнана под заданную длительность, остаток - тишина
+    let last_voiced = speech.audio.samples.iter().rposition(|s| s.abs() > 1e-4).unwrap();
+    let voiced_duration = (last_voiced + 1) as f64 / 44100.0;
+    assert!((voiced_duration - 1.0).abs() < 0.06, "{}", voiced_duration);
+    
+    let report = sync_core.report();
+    assert!((report.segments[0].adjusted_duration - 1.0).abs() < 0.06);
+    
+    // Тишина до речи или поровну с обеих сторон в зависимости от привязки
+    for (anchor, expected_lead) in [(SpeechAnchor::Center, 0.5), (SpeechAnchor::End, 1.0)] {
+        let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true).with_speech_anchor(anchor);
+        let track = sync_core.synchronize_subtitles(&subtitles, 5.0, &wav_tone()).await.unwrap();
+        let speech = track.segments.iter().find(|s| s.text == "Hello").unwrap();
+        assert!((speech.audio.duration() - 2.0).abs() < 0.01, "{:?}: {}", anchor, speech.audio.duration());
+        let first_voiced = speech.audio.samples.iter().position(|s| s.abs() > 1e-4).unwrap() as f64 / 44100.0;
+        assert!((first_voiced - expected_lead).abs() < 0.06, "{:?}: речь с {}", anchor, first_voiced);
+    }
+}
+
+/// Возвращает длину самой длинной серии тишины в диапазоне `[from, to)` секунд
+fn longest_silence(audio: &AudioData, from: f64, to: f64) -> usize {
+    let rate = audio.sample_rate as f64;
+    let range = &audio.samples[(from * rate) as usize..(to * rate) as usize];
+    let (mut longest, mut current) = (0, 0);
+    for sample in range {
+        current = if sample.abs() < 1e-4 { current + 1 } else { 0 };
+        longest = longest.max(current);
+    }
+    longest
+}
+
+#[tokio::test]
+async fn test_sync_core_reproduces_natural_gaps_between_cues() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(0.5, 1.5, "First".to_string()));
+    subtitles.add(Subtitle::new(3.0, 4.0, "Second".to_string()));
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    let track = sync_core.synchronize_subtitles(&subtitles, 5.0, &wav_tone()).await.unwrap();
+    
+    // Пауза после первой реплики занимает весь промежуток 1.5 с, а не 200 мс
+    let pause = track.segments.iter()
+        .find(|segment| segment.text.is_empty() && (segment.start_time - 1.5).abs() < 1e-6)
+        .expect("нет паузы после первой реплики");
+    assert!((pause.end_time - 3.0).abs() < 1e-6, "Конец паузы: {}", pause.end_time);
+    assert!((pause.audio.duration() - 1.5).abs() < 1e-3);
+    
+    let merged = track.merge().unwrap();
+    let silence = longest_silence(&merged, 1.4, 3.1) as f64 / 44100.0;
+    assert!((silence - 1.5).abs() < 0.05, "Тишина между репликами: {}", silence);
+    
+    // После последней реплики - пауза по умолчанию
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true).with_default_pause_ms(300);
+    let track = sync_core.synchronize_subtitles(&subtitles, 5.0, &wav_tone()).await.unwrap();
+    assert!(track.segments.iter().any(|segment| segment.text.is_empty()
+        && (segment.start_time - 4.0).abs() < 1e-6
+        && (segment.end_time - 4.3).abs() < 1e-6));
+}
+
+#[tokio::test]
+async fn test_sync_core_synchronize_track_matches_file() {
+    let mut vtt_file = NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut vtt_file, b"WEBVTT\n\n00:00:00.500 --> 00:00:01.500\nFirst\n\n00:00:02.000 --> 00:00:03.000\nSecond\n").unwrap();
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    let from_file = sync_core.synchronize(vtt_file.path().to_str().unwrap(), 4.0, &wav_tone()).await.unwrap();
+    
+    let subtitles = tts_sync::VttParser::parse_file(vtt_file.path().to_str().unwrap()).unwrap();
+    let from_track = sync_core.synchronize_track(&subtitles, 4.0, &wav_tone()).await.unwrap();
+    assert_eq!(from_track.merge().unwrap().samples, from_file.merge().unwrap().samples);
+    
+    // Пустой трек дает ту же ошибку синхронизации, что и раньше
+    let result = sync_core.synchronize_track(&SubtitleTrack::new(), 4.0, &wav_tone()).await;
+    assert!(matches!(result, Err(Error::Synchronization(_))));
+}
+
+#[tokio::test]
+async fn test_sync_core_without_segment_pause() {
+    // Реплики идут вплотную, промежутков в субтитрах нет
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(0.0, 1.0, "First".to_string()));
+    subtitles.add(Subtitle::new(1.0, 2.0, "Second".to_string()));
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true).with_segment_pause(0.0);
+    let track = sync_core.synchronize_subtitles(&subtitles, 2.0, &wav_tone()).await.unwrap();
+    
+    // Пауз не добавлено, длина трека равна сумме длительностей сегментов
+    assert!(track.segments.iter().all(|segment| !segment.text.is_empty()));
+    let segments_duration: f64 = track.segments.iter().map(|segment| segment.audio.duration()).sum();
+    let merged = track.merge().unwrap();
+    assert!((merged.duration() - segments_duration).abs() < 1e-3,
+        "Длительность трека {} вместо {}", merged.duration(), segments_duration);
+}
+
+#[tokio::test]
+async fn test_sync_core_accepts_provider_selected_at_runtime() {
+    // Провайдер выбирается по строке конфигурации
+    let select = |name: &str| -> std::sync::Arc<dyn DynTtsProvider> {
+        match name {
+            "tone" => std::sync::Arc::new(wav_tone()),
+            _ => std::sync::Arc::new(MockTtsProvider),
+        }
+    };
+    let provider = select("tone");
+    
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(0.5, 1.5, "Hello".to_string()));
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    let merged = sync_core.synchronize_subtitles(&subtitles, 2.0, &*provider).await
+        .unwrap().merge().unwrap();
+    assert!((merged.duration() - 2.0).abs() < 0.01, "Длительность: {}", merged.duration());
+    assert!(merged.samples.iter().any(|sample| sample.abs() > 0.1));
+    
+    // Запросы к провайдеру dyn можно выполнять в отдельной задаче
+    let segment = tokio::spawn(async move { provider.generate_segment("Hello", 1.0).await })
+        .await.unwrap().unwrap();
+    assert_eq!(segment.text, "Hello");
+}
+
+// Мок TtsProvider, запоминающий запросы и наибольшее число одновременных запросов
+#[derive(Default)]
+struct ConcurrencyRecordingProvider {
+    in_flight: std::sync::atomic::AtomicUsize,
+    max_in_flight: std::sync::atomic::AtomicUsize,
+    texts: std::sync::Mutex<Vec<String>>,
+}
+
+impl TtsProvider for ConcurrencyRecordingProvider {
+    async fn generate_speech(&self, _text: &str) -> Result<Vec<u8>> {
+        Ok(TtsSegment::test_tone("", 0.5, 440.0, 24000).audio_data)
+    }
+    
+    async fn generate_segment(&self, text: &str, target_duration: f64) -> Result<TtsSegment> {
+        use std::sync::atomic::Ordering;
+        
+        self.texts.lock().unwrap().push(text.to_string());
+        let current = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(current, Ordering::SeqCst);
+        
+        // Ранние реплики отвечают дольше, поэтому запросы завершаются не по порядку
+        let number: u64 = text.trim_start_matches("Cue ").parse().unwrap_or(0);
+        tokio::time::sleep(std::time::Duration::from_millis(60 - number * 5)).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        
+        let mut segment = TtsSegment::test_tone(text, 0.5, 440.0, 24000);
+        segment.target_duration = target_duration;
+        Ok(segment)
+    }
+    
+    async fn generate_speech_to_file<P: AsRef<Path> + Send>(&self, _text: &str, path: P) -> Result<()> {
+        std::fs::File::create(path)?;
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_sync_core_limits_concurrent_tts_requests() {
+    use std::sync::{Arc, Mutex};
+    use std::sync::atomic::Ordering;
+    
+    // 10 реплик, две из которых повторяют текст предыдущих
+    let texts: Vec<String> = (0..10)
+        .map(|i| format!("Cue {}", if i == 4 || i == 9 { 1 } else { i }))
+        .collect();
+    let mut subtitles = SubtitleTrack::new();
+    for (i, text) in texts.iter().enumerate() {
+        subtitles.add(Subtitle::new(i as f64, i as f64 + 0.8, text.clone()));
+    }
+    
+    let progress_values = Arc::new(Mutex::new(Vec::new()));
+    let progress_values_clone = progress_values.clone();
+    let progress_tracker = ProgressTracker::with_callback(Box::new(move |progress, _status| {
+        progress_values_clone.lock().unwrap().push(progress);
+    }));
+    let provider = ConcurrencyRecordingProvider::default();
+    let sync_core = SyncCore::new(progress_tracker, 24000, 1, true)
+        .with_max_concurrent_requests(3);
+    sync_core.synchronize_subtitles(&subtitles, 10.0, &provider).await.unwrap();
+    
+    // Запросы выполняются одновременно, но не более заданного предела
+    assert_eq!(provider.max_in_flight.load(Ordering::SeqCst), 3);
+    
+    // Одинаковый текст синтезируется один раз
+    let mut requested = provider.texts.lock().unwrap().clone();
+    requested.sort();
+    assert_eq!(requested.len(), 8);
+    requested.dedup();
+    assert_eq!(requested.len(), 8);
+    
+    // Сегменты соответствуют репликам по порядку, прогресс не убывает
+    let report = sync_core.report();
+    let reported: Vec<&str> = report.segments.iter().map(|segment| segment.text.as_str()).collect();
+    assert_eq!(reported, texts.iter().map(String::as_str).collect::<Vec<_>>());
+    for pair in progress_values.lock().unwrap().windows(2) {
+        assert!(pair[1] >= pair[0], "Прогресс уменьшился: {} -> {}", pair[0], pair[1]);
+    }
+}
+
+#[tokio::test]
+async fn test_sync_core_reports_cue_sample_map() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(0.5, 1.5, "First".to_string()));
+    subtitles.add(Subtitle::new(2.0, 3.0, "Second".to_string()));
+    subtitles.add(Subtitle::new(3.5, 4.5, "Third".to_string()));
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true).with_pre_roll_ms(250);
+    let track = sync_core.synchronize_subtitles(&subtitles, 5.0, &wav_tone()).await.unwrap();
+    let merged = track.merge().unwrap();
+    let sample_map = sync_core.report().sample_map;
+    
+    assert_eq!(sample_map.iter().map(|&(cue, _, _)| cue).collect::<Vec<_>>(), [0, 1, 2]);
+    for &(cue, start, end) in &sample_map {
+        // Диапазон соответствует времени реплики с учетом pre-roll
+        let expected_start = ((subtitles.subtitles[cue].start_time + 0.25) * 44100.0).round() as usize;
+        assert_eq!(start, expected_start);
+        
+        // В диапазоне объединенного буфера лежит аудио сегмента этой реплики
+        let segment = track.segments.iter().find(|segment| segment.cue == Some(cue)).unwrap();
+        assert_eq!(end - start, segment.audio.samples.len());
+        assert_eq!(&merged.samples[start..end], segment.audio.samples.as_slice());
+    }
+}
+
+#[tokio::test]
+async fn test_sync_core_removes_micro_gaps_between_cues() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(1.0, 2.0, "First".to_string()));
+    subtitles.add(Subtitle::new(2.05, 3.0, "Second".to_string()));
+    
+    // Промежуток 50 мс ниже порога по умолчанию - речь идет без паузы
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    let merged = sync_core.synchronize_subtitles(&subtitles, 4.0, &wav_tone()).await
+        .unwrap().merge().unwrap();
+    assert!(longest_silence(&merged, 1.1, 2.9) < 10);
+    
+    // С порогом ниже промежутка пауза сохраняется
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true)
+        .with_micro_gap_threshold_ms(20);
+    let merged = sync_core.synchronize_subtitles(&subtitles, 4.0, &wav_tone()).await
+        .unwrap().merge().unwrap();
+    assert!(longest_silence(&merged, 1.1, 2.9) >= (0.04 * 44100.0) as usize);
+}
+
+// Исполнитель команд, имитирующий ffmpeg и считающий одновременно запущенные процессы
+#[cfg(unix)]
+#[derive(Default)]
+struct CountingFfmpegRunner {
+    running: std::sync::atomic::AtomicUsize,
+    max_running: std::sync::atomic::AtomicUsize,
+}
+
+#[cfg(unix)]
+impl CommandRunner for CountingFfmpegRunner {
+    fn run(&self, _program: &str, args: &[&str]) -> std::io::Result<std::process::Output> {
+        use std::os::unix::process::ExitStatusExt;
+        use std::sync::atomic::Ordering;
+        
+        let success = std::process::Output {
+            status: std::process::ExitStatus::from_raw(0),
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        };
+        if args == ["-version"] {
+            return Ok(success);
+        }
+        
+        let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_running.fetch_max(running, Ordering::SeqCst);
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        
+        // Последний аргумент - выходной файл
+        std::fs::write(args.last().unwrap(), vec![1u8; 1024])?;
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        
+        Ok(success)
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_export_batch_limits_concurrent_encodes() {
+    let runner = std::sync::Arc::new(CountingFfmpegRunner::default());
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true)
+        .with_command_runner(std::sync::Arc::clone(&runner));
+    
+    let temp_dir = tempfile::tempdir().unwrap();
+    let jobs: Vec<BatchJob> = (0..6).map(|i| {
+        let mut track = AudioTrack::new(44100, 1);
+        track.add_segment(AudioSegment::new(
+            AudioData::new(vec![0.1; 4410], 44100, 1),
+            0.0,
+            0.1,
+            format!("Track {}", i),
+        ));
+        BatchJob::new(track, temp_dir.path().join(format!("out_{}.mp3", i)).to_str().unwrap())
+    }).collect();
+    
+    let results = sync_core.export_batch(&jobs, &BatchOptions { max_concurrent_encodes: 2, ..BatchOptions::default() }).await;
+    
+    assert!(results.iter().all(|result| result.is_ok()));
+    assert!(jobs.iter().all(|job| Path::new(&job.output_path).exists()));
+    assert_eq!(runner.max_running.load(std::sync::atomic::Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_sync_core_reports_speech_regions() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(1.0, 2.0, "First".to_string()));
+    subtitles.add(Subtitle::new(2.5, 3.5, "Second".to_string()));
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    let track = sync_core.synchronize_subtitles(&subtitles, 5.0, &wav_tone()).await.unwrap();
+    let regions = sync_core.report().speech_regions;
+    
+    // Интервалы совпадают с сегментами речи трека
+    let speech_segments: Vec<(f64, f64)> = track.segments.iter()
+        .filter(|segment| segment.audio.samples.iter().any(|s| s.abs() > 1e-4))
+        .map(|segment| (segment.start_time, segment.end_time))
+        .collect();
+    assert_eq!(regions, speech_segments);
+    assert_eq!(regions, track.speech_regions());
+    assert_eq!(regions, vec![(1.0, 2.0), (2.5, 3.5)]);
+}
+
+#[tokio::test]
+async fn test_sync_core_enforces_max_output_duration() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(1.0, 2.0, "First".to_string()));
+    subtitles.add(Subtitle::new(6.0, 7.0, "Second".to_string()));
+    
+    // Обрезка: трек не длиннее максимальной длительности, реплики за пределом удалены
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true)
+        .with_max_output_duration(4.0, DurationLimitBehavior::Truncate);
+    let track = sync_core.synchronize_subtitles(&subtitles, 10.0, &wav_tone()).await.unwrap();
+    let track_end = track.segments.iter().map(|s| s.end_time).fold(0.0, f64::max);
+    assert!((track_end - 4.0).abs() < 1e-9, "{}", track_end);
+    assert!(track.segments.iter().all(|s| s.text != "Second"));
+    
+    // Ошибка: синхронизация не выполняется
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true)
+        .with_max_output_duration(4.0, DurationLimitBehavior::Error);
+    let result = sync_core.synchronize_subtitles(&subtitles, 10.0, &wav_tone()).await;
+    assert!(matches!(result, Err(Error::Synchronization(_))));
+    
+    // В пределах ограничения трек строится как обычно
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true)
+        .with_max_output_duration(8.0, DurationLimitBehavior::Error);
+    let track = sync_core.synchronize_subtitles(&subtitles, 8.0, &wav_tone()).await.unwrap();
+    let track_end = track.segments.iter().map(|s| s.end_time).fold(0.0, f64::max);
+    assert!((track_end - 8.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_sync_core_truncation_skips_cues_and_counts_roll() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(1.0, 2.0, "First".to_string()));
+    subtitles.add(Subtitle::new(6.0, 7.0, "Second".to_string()));
+    
+    // Реплика за пределом не отправляется провайдеру
+    let texts = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let provider = wav_tone().with_log(texts.clone(), "");
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false)
+        .with_max_output_duration(4.0, DurationLimitBehavior::Truncate);
+    sync_core.synchronize_subtitles(&subtitles, 10.0, &provider).await.unwrap();
+    assert_eq!(*texts.lock().unwrap(), vec!["First".to_string()]);
+    
+    // Тишина pre-roll и post-roll входит в предел
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false)
+        .with_pre_roll_ms(1000)
+        .with_post_roll_ms(500)
+        .with_max_output_duration(4.0, DurationLimitBehavior::Truncate);
+    let track = sync_core.synchronize_subtitles(&subtitles, 10.0, &wav_tone()).await.unwrap();
+    assert!((track.merged_duration() - 4.0).abs() < 1e-3, "{}", track.merged_duration());
+    
+    // Видео с тишиной укладывается в предел только без нее
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false)
+        .with_pre_roll_ms(1000)
+        .with_max_output_duration(8.0, DurationLimitBehavior::Error);
+    let result = sync_core.synchronize_subtitles(&subtitles, 8.0, &wav_tone()).await;
+    assert!(matches!(result, Err(Error::Synchronization(_))));
+}
+
+#[tokio::test]
+async fn test_sync_core_speaks_normalized_text_and_reports_display_text() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let vtt_content = "WEBVTT\n\n00:00:01.000 --> 00:00:03.000\n<i>Hello</i> [door slams] world!\n";
+    std::fs::write(temp_file.path(), vtt_content).unwrap();
+    
+    let texts = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let provider = wav_tone().with_log(texts.clone(), "");
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true)
+        .with_spoken_text_normalization(true);
+    sync_core.synchronize(temp_file.path().to_str().unwrap(), 5.0, &provider).await.unwrap();
+    
+    assert_eq!(*texts.lock().unwrap(), vec!["Hello world!".to_string()]);
+    assert_eq!(sync_core.report().segments[0].text, "<i>Hello</i> [door slams] world!");
+}
+
+#[tokio::test]
+async fn test_sync_core_replaces_cue_without_spoken_text_with_silence() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let vtt_content = "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\nHello\n\n\
+        00:00:02.500 --> 00:00:04.000\n[music] ♪\n";
+    std::fs::write(temp_file.path(), vtt_content).unwrap();
+    
+    let texts = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let provider = wav_tone().with_log(texts.clone(), "");
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true)
+        .with_spoken_text_normalization(true);
+    let track = sync_core.synchronize(temp_file.path().to_str().unwrap(), 5.0, &provider).await.unwrap();
+    
+    // Провайдер не получает пустой текст, а реплика занята тишиной своей длительности
+    assert_eq!(*texts.lock().unwrap(), vec!["Hello".to_string()]);
+    let music = track.segments.iter().find(|segment| segment.cue == Some(1)).unwrap();
+    assert_eq!(music.text, "[music] ♪");
+    assert!((music.audio.duration() - 1.5).abs() < 1e-3, "{}", music.audio.duration());
+    assert!(music.audio.samples.iter().all(|&sample| sample == 0.0));
+    assert_eq!(sync_core.report().segments[1].text, "[music] ♪");
+}
+
+#[tokio::test]
+async fn test_sync_core_handles_out_of_range_cues() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let vtt_content = "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\nFirst\n\n\
+        00:00:04.000 --> 00:00:08.000\nSecond\n\n\
+        99:59:59.000 --> 99:59:59.999\nBroken\n";
+    std::fs::write(temp_file.path(), vtt_content).unwrap();
+    let vtt_path = temp_file.path().to_str().unwrap();
+    
+    // Обрезка: субтитр за концом видео пропущен, пересекающий конец - обрезан
+    let texts = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let provider = wav_tone().with_log(texts.clone(), "");
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    let track = sync_core.synchronize(vtt_path, 5.0, &provider).await.unwrap();
+    
+    assert_eq!(*texts.lock().unwrap(), vec!["First".to_string(), "Second".to_string()]);
+    let report = sync_core.report();
+    assert_eq!(report.segments.len(), 2);
+    assert_eq!((report.segments[1].start_time, report.segments[1].end_time), (4.0, 5.0));
+    let track_end = track.segments.iter().map(|s| s.end_time).fold(0.0, f64::max);
+    assert!((track_end - 5.0).abs() < 1e-9);
+    
+    // Отрицательное время начала обрезается до нуля
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(-0.5, 1.0, "Shifted".to_string()));
+    sync_core.synchronize_subtitles(&subtitles, 5.0, &wav_tone()).await.unwrap();
+    assert_eq!(sync_core.report().segments[0].start_time, 0.0);
+    
+    // Ошибка: синхронизация не выполняется
+    let texts = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let provider = wav_tone().with_log(texts.clone(), "");
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true)
+        .with_cue_range_behavior(CueRangeBehavior::Error);
+    let result = sync_core.synchronize(vtt_path, 5.0, &provider).await;
+    assert!(matches!(result, Err(Error::Synchronization(_))));
+    assert!(texts.lock().unwrap().is_empty());
+}
+
+// Приемник, сохраняющий полученные сегменты
+#[derive(Default)]
+struct CollectingSink {
+    segments: Vec<AudioSegment>,
+    finished: bool,
+}
+
+impl AudioSink for CollectingSink {
+    fn write_segment(&mut self, segment: &AudioSegment) -> Result<()> {
+        assert!(!self.finished, "Сегмент получен после завершения");
+        self.segments.push(segment.clone());
+        Ok(())
+    }
+    
+    fn finish(&mut self) -> Result<()> {
+        self.finished = true;
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_sync_core_streams_segments_to_sink() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let vtt_content = "WEBVTT\n\n00:00:02.500 --> 00:00:03.500\nSecond\n\n00:00:01.000 --> 00:00:02.000\nFirst\n";
+    std::fs::write(temp_file.path(), vtt_content).unwrap();
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    let mut sink = CollectingSink::default();
+    let track = sync_core
+        .synchronize_streaming(temp_file.path().to_str().unwrap(), 5.0, &wav_tone(), &mut sink)
+        .await
+        .unwrap();
+    
+    assert!(sink.finished);
+    
+    // Сегменты идут в порядке воспроизведения без промежутков, начиная с нуля
+    assert_eq!(sink.segments[0].start_time, 0.0);
+    assert!(sink.segments.windows(2).all(|pair| (pair[0].end_time - pair[1].start_time).abs() < 1e-9));
+    let texts: Vec<&str> = sink.segments.iter()
+        .map(|segment| segment.text.as_str())
+        .filter(|text| !text.is_empty())
+        .collect();
+    assert_eq!(texts, vec!["First", "Second"]);
+    
+    // Реплики переданы в тех же позициях и той же длительности, что и в треке
+    for streamed in sink.segments.iter().filter(|segment| !segment.text.is_empty()) {
+        let cue = track.segments.iter().find(|segment| segment.text == streamed.text).unwrap();
+        assert!((streamed.start_time - cue.start_time).abs() < 1e-9);
+        assert_eq!(streamed.audio.samples.len(), cue.audio.samples.len());
+    }
+    
+    // Последовательное воспроизведение длится столько же, сколько итоговый трек
+    let streamed_samples: usize = sink.segments.iter().map(|segment| segment.audio.samples.len()).sum();
+    let merged = track.merge().unwrap();
+    assert!(streamed_samples.abs_diff(merged.samples.len()) <= sink.segments.len());
+}
+
+// Приемник, записывающий полученные реплики в общий журнал
+struct LoggingSink {
+    log: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+impl AudioSink for LoggingSink {
+    fn write_segment(&mut self, segment: &AudioSegment) -> Result<()> {
+        if !segment.text.is_empty() {
+            self.log.lock().unwrap().push(format!("sink:{}", segment.text));
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_sync_core_streams_cues_while_rendering() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let vtt_content = "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\nFirst\n\n00:00:02.500 --> 00:00:03.500\nSecond\n\n00:00:04.000 --> 00:00:05.000\nThird\n";
+    std::fs::write(temp_file.path(), vtt_content).unwrap();
+    
+    let log = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let provider = wav_tone().with_delay_ms(100).with_log(log.clone(), "request:");
+    let mut sink = LoggingSink { log: log.clone() };
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true)
+        .with_max_concurrent_requests(1);
+    sync_core
+        .synchronize_streaming(temp_file.path().to_str().unwrap(), 6.0, &provider, &mut sink)
+        .await
+        .unwrap();
+    
+    // Первая реплика передана в приемник до запроса последней
+    let log = log.lock().unwrap();
+    let position = |entry: &str| log.iter().position(|e| e == entry).unwrap_or_else(|| panic!("{} нет в {:?}", entry, log));
+    assert!(position("sink:First") < position("request:Third"), "{:?}", log);
+    assert!(position("sink:Third") > position("request:Third"));
+}
+
+#[tokio::test]
+async fn test_sync_core_compresses_long_gaps() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(1.0, 2.0, "First".to_string()));
+    subtitles.add(Subtitle::new(32.0, 33.0, "Second".to_string()));
+    subtitles.add(Subtitle::new(34.0, 35.0, "Third".to_string()));
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true)
+        .with_max_gap_duration(Some(5.0));
+    let track = sync_core.synchronize_subtitles(&subtitles, 40.0, &wav_tone()).await.unwrap();
+    let report = sync_core.report();
+    
+    // Промежуток 30 секунд сокращен до 5, короткий промежуток не изменен
+    assert_eq!(report.speech_regions, vec![(1.0, 2.0), (7.0, 8.0), (9.0, 10.0)]);
+    let track_end = track.segments.iter().map(|s| s.end_time).fold(0.0, f64::max);
+    assert!((track_end - 15.0).abs() < 1e-9, "{}", track_end);
+    
+    // Сокращение записано для обратного сопоставления времени
+    assert_eq!(report.gap_compressions.len(), 1);
+    let gap = report.gap_compressions[0];
+    assert_eq!((gap.original_start, gap.original_duration, gap.compressed_duration), (2.0, 30.0, 5.0));
+    assert_eq!(gap.removed_duration(), 25.0);
+    assert_eq!(report.to_output_time(1.5), 1.5);
+    assert_eq!(report.to_output_time(4.0), 4.0);
+    assert_eq!(report.to_output_time(20.0), 7.0);
+    assert_eq!(report.to_output_time(34.5), 9.5);
+}
+
+#[tokio::test]
+async fn test_sync_core_reuses_segments_for_identical_texts() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(0.0, 1.0, "Yes".to_string()));
+    subtitles.add(Subtitle::new(1.5, 2.5, "No".to_string()));
+    subtitles.add(Subtitle::new(3.0, 4.0, "Yes".to_string()));
+    subtitles.add(Subtitle::new(4.5, 5.5, "Yes!".to_string()));
+    subtitles.add(Subtitle::new(6.0, 7.0, "No".to_string()));
+    
+    let texts = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let provider = wav_tone().with_log(texts.clone(), "");
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    let track = sync_core.synchronize_subtitles(&subtitles, 8.0, &provider).await.unwrap();
+    
+    // Повторяющиеся тексты синтезируются один раз, различные - каждый отдельно
+    assert_eq!(*texts.lock().unwrap(), vec!["Yes".to_string(), "No".to_string(), "Yes!".to_string()]);
+    let spoken: Vec<&str> = track.segments.iter()
+        .map(|segment| segment.text.as_str())
+        .filter(|text| !text.is_empty())
+        .collect();
+    assert_eq!(spoken, vec!["Yes", "No", "Yes", "Yes!", "No"]);
+}
+
+#[tokio::test]
+async fn test_sync_core_inserts_punctuation_pauses() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(1.0, 3.0, "One, two; three.".to_string()));
+    
+    let texts = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let provider = ToneTtsProvider::new(0.5, 24000).with_log(texts.clone(), "");
+    let pauses = PunctuationPauses { comma: 0.2, semicolon: 0.3, period: 0.5 };
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false)
+        .with_punctuation_pauses(Some(pauses));
+    let track = sync_core.synchronize_subtitles(&subtitles, 4.0, &provider).await.unwrap();
+    
+    // Реплика синтезирована по фразам
+    assert_eq!(*texts.lock().unwrap(), vec!["One,".to_string(), "two;".to_string(), "three.".to_string()]);
+    
+    // Три фразы по 0.5 с и паузы 0.2 и 0.3 с занимают реплику без изменения темпа
+    let speech = track.segments.iter().find(|s| s.text == "One, two; three.").unwrap();
+    let rate = speech.audio.sample_rate as f64;
+    let mut silences = Vec::new();
+    let mut run_start = None;
+    for (i, sample) in speech.audio.samples.iter().enumerate() {
+        match (sample.abs() < 1e-3, run_start) {
+            (true, None) => run_start = Some(i),
+            (false, Some(start)) => {
+                if (i - start) as f64 / rate > 0.05 {
+                    silences.push((start as f64 / rate, (i - start) as f64 / rate));
+                }
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    
+    assert_eq!(silences.len(), 2, "{:?}", silences);
+    for ((position, duration), (expected_position, expected_duration)) in silences.into_iter().zip([(0.5, 0.2), (1.2, 0.3)]) {
+        assert!((position - expected_position).abs() < 0.01, "{} vs {}", position, expected_position);
+        assert!((duration - expected_duration).abs() < 0.01, "{} vs {}", duration, expected_duration);
+    }
+}
+
+#[tokio::test]
+async fn test_recorded_responses_replay_identically() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let recording_path = temp_dir.path().join("responses.json");
+    
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(0.5, 1.5, "Hello, world!".to_string()));
+    subtitles.add(Subtitle::new(2.0, 3.0, "Second line".to_string()));
+    
+    // Запись ответов провайдера
+    let recorder = tts_sync::tts::RecordingTtsProvider::new(wav_tone(), &recording_path);
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    let recorded = sync_core.synchronize_subtitles(&subtitles, 4.0, &recorder).await.unwrap();
+    assert_eq!(recorder.len(), 2);
+    recorder.save().unwrap();
+    
+    // Воспроизведение без исходного провайдера дает тот же результат
+    let replay = tts_sync::tts::ReplayTtsProvider::load(&recording_path).unwrap();
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    let replayed = sync_core.synchronize_subtitles(&subtitles, 4.0, &replay).await.unwrap();
+    assert_eq!(replayed.merge().unwrap(), recorded.merge().unwrap());
+    
+    // Незаписанный запрос завершается ошибкой
+    assert!(replay.generate_segment("Unknown", 1.0).await.is_err());
+}
+
+#[tokio::test]
+async fn test_sync_core_enforces_min_segment_duration() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(1.0, 1.02, "Hi".to_string()));
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false)
+        .with_min_segment_duration(0.1);
+    let track = sync_core.synchronize_subtitles(&subtitles, 3.0, &wav_tone()).await.unwrap();
+    
+    // Речь 20-миллисекундной реплики подогнана под минимальную длительность
+    let report = sync_core.report();
+    assert!((report.segments[0].adjusted_duration - 0.1).abs() < 0.01, "{}", report.segments[0].adjusted_duration);
+    
+    let speech = track.segments.iter().find(|s| s.text == "Hi").unwrap();
+    assert!(speech.audio.duration() >= 0.1 - 1e-3, "{}", speech.audio.duration());
+    assert!(speech.audio.samples.iter().all(|s| s.is_finite()));
+    assert!(speech.audio.samples.iter().any(|s| s.abs() > 1e-4));
+    
+    // По умолчанию ограничение отключено и речь подгоняется под саму реплику
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false);
+    sync_core.synchronize_subtitles(&subtitles, 3.0, &wav_tone()).await.unwrap();
+    let report = sync_core.report();
+    assert!((report.segments[0].adjusted_duration - 0.02).abs() < 0.01, "{}", report.segments[0].adjusted_duration);
+}
+
+// Провайдер, возвращающий подозрительно малые данные
+struct TinyTtsProvider;
+
+impl TtsProvider for TinyTtsProvider {
+    async fn generate_speech(&self, _text: &str) -> Result<Vec<u8>> {
+        Ok(vec![0u8; 10])
+    }
+    
+    async fn generate_speech_to_file<P: AsRef<Path> + Send>(&self, _text: &str, path: P) -> Result<()> {
+        std::fs::write(path, vec![0u8; 10])?;
+        Ok(())
+    }
+    
+    async fn generate_segment(&self, text: &str, target_duration: f64) -> Result<TtsSegment> {
+        Ok(TtsSegment {
+            text: text.to_string(),
+            audio_data: vec![0u8; 10],
+            duration: None,
+            target_duration,
+            stretch_factor: None,
+            word_timings: None,
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_sync_core_collects_warnings_as_diagnostics() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(1.0, 2.0, "First".to_string()));
+    subtitles.add(Subtitle::new(2.5, 3.5, "Second".to_string()));
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    sync_core.synchronize_subtitles(&subtitles, 4.0, &TinyTtsProvider).await.unwrap();
+    
+    let report = sync_core.report();
+    assert!(report.has_diagnostic(DiagnosticKind::TinyTtsData));
+    let tiny: Vec<_> = report.diagnostics.iter()
+        .filter(|d| d.kind == DiagnosticKind::TinyTtsData)
+        .map(|d| d.segment)
+        .collect();
+    assert_eq!(tiny, vec![Some(0), Some(1)]);
+    assert!(report.diagnostics.iter().all(|d| !d.message.is_empty()));
+    
+    // Предупреждения сбрасываются при следующей синхронизации
+    sync_core.synchronize_subtitles(&subtitles, 4.0, &wav_tone()).await.unwrap();
+    assert!(!sync_core.report().has_diagnostic(DiagnosticKind::TinyTtsData));
+}
+
+#[tokio::test]
+async fn test_sync_core_reports_header_only_vtt() {
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    
+    let temp_file = NamedTempFile::new().unwrap();
+    std::fs::write(temp_file.path(), "WEBVTT\n\nNOTE no cues yet\n").unwrap();
+    let result = sync_core.synchronize(temp_file.path().to_str().unwrap(), 10.0, &MockTtsProvider).await;
+    
+    match result {
+        Err(Error::VttParsing(msg)) => assert!(msg.contains("contains no cues"), "{}", msg),
+        other => panic!("Expected VttParsing error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[tokio::test]
+async fn test_sync_core_reports_output_duration_error() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(0.5, 2.0, "First".to_string()));
+    subtitles.add(Subtitle::new(2.5, 4.0, "Second".to_string()));
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    let track = sync_core.synchronize_subtitles(&subtitles, 5.3337, &wav_tone()).await.unwrap();
+    
+    let report = sync_core.report();
+    let merged = track.merge().unwrap();
+    let actual_error = merged.duration() - 5.3337;
+    assert!((report.duration_error - actual_error).abs() < 1e-9, "{} vs {}", report.duration_error, actual_error);
+    assert!(report.duration_error.abs() <= 1.0 / 44100.0, "{}", report.duration_error);
+    assert!(!report.has_diagnostic(DiagnosticKind::DurationMismatch));
+    
+    // При нулевом допуске остаток округления попадает в предупреждения
+    let strict_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true)
+        .with_duration_tolerance(0.0);
+    strict_core.synchronize_subtitles(&subtitles, 5.3337, &wav_tone()).await.unwrap();
+    let strict_report = strict_core.report();
+    assert_eq!(strict_report.duration_error != 0.0, strict_report.has_diagnostic(DiagnosticKind::DurationMismatch));
+}
+
+#[tokio::test]
+async fn test_sync_core_skips_normalization_near_target_peak() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(1.0, 2.5, "Tone".to_string()));
+    
+    // Речь совпадает по длительности с субтитром и не пересэмплируется
+    let tone = TtsSegment::test_tone("", 1.5, 440.0, 24000);
+    let decoded = decode_tts_audio(&tone.audio_data, 24000, 24000, SampleRateMismatch::Error).unwrap();
+    let peak = decoded.samples.iter().fold(0.0f32, |max, &s| max.max(s.abs()));
+    let target_peak = peak + 0.0005;
+    
+    let sync_core = SyncCore::new_with_options(ProgressTracker::new(), 24000, 1, true, target_peak, true, TempoAlgorithm::Sinc);
+    let track = sync_core.synchronize_subtitles(&subtitles, 3.0, &wav_tone()).await.unwrap();
+    let speech = track.segments.iter().find(|s| s.text == "Tone").unwrap();
+    // Последние 10 мс реплики затухают перед завершающей тишиной
+    let unfaded = decoded.samples.len() - 240;
+    assert_eq!(&speech.audio.samples[..unfaded], &decoded.samples[..unfaded]);
+    
+    // Без допуска к сегменту применяется усиление
+    let sync_core = SyncCore::new_with_options(ProgressTracker::new(), 24000, 1, true, target_peak, true, TempoAlgorithm::Sinc)
+        .with_normalization_tolerance(0.0);
+    let track = sync_core.synchronize_subtitles(&subtitles, 3.0, &wav_tone()).await.unwrap();
+    let speech = track.segments.iter().find(|s| s.text == "Tone").unwrap();
+    assert_ne!(&speech.audio.samples[..decoded.samples.len()], &decoded.samples[..]);
+}
+
+// Провайдер, возвращающий тайминги слов по запросу
+fn aligned_tone() -> ToneTtsProvider {
+    ToneTtsProvider::new(1.0, 24000)
+        .with_word_timings(vec![WordTiming::new("Hello", 0.0, 0.4), WordTiming::new("world", 0.5, 1.0)])
+}
+
+#[tokio::test]
+async fn test_sync_core_reports_provider_word_timings() {
+    let temp_file = NamedTempFile::new().unwrap();
+    std::fs::write(temp_file.path(), "WEBVTT\n\n00:00:01.000 --> 00:00:03.000\nHello world\n").unwrap();
+    let vtt_path = temp_file.path().to_str().unwrap();
+    
+    // Провайдер возвращает тайминги в сегменте
+    let segment = aligned_tone().generate_segment_with_timestamps("Hello world", 2.0).await.unwrap();
+    assert_eq!(segment.word_timings.unwrap()[1], WordTiming::new("world", 0.5, 1.0));
+    
+    // Без запроса таймингов отчет их не содержит
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false);
+    sync_core.synchronize(vtt_path, 4.0, &aligned_tone()).await.unwrap();
+    assert_eq!(sync_core.report().segments[0].word_timings, None);
+    
+    // Речь длительностью 1 с растянута до 2 с, тайминги переносятся на шкалу результата
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false)
+        .with_request_timestamps(true);
+    sync_core.synchronize(vtt_path, 4.0, &aligned_tone()).await.unwrap();
+    let report = sync_core.report();
+    let timings = report.segments[0].word_timings.as_ref().unwrap();
+    assert_eq!(timings.len(), 2);
+    assert_eq!(timings[1].word, "world");
+    assert!((timings[0].start - 1.0).abs() < 0.05, "{:?}", timings);
+    assert!((timings[1].start - 2.0).abs() < 0.05, "{:?}", timings);
+    assert!((timings[1].end - 3.0).abs() < 0.05, "{:?}", timings);
+}
+
+#[tokio::test]
+async fn test_sync_core_word_timings_follow_track_shifts() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(0.0, 0.5, "Intro".to_string()));
+    subtitles.add(Subtitle::new(5.0, 7.0, "Hello world".to_string()));
+    
+    // Промежуток 4.5 с сокращается до 1 с, затем весь дубляж сдвигается на 0.5 с:
+    // реплика начинается в 1.5 + 0.5 = 2.0 с
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false)
+        .with_request_timestamps(true)
+        .with_max_gap_duration(Some(1.0))
+        .with_global_offset_ms(500);
+    let track = sync_core.synchronize_subtitles(&subtitles, 8.0, &aligned_tone()).await.unwrap();
+    let cue = track.segments.iter().find(|segment| segment.cue == Some(1)).unwrap();
+    assert!((cue.start_time - 2.0).abs() < 1e-9, "{}", cue.start_time);
+    
+    let report = sync_core.report();
+    let timings = report.segments.iter().find(|segment| segment.index == 1).unwrap()
+        .word_timings.clone().unwrap();
+    assert!((timings[0].start - 2.0).abs() < 0.05, "{:?}", timings);
+    assert!((timings[1].start - 3.0).abs() < 0.05, "{:?}", timings);
+    assert!((timings[1].end - 4.0).abs() < 0.05, "{:?}", timings);
+}
+
+#[tokio::test]
+async fn test_sync_core_pre_roll_shifts_report_timings() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(1.0, 3.0, "Hello world".to_string()));
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false)
+        .with_request_timestamps(true)
+        .with_pre_roll_ms(1000);
+    let track = sync_core.synchronize_subtitles(&subtitles, 4.0, &aligned_tone()).await.unwrap();
+    let cue = track.segments.iter().find(|segment| segment.cue == Some(0)).unwrap();
+    
+    // Реплика и ее слова в отчете совпадают с положением в треке после pre-roll
+    let report = sync_core.report();
+    let segment = &report.segments[0];
+    assert!((segment.start_time - cue.start_time).abs() < 1e-9, "{} vs {}", segment.start_time, cue.start_time);
+    assert!((segment.start_time - 2.0).abs() < 1e-9 && (segment.end_time - 4.0).abs() < 1e-9);
+    let timings = segment.word_timings.as_ref().unwrap();
+    assert!((timings[0].start - 2.0).abs() < 0.05, "{:?}", timings);
+    assert!((timings[1].end - 4.0).abs() < 0.05, "{:?}", timings);
+}
+
+#[tokio::test]
+async fn test_sync_core_adds_pre_roll_and_post_roll() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(0.0, 1.5, "Tone".to_string()));
+    
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, false)
+        .with_pre_roll_ms(1000)
+        .with_post_roll_ms(500);
+    let track = sync_core.synchronize_subtitles(&subtitles, 2.0, &wav_tone()).await.unwrap();
+    let merged = track.merge().unwrap();
+    
+    // Видео 2 с, тишина 1 с в начале и 0.5 с в конце
+    assert!((merged.duration() - 3.5).abs() < 1e-3, "{}", merged.duration());
+    assert!(sync_core.report().duration_error.abs() < 1e-3);
+    
+    let pre_roll = 44100;
+    let post_roll = 22050;
+    assert!(merged.samples[..pre_roll].iter().all(|&sample| sample == 0.0));
+    assert!(merged.samples[pre_roll..pre_roll + 4410].iter().any(|&sample| sample.abs() > 0.1));
+    assert!(merged.samples[merged.samples.len() - post_roll..].iter().all(|&sample| sample == 0.0));
+    
+    // Интервалы речи отсчитываются от начала результата
+    let (speech_start, _) = sync_core.report().speech_regions[0];
+    assert!((speech_start - 1.0).abs() < 0.01, "{}", speech_start);
+}
+
+#[tokio::test]
+async fn test_sync_core_pipelines_adjustment_with_synthesis() {
+    let mut subtitles = SubtitleTrack::new();
+    subtitles.add(Subtitle::new(0.0, 1.0, "One".to_string()));
+    subtitles.add(Subtitle::new(1.0, 2.0, "Two".to_string()));
+    subtitles.add(Subtitle::new(2.0, 3.0, "Three".to_string()));
+    
+    // Журнал запросов к провайдеру и этапов прогресса
+    let run = |pipelined: bool| {
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let progress_events = events.clone();
+        let progress_values = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let values = progress_values.clone();
+        let tracker = ProgressTracker::with_callback(Box::new(move |progress, status| {
+            progress_events.lock().unwrap().push(status.to_string());
+            values.lock().unwrap().push(progress);
+        }));
+        let provider = ToneTtsProvider::new(1.0, 24000).with_delay_ms(20).with_log(events.clone(), "generate ");
+        let subtitles = subtitles.clone();
+        async move {
+            // Синтез по одной реплике, чтобы порядок запросов был детерминирован
+            let sync_core = SyncCore::new(tracker, 44100, 1, false)
+                .with_pipelining(pipelined)
+                .with_max_concurrent_requests(1);
+            sync_core.synchronize_subtitles(&subtitles, 3.0, &provider).await.unwrap();
+            let events = events.lock().unwrap().clone();
+            let values = progress_values.lock().unwrap().clone();
+            (events, values, sync_core.report())
+        }
+    };
+    let position = |events: &[String], event: &str| events.iter().position(|e| e == event).unwrap();
+    
+    // Первый сегмент корректируется до запроса последнего
+    let (events, values, pipelined_report) = run(true).await;
+    assert!(position(&events, "Анализ и корректировка сегмента 1/3") < position(&events, "generate Three"), "{:?}", events);
+    assert!(values.windows(2).all(|pair| pair[1] >= pair[0]), "{:?}", values);
+    
+    // При последовательной обработке корректировка начинается после всего синтеза
+    let (events, _, sequential_report) = run(false).await;
+    assert!(position(&events, "Анализ и корректировка сегмента 1/3") > position(&events, "generate Three"), "{:?}", events);
+    
+    assert_eq!(pipelined_report.segments, sequential_report.segments);
+}
@@ -1,5 +1,6 @@
-use tts_sync::{TtsSync, SyncOptions, AudioFormat, Result, TempoAlgorithm as ConfigTempoAlgorithm};
+use tts_sync::{TtsSync, SyncOptions, AudioFormat, Result, TempoAlgorithm as ConfigTempoAlgorithm, BatchOptions, SyncJob, Error, OpenAiTtsModel};
 use tts_sync::tts::{TtsProvider, TtsSegment};
+use tts_sync::audio::AudioProcessor;
 use std::path::Path;
 use std::future::Future;
 use tempfile::NamedTempFile;
@@ -12,6 +13,9 @@ use tts_sync::{
     progress::ProgressTracker,
 };
 
+mod common;
+use common::ToneTtsProvider;
+
 // Функция для инициализации логгера в тестах
 fn init_test_logger() {
     setup_test_logging(LevelFilter::Debug);
@@ -52,11 +56,12 @@ impl TtsProvider for MockTtsProvider {
                 duration: Some(target_duration),
                 target_duration,
                 stretch_factor: None,
+                word_timings: None,
             })
         }
     }
 
-    fn generate_speech_to_file<P: AsRef<Path>>(&self, _text: &str, path: P) -> impl Future<Output = Result<()>> {
+    fn generate_speech_to_file<P: AsRef<Path> + Send>(&self, _text: &str, path: P) -> impl Future<Output = Result<()>> {
         async move {
             // Для тестов просто создаем пустой файл
             std::fs::File::create(path)?;
@@ -133,6 +138,7 @@ async fn test_tts_sync_with_options() {
         eq_high_freq: 3000.0,
         normalization_target_db: -3.0,
         log_level: LevelFilter::Info,
+        ..SyncOptions::default()
     };
     
     // Создаем экземпляр TtsSync с пользовательскими настройками
@@ -158,6 +164,79 @@ async fn test_tts_sync_with_fluent_interface() {
     assert!(tts_sync.synchronize("nonexistent.vtt", 10.0, "fake_api_key").await.is_err());
 }
 
+#[tokio::test]
+async fn test_tts_sync_rejects_out_of_range_speed() {
+    init_test_logger();
+    
+    // Скорость проверяется до обращения к API и чтения субтитров
+    for speed in [0.1, 4.5] {
+        let tts_sync = TtsSync::default()
+            .with_model(OpenAiTtsModel::Tts1)
+            .with_speed(speed);
+        let err = tts_sync.synchronize("nonexistent.vtt", 10.0, "fake_api_key").await.unwrap_err();
+        assert!(matches!(err, Error::InvalidParameters(_)), "{:?}", err);
+        let err = tts_sync.synchronize_to_memory("nonexistent.vtt", 10.0, "fake_api_key").await.unwrap_err();
+        assert!(matches!(err, Error::InvalidParameters(_)), "{:?}", err);
+    }
+    
+    // По умолчанию используется модель tts-1 со скоростью 1.0
+    let options = SyncOptions::default();
+    assert_eq!(options.model, OpenAiTtsModel::Tts1);
+    assert_eq!(options.speed, 1.0);
+}
+
+#[test]
+fn test_sync_options_builder() {
+    let options = SyncOptions::builder()
+        .with_voice("nova")
+        .with_sample_rate(48000)
+        .with_output_format(AudioFormat::Wav)
+        .with_channels(2)
+        .with_compression(-18.0, 3.0, 5.0, 80.0, 4.0)
+        .build()
+        .unwrap();
+    assert_eq!(options.voice, "nova");
+    assert_eq!(options.sample_rate, 48000);
+    assert_eq!(options.output_format, AudioFormat::Wav);
+    assert_eq!(options.stereo_pan, Some(0.0));
+    assert!(options.apply_compression);
+    assert_eq!(options.compression_ratio, 3.0);
+    
+    // Незаданные параметры совпадают со значениями по умолчанию
+    let defaults = SyncOptions::default();
+    assert_eq!(options.model, defaults.model);
+    assert_eq!(options.eq_low_freq, defaults.eq_low_freq);
+    assert!(SyncOptions::builder().build().is_ok());
+    
+    // Некорректные сочетания отклоняются
+    let invalid = [
+        SyncOptions::builder().with_sample_rate(1000),
+        SyncOptions::builder().with_compression(-20.0, 1.0, 10.0, 100.0, 0.0),
+        SyncOptions::builder().with_equalization(0.0, 0.0, 0.0, 3000.0, 300.0),
+        SyncOptions::builder().with_sample_rate(8000).with_equalization(0.0, 0.0, 0.0, 300.0, 6000.0),
+        SyncOptions::builder().with_channels(6),
+        SyncOptions::builder().with_speed(10.0),
+        SyncOptions::builder().with_mp3_bitrate(100),
+        SyncOptions::builder().with_limiter_ceiling_db(3.0),
+        SyncOptions::builder().with_keep_original_ranges(vec![(2.0, 1.0)]),
+        SyncOptions::builder().with_keep_original_ranges(vec![(f64::NAN, 1.0)]),
+    ];
+    for builder in invalid {
+        let err = builder.clone().build().unwrap_err();
+        assert!(matches!(err, Error::InvalidParameters(_)), "{:?}: {:?}", builder, err);
+    }
+    
+    // Лимитер выключен по умолчанию и включается заданием потолка;
+    // явный потолок заменяет вычисленный по уровню нормализации
+    assert!(!SyncOptions::default().apply_limiter);
+    let options = SyncOptions::builder().with_limiter_ceiling_db(-6.0).build().unwrap();
+    assert!(options.apply_limiter);
+    assert!((options.limiter_ceiling() - 10.0f32.powf(-6.0 / 20.0)).abs() < 1e-6);
+    
+    // Битрейт MP3 проверяется только для вывода в MP3
+    assert!(SyncOptions::builder().with_mp3_bitrate(100).with_output_format(AudioFormat::Wav).build().is_ok());
+}
+
 #[tokio::test]
 async fn test_progress_tracking() {
     // Создаем экземпляр TtsSync с отслеживанием прогресса
@@ -219,4 +298,543 @@ This is a test.
     assert!(std::path::Path::new(&temp_path).exists());
     
     Ok(())
-}
\ No newline at end of file
+}
+
+// Провайдер, возвращающий тон 440 Гц длительностью 1 с в формате WAV
+fn tone() -> ToneTtsProvider {
+    ToneTtsProvider::new(1.0, 44100)
+}
+
+/// Возвращает пиковую амплитуду 16-битного WAV файла
+fn wav_peak(path: &str) -> f32 {
+    let bytes = std::fs::read(path).unwrap();
+    bytes[44..]
+        .chunks_exact(2)
+        .map(|pair| (i16::from_le_bytes([pair[0], pair[1]]) as f32 / 32767.0).abs())
+        .fold(0.0, f32::max)
+}
+
+#[tokio::test]
+async fn test_synchronize_with_post_hook() -> Result<()> {
+    init_test_logger();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let vtt_path = temp_dir.path().join("subs.vtt");
+    std::fs::write(&vtt_path, "WEBVTT\n\n00:00:00.500 --> 00:00:01.500\nHello, world!\n").unwrap();
+    let vtt_path = vtt_path.to_str().unwrap();
+
+    let options = SyncOptions {
+        output_format: AudioFormat::Wav,
+        ..SyncOptions::default()
+    };
+    let tts_sync = TtsSync::new(options);
+
+    // Без пользовательской обработки
+    let output_path = tts_sync.synchronize_with_provider(vtt_path, 3.0, &tone()).await?;
+    let reference_peak = wav_peak(&output_path);
+    assert!(reference_peak > 0.1);
+
+    // Пользовательская обработка уменьшает громкость вдвое
+    let hook_called = Arc::new(Mutex::new(false));
+    let hook_called_clone = hook_called.clone();
+    let output_path = tts_sync
+        .synchronize_with_provider_and_post_hook(vtt_path, 3.0, &tone(), move |audio| {
+            *hook_called_clone.lock().unwrap() = true;
+            for sample in audio.samples.iter_mut() {
+                *sample *= 0.5;
+            }
+        })
+        .await?;
+    let scaled_peak = wav_peak(&output_path);
+
+    assert!(*hook_called.lock().unwrap());
+    assert!((scaled_peak / reference_peak - 0.5).abs() < 0.01,
+        "Ожидалось уменьшение пика вдвое: {} -> {}", reference_peak, scaled_peak);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_synchronize_progress_is_monotonic() -> Result<()> {
+    init_test_logger();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let vtt_path = temp_dir.path().join("subs.vtt");
+    std::fs::write(&vtt_path, "WEBVTT\n\n00:00:00.500 --> 00:00:01.500\nFirst\n\n00:00:02.000 --> 00:00:03.000\nSecond\n").unwrap();
+    let vtt_path = vtt_path.to_str().unwrap();
+
+    let progress_values = Arc::new(Mutex::new(Vec::new()));
+    let progress_values_clone = progress_values.clone();
+
+    // Включаем эффекты, чтобы пройти все этапы внешней шкалы прогресса
+    let options = SyncOptions {
+        output_format: AudioFormat::Wav,
+        apply_compression: true,
+        ..SyncOptions::default()
+    };
+    let tts_sync = TtsSync::new(options)
+        .with_progress_callback(Box::new(move |progress, _status| {
+            progress_values_clone.lock().unwrap().push(progress);
+        }));
+
+    tts_sync.synchronize_with_provider(vtt_path, 4.0, &tone()).await?;
+
+    let values = progress_values.lock().unwrap();
+    assert!(values.len() > 2);
+    for pair in values.windows(2) {
+        assert!(pair[1] >= pair[0], "Прогресс уменьшился: {} -> {}", pair[0], pair[1]);
+    }
+    assert_eq!(*values.last().unwrap(), 100.0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_synchronize_silent() -> Result<()> {
+    init_test_logger();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let vtt_path = temp_dir.path().join("subs.vtt");
+    std::fs::write(&vtt_path, "WEBVTT\n\n00:00:01.000 --> 00:00:02.500\nFirst\n\n00:00:03.000 --> 00:00:04.000\nSecond\n\n00:00:04.500 --> 00:00:05.000\nThird\n").unwrap();
+    let vtt_path = vtt_path.to_str().unwrap();
+
+    // Ядро строит по сегменту тишины на каждый субтитр
+    let sync_core = SyncCore::new(ProgressTracker::new(), 44100, 1, true);
+    let track = sync_core.synchronize_silent(vtt_path, 6.0)?;
+    let cue_segments: Vec<_> = track.segments.iter().filter(|s| !s.text.is_empty()).collect();
+    assert_eq!(cue_segments.len(), 3);
+    assert!((cue_segments[0].audio.duration() - 1.5).abs() < 1e-3);
+    assert!(track.segments.iter().all(|s| s.audio.samples.iter().all(|&x| x == 0.0)));
+
+    // Отчет описывает положение субтитров так же, как при синтезе
+    let report = sync_core.report();
+    assert_eq!(report.speech_regions, vec![(1.0, 2.5), (3.0, 4.0), (4.5, 5.0)]);
+    assert_eq!(report.sample_map, track.cue_sample_map());
+    assert_eq!(report.sample_map.len(), 3);
+
+    // Выходной файл покрывает всю длительность видео
+    let options = SyncOptions {
+        output_format: AudioFormat::Wav,
+        ..SyncOptions::default()
+    };
+    let output_path = TtsSync::new(options).synchronize_silent(vtt_path, 6.0).await?;
+    let bytes = std::fs::read(&output_path).unwrap();
+    let duration = (bytes.len() - 44) as f64 / 2.0 / 44100.0;
+    assert!((duration - 6.0).abs() < 0.01, "Длительность файла: {}", duration);
+    
+    // Отрицательная или нечисловая пауза после реплик отклоняется
+    for pause in [-0.5, f64::NAN] {
+        let options = SyncOptions { trailing_pause: pause, ..SyncOptions::default() };
+        assert!(matches!(options.validate(), Err(Error::InvalidParameters(_))));
+        let result = TtsSync::default().with_segment_pause(pause).synchronize_silent(vtt_path, 6.0).await;
+        assert!(matches!(result, Err(Error::InvalidParameters(_))), "{:?}", result);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_synchronize_to_audiodata() -> Result<()> {
+    init_test_logger();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let vtt_path = temp_dir.path().join("subs.vtt");
+    std::fs::write(&vtt_path, "WEBVTT\n\n00:00:00.000 --> 00:00:01.000\nFirst\n\n00:00:01.500 --> 00:00:02.500\nSecond\n").unwrap();
+    let vtt_path = vtt_path.to_str().unwrap();
+
+    let options = SyncOptions {
+        sample_rate: 22050,
+        ..SyncOptions::default()
+    };
+    let audio = TtsSync::new(options)
+        .synchronize_to_audiodata_with_provider(vtt_path, 3.0, &tone())
+        .await?;
+
+    assert_eq!(audio.sample_rate, 22050);
+    assert_eq!(audio.channels, 1);
+    assert!((audio.duration() - 3.0).abs() < 0.01, "Длительность: {}", audio.duration());
+
+    // PCM представление содержит по 2 байта на сэмпл
+    let (pcm_data, clipped_samples) = audio.to_pcm16_le();
+    assert_eq!(pcm_data.len(), audio.samples.len() * 2);
+    assert_eq!(clipped_samples, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_effects_chain_stays_below_limiter_ceiling() -> Result<()> {
+    init_test_logger();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let vtt_path = temp_dir.path().join("subs.vtt");
+    std::fs::write(&vtt_path, "WEBVTT\n\n00:00:00.500 --> 00:00:01.500\nHello, world!\n").unwrap();
+    let vtt_path = vtt_path.to_str().unwrap();
+
+    for normalize_volume in [true, false] {
+        let options = SyncOptions {
+            output_format: AudioFormat::Wav,
+            apply_compression: true,
+            apply_equalization: true,
+            apply_limiter: true,
+            normalize_volume,
+            ..SyncOptions::default()
+        };
+        let ceiling = options.limiter_ceiling();
+        assert!(ceiling < 1.0);
+
+        let final_peak = Arc::new(Mutex::new(0.0f32));
+        let final_peak_clone = final_peak.clone();
+        TtsSync::new(options)
+            .synchronize_with_provider_and_post_hook(vtt_path, 2.0, &tone(), move |audio| {
+                *final_peak_clone.lock().unwrap() = audio.sample_peak();
+            })
+            .await?;
+
+        // Итоговый пик не превышает потолок лимитера, клиппинга нет
+        let peak = *final_peak.lock().unwrap();
+        assert!(peak > 0.0);
+        assert!(peak <= ceiling + 1e-6, "Пик {} выше потолка {} (нормализация: {})", peak, ceiling, normalize_volume);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_synchronize_track_without_vtt_file() -> Result<()> {
+    init_test_logger();
+
+    let mut subtitles = tts_sync::SubtitleTrack::new();
+    subtitles.add(tts_sync::Subtitle::new(0.5, 1.5, "Hello, world!".to_string()));
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output_path = temp_dir.path().join("dub.wav");
+    let output_path = output_path.to_str().unwrap();
+
+    let options = SyncOptions {
+        output_format: AudioFormat::Wav,
+        ..SyncOptions::default()
+    };
+    let saved_path = TtsSync::new(options)
+        .synchronize_track_with_provider(&subtitles, 2.0, &tone(), output_path)
+        .await?;
+
+    assert_eq!(saved_path, output_path);
+    assert!(wav_peak(output_path) > 0.1);
+    
+    // Несортированные субтитры упорядочиваются, отчет идет по времени реплик
+    let mut unsorted = tts_sync::SubtitleTrack::new();
+    unsorted.add(tts_sync::Subtitle::new(1.5, 2.0, "Second".to_string()));
+    unsorted.add(tts_sync::Subtitle::new(0.0, 1.0, "First".to_string()));
+    let result = TtsSync::new(SyncOptions { output_format: AudioFormat::Wav, ..SyncOptions::default() })
+        .synchronize_track_detailed_with_provider(&unsorted, 2.0, &tone(), output_path)
+        .await?;
+    assert_eq!(result.output_path, output_path);
+    let texts: Vec<&str> = result.segments.iter().map(|segment| segment.text.as_str()).collect();
+    assert_eq!(texts, ["First", "Second"]);
+    assert!((result.total_duration - 2.0).abs() < 0.01, "{}", result.total_duration);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_synchronize_detailed_reports_segments() -> Result<()> {
+    init_test_logger();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let vtt_path = temp_dir.path().join("subs.vtt");
+    std::fs::write(&vtt_path, "WEBVTT\n\n00:00:00.500 --> 00:00:01.000\nFirst\n\n00:00:01.500 --> 00:00:03.000\nSecond\n").unwrap();
+    let vtt_path = vtt_path.to_str().unwrap();
+
+    let options = SyncOptions {
+        output_format: AudioFormat::Wav,
+        ..SyncOptions::default()
+    };
+    let result = TtsSync::new(options)
+        .synchronize_detailed_with_provider(vtt_path, 4.0, &tone())
+        .await?;
+
+    assert!(Path::new(&result.output_path).exists());
+    assert!((result.total_duration - 4.0).abs() < 0.05, "Длительность: {}", result.total_duration);
+
+    let texts: Vec<&str> = result.segments.iter().map(|segment| segment.text.as_str()).collect();
+    assert_eq!(texts, ["First", "Second"]);
+    for segment in &result.segments {
+        assert!((segment.tts_duration - 1.0).abs() < 0.01);
+        assert!((segment.stretch_factor - segment.adjusted_duration / segment.tts_duration).abs() < 1e-9);
+    }
+    // Речь первой реплики сжата под 0.5 с, второй - растянута
+    assert!(result.segments[0].stretch_factor < 0.6);
+    assert!(result.segments[1].stretch_factor > 1.0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_synchronize_with_loudness_normalization() -> Result<()> {
+    init_test_logger();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let vtt_path = temp_dir.path().join("subs.vtt");
+    std::fs::write(&vtt_path, "WEBVTT\n\n00:00:00.000 --> 00:00:02.000\nHello, world!\n").unwrap();
+    let vtt_path = vtt_path.to_str().unwrap();
+
+    let options = SyncOptions {
+        output_format: AudioFormat::Wav,
+        apply_limiter: false,
+        ..SyncOptions::default()
+    };
+
+    let loudness = Arc::new(Mutex::new(None));
+    let loudness_clone = loudness.clone();
+    TtsSync::new(options)
+        .with_loudness_normalization(-20.0)
+        .synchronize_with_provider_and_post_hook(vtt_path, 2.0, &tone(), move |audio| {
+            *loudness_clone.lock().unwrap() = AudioProcessor::integrated_loudness(audio);
+        })
+        .await?;
+
+    let loudness = loudness.lock().unwrap().expect("Громкость не измерена");
+    assert!((loudness + 20.0).abs() < 0.5, "Громкость {} LUFS вместо -20", loudness);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_loudness_normalization_keeps_peaks_below_limiter_ceiling() -> Result<()> {
+    init_test_logger();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let vtt_path = temp_dir.path().join("subs.vtt");
+    std::fs::write(&vtt_path, "WEBVTT\n\n00:00:00.000 --> 00:00:02.000\nHello, world!\n").unwrap();
+    let vtt_path = vtt_path.to_str().unwrap();
+
+    let options = SyncOptions {
+        output_format: AudioFormat::Wav,
+        apply_limiter: true,
+        ..SyncOptions::default()
+    };
+    let ceiling = options.limiter_ceiling();
+
+    // Тихий тон с редкими громкими щелчками
+    let provider = ToneTtsProvider::new(1.0, 44100).with_amplitude(0.05).with_clicks(22050);
+    let processed = Arc::new(Mutex::new(None));
+    let processed_clone = processed.clone();
+    TtsSync::new(options)
+        .with_loudness_normalization(-14.0)
+        .synchronize_with_provider_and_post_hook(vtt_path, 2.0, &provider, move |audio| {
+            *processed_clone.lock().unwrap() = Some(audio.clone());
+        })
+        .await?;
+
+    // Усиление до -14 LUFS поднимает щелчки выше полной шкалы, но лимитер
+    // применяется после нормализации и удерживает их под потолком
+    let audio = processed.lock().unwrap().take().expect("Аудио не обработано");
+    let peak = audio.samples.iter().fold(0.0f32, |max, &sample| max.max(sample.abs()));
+    assert!(peak <= ceiling + 1e-4, "Пик {} выше потолка {}", peak, ceiling);
+
+    let loudness = AudioProcessor::integrated_loudness(&audio).expect("Громкость не измерена");
+    assert!((loudness + 14.0).abs() < 1.0, "Громкость {} LUFS вместо -14", loudness);
+
+    Ok(())
+}
+
+// Мок TtsProvider, который всегда возвращает ошибку и считает обращения
+struct FailingTtsProvider {
+    calls: Arc<Mutex<usize>>,
+}
+
+impl TtsProvider for FailingTtsProvider {
+    fn generate_speech(&self, _text: &str) -> impl Future<Output = Result<Vec<u8>>> {
+        async move {
+            *self.calls.lock().unwrap() += 1;
+            Err(tts_sync::Error::OpenAi("Invalid API key".to_string()))
+        }
+    }
+
+    fn generate_segment(&self, text: &str, _target_duration: f64) -> impl Future<Output = Result<TtsSegment>> {
+        async move {
+            self.generate_speech(text).await?;
+            unreachable!()
+        }
+    }
+
+    fn generate_speech_to_file<P: AsRef<Path> + Send>(&self, text: &str, _path: P) -> impl Future<Output = Result<()>> {
+        async move {
+            self.generate_speech(text).await?;
+            Ok(())
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_synchronize_batch_aborts_on_failure_threshold() {
+    init_test_logger();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let jobs: Vec<SyncJob> = (0..10)
+        .map(|i| {
+            let vtt_path = temp_dir.path().join(format!("subs{}.vtt", i));
+            std::fs::write(&vtt_path, "WEBVTT\n\n00:00:00.000 --> 00:00:01.000\nHello\n").unwrap();
+            SyncJob::new(vtt_path.to_str().unwrap(), 2.0)
+        })
+        .collect();
+
+    let tts_sync = TtsSync::new(SyncOptions {
+        output_format: AudioFormat::Wav,
+        ..SyncOptions::default()
+    });
+
+    // Без порога выполняются все задания
+    let calls = Arc::new(Mutex::new(0));
+    let provider = FailingTtsProvider { calls: calls.clone() };
+    let results = tts_sync
+        .synchronize_batch_with_provider(&jobs, &provider, &BatchOptions::default())
+        .await;
+    assert_eq!(results.len(), 10);
+    assert!(results.iter().all(|r| r.is_err()));
+    assert_eq!(*calls.lock().unwrap(), 10);
+
+    // Больше 50% неудач среди первых трех заданий прерывает пакет
+    let calls = Arc::new(Mutex::new(0));
+    let provider = FailingTtsProvider { calls: calls.clone() };
+    let options = BatchOptions {
+        max_failure_rate: Some(0.5),
+        failure_check_after: 3,
+        ..BatchOptions::default()
+    };
+    let results = tts_sync.synchronize_batch_with_provider(&jobs, &provider, &options).await;
+    assert_eq!(results.len(), 10);
+    assert_eq!(*calls.lock().unwrap(), 3);
+    assert!(!matches!(results[2], Err(tts_sync::Error::Synchronization(_))));
+    assert!(results[3..].iter().all(|r| matches!(r, Err(tts_sync::Error::Synchronization(_)))));
+}
+
+/// Возвращает энергию левого и правого каналов стерео WAV файла в интервале `[from, to)` секунд
+fn wav_channel_energy(path: &str, sample_rate: u32, from: f64, to: f64) -> (f64, f64) {
+    let bytes = std::fs::read(path).unwrap();
+    let frames: Vec<(f64, f64)> = bytes[44..]
+        .chunks_exact(4)
+        .map(|frame| (
+            i16::from_le_bytes([frame[0], frame[1]]) as f64 / 32767.0,
+            i16::from_le_bytes([frame[2], frame[3]]) as f64 / 32767.0,
+        ))
+        .collect();
+    let range = &frames[(from * sample_rate as f64) as usize..(to * sample_rate as f64) as usize];
+    range.iter().fold((0.0, 0.0), |(left, right), (l, r)| (left + l * l, right + r * r))
+}
+
+#[tokio::test]
+async fn test_synchronize_pans_cues_by_position() -> Result<()> {
+    init_test_logger();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let vtt_path = temp_dir.path().join("subs.vtt");
+    std::fs::write(&vtt_path, "WEBVTT\n\n\
+        00:00:00.500 --> 00:00:01.500 position:10% align:start\nLeft speaker\n\n\
+        00:00:02.000 --> 00:00:03.000 position:90%,line-right\nRight speaker\n\n\
+        00:00:03.500 --> 00:00:04.500\nNarrator\n").unwrap();
+    let vtt_path = vtt_path.to_str().unwrap();
+
+    let options = SyncOptions {
+        output_format: AudioFormat::Wav,
+        stereo_pan: Some(0.0),
+        position_panning: true,
+        ..SyncOptions::default()
+    };
+    let sample_rate = options.sample_rate;
+    let output_path = TtsSync::new(options).synchronize_with_provider(vtt_path, 5.0, &tone()).await?;
+
+    let (left, right) = wav_channel_energy(&output_path, sample_rate, 0.6, 1.4);
+    assert!(left > 10.0 * right, "Реплика слева: L={} R={}", left, right);
+
+    let (left, right) = wav_channel_energy(&output_path, sample_rate, 2.1, 2.9);
+    assert!(right > 10.0 * left, "Реплика справа: L={} R={}", left, right);
+
+    // Реплика без позиции остается в общей панораме (по центру)
+    let (left, right) = wav_channel_energy(&output_path, sample_rate, 3.6, 4.4);
+    assert!(left > 0.0 && (left / right - 1.0).abs() < 0.01, "Реплика по центру: L={} R={}", left, right);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_auto_sample_rate_keeps_provider_rate() -> Result<()> {
+    init_test_logger();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let vtt_path = temp_dir.path().join("subs.vtt");
+    std::fs::write(&vtt_path, "WEBVTT\n\n00:00:00.500 --> 00:00:01.500\nHello, world!\n").unwrap();
+    let vtt_path = vtt_path.to_str().unwrap();
+
+    let options = SyncOptions {
+        output_format: AudioFormat::Wav,
+        auto_sample_rate: true,
+        normalize_volume: false,
+        ..SyncOptions::default()
+    };
+    assert_eq!(options.output_sample_rate(), 24000);
+    let output_path = TtsSync::new(options).synchronize_with_provider(vtt_path, 3.0, &ToneTtsProvider::new(1.0, 24000)).await?;
+
+    let bytes = std::fs::read(&output_path).unwrap();
+    assert_eq!(u32::from_le_bytes([bytes[24], bytes[25], bytes[26], bytes[27]]), 24000);
+    assert_eq!((bytes.len() - 44) / 2, 3 * 24000);
+
+    // Речь не пересэмплирована: сэмплы совпадают с ответом провайдера
+    // с точностью до округления при повторном квантовании
+    let to_i16 = |data: &[u8]| {
+        data.chunks_exact(2)
+            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+            .collect::<Vec<_>>()
+    };
+    let expected = to_i16(&TtsSegment::test_tone("", 1.0, 440.0, 24000).audio_data[44..]);
+    let speech = to_i16(&bytes[44 + 2 * 12000..]);
+    let max_diff = expected.iter()
+        .zip(&speech)
+        .take(12000)
+        .map(|(a, b)| (*a as i32 - *b as i32).abs())
+        .max()
+        .unwrap();
+    assert!(max_diff <= 2, "{}", max_diff);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_synchronize_uses_configured_tempo_algorithm() -> Result<()> {
+    init_test_logger();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let vtt_path = temp_dir.path().join("subs.vtt");
+    // Сегменты провайдера длиной 1 с сжимаются до 0.7 с
+    std::fs::write(&vtt_path, "WEBVTT\n\n00:00:00.000 --> 00:00:00.700\nFirst\n\n00:00:01.000 --> 00:00:01.700\nSecond\n").unwrap();
+    let vtt_path = vtt_path.to_str().unwrap();
+
+    let synchronize = |algorithm: ConfigTempoAlgorithm| async move {
+        TtsSync::new(SyncOptions::default())
+            .with_tempo_algorithm(algorithm)
+            .with_preserve_pauses(false)
+            .synchronize_to_audiodata_with_provider(vtt_path, 2.0, &tone())
+            .await
+    };
+    let linear = synchronize(ConfigTempoAlgorithm::Linear).await?;
+    let sinc = synchronize(ConfigTempoAlgorithm::Sinc).await?;
+
+    // Результат совпадает с ядром, явно настроенным на линейную интерполяцию
+    let expected = SyncCore::new_with_options(
+        ProgressTracker::new(),
+        44100,
+        1,
+        true,
+        10.0f32.powf(SyncOptions::default().normalization_target_db / 20.0),
+        false,
+        tts_sync::audio::TempoAlgorithm::Linear,
+    )
+    .synchronize(vtt_path, 2.0, &tone()).await?
+    .merge()?;
+
+    assert!(linear.approx_eq(&expected, 1e-6));
+    assert!(!linear.approx_eq(&sinc, 1e-3));
+
+    Ok(())
+}
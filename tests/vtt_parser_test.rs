@@ -1,4 +1,4 @@
-use tts_sync::{VttParser, Result, vtt::{Subtitle, SubtitleTrack}, error::Error};
+use tts_sync::{EmptyVttBehavior, VttParser, Result, vtt::{Subtitle, SubtitleTrack}, error::Error};
 use tempfile::NamedTempFile;
 
 #[test]
@@ -227,12 +227,22 @@ fn test_subtitle_track_operations() -> Result<()> {
         start_time: 1.0,
         end_time: 5.0,
         text: "Hello, world!".to_string(),
+        display_text: "Hello, world!".to_string(),
+        id: None,
+        target_speech_duration: None,
+        spoken_text: None,
+        position: None,
     });
     
     track.add(Subtitle {
         start_time: 6.0,
         end_time: 10.0,
         text: "This is a test.".to_string(),
+        display_text: "This is a test.".to_string(),
+        id: None,
+        target_speech_duration: None,
+        spoken_text: None,
+        position: None,
     });
     
     // Проверяем, что субтитры добавлены
@@ -263,12 +273,22 @@ fn test_subtitle_track_operations() -> Result<()> {
         start_time: 6.0,
         end_time: 10.0,
         text: "This is a test.".to_string(),
+        display_text: "This is a test.".to_string(),
+        id: None,
+        target_speech_duration: None,
+        spoken_text: None,
+        position: None,
     });
     
     unsorted_track.add(Subtitle {
         start_time: 1.0,
         end_time: 5.0,
         text: "Hello, world!".to_string(),
+        display_text: "Hello, world!".to_string(),
+        id: None,
+        target_speech_duration: None,
+        spoken_text: None,
+        position: None,
     });
     
     // Сортируем трек
@@ -335,4 +355,163 @@ fn test_parse_str_invalid_header() {
     } else {
         panic!("Expected VttParsing error");
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_subtitle_track_slice() {
+    let mut track = SubtitleTrack::new();
+    track.add(Subtitle::new(0.0, 2.0, "First".to_string()));
+    track.add(Subtitle::new(3.0, 6.0, "Second".to_string()));
+    track.add(Subtitle::new(7.0, 9.0, "Third".to_string()));
+    track.add(Subtitle::new(10.0, 12.0, "Fourth".to_string()));
+    
+    // Диапазон захватывает конец второго и начало третьего субтитра
+    let sliced = track.slice(4.0, 8.0);
+    assert_eq!(sliced.len(), 2);
+    assert_eq!(sliced[0].text, "Second");
+    assert_eq!(sliced[0].start_time, 3.0);
+    assert_eq!(sliced[0].end_time, 6.0);
+    assert_eq!(sliced[1].text, "Third");
+    assert_eq!(sliced[1].start_time, 7.0);
+    assert_eq!(sliced[1].end_time, 9.0);
+    
+    // С обрезкой время субтитров ограничивается диапазоном
+    let clipped = track.slice_clipped(4.0, 8.0);
+    assert_eq!(clipped.len(), 2);
+    assert_eq!(clipped[0].start_time, 4.0);
+    assert_eq!(clipped[0].end_time, 6.0);
+    assert_eq!(clipped[1].start_time, 7.0);
+    assert_eq!(clipped[1].end_time, 8.0);
+    
+    // Субтитры, только касающиеся границ, не попадают в срез
+    let touching = track.slice(2.0, 3.0);
+    assert!(touching.is_empty());
+}
+
+#[test]
+fn test_subtitle_track_get_and_sort_with_nan() {
+    let mut track = SubtitleTrack::new();
+    track.add(Subtitle::new(5.0, 6.0, "Second".to_string()));
+    track.add(Subtitle::new(f64::NAN, 1.0, "Broken".to_string()));
+    track.add(Subtitle::new(1.0, 2.0, "First".to_string()));
+    
+    // Индекс вне диапазона не вызывает панику
+    assert_eq!(track.get(0).map(|s| s.text.as_str()), Some("Second"));
+    assert!(track.get(3).is_none());
+    assert!(SubtitleTrack::new().get(0).is_none());
+    
+    // Субтитр с некорректным временем начала оказывается в конце
+    track.sort();
+    let texts: Vec<&str> = track.iter().map(|s| s.text.as_str()).collect();
+    assert_eq!(texts, ["First", "Second", "Broken"]);
+}
+
+#[test]
+fn test_normalize_spoken_text_keeps_display_text() {
+    let original = "<v Anna><i>Hello</i> [music] ,\n  world! ♪";
+    let mut track = SubtitleTrack::new();
+    track.add(Subtitle::new(1.0, 2.0, original.to_string()));
+    track.add(Subtitle::new(3.0, 4.0, "Plain text".to_string()));
+    
+    track.normalize_spoken_text();
+    
+    let first = &track.subtitles[0];
+    assert_eq!(first.spoken_text.as_deref(), Some("Hello, world!"));
+    assert_eq!(first.display_text(), original);
+    assert_eq!(first.display_text, original);
+    assert_eq!(first.text, original);
+    
+    let second = &track.subtitles[1];
+    assert_eq!(second.spoken_text.as_deref(), Some("Plain text"));
+    assert_eq!(second.display_text(), "Plain text");
+}
+
+#[test]
+fn test_parse_str_distinguishes_empty_files() {
+    // Без заголовка - ошибка парсинга
+    match VttParser::parse_str_with("", EmptyVttBehavior::Error) {
+        Err(Error::VttParsing(msg)) => assert!(msg.contains("missing WEBVTT header"), "{}", msg),
+        other => panic!("Expected VttParsing error, got {:?}", other),
+    }
+    
+    // Только заголовок и комментарии - отдельная ошибка
+    let header_only = "WEBVTT\nKind: captions\n\nNOTE Translated by hand\n";
+    match VttParser::parse_str_with(header_only, EmptyVttBehavior::Error) {
+        Err(Error::VttParsing(msg)) => assert!(msg.contains("contains no cues"), "{}", msg),
+        other => panic!("Expected VttParsing error, got {:?}", other),
+    }
+    
+    // С субтитрами - трек
+    let with_cues = "WEBVTT\n\n00:00:01.000 --> 00:00:04.000\nHello, world!";
+    assert_eq!(VttParser::parse_str_with(with_cues, EmptyVttBehavior::Error).unwrap().len(), 1);
+    
+    // По умолчанию файлы без субтитров дают пустой трек
+    assert!(VttParser::parse_str("").unwrap().is_empty());
+    assert!(VttParser::parse_str(header_only).unwrap().is_empty());
+}
+
+#[test]
+fn test_parse_str_cue_position() {
+    let vtt = "WEBVTT\n\n00:00:01.000 --> 00:00:02.000 position:20% align:start\nLeft\n\n\
+        00:00:03.000 --> 00:00:04.000 line:0 position:75%,line-right\nRight\n\n\
+        00:00:05.000 --> 00:00:06.000\nCenter";
+    let track = VttParser::parse_str(vtt).unwrap();
+
+    assert_eq!(track.subtitles[0].position, Some(20.0));
+    assert_eq!(track.subtitles[1].position, Some(75.0));
+    assert_eq!(track.subtitles[2].position, None);
+    assert!((track.subtitles[0].position_pan().unwrap() + 0.6).abs() < 1e-6);
+    assert!((track.subtitles[1].position_pan().unwrap() - 0.5).abs() < 1e-6);
+}
+
+/// Логгер, сохраняющий предупреждения для проверки в тестах
+struct WarningCapture {
+    messages: std::sync::Mutex<Vec<String>>,
+}
+
+impl log::Log for WarningCapture {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Warn
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            self.messages.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn warning_capture() -> &'static WarningCapture {
+    static CAPTURE: std::sync::OnceLock<&'static WarningCapture> = std::sync::OnceLock::new();
+    CAPTURE.get_or_init(|| {
+        let capture: &'static WarningCapture = Box::leak(Box::new(WarningCapture {
+            messages: std::sync::Mutex::new(Vec::new()),
+        }));
+        let _ = log::set_logger(capture);
+        log::set_max_level(log::LevelFilter::Warn);
+        capture
+    })
+}
+
+#[test]
+fn test_parse_str_warns_about_trailing_timestamp_without_text() {
+    let capture = warning_capture();
+    let dangling_warnings = |timestamp: &str| {
+        capture.messages.lock().unwrap().iter()
+            .filter(|message| message.contains(timestamp) && message.contains("не содержит текста"))
+            .count()
+    };
+    
+    // Файл обрывается сразу после временных меток последнего субтитра
+    let vtt = "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\nHello\n\n00:00:03.000 --> 00:00:04.500";
+    let track = VttParser::parse_str(vtt).unwrap();
+    assert_eq!(track.len(), 1);
+    assert_eq!(dangling_warnings("3.000 --> 4.500"), 1);
+    
+    // Последний субтитр без завершающей пустой строки добавляется без предупреждения
+    let vtt = "WEBVTT\n\n00:00:05.000 --> 00:00:06.000\nHello";
+    assert_eq!(VttParser::parse_str(vtt).unwrap().len(), 1);
+    assert_eq!(dangling_warnings("5.000 --> 6.000"), 0);
+}